@@ -16,11 +16,12 @@ use crate::{allow_std_io::AllowStdIo,
 use broadcast::BroadcastWriter;
 use bytes::BytesMut;
 use futures::stream::TryStreamExt;
-use habitat_core::{crypto::keys::box_key_pair::WrappedSealedBox,
+use habitat_core::{crypto::{hash,
+                            keys::box_key_pair::WrappedSealedBox},
                    fs::{AtomicWriter,
-                        Permissions,
-                        DEFAULT_CACHED_ARTIFACT_PERMISSIONS},
-                   package::{Identifiable,
+                        Permissions},
+                   package::{ident::version_sort,
+                             Identifiable,
                              PackageArchive,
                              PackageIdent,
                              PackageTarget},
@@ -28,27 +29,34 @@ use habitat_core::{crypto::keys::box_key_pair::WrappedSealedBox,
 use percent_encoding::{percent_encode,
                        AsciiSet,
                        CONTROLS};
-use reqwest::{header::CONTENT_LENGTH,
+use reqwest::{header::{CONTENT_LENGTH,
+                       RANGE},
               Body,
               IntoUrl,
               RequestBuilder,
               StatusCode};
-use std::{fs::{self,
+use std::{cmp::Ordering,
+          fs::{self,
                File},
           future::Future,
           io::{self,
-               Cursor},
+               Cursor,
+               Write},
           path::{Path,
                  PathBuf},
           string::ToString};
 use tee::TeeReader;
-use tokio::task;
+use tokio::{io::AsyncWriteExt,
+            task};
 use tokio_util::codec::{BytesCodec,
                         FramedRead};
 use url::Url;
 
 const X_FILENAME: &str = "x-filename";
 
+/// The extension of a package while it is being downloaded.
+const PARTIAL_DOWNLOAD_EXTENSION: &str = "hart.part";
+
 const DEFAULT_API_PATH: &str = "/v1";
 
 // The characters in this set are copied from
@@ -114,12 +122,18 @@ mod json {
         pub release: String,
     }
 
+    #[derive(Clone, Deserialize)]
+    pub struct PackageVersion {
+        pub version: String,
+    }
+
     impl From<PackageIdent> for super::PackageIdent {
         fn from(ident: PackageIdent) -> Self {
-            super::PackageIdent { origin:  ident.origin,
-                                  name:    ident.name,
-                                  version: Some(ident.version),
-                                  release: Some(ident.release), }
+            super::PackageIdent { origin:     ident.origin,
+                                  name:       ident.name,
+                                  version:    Some(ident.version),
+                                  release:    Some(ident.release),
+                                  constraint: None, }
         }
     }
 }
@@ -284,6 +298,71 @@ impl BuilderAPIClient {
         }
     }
 
+    /// Returns every version of `package` that was ever uploaded, in any channel.
+    async fn package_versions(&self,
+                              package: &PackageIdent,
+                              token: Option<&str>)
+                              -> Result<Vec<String>> {
+        debug!("Listing versions of {}", package);
+        let path = format!("depot/pkgs/{}/{}/versions",
+                           package.origin(),
+                           package.name());
+        let resp = self.maybe_add_authz(self.0.get(&path), token)
+                       .send()
+                       .await?;
+        let resp = response::ok_if(resp, &[StatusCode::OK]).await?;
+
+        let encoded = resp.text().await.map_err(Error::BadResponseBody)?;
+        trace!(target: "habitat_http_client::api_client::package_versions", "{:?}", encoded);
+
+        let versions: Vec<json::PackageVersion> = serde_json::from_str(&encoded)?;
+        Ok(versions.into_iter().map(|v| v.version).collect())
+    }
+
+    /// Returns a package struct for the latest release of `package` in `channel` whose version
+    /// satisfies the package's version constraint.
+    ///
+    /// Rather than paging through every release in the channel, the versions satisfying the
+    /// constraint are looked up in the channel one at a time, newest first, until one of them
+    /// has a release there.
+    async fn latest_matching_release(&self,
+                                     (package, target): (&PackageIdent, PackageTarget),
+                                     channel: &ChannelIdent,
+                                     token: Option<&str>)
+                                     -> Result<Package> {
+        let mut candidates = self.package_versions(package, token)
+                                 .await?
+                                 .into_iter()
+                                 .map(|version| {
+                                     PackageIdent::new(package.origin.clone(),
+                                                       package.name.clone(),
+                                                       Some(version),
+                                                       None)
+                                 })
+                                 .filter(|candidate| candidate.satisfies(package))
+                                 .collect::<Vec<_>>();
+        // Versions which satisfy a constraint can always be compared
+        candidates.sort_by(|a, b| {
+                      version_sort(a.version().unwrap_or_default(),
+                                   b.version().unwrap_or_default()).unwrap_or(Ordering::Equal)
+                  });
+        for candidate in candidates.iter().rev() {
+            match self.channel_package_metadata((candidate, target), channel, token)
+                      .await
+            {
+                Ok(release) => return Ok(release),
+                Err(Error::APIError(StatusCode::NOT_FOUND, _)) => {
+                    debug!("{} has no release in the {} channel", candidate, channel);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(Error::APIError(StatusCode::NOT_FOUND,
+                            format!("No release of {} in the {} channel satisfies its \
+                                     version constraint",
+                                    package, channel)))
+    }
+
     /// Retrieves the status of every group job in an origin
     ///
     /// # Failures
@@ -990,11 +1069,16 @@ impl BuilderAPIClient {
     /// e.g. /pkgs/core/redis/latest but that only _shows_ you the details - it doesn't download
     /// the package.
     ///
+    /// The package is downloaded next to its final location, with a `.part` extension, and is
+    /// only moved into place once its checksum matches the one Builder reports for it. If an
+    /// earlier download of the package was interrupted, it is resumed from where it stopped.
+    ///
     /// # Failures
     ///
     /// * Package cannot be found
     /// * Remote Builder is not available
     /// * File cannot be created and written to
+    /// * The downloaded package doesn't match Builder's checksum
     pub async fn fetch_package<'a>(&'a self,
                                    (ident, target): (&'a PackageIdent, PackageTarget),
                                    token: Option<&'a str>,
@@ -1009,16 +1093,91 @@ impl BuilderAPIClient {
             return Err(Error::IdentNotFullyQualified);
         }
 
-        let req_builder = self.0.get_with_custom_url(&package_download(ident), |u| {
-                                    u.set_query(Some(&format!("target={}", target)))
-                                });
-        self.download(req_builder,
-                      dst_path.as_ref(),
-                      token,
-                      DEFAULT_CACHED_ARTIFACT_PERMISSIONS,
-                      progress)
-            .await
-            .map(PackageArchive::new)
+        let expected = self.package_checksum((ident, target), token).await?;
+        fs::create_dir_all(&dst_path)?;
+        let dst_file_path = dst_path.join(ident.archive_name_with_target(target)?);
+        let part_path = dst_file_path.with_extension(PARTIAL_DOWNLOAD_EXTENSION);
+        self.download_package_part((ident, target), token, &part_path, progress)
+            .await?;
+
+        let path = part_path.clone();
+        let actual = task::spawn_blocking(move || hash::hash_file(path)).await??;
+        if actual != expected {
+            // Whatever is wrong with the file, resuming the download won't fix it.
+            fs::remove_file(&part_path)?;
+            return Err(Error::ChecksumMismatch(part_path, actual, expected));
+        }
+        fs::rename(&part_path, &dst_file_path)?;
+        Ok(PackageArchive::new(dst_file_path))
+    }
+
+    /// The checksum Builder recorded for a package when it was uploaded.
+    async fn package_checksum(&self,
+                              (ident, target): (&PackageIdent, PackageTarget),
+                              token: Option<&str>)
+                              -> Result<String> {
+        let resp = self.maybe_add_authz(self.0.get_with_custom_url(&package_path(ident), |u| {
+                                                  u.set_query(Some(&format!("target={}", target)))
+                                              }),
+                                        token)
+                       .send()
+                       .await?;
+        let resp = response::ok_if(resp, &[StatusCode::OK]).await?;
+        let encoded = resp.text().await.map_err(Error::BadResponseBody)?;
+        let package = serde_json::from_str::<Package>(&encoded)?;
+        Ok(package.checksum)
+    }
+
+    /// Download the rest of a package into `part_path`, starting from the end of what was already
+    /// downloaded into it. The file is written as the package arrives, so an interrupted download
+    /// can be resumed.
+    async fn download_package_part(&self,
+                                   (ident, target): (&PackageIdent, PackageTarget),
+                                   token: Option<&str>,
+                                   part_path: &Path,
+                                   mut progress: Option<Box<dyn DisplayProgress>>)
+                                   -> Result<()> {
+        let offset = match fs::metadata(part_path) {
+            Ok(metadata) => metadata.len(),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(Error::IO(e)),
+        };
+        let mut rb = self.0.get_with_custom_url(&package_download(ident), |u| {
+                               u.set_query(Some(&format!("target={}", target)))
+                           });
+        if offset > 0 {
+            debug!("Resuming download of {} from byte {}", ident, offset);
+            rb = rb.header(RANGE, format!("bytes={}-", offset));
+        }
+        let resp = self.maybe_add_authz(rb, token).send().await?;
+        if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            // Everything was downloaded before the download was interrupted. If the file is
+            // not the package after all, it fails verification and is removed.
+            debug!("{} is already completely downloaded", part_path.display());
+            return Ok(());
+        }
+        let resp = response::ok_if(resp, &[StatusCode::OK, StatusCode::PARTIAL_CONTENT]).await?;
+
+        // A server which doesn't support ranges sends the whole package instead.
+        let resume = resp.status() == StatusCode::PARTIAL_CONTENT;
+        let write_error = |e| Error::DownloadWrite(part_path.to_path_buf(), e);
+        let mut file = open_partial_download(part_path, resume).await
+                                                               .map_err(write_error)?;
+        // There will be no CONTENT_LENGTH header if an on prem builder is using chunked transfer
+        // encoding
+        if let (Some(progress), Ok(content_length)) =
+            (progress.as_mut(), response::get_header(&resp, CONTENT_LENGTH))
+        {
+            progress.size(content_length.parse().map_err(Error::ParseIntError)?);
+        }
+        let mut body = resp.bytes_stream();
+        while let Some(chunk) = body.try_next().await? {
+            file.write_all(&chunk).await.map_err(write_error)?;
+            if let Some(ref mut progress) = progress {
+                progress.write_all(&chunk)?;
+            }
+        }
+        file.sync_all().await.map_err(write_error)
     }
 
     /// Checks whether a specified package exists
@@ -1077,7 +1236,8 @@ impl BuilderAPIClient {
     /// Returns a package struct for the latest package.
     ///
     /// An optional version can be specified which will scope the release returned to the latest
-    /// release of that package.
+    /// release of that package. Alternatively, a version constraint scopes it to the latest
+    /// release whose version satisfies the constraint.
     ///
     /// # Failures
     ///
@@ -1091,6 +1251,22 @@ impl BuilderAPIClient {
         debug!("Retrieving package metadata for {}, target {}",
                package, target);
 
+        if package.constraint.is_some() {
+            self.latest_matching_release((package, target), channel, token)
+                .await
+        } else {
+            self.channel_package_metadata((package, target), channel, token)
+                .await
+        }
+    }
+
+    /// Returns a package struct for the latest release of `package` in `channel`, ignoring the
+    /// package's version constraint.
+    async fn channel_package_metadata(&self,
+                                      (package, target): (&PackageIdent, PackageTarget),
+                                      channel: &ChannelIdent,
+                                      token: Option<&str>)
+                                      -> Result<Package> {
         let mut url = channel_package_path(channel, package);
 
         if !package.fully_qualified() {
@@ -1408,6 +1584,27 @@ fn package_download(package: &PackageIdent) -> String {
 
 fn package_path(package: &PackageIdent) -> String { format!("depot/pkgs/{}", package) }
 
+/// Open a partially downloaded package for writing, either to resume its download or, if `resume`
+/// is false, to download it again from the beginning.
+async fn open_partial_download(path: &Path, resume: bool) -> io::Result<tokio::fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.create(true).write(true);
+    if resume {
+        options.append(true);
+    } else {
+        options.truncate(true);
+    }
+    #[cfg(not(windows))]
+    {
+        use habitat_core::fs::DEFAULT_CACHED_ARTIFACT_PERMISSIONS;
+        use std::os::unix::fs::OpenOptionsExt;
+        if let Permissions::Explicit(mode) = DEFAULT_CACHED_ARTIFACT_PERMISSIONS {
+            options.mode(mode);
+        }
+    }
+    tokio::fs::OpenOptions::from(options).open(path).await
+}
+
 fn package_search(term: &str) -> String {
     let encoded_term = percent_encode(term.as_bytes(), PATH_SEGMENT_ENCODE_SET);
     format!("depot/pkgs/search/{}", encoded_term)
@@ -1461,6 +1658,13 @@ mod tests {
     use futures::future::{self,
                           Ready};
     use serde_json;
+    use std::{io::Read,
+              net::TcpListener,
+              str::FromStr,
+              sync::{Arc,
+                     Mutex},
+              thread};
+    use tempfile::TempDir;
 
     #[test]
     fn json_round_trip_u64_fields() {
@@ -1493,10 +1697,11 @@ mod tests {
     }
 
     fn get_test_ident(name: &str) -> PackageIdent {
-        PackageIdent { origin:  String::from("test"),
-                       name:    String::from(name),
-                       version: None,
-                       release: None, }
+        PackageIdent { origin:     String::from("test"),
+                       name:       String::from(name),
+                       version:    None,
+                       release:    None,
+                       constraint: None, }
     }
 
     fn seach_generator<'a>(
@@ -1537,6 +1742,177 @@ mod tests {
         }
     }
 
+    const PACKAGE_CONTENT: &[u8] = b"HART-1\nthe rest of the package";
+
+    /// Serve each response to one connection in turn. Returns the URL of the server and the
+    /// request heads it received.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = Vec::new();
+                let mut buf = [0; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buf[..n]);
+                }
+                received.lock()
+                        .unwrap()
+                        .push(String::from_utf8_lossy(&head).to_lowercase());
+                stream.write_all(&response).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn http_response(status: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: \
+                                    close\r\n\r\n",
+                                   status,
+                                   body.len()).into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn package_response(ident: &PackageIdent, checksum: &str) -> Vec<u8> {
+        let package = json!({
+            "ident": ident,
+            "checksum": checksum,
+            "manifest": "",
+            "deps": [],
+            "tdeps": [],
+            "exposes": [],
+            "config": ""
+        });
+        http_response("200 OK", package.to_string().as_bytes())
+    }
+
+    fn test_package() -> (PackageIdent, PackageTarget) {
+        (PackageIdent::from_str("test/pkg/1.0.0/20200101000000").unwrap(),
+         PackageTarget::from_str("x86_64-linux").unwrap())
+    }
+
+    #[tokio::test]
+    async fn fetch_package_resumes_a_partial_download() {
+        let (ident, target) = test_package();
+        let tmpdir = TempDir::new().unwrap();
+        let dst_file_path = tmpdir.path()
+                                  .join(ident.archive_name_with_target(target).unwrap());
+        let part_path = dst_file_path.with_extension(PARTIAL_DOWNLOAD_EXTENSION);
+        let (downloaded, rest) = PACKAGE_CONTENT.split_at(7);
+        fs::write(&part_path, downloaded).unwrap();
+        let (url, requests) = serve(vec![package_response(&ident,
+                                                          &hash::hash_bytes(PACKAGE_CONTENT)),
+                                         http_response("206 Partial Content", rest),]);
+        let client = BuilderAPIClient::new(url.as_str(), "", "", None).unwrap();
+
+        let archive = client.fetch_package((&ident, target), None, tmpdir.path(), None)
+                            .await
+                            .unwrap();
+
+        assert_eq!(archive.path, dst_file_path);
+        assert_eq!(fs::read(&dst_file_path).unwrap(), PACKAGE_CONTENT);
+        assert!(!part_path.exists());
+        assert!(requests.lock().unwrap()[1].contains("range: bytes=7-"));
+    }
+
+    #[tokio::test]
+    async fn fetch_package_restarts_a_download_if_ranges_are_not_supported() {
+        let (ident, target) = test_package();
+        let tmpdir = TempDir::new().unwrap();
+        let dst_file_path = tmpdir.path()
+                                  .join(ident.archive_name_with_target(target).unwrap());
+        let part_path = dst_file_path.with_extension(PARTIAL_DOWNLOAD_EXTENSION);
+        fs::write(&part_path, &PACKAGE_CONTENT[..7]).unwrap();
+        let (url, _) = serve(vec![package_response(&ident, &hash::hash_bytes(PACKAGE_CONTENT)),
+                                  http_response("200 OK", PACKAGE_CONTENT),]);
+        let client = BuilderAPIClient::new(url.as_str(), "", "", None).unwrap();
+
+        client.fetch_package((&ident, target), None, tmpdir.path(), None)
+              .await
+              .unwrap();
+
+        assert_eq!(fs::read(&dst_file_path).unwrap(), PACKAGE_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn fetch_package_removes_a_download_with_the_wrong_checksum() {
+        let (ident, target) = test_package();
+        let tmpdir = TempDir::new().unwrap();
+        let dst_file_path = tmpdir.path()
+                                  .join(ident.archive_name_with_target(target).unwrap());
+        let part_path = dst_file_path.with_extension(PARTIAL_DOWNLOAD_EXTENSION);
+        let (url, _) = serve(vec![package_response(&ident, &hash::hash_bytes(b"something else")),
+                                  http_response("200 OK", PACKAGE_CONTENT),]);
+        let client = BuilderAPIClient::new(url.as_str(), "", "", None).unwrap();
+
+        match client.fetch_package((&ident, target), None, tmpdir.path(), None)
+                    .await
+        {
+            Err(Error::ChecksumMismatch(path, actual, expected)) => {
+                assert_eq!(path, part_path);
+                assert_eq!(actual, hash::hash_bytes(PACKAGE_CONTENT));
+                assert_eq!(expected, hash::hash_bytes(b"something else"));
+            }
+            Err(e) => panic!("Expected a checksum mismatch, got {}", e),
+            Ok(_) => panic!("Expected a checksum mismatch"),
+        }
+        assert!(!part_path.exists());
+        assert!(!dst_file_path.exists());
+    }
+
+    #[tokio::test]
+    async fn show_package_metadata_resolves_version_constraints() {
+        let versions = json!([{ "version": "1.3.0" },
+                              { "version": "1.10.0" },
+                              { "version": "1.4.2" },
+                              { "version": "2.0.0" }]);
+        let release = PackageIdent::from_str("test/pkg/1.4.2/20200101000000").unwrap();
+        let (url, requests) = serve(vec![http_response("200 OK", versions.to_string().as_bytes()),
+                                         http_response("404 Not Found", b""),
+                                         package_response(&release, "abc"),]);
+        let client = BuilderAPIClient::new(url.as_str(), "", "", None).unwrap();
+        let package = PackageIdent::from_str("test/pkg@>=1.4, <2").unwrap();
+        let target = PackageTarget::from_str("x86_64-linux").unwrap();
+
+        let found = client.show_package_metadata((&package, target), &ChannelIdent::stable(), None)
+                          .await
+                          .unwrap();
+        assert_eq!(found.ident, release);
+
+        // Only the versions satisfying the constraint are looked up, newest first
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains("/depot/pkgs/test/pkg/versions "));
+        assert!(requests[1].contains("/depot/channels/test/stable/pkgs/pkg/1.10.0/latest?"));
+        assert!(requests[2].contains("/depot/channels/test/stable/pkgs/pkg/1.4.2/latest?"));
+    }
+
+    #[tokio::test]
+    async fn show_package_metadata_fails_if_no_release_satisfies_the_constraint() {
+        let versions = json!([{ "version": "1.3.0" }, { "version": "1.4.2" }]);
+        let (url, _) = serve(vec![http_response("200 OK", versions.to_string().as_bytes()),
+                                  http_response("404 Not Found", b""),]);
+        let client = BuilderAPIClient::new(url.as_str(), "", "", None).unwrap();
+        let package = PackageIdent::from_str("test/pkg@>=1.4").unwrap();
+        let target = PackageTarget::from_str("x86_64-linux").unwrap();
+
+        match client.show_package_metadata((&package, target), &ChannelIdent::stable(), None)
+                    .await
+        {
+            Err(Error::APIError(StatusCode::NOT_FOUND, _)) => (),
+            Err(e) => panic!("Expected a not found error, got {}", e),
+            Ok(_) => panic!("Expected a not found error"),
+        }
+    }
+
     #[tokio::test]
     async fn package_search() {
         let client = BuilderAPIClient::new("http://test.com", "", "", None).expect("valid client");
@@ -17,6 +17,8 @@ pub type Result<T> = result::Result<T, Error>;
 pub enum Error {
     APIError(reqwest::StatusCode, String),
     BadResponseBody(reqwest::Error),
+    /// The checksum of a downloaded package, followed by the checksum Builder reports for it
+    ChecksumMismatch(PathBuf, String, String),
     DownloadWrite(PathBuf, io::Error),
     HabitatCore(hab_core::Error),
     HabitatHttpClient(hab_http::Error),
@@ -43,6 +45,12 @@ impl fmt::Display for Error {
             Error::APIError(ref c, ref m) if !m.is_empty() => format!("[{}] {}", c, m),
             Error::APIError(ref c, _) => format!("[{}]", c),
             Error::BadResponseBody(ref e) => format!("Failed to read response body, {}", e),
+            Error::ChecksumMismatch(ref p, ref actual, ref expected) => {
+                format!("The checksum of the downloaded package {} is {}, but Builder reports {}",
+                        p.display(),
+                        actual,
+                        expected)
+            }
             Error::DownloadWrite(ref p, ref e) => {
                 format!("Failed to write contents of builder response, {}, {}",
                         p.display(),
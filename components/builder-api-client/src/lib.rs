@@ -164,10 +164,11 @@ mod json {
 
     impl From<PackageIdent> for super::PackageIdent {
         fn from(ident: PackageIdent) -> Self {
-            super::PackageIdent { origin:  ident.origin,
-                                  name:    ident.name,
-                                  version: Some(ident.version),
-                                  release: Some(ident.release), }
+            super::PackageIdent { origin:     ident.origin,
+                                  name:       ident.name,
+                                  version:    Some(ident.version),
+                                  release:    Some(ident.release),
+                                  constraint: None, }
         }
     }
 }
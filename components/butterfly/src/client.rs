@@ -2,28 +2,28 @@
 //!
 //! This will connect to a given butterfly members `Pull` thread, and inject a rumor.
 
-use habitat_core::{crypto::SymKey,
-                   service::ServiceGroup};
+use habitat_core::service::ServiceGroup;
 use zmq;
 
 use crate::{error::{Error,
                     Result},
             message,
+            ring_keys::RingKeys,
             rumor::{departure::Departure,
                     service_config::ServiceConfig,
                     service_file::ServiceFile,
                     Rumor},
             ZMQ_CONTEXT};
 
-/// Holds a ZMQ Push socket, and an optional ring encryption key.
+/// Holds a ZMQ Push socket, and optional ring encryption keys.
 pub struct Client {
-    socket:   zmq::Socket,
-    ring_key: Option<SymKey>,
+    socket:    zmq::Socket,
+    ring_keys: Option<RingKeys>,
 }
 
 impl Client {
     /// Connect this client to the address, and optionally encrypt the traffic.
-    pub fn new(addr: &str, ring_key: Option<SymKey>) -> Result<Client> {
+    pub fn new(addr: &str, ring_keys: Option<RingKeys>) -> Result<Client> {
         let socket = (**ZMQ_CONTEXT).as_mut()
                                     .socket(zmq::PUSH)
                                     .expect("Failure to create the ZMQ push socket");
@@ -39,7 +39,7 @@ impl Client {
               .expect("Failure to set the ZMQ send timeout");
         let to_addr = format!("tcp://{}", addr);
         socket.connect(&to_addr).map_err(Error::ZmqConnectError)?;
-        Ok(Client { socket, ring_key })
+        Ok(Client { socket, ring_keys })
     }
 
     /// Create a departure notification and send it to the server.
@@ -82,7 +82,7 @@ impl Client {
         where T: Rumor
     {
         let bytes = rumor.write_to_bytes()?;
-        let wire_msg = message::generate_wire(bytes, self.ring_key.as_ref())?;
+        let wire_msg = message::generate_wire(bytes, self.ring_keys.as_ref())?;
         self.socket.send(&wire_msg, 0).map_err(Error::ZmqSendError)
     }
 }
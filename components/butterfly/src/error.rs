@@ -35,6 +35,7 @@ pub enum Error {
     SocketSetWriteTimeout(io::Error),
     Timeout(String),
     UnknownMember(String),
+    UnknownRingKey(String),
     ZmqConnectError(zmq::Error),
     ZmqSendError(zmq::Error),
     UnknownIOError(io::Error),
@@ -100,6 +101,10 @@ impl fmt::Display for Error {
             }
             Error::Timeout(ref msg) => format!("Timed out {}", msg),
             Error::UnknownMember(ref member_id) => format!("Unknown member ID: {}", member_id),
+            Error::UnknownRingKey(ref name_with_rev) => {
+                format!("Message was encrypted with unknown ring key {}",
+                        name_with_rev)
+            }
             Error::ZmqConnectError(ref err) => format!("Cannot connect ZMQ socket: {}", err),
             Error::ZmqSendError(ref err) => {
                 format!("Cannot send message through ZMQ socket: {}", err)
@@ -1,18 +1,24 @@
 use bytes::BytesMut;
-use habitat_core::crypto::SymKey;
 use prost::Message;
 
 use crate::{error::{Error,
                     Result},
-            protocol::Wire};
+            protocol::Wire,
+            ring_keys::RingKeys};
 
-pub fn generate_wire(payload: Vec<u8>, ring_key: Option<&SymKey>) -> Result<Vec<u8>> {
+/// Wrap a payload for the wire, encrypting it with the newest ring key if there is one.
+///
+/// # Locking (see locking.md)
+/// * `RingKeys::keys` (read)
+pub fn generate_wire(payload: Vec<u8>, ring_keys: Option<&RingKeys>) -> Result<Vec<u8>> {
     let mut wire = Wire::default();
-    if let Some(ring_key) = ring_key {
+    if let Some(ring_keys) = ring_keys {
+        let ring_key = ring_keys.encryption_key_rkr();
         wire.encrypted = Some(true);
         let (nonce, encrypted_payload) = ring_key.encrypt(&payload)?;
         wire.nonce = Some(nonce);
         wire.payload = Some(encrypted_payload);
+        wire.key_name_with_rev = Some(ring_key.name_with_rev());
     } else {
         wire.payload = Some(payload);
     }
@@ -21,13 +27,33 @@ pub fn generate_wire(payload: Vec<u8>, ring_key: Option<&SymKey>) -> Result<Vec<
     Ok(buf.to_vec())
 }
 
-pub fn unwrap_wire(payload: &[u8], ring_key: Option<&SymKey>) -> Result<Vec<u8>> {
+/// Unwrap a payload from the wire, decrypting it with the ring key revision it names.
+///
+/// # Locking (see locking.md)
+/// * `RingKeys::keys` (read)
+pub fn unwrap_wire(payload: &[u8], ring_keys: Option<&RingKeys>) -> Result<Vec<u8>> {
     let wire = Wire::decode(payload)?;
     let payload = wire.payload
                       .ok_or(Error::ProtocolMismatch("missing payload"))?;
-    if let Some(ring_key) = ring_key {
+    if let Some(ring_keys) = ring_keys {
         let nonce = wire.nonce.ok_or(Error::ProtocolMismatch("missing nonce"))?;
-        Ok(ring_key.decrypt(&nonce, &payload)?)
+        match wire.key_name_with_rev {
+            Some(name_with_rev) => {
+                let ring_key = ring_keys.get_rkr(&name_with_rev)
+                                        .ok_or(Error::UnknownRingKey(name_with_rev))?;
+                Ok(ring_key.decrypt(&nonce, &payload)?)
+            }
+            // Members that predate ring key rotation do not name the revision they used, so try
+            // each of them.
+            None => {
+                let revisions = ring_keys.revisions_rkr();
+                let decrypted = revisions.iter()
+                                         .map(|ring_key| ring_key.decrypt(&nonce, &payload))
+                                         .find(|decrypted| decrypted.is_ok())
+                                         .unwrap_or_else(|| revisions[0].decrypt(&nonce, &payload));
+                Ok(decrypted?)
+            }
+        }
     } else {
         Ok(payload)
     }
@@ -72,17 +72,12 @@ impl From<CElectionUpdate> for Rumor {
 
 impl From<CService> for Rumor {
     fn from(value: CService) -> Self {
-        let payload = Service { member_id:     Some(value.member_id.clone()),
-                                service_group: Some(value.service_group.to_string()),
-                                incarnation:   Some(value.incarnation),
-                                initialized:   Some(value.initialized),
-                                pkg:           Some(value.pkg),
-                                cfg:           Some(value.cfg),
-                                sys:           Some(value.sys.into()), };
-        Rumor { r#type:  RumorType::Service as i32,
-                tag:     Vec::default(),
-                from_id: Some(value.member_id),
-                payload: Some(RumorPayload::Service(payload)), }
+        let from_id = Some(value.member_id.clone());
+        let payload = Service::from(value);
+        Rumor { r#type: RumorType::Service as i32,
+                tag: Vec::default(),
+                from_id,
+                payload: Some(RumorPayload::Service(payload)) }
     }
 }
 
@@ -0,0 +1,303 @@
+//! The ring keys used to encrypt and decrypt traffic on the wire.
+//!
+//! A member may hold several revisions of its ring key. Messages name the revision they were
+//! encrypted with, so a member can decrypt a message sent with any revision it knows about. It
+//! encrypts its own messages with the revision it was configured with: the revision given to it,
+//! or the newest revision in the key cache when it started. The revisions are reloaded from the
+//! key cache every `RingKeyReloadInterval`, so a member can decrypt messages sent with a revision
+//! imported while it runs without being restarted. It only starts encrypting with such a revision
+//! when it is restarted, or as soon as it has reloaded it if `RingKeyEncryptWithNewest` is set.
+
+use crate::error::{Error,
+                   Result};
+use habitat_common::sync::Lock;
+use habitat_core::crypto::SymKey;
+use std::{path::{Path,
+                 PathBuf},
+          sync::Arc,
+          time::Duration};
+
+habitat_core::env_config!(
+    /// How often the revisions of the ring key are reloaded from the key cache
+    pub RingKeyReloadInterval,
+    Duration,
+    HAB_RING_KEY_RELOAD_INTERVAL_SECS,
+    Duration::from_secs(60),
+    std::num::ParseIntError,
+    s,
+    Ok(Self(Duration::from_secs(s.parse()?))));
+
+habitat_core::env_config!(
+    /// Whether to encrypt with the newest revision of the ring key as soon as it is reloaded,
+    /// rather than with the revision the ring keys were loaded with
+    RingKeyEncryptWithNewest,
+    bool,
+    HAB_RING_KEY_ENCRYPT_WITH_NEWEST,
+    false,
+    std::str::ParseBoolError,
+    s,
+    Ok(Self(s.parse()?)));
+
+/// Every known revision of a ring key, and the one used to encrypt messages.
+#[derive(Clone, Debug, PartialEq)]
+struct Revisions {
+    /// Every known revision, newest first
+    keys:       Vec<SymKey>,
+    encryption: SymKey,
+}
+
+impl Revisions {
+    fn new(mut keys: Vec<SymKey>, encryption: SymKey) -> Self {
+        // Never forget the revision we encrypt with, even if its key file has been removed.
+        if !keys.contains(&encryption) {
+            keys.push(encryption.clone());
+        }
+        keys.sort_by(|a, b| b.rev.cmp(&a.rev));
+        Self { keys, encryption }
+    }
+}
+
+/// Every known revision of a ring key, newest first, and the revision used to encrypt messages.
+/// Clones share the revisions, so reloading them updates every clone.
+#[derive(Clone, Debug)]
+pub struct RingKeys {
+    name:                String,
+    /// The key cache the revisions were loaded from, if any
+    cache_key_path:      Option<PathBuf>,
+    encrypt_with_newest: bool,
+    keys:                Arc<Lock<Revisions>>,
+}
+
+impl RingKeys {
+    fn new(name: String,
+           keys: Vec<SymKey>,
+           encryption: SymKey,
+           cache_key_path: Option<PathBuf>)
+           -> Self {
+        Self { name,
+               cache_key_path,
+               encrypt_with_newest: RingKeyEncryptWithNewest::configured_value().into(),
+               keys: Arc::new(Lock::new(Revisions::new(keys, encryption))) }
+    }
+
+    /// Load every revision of the ring key `name` from the key cache. Messages are encrypted with
+    /// the newest of them.
+    pub fn load<P>(name: &str, cache_key_path: &P) -> Result<Self>
+        where P: AsRef<Path> + ?Sized
+    {
+        let keys = Self::load_keys(name, cache_key_path.as_ref())?;
+        let newest = keys.iter()
+                         .max_by(|a, b| a.rev.cmp(&b.rev))
+                         .expect("loaded ring keys are never empty")
+                         .clone();
+        Ok(Self::new(name.to_string(),
+                     keys,
+                     newest,
+                     Some(cache_key_path.as_ref().to_path_buf())))
+    }
+
+    /// Load every revision of the ring key `encryption` is a revision of from the key cache.
+    /// Messages are encrypted with `encryption`, even if the key cache holds newer revisions.
+    pub fn load_with_encryption_key<P>(encryption: SymKey, cache_key_path: &P) -> Result<Self>
+        where P: AsRef<Path> + ?Sized
+    {
+        let keys = Self::load_keys(&encryption.name, cache_key_path.as_ref())?;
+        Ok(Self::new(encryption.name.clone(),
+                     keys,
+                     encryption,
+                     Some(cache_key_path.as_ref().to_path_buf())))
+    }
+
+    fn load_keys(name: &str, cache_key_path: &Path) -> Result<Vec<SymKey>> {
+        let keys = SymKey::get_pairs_for(name, cache_key_path)?;
+        if keys.is_empty() {
+            let msg = format!("No revisions found for {} sym key", name);
+            return Err(Error::HabitatCore(habitat_core::Error::CryptoError(msg)));
+        }
+        Ok(keys)
+    }
+
+    /// The name of the ring key.
+    pub fn name(&self) -> &str { &self.name }
+
+    /// The revision used to encrypt messages.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RingKeys::keys` (read)
+    pub fn encryption_key_rkr(&self) -> SymKey { self.keys.read().encryption.clone() }
+
+    /// The revision named `name_with_rev`, if it is known.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RingKeys::keys` (read)
+    pub fn get_rkr(&self, name_with_rev: &str) -> Option<SymKey> {
+        self.keys
+            .read()
+            .keys
+            .iter()
+            .find(|key| key.name_with_rev() == name_with_rev)
+            .cloned()
+    }
+
+    /// Every known revision, newest first.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RingKeys::keys` (read)
+    pub fn revisions_rkr(&self) -> Vec<SymKey> { self.keys.read().keys.clone() }
+
+    /// Reload the revisions from the key cache they were loaded from. Returns `true` if they
+    /// changed. Ring keys that were not loaded from a key cache never change. Messages are still
+    /// encrypted with the same revision afterwards, unless `RingKeyEncryptWithNewest` is set.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RingKeys::keys` (write)
+    pub fn reload_rkw(&self) -> Result<bool> {
+        let cache_key_path = match &self.cache_key_path {
+            Some(cache_key_path) => cache_key_path,
+            None => return Ok(false),
+        };
+        let keys = Self::load_keys(&self.name, cache_key_path)?;
+        let mut current = self.keys.write();
+        let encryption = if self.encrypt_with_newest {
+            keys.iter()
+                .max_by(|a, b| a.rev.cmp(&b.rev))
+                .expect("loaded ring keys are never empty")
+                .clone()
+        } else {
+            current.encryption.clone()
+        };
+        let revisions = Revisions::new(keys, encryption);
+        if *current == revisions {
+            Ok(false)
+        } else {
+            *current = revisions;
+            Ok(true)
+        }
+    }
+}
+
+impl PartialEq for RingKeys {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+        && self.cache_key_path == other.cache_key_path
+        && self.encrypt_with_newest == other.encrypt_with_newest
+        && (Arc::ptr_eq(&self.keys, &other.keys) || *self.keys.read() == *other.keys.read())
+    }
+}
+
+impl From<SymKey> for RingKeys {
+    fn from(key: SymKey) -> Self { Self::new(key.name.clone(), vec![key.clone()], key, None) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message;
+    use tempfile::TempDir;
+
+    fn ring_key(rev: &str) -> SymKey {
+        let mut key = SymKey::generate_pair_for_ring("wolverine");
+        key.rev = rev.to_string();
+        key
+    }
+
+    #[test]
+    fn encrypts_with_the_newest_revision_in_the_key_cache_when_loaded_by_name() {
+        let cache = TempDir::new().expect("Could not create tempdir");
+        let old = ring_key("20160504220722");
+        let new = ring_key("20170504220722");
+        old.to_pair_files(cache.path()).unwrap();
+        new.to_pair_files(cache.path()).unwrap();
+
+        let keys = RingKeys::load("wolverine", cache.path()).unwrap();
+
+        assert_eq!(keys.encryption_key_rkr().name_with_rev(),
+                   new.name_with_rev());
+        assert_eq!(keys.revisions_rkr().len(), 2);
+    }
+
+    #[test]
+    fn encrypts_with_the_given_revision_even_if_a_newer_one_is_cached() {
+        let cache = TempDir::new().expect("Could not create tempdir");
+        let old = ring_key("20160504220722");
+        let new = ring_key("20170504220722");
+        old.to_pair_files(cache.path()).unwrap();
+        new.to_pair_files(cache.path()).unwrap();
+
+        let keys = RingKeys::load_with_encryption_key(old.clone(), cache.path()).unwrap();
+
+        assert_eq!(keys.encryption_key_rkr().name_with_rev(),
+                   old.name_with_rev());
+        assert!(keys.get_rkr(&new.name_with_rev()).is_some());
+    }
+
+    #[test]
+    fn decrypts_messages_sent_with_any_known_revision() {
+        let old = ring_key("20160504220722");
+        let new = ring_key("20170504220722");
+        let sender = RingKeys::from(old.clone());
+        let receiver = RingKeys::new("wolverine".to_string(), vec![old.clone()], new, None);
+        let wire = message::generate_wire(b"gossip".to_vec(), Some(&sender)).unwrap();
+        assert_eq!(message::unwrap_wire(&wire, Some(&receiver)).unwrap(),
+                   b"gossip".to_vec());
+
+        let stranger = RingKeys::from(ring_key("20180504220722"));
+        match message::unwrap_wire(&wire, Some(&stranger)) {
+            Err(Error::UnknownRingKey(_)) => (),
+            other => panic!("Expected Error::UnknownRingKey, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reload_picks_up_revisions_imported_into_the_key_cache_for_decryption() {
+        let cache = TempDir::new().expect("Could not create tempdir");
+        let old = ring_key("20160504220722");
+        old.to_pair_files(cache.path()).unwrap();
+        let keys = RingKeys::load("wolverine", cache.path()).unwrap();
+        let clone = keys.clone();
+        assert!(!keys.reload_rkw().unwrap());
+
+        let new = ring_key("20170504220722");
+        new.to_pair_files(cache.path()).unwrap();
+        assert!(keys.reload_rkw().unwrap());
+        assert!(clone.get_rkr(&new.name_with_rev()).is_some());
+        assert_eq!(clone.encryption_key_rkr().name_with_rev(),
+                   old.name_with_rev(),
+                   "peers may not have the new revision yet");
+    }
+
+    #[test]
+    fn reload_keeps_the_encryption_key_when_its_key_file_is_removed() {
+        let cache = TempDir::new().expect("Could not create tempdir");
+        let old = ring_key("20160504220722");
+        let new = ring_key("20170504220722");
+        old.to_pair_files(cache.path()).unwrap();
+        new.to_pair_files(cache.path()).unwrap();
+        let keys = RingKeys::load_with_encryption_key(old.clone(), cache.path()).unwrap();
+
+        for entry in std::fs::read_dir(cache.path()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.to_string_lossy().contains(&old.rev) {
+                std::fs::remove_file(path).unwrap();
+            }
+        }
+        assert!(!keys.reload_rkw().unwrap());
+        assert_eq!(keys.encryption_key_rkr().name_with_rev(),
+                   old.name_with_rev());
+    }
+
+    #[test]
+    fn reload_switches_to_the_newest_revision_when_asked_to() {
+        let cache = TempDir::new().expect("Could not create tempdir");
+        let old = ring_key("20160504220722");
+        old.to_pair_files(cache.path()).unwrap();
+        let mut keys = RingKeys::load("wolverine", cache.path()).unwrap();
+        keys.encrypt_with_newest = true;
+
+        let new = ring_key("20170504220722");
+        new.to_pair_files(cache.path()).unwrap();
+        assert!(keys.reload_rkw().unwrap());
+        assert_eq!(keys.encryption_key_rkr().name_with_rev(),
+                   new.name_with_rev());
+    }
+}
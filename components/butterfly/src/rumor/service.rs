@@ -2,6 +2,9 @@
 //!
 //! Service rumors declare that a given `Server` is running this Service.
 
+pub use crate::protocol::newscast::service::{CanaryDecision,
+                                             Health as ServiceHealth,
+                                             RolloutStatus};
 use crate::{error::{Error,
                     Result},
             protocol::{self,
@@ -31,6 +34,26 @@ pub struct Service {
     pub pkg:           String,
     pub cfg:           Vec<u8>,
     pub sys:           SysInfo,
+    pub health:        ServiceHealth,
+    pub canary:        Option<Canary>,
+    pub rollout:       Option<Rollout>,
+}
+
+/// An operator decision on the canary stage of a rolling update to `pkg`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Canary {
+    pub pkg:      String,
+    pub decision: CanaryDecision,
+}
+
+/// A rolling update from `from` to `to` led by the member gossiping it. A rollout which is
+/// `RolledBack` tells the rest of the group that `to` was rejected and they should return to
+/// `from`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rollout {
+    pub from:   String,
+    pub to:     String,
+    pub status: RolloutStatus,
 }
 
 impl fmt::Display for Service {
@@ -100,6 +123,9 @@ impl Service {
                   initialized: false,
                   pkg: package.to_string(),
                   sys,
+                  health: ServiceHealth::Unknown,
+                  canary: None,
+                  rollout: None,
                   cfg: cfg.map(|v| {
                               // Directly serializing a toml::value::Table can lead to an error
                               // Wrapping it in a toml::value::Value makes this operation safe
@@ -133,19 +159,53 @@ impl FromProto<newscast::Rumor> for Service {
                      cfg:           payload.cfg.unwrap_or_default(),
                      sys:           payload.sys
                                            .ok_or(Error::ProtocolMismatch("sys"))
-                                           .and_then(SysInfo::from_proto)?, })
+                                           .and_then(SysInfo::from_proto)?,
+                     health:        payload.health
+                                           .and_then(ServiceHealth::from_i32)
+                                           .unwrap_or(ServiceHealth::Unknown),
+                     canary:        match (payload.canary_pkg,
+                                           payload.canary_decision
+                                                  .and_then(CanaryDecision::from_i32))
+                     {
+                         (Some(pkg), Some(decision)) => Some(Canary { pkg, decision }),
+                         _ => None,
+                     },
+                     rollout:       match (payload.rollout_from,
+                                           payload.rollout_to,
+                                           payload.rollout_status
+                                                  .and_then(RolloutStatus::from_i32))
+                     {
+                         (Some(from), Some(to), Some(status)) => {
+                             Some(Rollout { from, to, status })
+                         }
+                         _ => None,
+                     }, })
     }
 }
 
 impl From<Service> for newscast::Service {
     fn from(value: Service) -> Self {
-        newscast::Service { member_id:     Some(value.member_id),
+        let (canary_pkg, canary_decision) = match value.canary {
+            Some(canary) => (Some(canary.pkg), Some(canary.decision as i32)),
+            None => (None, None),
+        };
+        let (rollout_from, rollout_to, rollout_status) = match value.rollout {
+            Some(rollout) => (Some(rollout.from), Some(rollout.to), Some(rollout.status as i32)),
+            None => (None, None, None),
+        };
+        newscast::Service { member_id: Some(value.member_id),
                             service_group: Some(value.service_group.to_string()),
-                            incarnation:   Some(value.incarnation),
-                            initialized:   Some(value.initialized),
-                            pkg:           Some(value.pkg),
-                            cfg:           Some(value.cfg),
-                            sys:           Some(value.sys.into()), }
+                            incarnation: Some(value.incarnation),
+                            initialized: Some(value.initialized),
+                            pkg: Some(value.pkg),
+                            cfg: Some(value.cfg),
+                            sys: Some(value.sys.into()),
+                            health: Some(value.health as i32),
+                            canary_pkg,
+                            canary_decision,
+                            rollout_from,
+                            rollout_to,
+                            rollout_status }
     }
 }
 
@@ -229,9 +289,15 @@ mod tests {
                                  PackageIdent},
                        service::ServiceGroup};
 
-    use super::Service;
-    use crate::rumor::{service::SysInfo,
-                       Rumor};
+    use super::{Canary,
+                CanaryDecision,
+                Rollout,
+                RolloutStatus,
+                Service,
+                ServiceHealth};
+    use crate::{protocol::Message,
+                rumor::{service::SysInfo,
+                        Rumor}};
 
     fn create_service(member_id: &str) -> Service {
         let pkg = PackageIdent::from_str("core/neurosis/1.2.3/20161208121212").unwrap();
@@ -317,6 +383,45 @@ mod tests {
         assert_eq!(s1, s1_check);
     }
 
+    #[test]
+    fn new_services_have_unknown_health() {
+        let s1 = create_service("adam");
+        assert_eq!(s1.health, ServiceHealth::Unknown);
+    }
+
+    #[test]
+    fn health_survives_a_round_trip_through_bytes() {
+        let mut s1 = create_service("adam");
+        s1.health = ServiceHealth::Critical;
+        let bytes = s1.write_to_bytes().unwrap();
+        let s2 = Service::from_bytes(&bytes).unwrap();
+        assert_eq!(s2.health, ServiceHealth::Critical);
+    }
+
+    #[test]
+    fn canary_decision_survives_a_round_trip_through_bytes() {
+        let mut s1 = create_service("adam");
+        let canary = Canary { pkg:      "core/neurosis/1.2.4/20161209121212".to_string(),
+                              decision: CanaryDecision::Abort, };
+        s1.canary = Some(canary.clone());
+        let bytes = s1.write_to_bytes().unwrap();
+        let s2 = Service::from_bytes(&bytes).unwrap();
+        assert_eq!(s2.canary, Some(canary));
+    }
+
+    #[test]
+    fn rollout_survives_a_round_trip_through_bytes() {
+        let mut s1 = create_service("adam");
+        assert_eq!(s1.rollout, None);
+        let rollout = Rollout { from:   "core/neurosis/1.2.3/20161208121212".to_string(),
+                                to:     "core/neurosis/1.2.4/20161209121212".to_string(),
+                                status: RolloutStatus::RolledBack, };
+        s1.rollout = Some(rollout.clone());
+        let bytes = s1.write_to_bytes().unwrap();
+        let s2 = Service::from_bytes(&bytes).unwrap();
+        assert_eq!(s2.rollout, Some(rollout));
+    }
+
     #[test]
     #[should_panic]
     fn service_package_name_mismatch() {
@@ -23,6 +23,7 @@ use crate::{error::{Error,
                      MemberList,
                      MemberListProxy},
             message,
+            ring_keys::RingKeys,
             rumor::{dat_file::{DatFileReader,
                                DatFileWriter},
                     departure::Departure,
@@ -43,7 +44,6 @@ use crate::{error::{Error,
 use habitat_common::{liveliness_checker,
                      sync::Lock,
                      FeatureFlag};
-use habitat_core::crypto::SymKey;
 use prometheus::{HistogramTimer,
                  HistogramVec,
                  IntGauge};
@@ -276,7 +276,7 @@ pub struct Server {
     // depends on it being so. Refactor so it can be private.
     myself:                   Arc<Myself>,
     pub member_list:          Arc<MemberList>,
-    ring_key:                 Arc<Option<SymKey>>,
+    ring_keys:                Arc<Option<RingKeys>>,
     rumor_heat:               Arc<RumorHeat>,
     pub service_store:        RumorStore<Service>,
     pub service_config_store: RumorStore<ServiceConfig>,
@@ -305,7 +305,7 @@ impl Clone for Server {
                  member_id:            self.member_id.clone(),
                  myself:               self.myself.clone(),
                  member_list:          self.member_list.clone(),
-                 ring_key:             self.ring_key.clone(),
+                 ring_keys:            self.ring_keys.clone(),
                  rumor_heat:           self.rumor_heat.clone(),
                  service_store:        self.service_store.clone(),
                  service_config_store: self.service_config_store.clone(),
@@ -330,12 +330,12 @@ impl Clone for Server {
 
 impl Server {
     /// Create a new server, bound to the `addr`, hosting a particular `member`, and with a
-    /// ring_key if you want encryption on the wire, and an optional server name.
+    /// ring_keys if you want encryption on the wire, and an optional server name.
     #[allow(clippy::too_many_arguments)]
     pub fn new(swim_addr: SocketAddr,
                gossip_addr: SocketAddr,
                mut member: Member,
-               ring_key: Option<SymKey>,
+               ring_keys: Option<RingKeys>,
                name: Option<String>,
                // TODO (CM): having data_path as optional is only something
                // that's used in testing, but it cascades outward and
@@ -367,7 +367,7 @@ impl Server {
                             member_id: Arc::new(member_id),
                             myself: Arc::new(myself),
                             member_list: Arc::new(MemberList::new()),
-                            ring_key: Arc::new(ring_key),
+                            ring_keys: Arc::new(ring_keys),
                             rumor_heat: Arc::default(),
                             service_store: RumorStore::default(),
                             service_config_store: RumorStore::default(),
@@ -1207,11 +1207,11 @@ impl Server {
     }
 
     fn generate_wire(&self, payload: Vec<u8>) -> Result<Vec<u8>> {
-        message::generate_wire(payload, (*self.ring_key).as_ref())
+        message::generate_wire(payload, (*self.ring_keys).as_ref())
     }
 
     fn unwrap_wire(&self, payload: &[u8]) -> Result<Vec<u8>> {
-        message::unwrap_wire(payload, (*self.ring_key).as_ref())
+        message::unwrap_wire(payload, (*self.ring_keys).as_ref())
     }
 
     /// # Locking (see locking.md)
@@ -1417,7 +1417,8 @@ mod election_trigger {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rumor::election::Term;
+    use crate::rumor::{election::Term,
+                       service::ServiceHealth};
     use habitat_core::service::ServiceGroup;
     use std::str::FromStr;
 
@@ -1430,7 +1431,10 @@ mod tests {
                   initialized:   Default::default(),
                   pkg:           Default::default(),
                   cfg:           Default::default(),
-                  sys:           Default::default(), }
+                  sys:           Default::default(),
+                  health:        ServiceHealth::Unknown,
+                  canary:        None,
+                  rollout:       None, }
     }
 
     #[test]
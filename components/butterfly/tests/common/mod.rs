@@ -1,6 +1,7 @@
 use habitat_butterfly::{error::Error,
                         member::{Health,
                                  Member},
+                        ring_keys::RingKeys,
                         rumor::{departure::Departure,
                                 election::ElectionStatus,
                                 service::{Service,
@@ -59,7 +60,7 @@ pub fn start_server_smw_rhw(name: &str, ring_key: Option<SymKey>, suitability: u
     let mut server = Server::new(listen_swim,
                                  listen_gossip,
                                  member,
-                                 ring_key,
+                                 ring_key.map(RingKeys::from),
                                  Some(String::from(name)),
                                  None,
                                  Arc::new(NSuitability(suitability))).unwrap();
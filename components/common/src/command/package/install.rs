@@ -18,12 +18,19 @@
 //!
 //! * Download the artifact
 //! * Verify it is un-altered
-//! * Unpack it
+//! * Download and verify its missing dependencies, up to `InstallParallelism` at once
+//! * Unpack the dependencies in dependency order, then the artifact
+//!
+//! `lock` resolves and downloads a package the same way, but records the exact artifacts in a
+//! lockfile instead of installing them, and `start_locked` installs the artifacts in a lockfile.
 
 use crate::{api_client::{self,
                          BuilderAPIClient,
                          Client,
+                         DisplayProgress,
                          Error::APIError},
+            command::package::lockfile::{LockedPackage,
+                                         Lockfile},
             error::{Error,
                     Result},
             templating::{self,
@@ -33,6 +40,9 @@ use crate::{api_client::{self,
             ui::{Status,
                  UIWriter},
             FeatureFlag};
+use futures::{stream,
+              StreamExt,
+              TryStreamExt};
 use glob;
 use habitat_core::{self,
                    crypto::{artifact,
@@ -53,7 +63,6 @@ use habitat_core::{self,
                              PackageTarget},
                    ChannelIdent};
 use reqwest::StatusCode;
-use retry::delay;
 use std::{convert::TryFrom,
           fs::{self,
                File},
@@ -65,11 +74,24 @@ use std::{convert::TryFrom,
           pin::Pin,
           result::Result as StdResult,
           str::FromStr,
+          sync::{atomic::{AtomicBool,
+                          Ordering},
+                 Arc,
+                 Mutex,
+                 MutexGuard},
           time::Duration};
+use tokio::{sync::Mutex as AsyncMutex,
+            task};
 
 pub const RETRIES: usize = 5;
 pub const RETRY_WAIT: Duration = Duration::from_millis(3000);
 
+habitat_core::env_config_int!(/// How many artifacts an install downloads and verifies at once
+                              InstallParallelism,
+                              usize,
+                              HAB_INSTALL_PARALLELISM,
+                              4);
+
 /// Represents a locally-available `.hart` file for package
 /// installation purposes only.
 ///
@@ -367,6 +389,68 @@ pub fn type_erased_start<'a, U>(
                    install_hook_mode))
 }
 
+/// Resolve a package the same way `start` does, ensure the package and all of its transitive
+/// dependencies are in the artifact cache, and return a lockfile of their exact artifacts.
+/// Nothing is installed.
+#[allow(clippy::too_many_arguments)]
+pub async fn lock<U>(ui: &mut U,
+                     url: &str,
+                     channel: &ChannelIdent,
+                     (ident, target): (&PackageIdent, PackageTarget),
+                     product: &str,
+                     version: &str,
+                     fs_root_path: &Path,
+                     artifact_cache_path: &Path,
+                     token: Option<&str>)
+                     -> Result<Lockfile>
+    where U: UIWriter
+{
+    let key_cache_path = &cache_key_path(fs_root_path);
+    let api_client = Client::new(url, product, version, Some(fs_root_path))?;
+    let task = InstallTask { install_mode: &InstallMode::default(),
+                             local_package_usage: &LocalPackageUsage::default(),
+                             api_client,
+                             channel,
+                             fs_root_path,
+                             artifact_cache_path,
+                             key_cache_path,
+                             install_hook_mode: InstallHookMode::Ignore };
+
+    task.lock(ui, (ident.clone(), target), token).await
+}
+
+/// Install exactly the artifacts recorded in `lockfile`, failing if any artifact has a different
+/// checksum or signer than the one that was locked. Packages that are already installed are
+/// verified against the artifacts they were installed from before they are used.
+#[allow(clippy::too_many_arguments)]
+pub async fn start_locked<U>(ui: &mut U,
+                             url: &str,
+                             lockfile: &Lockfile,
+                             product: &str,
+                             version: &str,
+                             fs_root_path: &Path,
+                             artifact_cache_path: &Path,
+                             token: Option<&str>,
+                             install_mode: &InstallMode,
+                             install_hook_mode: InstallHookMode)
+                             -> Result<PackageInstall>
+    where U: UIWriter
+{
+    let key_cache_path = &cache_key_path(fs_root_path);
+    let api_client = Client::new(url, product, version, Some(fs_root_path))?;
+    // Every locked ident is fully qualified, so no channel is ever consulted
+    let task = InstallTask { install_mode,
+                             local_package_usage: &LocalPackageUsage::default(),
+                             api_client,
+                             channel: &ChannelIdent::stable(),
+                             fs_root_path,
+                             artifact_cache_path,
+                             key_cache_path,
+                             install_hook_mode };
+
+    task.with_lockfile(ui, lockfile, token).await
+}
+
 pub async fn check_install_hooks<T, P>(ui: &mut T,
                                        package: &PackageInstall,
                                        fs_root_path: P)
@@ -534,6 +618,88 @@ impl<'a> InstallTask<'a> {
         }
     }
 
+    /// Resolve a package identifier and cache the package and its transitive dependencies,
+    /// recording their exact artifacts in a lockfile.
+    async fn lock<T>(&self,
+                     ui: &mut T,
+                     (ident, target): (PackageIdent, PackageTarget),
+                     token: Option<&str>)
+                     -> Result<Lockfile>
+        where T: UIWriter
+    {
+        ui.begin(format!("Locking {}", &ident))?;
+        let target_ident = self.determine_latest_from_ident(ui, (ident, target), token)
+                               .await?;
+
+        let mut artifact = self.get_cached_artifacts(ui, &[target_ident.clone()], target, token)
+                               .await?
+                               .remove(0);
+        let dependencies = artifact.tdeps()?
+                                   .iter()
+                                   .map(FullyQualifiedPackageIdent::try_from)
+                                   .collect::<StdResult<Vec<_>, _>>()?;
+        let mut artifacts = self.get_cached_artifacts(ui, &dependencies, target, token)
+                                .await?;
+        artifacts.push(artifact);
+
+        let packages = artifacts.iter_mut()
+                                .map(LockedPackage::from_archive)
+                                .collect::<Result<Vec<_>>>()?;
+        ui.end(format!("Locked {} and {} dependencies.",
+                       &target_ident,
+                       dependencies.len()))?;
+        Lockfile::new(packages)
+    }
+
+    /// Install the packages in a lockfile, verifying the artifact of every package, whether it is
+    /// installed already or not, against the lockfile before installing any of them.
+    ///
+    /// An installed package keeps no record of the artifact it was unpacked from, so its
+    /// artifact is taken from the artifact cache, and downloaded again if it is not cached.
+    async fn with_lockfile<T>(&self,
+                              ui: &mut T,
+                              lockfile: &Lockfile,
+                              token: Option<&str>)
+                              -> Result<PackageInstall>
+        where T: UIWriter
+    {
+        let target_ident = &lockfile.locked_package().ident;
+        ui.begin(format!("Installing {} from lockfile", target_ident))?;
+
+        let idents = lockfile.packages()
+                             .iter()
+                             .map(|locked| locked.ident.clone())
+                             .collect::<Vec<_>>();
+        let artifacts =
+            self.get_cached_artifacts(ui, &idents, PackageTarget::active_target(), token)
+                .await?;
+        for (locked, artifact) in lockfile.packages().iter().zip(artifacts.iter()) {
+            locked.verify(artifact)?;
+        }
+
+        let mut artifacts_to_install = Vec::new();
+        for (locked, artifact) in lockfile.packages().iter().zip(artifacts) {
+            if self.installed_package(&locked.ident).is_some() {
+                ui.status(Status::Using, &locked.ident)?;
+            } else {
+                artifacts_to_install.push(artifact);
+            }
+        }
+        for artifact in artifacts_to_install.iter_mut() {
+            self.unpack_artifact(ui, artifact)?;
+        }
+
+        let package_install = PackageInstall::load(target_ident.as_ref(), Some(self.fs_root_path))?;
+        if self.install_hook_mode != InstallHookMode::Ignore {
+            check_install_hooks(ui, &package_install, self.fs_root_path).await?;
+        }
+
+        ui.end(format!("Install of {} complete with {} new packages installed.",
+                       target_ident,
+                       artifacts_to_install.len()))?;
+        Ok(package_install)
+    }
+
     async fn determine_latest_from_ident<T>(&self,
                                             ui: &mut T,
                                             (ident, target): (PackageIdent, PackageTarget),
@@ -639,28 +805,25 @@ impl<'a> InstallTask<'a> {
         where T: UIWriter
     {
         // TODO (CM): rename artifact to archive
-        let mut artifact = self.get_cached_artifact(ui, (ident, target), token).await?;
+        let mut artifact = self.get_cached_artifacts(ui, &[ident.clone()], target, token)
+                               .await?
+                               .remove(0);
 
         // Ensure that all transitive dependencies, as well as the
         // original package itself, are cached locally.
         let dependencies = artifact.tdeps()?;
-        let mut artifacts_to_install = Vec::with_capacity(dependencies.len() + 1);
-        // TODO fn: I'd prefer this list to be a `Vec<FullyQualifiedPackageIdent>` but that
-        // requires a conversion that could fail (i.e. returns a `Result<...>`). Should be
-        // possible though.
+        let mut missing_dependencies = Vec::with_capacity(dependencies.len());
         for dependency in dependencies.iter() {
-            if self.installed_package(&FullyQualifiedPackageIdent::try_from(dependency)?)
-                   .is_some()
-            {
-                ui.status(Status::Using, dependency)?;
+            let dependency = FullyQualifiedPackageIdent::try_from(dependency)?;
+            if self.installed_package(&dependency).is_some() {
+                ui.status(Status::Using, &dependency)?;
             } else {
-                artifacts_to_install.push(self.get_cached_artifact(
-                    ui,
-                    (&FullyQualifiedPackageIdent::try_from(dependency)?, target),
-                    token,
-                ).await?);
+                missing_dependencies.push(dependency);
             }
         }
+        let mut artifacts_to_install =
+            self.get_cached_artifacts(ui, &missing_dependencies, target, token)
+                .await?;
         // The package we're actually trying to install goes last; we
         // want to ensure that its dependencies get installed before
         // it does.
@@ -685,12 +848,48 @@ impl<'a> InstallTask<'a> {
         PackageInstall::load(ident.as_ref(), Some(self.fs_root_path)).map_err(Error::from)
     }
 
+    /// This ensures the identified packages are in the local cache,
+    /// verifies them, and returns handles to their metadata, in the
+    /// same order as `idents`.
+    ///
+    /// Up to `InstallParallelism` packages are downloaded and
+    /// verified at once, sharing a single progress bar.
+    async fn get_cached_artifacts<T>(&self,
+                                     ui: &mut T,
+                                     idents: &[FullyQualifiedPackageIdent],
+                                     target: PackageTarget,
+                                     token: Option<&str>)
+                                     -> Result<Vec<PackageArchive>>
+        where T: UIWriter
+    {
+        let parallelism = usize::from(InstallParallelism::configured_value()).max(1);
+        let progress = ui.progress().map(SharedProgress::new);
+        let ui = Mutex::new(ui);
+        let key_fetch = AsyncMutex::new(());
+        let fetches = idents.iter().map(|ident| {
+                                       self.get_cached_artifact(&ui,
+                                                                (ident, target),
+                                                                token,
+                                                                progress.as_ref(),
+                                                                &key_fetch)
+                                   });
+        let artifacts = stream::iter(fetches).buffered(parallelism)
+                                             .try_collect::<Vec<_>>()
+                                             .await;
+        if let Some(progress) = progress {
+            progress.finish_if_incomplete();
+        }
+        artifacts
+    }
+
     /// This ensures the identified package is in the local cache,
     /// verifies it, and returns a handle to the package's metadata.
     async fn get_cached_artifact<T>(&self,
-                                    ui: &mut T,
+                                    ui: &Mutex<&mut T>,
                                     (ident, target): (&FullyQualifiedPackageIdent, PackageTarget),
-                                    token: Option<&str>)
+                                    token: Option<&str>,
+                                    progress: Option<&SharedProgress>,
+                                    key_fetch: &AsyncMutex<()>)
                                     -> Result<PackageArchive>
         where T: UIWriter
     {
@@ -699,19 +898,14 @@ impl<'a> InstallTask<'a> {
                    ident);
         } else if self.is_offline() {
             return Err(Error::OfflineArtifactNotFound(ident.as_ref().clone()));
-        } else if let Err(err) =
-            retry::retry_future!(delay::Fixed::from(RETRY_WAIT).take(RETRIES),
-                                 self.fetch_artifact(ui, (ident, target), token)).await
-        {
-            return Err(Error::DownloadFailed(format!("We tried {} times but \
-                                                      could not download {}. \
-                                                      Last error was: {}",
-                                                     RETRIES, ident, err)));
+        } else {
+            self.fetch_artifact(ui, (ident, target), token, progress, RETRY_WAIT)
+                .await?;
         }
 
         let mut artifact = PackageArchive::new(self.cached_artifact_path(ident));
-        ui.status(Status::Verifying, artifact.ident()?)?;
-        self.verify_artifact(ui, ident, token, &mut artifact)
+        lock_ui(ui).status(Status::Verifying, artifact.ident()?)?;
+        self.verify_artifact(ui, ident, token, &mut artifact, key_fetch)
             .await?;
         Ok(artifact)
     }
@@ -819,7 +1013,7 @@ impl<'a> InstallTask<'a> {
                 continue;
             }
             let artifact_ident = artifact_ident.unwrap();
-            if artifact_ident.origin == ident.origin && artifact_ident.name == ident.name {
+            if artifact_ident.satisfies(ident) {
                 if latest.is_empty() {
                     latest.push((artifact_ident, artifact));
                 } else if artifact_ident > latest[0].0 {
@@ -872,19 +1066,49 @@ impl<'a> InstallTask<'a> {
 
     /// Retrieve the identified package from the depot, ensuring that
     /// the artifact is cached locally.
+    ///
+    /// A failed download is retried up to `RETRIES` times, waiting
+    /// `retry_wait` before the first retry and twice as long before
+    /// each one after it. Every attempt resumes the download where the
+    /// previous one stopped.
     async fn fetch_artifact<T>(&self,
-                               ui: &mut T,
+                               ui: &Mutex<&mut T>,
                                (ident, target): (&FullyQualifiedPackageIdent, PackageTarget),
-                               token: Option<&str>)
+                               token: Option<&str>,
+                               progress: Option<&SharedProgress>,
+                               retry_wait: Duration)
                                -> Result<()>
         where T: UIWriter
     {
-        ui.status(Status::Downloading, ident)?;
+        let backoff = (0..RETRIES as u32).map(|n| retry_wait * 2u32.pow(n));
+        let progress = progress.cloned().map(ArtifactProgress::new);
+        retry::retry_future!(backoff,
+                             self.fetch_artifact_once(ui,
+                                                      (ident, target),
+                                                      token,
+                                                      progress.as_ref())).await
+            .map_err(|err| {
+                Error::DownloadFailed(format!("We tried {} times but could not download {}. \
+                                               Last error was: {}",
+                                              RETRIES, ident, err))
+            })
+    }
+
+    async fn fetch_artifact_once<T>(&self,
+                                    ui: &Mutex<&mut T>,
+                                    (ident, target): (&FullyQualifiedPackageIdent, PackageTarget),
+                                    token: Option<&str>,
+                                    progress: Option<&ArtifactProgress>)
+                                    -> Result<()>
+        where T: UIWriter
+    {
+        lock_ui(ui).status(Status::Downloading, ident)?;
+        let progress = progress.map(|p| Box::new(p.clone()) as Box<dyn DisplayProgress>);
         match self.api_client
                   .fetch_package((ident.as_ref(), target),
                                  token,
                                  self.artifact_cache_path,
-                                 ui.progress())
+                                 progress)
                   .await
         {
             Ok(_) => Ok(()),
@@ -898,7 +1122,7 @@ impl<'a> InstallTask<'a> {
     }
 
     async fn fetch_origin_key<T>(&self,
-                                 ui: &mut T,
+                                 ui: &Mutex<&mut T>,
                                  name_with_rev: &str,
                                  token: Option<&str>)
                                  -> Result<()>
@@ -907,14 +1131,16 @@ impl<'a> InstallTask<'a> {
         if self.is_offline() {
             Err(Error::OfflineOriginKeyNotFound(name_with_rev.to_string()))
         } else {
-            ui.status(Status::Downloading,
-                      format!("{} public origin key", &name_with_rev))?;
+            lock_ui(ui).status(Status::Downloading,
+                               format!("{} public origin key", &name_with_rev))?;
             let (name, rev) = parse_name_with_rev(&name_with_rev)?;
+            // Keys are tiny, and a progress bar of their own would be drawn over the progress
+            // bar of any artifacts being downloaded at the same time.
             self.api_client
-                .fetch_origin_key(&name, &rev, token, self.key_cache_path, ui.progress())
+                .fetch_origin_key(&name, &rev, token, self.key_cache_path, None)
                 .await?;
-            ui.status(Status::Cached,
-                      format!("{} public origin key", &name_with_rev))?;
+            lock_ui(ui).status(Status::Cached,
+                               format!("{} public origin key", &name_with_rev))?;
             Ok(())
         }
     }
@@ -959,11 +1185,15 @@ impl<'a> InstallTask<'a> {
         Ok(())
     }
 
+    /// Verify the artifact's ident, target and signature, fetching the origin key that signed it
+    /// if it is not cached. `key_fetch` is held while checking for and fetching the key, so
+    /// artifacts verified at the same time download and write each key only once.
     async fn verify_artifact<T>(&self,
-                                ui: &mut T,
+                                ui: &Mutex<&mut T>,
                                 ident: &FullyQualifiedPackageIdent,
                                 token: Option<&str>,
-                                artifact: &mut PackageArchive)
+                                artifact: &mut PackageArchive,
+                                key_fetch: &AsyncMutex<()>)
                                 -> Result<()>
         where T: UIWriter
     {
@@ -988,11 +1218,18 @@ impl<'a> InstallTask<'a> {
         }
 
         let nwr = artifact::artifact_signer(&artifact.path)?;
-        if SigKeyPair::get_public_key_path(&nwr, self.key_cache_path).is_err() {
-            self.fetch_origin_key(ui, &nwr, token).await?;
+        {
+            let _key_fetch = key_fetch.lock().await;
+            if SigKeyPair::get_public_key_path(&nwr, self.key_cache_path).is_err() {
+                self.fetch_origin_key(ui, &nwr, token).await?;
+            }
         }
 
-        artifact.verify(&self.key_cache_path)?;
+        // Verifying hashes the whole artifact, so keep it off the runtime's threads to let other
+        // downloads and verifications carry on meanwhile.
+        let path = artifact.path.clone();
+        let key_cache_path = self.key_cache_path.to_path_buf();
+        task::spawn_blocking(move || artifact::verify(&path, &key_cache_path)).await??;
         debug!("Verified {} signed by {}", ident, &nwr);
         Ok(())
     }
@@ -1070,3 +1307,281 @@ impl<'a> InstallTask<'a> {
         Ok(res)
     }
 }
+
+/// Lock a UI shared by concurrent downloads. The lock must not be held across an `.await`, or
+/// the other downloads would block the thread waiting for it.
+fn lock_ui<'a, 'b, T>(ui: &'a Mutex<&'b mut T>) -> MutexGuard<'a, &'b mut T> {
+    ui.lock().expect("UI lock poisoned")
+}
+
+/// A progress bar shared by concurrent downloads. Its size is the total size of the downloads
+/// started so far, and it advances as any of them makes progress.
+#[derive(Clone)]
+struct SharedProgress(Arc<Mutex<SharedProgressState>>);
+
+struct SharedProgressState {
+    bar:     Box<dyn DisplayProgress>,
+    size:    u64,
+    written: u64,
+}
+
+impl SharedProgress {
+    fn new(bar: Box<dyn DisplayProgress>) -> Self {
+        SharedProgress(Arc::new(Mutex::new(SharedProgressState { bar,
+                                                                 size: 0,
+                                                                 written: 0 })))
+    }
+
+    /// Finish the bar if a download failed part way through. Otherwise, it finished when the last
+    /// byte was written.
+    fn finish_if_incomplete(&self) {
+        let mut state = self.lock();
+        if state.written < state.size {
+            state.bar.finish();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<SharedProgressState> {
+        self.0.lock().expect("SharedProgress lock poisoned")
+    }
+}
+
+impl DisplayProgress for SharedProgress {
+    fn size(&mut self, size: u64) {
+        let mut state = self.lock();
+        state.size += size;
+        let size = state.size;
+        state.bar.size(size);
+    }
+
+    // Each download finishes on its own, but the bar is shared by all of them.
+    fn finish(&mut self) {}
+}
+
+impl io::Write for SharedProgress {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.lock();
+        let n = state.bar.write(buf)?;
+        state.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> { self.lock().bar.flush() }
+}
+
+/// The progress of every attempt to download one artifact. Only the size reported by the first
+/// attempt is added to the shared progress; a retry reports what it has left to download, which
+/// was already counted.
+#[derive(Clone)]
+struct ArtifactProgress {
+    shared: SharedProgress,
+    sized:  Arc<AtomicBool>,
+}
+
+impl ArtifactProgress {
+    fn new(shared: SharedProgress) -> Self {
+        ArtifactProgress { shared,
+                           sized: Arc::new(AtomicBool::new(false)) }
+    }
+}
+
+impl DisplayProgress for ArtifactProgress {
+    fn size(&mut self, size: u64) {
+        if !self.sized.swap(true, Ordering::SeqCst) {
+            self.shared.size(size);
+        }
+    }
+
+    fn finish(&mut self) { self.shared.finish(); }
+}
+
+impl io::Write for ArtifactProgress {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.shared.write(buf) }
+
+    fn flush(&mut self) -> io::Result<()> { self.shared.flush() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::UI;
+    use habitat_core::crypto::hash;
+    use std::{io::{Read,
+                   Write},
+              net::TcpListener,
+              thread};
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct RecordingProgress {
+        sizes:    Arc<Mutex<Vec<u64>>>,
+        written:  Arc<Mutex<u64>>,
+        finished: Arc<Mutex<bool>>,
+    }
+
+    impl DisplayProgress for RecordingProgress {
+        fn size(&mut self, size: u64) { self.sizes.lock().unwrap().push(size); }
+
+        fn finish(&mut self) { *self.finished.lock().unwrap() = true; }
+    }
+
+    impl Write for RecordingProgress {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            *self.written.lock().unwrap() += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn shared_progress_adds_up_concurrent_downloads() {
+        let bar = RecordingProgress::default();
+        let (sizes, written, finished) =
+            (Arc::clone(&bar.sizes), Arc::clone(&bar.written), Arc::clone(&bar.finished));
+        let progress = SharedProgress::new(Box::new(bar));
+
+        let mut first = progress.clone();
+        let mut second = progress.clone();
+        first.size(10);
+        first.write_all(&[0; 4]).unwrap();
+        second.size(5);
+        second.write_all(&[0; 5]).unwrap();
+        second.finish();
+        first.write_all(&[0; 2]).unwrap();
+
+        assert_eq!(*sizes.lock().unwrap(), vec![10, 15]);
+        assert_eq!(*written.lock().unwrap(), 11);
+        assert!(!*finished.lock().unwrap());
+        progress.finish_if_incomplete();
+        assert!(*finished.lock().unwrap());
+    }
+
+    #[test]
+    fn artifact_progress_counts_the_size_of_an_artifact_once() {
+        let bar = RecordingProgress::default();
+        let (sizes, written) = (Arc::clone(&bar.sizes), Arc::clone(&bar.written));
+        let progress = ArtifactProgress::new(SharedProgress::new(Box::new(bar)));
+
+        let mut first_attempt = progress.clone();
+        first_attempt.size(10);
+        first_attempt.write_all(&[0; 4]).unwrap();
+        let mut retry = progress.clone();
+        retry.size(6);
+        retry.write_all(&[0; 6]).unwrap();
+
+        assert_eq!(*sizes.lock().unwrap(), vec![10]);
+        assert_eq!(*written.lock().unwrap(), 10);
+    }
+
+    /// Serve each response to one connection in turn, and return the URL of the server.
+    fn serve(responses: Vec<Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = Vec::new();
+                let mut buf = [0; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buf[..n]);
+                }
+                stream.write_all(&response).unwrap();
+            }
+        });
+        url
+    }
+
+    fn http_response(status: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: \
+                                    close\r\n\r\n",
+                                   status,
+                                   body.len()).into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[tokio::test]
+    async fn fetch_artifact_retries_failed_downloads() {
+        let content = b"HART-1\nthe package";
+        let ident = PackageIdent::from_str("test/pkg/1.0.0/20200101000000").unwrap();
+        let target = PackageTarget::from_str("x86_64-linux").unwrap();
+        let package = serde_json::json!({
+            "ident": ident,
+            "checksum": hash::hash_bytes(content),
+            "manifest": "",
+            "deps": [],
+            "tdeps": [],
+            "exposes": [],
+            "config": ""
+        });
+        let url = serve(vec![http_response("503 Service Unavailable", b""),
+                             http_response("200 OK", package.to_string().as_bytes()),
+                             http_response("503 Service Unavailable", b""),
+                             http_response("200 OK", package.to_string().as_bytes()),
+                             http_response("200 OK", content),]);
+        let tmpdir = TempDir::new().unwrap();
+        let channel = ChannelIdent::stable();
+        let task =
+            InstallTask { install_mode:        &InstallMode::default(),
+                          local_package_usage: &LocalPackageUsage::default(),
+                          api_client:
+                              BuilderAPIClient::new(url.as_str(), "", "", None).unwrap(),
+                          channel:             &channel,
+                          fs_root_path:        tmpdir.path(),
+                          artifact_cache_path: tmpdir.path(),
+                          key_cache_path:      tmpdir.path(),
+                          install_hook_mode:   InstallHookMode::Ignore, };
+        let ident = FullyQualifiedPackageIdent::try_from(&ident).unwrap();
+        let mut ui = UI::with_sinks();
+
+        task.fetch_artifact(&Mutex::new(&mut ui),
+                            (&ident, target),
+                            None,
+                            None,
+                            Duration::from_millis(1))
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(task.cached_artifact_path(&ident)).unwrap(),
+                   content.to_vec());
+    }
+
+    #[tokio::test]
+    async fn fetch_artifact_gives_up_after_its_retries() {
+        let url = serve((0..=RETRIES).map(|_| http_response("503 Service Unavailable", b""))
+                                     .collect());
+        let tmpdir = TempDir::new().unwrap();
+        let channel = ChannelIdent::stable();
+        let task =
+            InstallTask { install_mode:        &InstallMode::default(),
+                          local_package_usage: &LocalPackageUsage::default(),
+                          api_client:
+                              BuilderAPIClient::new(url.as_str(), "", "", None).unwrap(),
+                          channel:             &channel,
+                          fs_root_path:        tmpdir.path(),
+                          artifact_cache_path: tmpdir.path(),
+                          key_cache_path:      tmpdir.path(),
+                          install_hook_mode:   InstallHookMode::Ignore, };
+        let ident = PackageIdent::from_str("test/pkg/1.0.0/20200101000000").unwrap();
+        let ident = FullyQualifiedPackageIdent::try_from(&ident).unwrap();
+        let target = PackageTarget::from_str("x86_64-linux").unwrap();
+        let mut ui = UI::with_sinks();
+
+        match task.fetch_artifact(&Mutex::new(&mut ui),
+                                  (&ident, target),
+                                  None,
+                                  None,
+                                  Duration::from_millis(1))
+                  .await
+        {
+            Err(Error::DownloadFailed(_)) => {}
+            Err(e) => panic!("Expected the download to fail, got {}", e),
+            Ok(()) => panic!("Expected the download to fail"),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Lockfiles pinning the exact artifacts of a package and its transitive dependencies.
+//!
+//! `hab pkg lock` resolves a package the same way `hab pkg install` does and records every
+//! artifact it would install, and `hab pkg install --locked` installs exactly those artifacts:
+//!
+//! ```toml
+//! [[package]]
+//! ident = "core/glibc/2.29/20200305172459"
+//! checksum = "d3ab9d4c..."
+//! signer = "core-20180119235000"
+//!
+//! [[package]]
+//! ident = "core/redis/5.0.7/20200306144805"
+//! checksum = "8f3ae40b..."
+//! signer = "core-20180119235000"
+//! ```
+//!
+//! The package that was locked comes last, after its dependencies.
+
+use crate::error::{Error,
+                   Result};
+use habitat_core::{crypto::artifact,
+                   package::{FullyQualifiedPackageIdent,
+                             PackageArchive},
+                   util::serde_string};
+use std::{convert::TryFrom,
+          fs,
+          path::Path};
+
+/// The exact artifacts making up the install of a package.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lockfile {
+    #[serde(rename = "package")]
+    packages: Vec<LockedPackage>,
+}
+
+impl Lockfile {
+    /// A lockfile of `packages`, which must end with the locked package itself.
+    pub fn new(packages: Vec<LockedPackage>) -> Result<Self> {
+        if packages.is_empty() {
+            return Err(Error::EmptyLockfile);
+        }
+        Ok(Lockfile { packages })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| Error::LockfileRead(path.to_path_buf(), e))?;
+        let lockfile: Lockfile =
+            toml::from_str(&contents).map_err(|e| Error::LockfileParse(path.to_path_buf(), e))?;
+        Lockfile::new(lockfile.packages)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string(self).map_err(Error::TomlSerializeError)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// The locked package followed by its dependencies' packages, with dependencies first.
+    pub fn packages(&self) -> &[LockedPackage] { &self.packages }
+
+    /// The package that was locked.
+    pub fn locked_package(&self) -> &LockedPackage {
+        self.packages
+            .last()
+            .expect("Lockfiles always have a package")
+    }
+}
+
+/// A single artifact recorded in a lockfile.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedPackage {
+    #[serde(with = "serde_string")]
+    pub ident:    FullyQualifiedPackageIdent,
+    /// The BLAKE2b checksum of the artifact.
+    pub checksum: String,
+    /// The name and revision of the origin key that signed the artifact.
+    pub signer:   String,
+}
+
+impl LockedPackage {
+    pub fn from_archive(archive: &mut PackageArchive) -> Result<Self> {
+        Ok(LockedPackage { ident:    FullyQualifiedPackageIdent::try_from(archive.ident()?)?,
+                           checksum: archive.checksum()?,
+                           signer:   artifact::artifact_signer(&archive.path)?, })
+    }
+
+    /// Check that `archive` is the artifact that was locked.
+    pub fn verify(&self, archive: &PackageArchive) -> Result<()> {
+        let checksum = archive.checksum()?;
+        if checksum != self.checksum {
+            return Err(Error::LockedChecksumMismatch(self.ident.clone().into(),
+                                                     checksum,
+                                                     self.checksum.clone()));
+        }
+        let signer = artifact::artifact_signer(&archive.path)?;
+        if signer != self.signer {
+            return Err(Error::LockedSignerMismatch(self.ident.clone().into(),
+                                                   signer,
+                                                   self.signer.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn fixture_archive() -> PackageArchive {
+        let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+                                                                .join("fixtures");
+        PackageArchive::new(fixtures.join("happyhumans-possums-8.1.4-20160427165340-x86_64-linux.\
+                                           hart"))
+    }
+
+    fn locked(ident: &str, checksum: &str) -> LockedPackage {
+        LockedPackage { ident:    ident.parse().unwrap(),
+                        checksum: checksum.to_string(),
+                        signer:   "core-20180119235000".to_string(), }
+    }
+
+    #[test]
+    fn round_trips_through_files() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("habitat.lock");
+        let lockfile =
+            Lockfile::new(vec![locked("core/glibc/2.29/20200305172459", "abc"),
+                               locked("core/redis/5.0.7/20200306144805", "def")]).unwrap();
+        lockfile.write(&path).unwrap();
+
+        let read = Lockfile::read(&path).unwrap();
+        assert_eq!(read, lockfile);
+        assert_eq!(read.locked_package().ident.to_string(),
+                   "core/redis/5.0.7/20200306144805");
+    }
+
+    #[test]
+    fn rejects_invalid_lockfiles() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("habitat.lock");
+
+        fs::write(&path, "").unwrap();
+        match Lockfile::read(&path) {
+            Err(Error::LockfileParse(..)) => (),
+            other => panic!("Expected Error::LockfileParse, got {:?}", other),
+        }
+        fs::write(&path, "package = []").unwrap();
+        match Lockfile::read(&path) {
+            Err(Error::EmptyLockfile) => (),
+            other => panic!("Expected Error::EmptyLockfile, got {:?}", other),
+        }
+        let unqualified = "[[package]]\nident = \"core/redis\"\nchecksum = \"def\"\nsigner = \"\"";
+        fs::write(&path, unqualified).unwrap();
+        match Lockfile::read(&path) {
+            Err(Error::LockfileParse(..)) => (),
+            other => panic!("Expected Error::LockfileParse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn lockfiles_need_a_package() {
+        match Lockfile::new(Vec::new()) {
+            Err(Error::EmptyLockfile) => (),
+            other => panic!("Expected Error::EmptyLockfile, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verifies_artifacts_against_the_lockfile() {
+        let mut archive = fixture_archive();
+        let locked = LockedPackage::from_archive(&mut archive).unwrap();
+        assert_eq!(locked.signer, "happyhumans-20160424223347");
+        locked.verify(&archive).unwrap();
+
+        let other_checksum = LockedPackage { checksum: "abc".to_string(),
+                                             ..locked.clone() };
+        match other_checksum.verify(&archive) {
+            Err(Error::LockedChecksumMismatch(_, actual, expected)) => {
+                assert_eq!(actual, locked.checksum);
+                assert_eq!(expected, "abc");
+            }
+            other => panic!("Expected Error::LockedChecksumMismatch, got {:?}", other),
+        }
+
+        let other_signer = LockedPackage { signer: "core-20180119235000".to_string(),
+                                           ..locked.clone() };
+        match other_signer.verify(&archive) {
+            Err(Error::LockedSignerMismatch(_, actual, expected)) => {
+                assert_eq!(actual, "happyhumans-20160424223347");
+                assert_eq!(expected, "core-20180119235000");
+            }
+            other => panic!("Expected Error::LockedSignerMismatch, got {:?}", other),
+        }
+    }
+}
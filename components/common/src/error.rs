@@ -7,6 +7,7 @@ use std::{env,
           result,
           str,
           string};
+use tokio::task::JoinError;
 use toml;
 
 use crate::{api_client,
@@ -24,10 +25,14 @@ pub enum Error {
     BadGlyphStyle(String),
     CantUploadGossipToml,
     ChannelNotFound,
+    /// Occurs when a package's configuration schema cannot be parsed or compiled
+    ConfigSchema(String),
     CryptoKeyError(String),
     DownloadFailed(String),
     EditorEnv(env::VarError),
     EditStatus,
+    /// Occurs when a lockfile lists no packages.
+    EmptyLockfile,
     FileNameError,
     /// Occurs when a file that should exist does not or could not be read.
     FileNotFound(String),
@@ -35,12 +40,20 @@ pub enum Error {
     HabitatCore(hcore::Error),
     InstallHookFailed(PackageIdent),
     InterpreterNotFound(PackageIdent, Box<Self>),
+    InvalidEventStreamSyslogAddress(String),
     InvalidEventStreamToken(String),
     InvalidInstallHookMode(String),
     /// Occurs when making lower level IO calls.
     IO(io::Error),
     /// Errors when joining paths :)
     JoinPathsError(env::JoinPathsError),
+    /// Occurs when an artifact's checksum differs from the one recorded in a lockfile.
+    LockedChecksumMismatch(PackageIdent, String, String),
+    /// Occurs when an artifact was signed by a different key than the one recorded in a
+    /// lockfile.
+    LockedSignerMismatch(PackageIdent, String, String),
+    LockfileParse(PathBuf, toml::de::Error),
+    LockfileRead(PathBuf, io::Error),
     MissingCLIInputError(String),
     NamedPipeTimeoutOnStart(String, String, io::Error),
     NativeTls(native_tls::Error),
@@ -57,6 +70,7 @@ pub enum Error {
     StatusFileCorrupt(PathBuf),
     StrFromUtf8Error(str::Utf8Error),
     StringFromUtf8Error(string::FromUtf8Error),
+    TaskJoin(JoinError),
     /// When an error occurs registering template file
     // Boxed due to clippy::large_enum_variant
     TemplateFileError(Box<handlebars::TemplateFileError>),
@@ -92,10 +106,12 @@ impl fmt::Display for Error {
                 "Can't upload gossip.toml, it's a reserved file name".to_string()
             }
             Error::ChannelNotFound => "Channel not found".to_string(),
+            Error::ConfigSchema(ref e) => format!("Invalid configuration schema: {}", e),
             Error::CryptoKeyError(ref s) => format!("Missing or invalid key: {}", s),
             Error::DownloadFailed(ref msg) => msg.to_string(),
             Error::EditorEnv(ref e) => format!("Missing EDITOR environment variable: {}", e),
             Error::EditStatus => "Failed edit text command".to_string(),
+            Error::EmptyLockfile => "Lockfile does not list any packages".to_string(),
             Error::FileNameError => "Failed to extract a filename".to_string(),
             Error::FileNotFound(ref e) => format!("File not found at: {}", e),
             Error::GossipFileRelativePath(ref s) => {
@@ -112,6 +128,11 @@ impl fmt::Display for Error {
             Error::InterpreterNotFound(ref ident, ref e) => {
                 format!("Unable to install interpreter ident: {} - {}", ident, e)
             }
+            Error::InvalidEventStreamSyslogAddress(ref s) => {
+                format!("Invalid event stream syslog address provided (must be an absolute path \
+                         to a socket or a host:port pair): '{}'",
+                        s)
+            }
             Error::InvalidEventStreamToken(ref s) => {
                 format!("Invalid event stream token provided: '{}'", s)
             }
@@ -120,6 +141,20 @@ impl fmt::Display for Error {
             }
             Error::IO(ref err) => format!("{}", err),
             Error::JoinPathsError(ref err) => format!("{}", err),
+            Error::LockedChecksumMismatch(ref ident, ref actual, ref expected) => {
+                format!("Artifact for {} has checksum {}, but the lockfile requires {}",
+                        ident, actual, expected)
+            }
+            Error::LockedSignerMismatch(ref ident, ref actual, ref expected) => {
+                format!("Artifact for {} was signed by {}, but the lockfile requires {}",
+                        ident, actual, expected)
+            }
+            Error::LockfileParse(ref path, ref err) => {
+                format!("Failed to parse lockfile {}: {}", path.display(), err)
+            }
+            Error::LockfileRead(ref path, ref err) => {
+                format!("Failed to read lockfile {}: {}", path.display(), err)
+            }
             Error::NamedPipeTimeoutOnStart(ref group, ref hook, ref err) => {
                 format!("Unable to start powershell named pipe for {} hook of {}: {}",
                         hook, group, err)
@@ -152,6 +187,7 @@ impl fmt::Display for Error {
             }
             Error::StrFromUtf8Error(ref e) => format!("{}", e),
             Error::StringFromUtf8Error(ref e) => format!("{}", e),
+            Error::TaskJoin(ref err) => err.to_string(),
             Error::TemplateFileError(ref err) => format!("{:?}", err),
             Error::TemplateRenderError(ref err) => err.to_string(),
             Error::TomlMergeError(ref e) => format!("Failed to merge TOML: {}", e),
@@ -193,6 +229,10 @@ impl From<string::FromUtf8Error> for Error {
     fn from(err: string::FromUtf8Error) -> Self { Error::StringFromUtf8Error(err) }
 }
 
+impl From<JoinError> for Error {
+    fn from(err: JoinError) -> Self { Error::TaskJoin(err) }
+}
+
 impl From<toml::ser::Error> for Error {
     fn from(err: toml::ser::Error) -> Self { Error::TomlSerializeError(err) }
 }
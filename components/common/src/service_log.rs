@@ -0,0 +1,171 @@
+//! The files a service's output is captured in.
+//!
+//! The Launcher appends every line a service prints to its log file. Once the file grows past
+//! `ServiceLogMaxBytes`, it is rotated: `service.log` becomes `service.log.1`, `service.log.1`
+//! becomes `service.log.2`, and so on, keeping at most `ServiceLogMaxFiles` rotated files.
+
+use std::{collections::VecDeque,
+          fs::{self,
+               File,
+               OpenOptions},
+          io::{self,
+               BufRead,
+               BufReader,
+               Write},
+          path::{Path,
+                 PathBuf}};
+
+habitat_core::env_config_int!(/// The size in bytes past which a service's log file is rotated
+                              ServiceLogMaxBytes,
+                              u64,
+                              HAB_SERVICE_LOG_MAX_BYTES,
+                              10 * 1024 * 1024);
+
+habitat_core::env_config_int!(/// The number of rotated log files kept for each service
+                              ServiceLogMaxFiles,
+                              u32,
+                              HAB_SERVICE_LOG_MAX_FILES,
+                              5);
+
+/// A log file which is rotated once it grows past a size limit.
+#[derive(Debug)]
+pub struct RotatingLog {
+    path:      PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    file:      File,
+    size:      u64,
+}
+
+impl RotatingLog {
+    /// Open the log file `path` for appending, creating it if necessary. The file is rotated
+    /// according to `ServiceLogMaxBytes` and `ServiceLogMaxFiles`.
+    pub fn open<P>(path: P) -> io::Result<Self>
+        where P: Into<PathBuf>
+    {
+        Self::with_limits(path,
+                          ServiceLogMaxBytes::configured_value().into(),
+                          ServiceLogMaxFiles::configured_value().into())
+    }
+
+    fn with_limits<P>(path: P, max_bytes: u64, max_files: u32) -> io::Result<Self>
+        where P: Into<PathBuf>
+    {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingLog { path,
+                         max_bytes,
+                         max_files,
+                         file,
+                         size })
+    }
+
+    /// Append a line to the log, rotating it first if the line would not fit.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            for n in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = open_append(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Read the last `lines` lines of the log file `path`, including those already rotated out of
+/// it, oldest first.
+pub fn tail<P>(path: P, lines: usize) -> io::Result<Vec<String>>
+    where P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let mut tail = VecDeque::with_capacity(lines);
+    let mut n = 0;
+    while tail.len() < lines {
+        let file = if n == 0 {
+            path.to_path_buf()
+        } else {
+            rotated_path(path, n)
+        };
+        let file = match File::open(&file) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound && n > 0 => break,
+            Err(e) => return Err(e),
+        };
+        let file_lines = BufReader::new(file).lines().collect::<io::Result<Vec<_>>>()?;
+        for line in file_lines.into_iter().rev().take(lines - tail.len()) {
+            tail.push_front(line);
+        }
+        n += 1;
+    }
+    Ok(tail.into())
+}
+
+/// The path the log file `path` is moved to when it is rotated for the `n`th time.
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", n));
+    rotated.into()
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn rotates_past_the_size_limit() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("logs").join("service.log");
+        // Each line is 6 bytes, so two lines fit in a file.
+        let mut log = RotatingLog::with_limits(&path, 12, 2).unwrap();
+        for line in &["one", "two", "three", "four", "five", "six", "seven"] {
+            log.write_line(&format!("{:5}", line)).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "seven\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+                   "five \nsix  \n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+                   "three\nfour \n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn tails_across_rotated_files() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("service.log");
+        let mut log = RotatingLog::with_limits(&path, 12, 2).unwrap();
+        for line in &["one", "two", "three", "four", "five"] {
+            log.write_line(&format!("{:5}", line)).unwrap();
+        }
+        assert_eq!(tail(&path, 2).unwrap(), vec!["four ", "five "]);
+        assert_eq!(tail(&path, 4).unwrap(),
+                   vec!["two  ", "three", "four ", "five "]);
+        assert_eq!(tail(&path, 10).unwrap().len(), 5);
+        assert!(tail(tmpdir.path().join("missing.log"), 1).is_err());
+    }
+}
@@ -66,6 +66,7 @@ impl TemplateRenderer {
     pub fn new() -> Self {
         let mut handlebars = Handlebars::new();
         handlebars.register_helper("eachAlive", Box::new(helpers::EACH_ALIVE));
+        handlebars.register_helper("eachHealthy", Box::new(helpers::EACH_HEALTHY));
         handlebars.register_helper("pkgPathFor", Box::new(helpers::PKG_PATH_FOR));
         handlebars.register_helper("strConcat", Box::new(helpers::STR_CONCAT));
         handlebars.register_helper("strJoin", Box::new(helpers::STR_JOIN));
@@ -402,6 +403,26 @@ test: something"#
         assert_eq!(each_alive_render, each_if_render);
     }
 
+    #[test]
+    fn each_healthy_helper_content() {
+        let mut renderer = TemplateRenderer::new();
+        renderer.register_template_file("each_healthy", templates().join("each_healthy.txt"))
+                .unwrap();
+
+        // template using an each block with a nested if block filtering on `healthy`
+        renderer.register_template_file("healthy_members", templates().join("healthy_members.txt"))
+                .unwrap();
+
+        let data = service_config_json_from_toml_file("multiple_supervisors_config.toml");
+
+        let each_healthy_render = renderer.render("each_healthy", &data).unwrap();
+        let each_if_render = renderer.render("healthy_members", &data).unwrap();
+
+        assert_eq!(each_healthy_render, each_if_render);
+        assert!(each_healthy_render.contains("b162bfc10cf54eb4bce93689a8023eb9"));
+        assert!(!each_healthy_render.contains("8325c1d9c12543dc83a99f196500f44c"));
+    }
+
     #[tokio::test]
     async fn render_package_install() {
         let root = TempDir::new().expect("create temp dir").into_path();
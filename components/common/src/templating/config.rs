@@ -22,9 +22,13 @@ use std::{self,
                  PathBuf},
           result};
 use toml;
+use valico::json_schema;
 
 static LOGKEY: &str = "CF";
 static ENV_VAR_PREFIX: &str = "HAB";
+/// The name of the optional JSON Schema a package ships next to its `default.toml` to describe
+/// the configuration it accepts.
+pub const CONFIG_SCHEMA_FILE: &str = "config_schema.json";
 /// The maximum TOML table merge depth allowed before failing the operation. The value here is
 /// somewhat arbitrary (stack size cannot be easily computed beforehand and different libc
 /// implementations will impose different size constraints), however a parallel data structure that
@@ -87,6 +91,8 @@ impl PackageConfigPaths for Pkg {
 pub struct Cfg {
     /// Default level configuration loaded by a Package's `default.toml`
     pub default:            Option<toml::value::Table>,
+    /// Configuration schema loaded by a Package's `config_schema.json`
+    pub schema:             Option<serde_json::Value>,
     /// User level configuration loaded by a Service's `user.toml`
     pub user:               Option<toml::value::Table>,
     /// Gossip level configuration loaded by a census group
@@ -107,17 +113,18 @@ impl Cfg {
         where P: PackageConfigPaths
     {
         let override_config_dir = config_from.map(Clone::clone);
-        let default = {
+        let (default, schema) = {
             let pkg_root = match override_config_dir {
                 Some(ref path) => Cow::Borrowed(path),
                 None => Cow::Owned(package.default_config_dir()),
             };
-            Self::load_default(pkg_root.as_ref())?
+            (Self::load_default(pkg_root.as_ref())?, Self::load_schema(pkg_root.as_ref())?)
         };
         let user_config_path = Self::determine_user_config_path(package);
         let user = Self::load_user(user_config_path.get_path())?;
         let environment = Self::load_environment(&package.name())?;
         Ok(Self { default,
+                  schema,
                   user,
                   gossip: None,
                   environment,
@@ -128,37 +135,57 @@ impl Cfg {
 
     /// Validates a service configuration against a configuration interface.
     ///
-    /// Returns `None` if valid and `Some` containing a list of errors if invalid.
-    pub fn validate(interface: &toml::value::Table,
+    /// Returns `None` if valid and `Some` containing a list of errors if invalid. Each error names
+    /// the offending key using its dotted path (ex: `datastore.port`).
+    pub fn validate(interface: &serde_json::Value,
                     cfg: &toml::value::Table)
-                    -> Option<Vec<String>> {
-        let mut errors = vec![];
-        for key in cfg.keys() {
-            if !interface.contains_key(key) {
-                errors.push(format!("Unknown key: {}", key));
-            }
-        }
-        if errors.is_empty() {
-            None
-        } else {
-            Some(errors)
+                    -> Result<Option<Vec<String>>> {
+        let mut scope = json_schema::Scope::new();
+        let schema = scope.compile_and_return(interface.clone(), false)
+                          .map_err(|e| Error::ConfigSchema(format!("{:?}", e)))?;
+        let cfg = serde_json::to_value(cfg).map_err(Error::RenderContextSerialization)?;
+        let state = schema.validate(&cfg);
+        if state.is_valid() {
+            return Ok(None);
         }
+        let errors = state.errors
+                          .iter()
+                          .map(|e| {
+                              let key = e.get_path().trim_start_matches('/').replace('/', ".");
+                              let reason = e.get_detail().unwrap_or_else(|| e.get_title());
+                              if key.is_empty() {
+                                  reason.to_string()
+                              } else {
+                                  format!("{}: {}", key, reason)
+                              }
+                          })
+                          .collect();
+        Ok(Some(errors))
     }
 
-    /// A structured interface which describes configuration keys which are configurable and their
-    /// optional default values.
-    pub fn interface(&self) -> Option<&toml::value::Table> {
-        // JW TODO: For now let's use `default.toml` as it is for the interface. In the future,
-        // we will need to be able to describe more than just the key value relationship that
-        // `default.toml` provides. We will need to be able to describe things like:
-        //
-        // * Keys which have no default
-        // * Key with a default value
-        // * Keys which only accept certain values
-        // * Allowed types for a key
-        self.default.as_ref()
+    /// Validates the configuration the service would have if `gossip` became its gossip layer
+    /// against its configuration interface, so that a partial `hab config apply` is checked
+    /// together with the default, environment, and user layers it is merged with. Each applied
+    /// configuration replaces the gossip layer, so the current gossip layer takes no part.
+    ///
+    /// Returns `None` if the merged configuration is valid or the service has no interface.
+    pub fn validate_gossip(&self, gossip: &toml::value::Table) -> Result<Option<Vec<String>>> {
+        let interface = match self.interface() {
+            Some(interface) => interface,
+            None => return Ok(None),
+        };
+        let mut cfg = self.clone();
+        cfg.gossip = Some(gossip.clone());
+        let merged = toml::Value::try_from(&cfg).map_err(Error::TomlSerializeError)?;
+        Self::validate(interface, &merged.as_table().cloned().unwrap_or_default())
     }
 
+    /// A structured interface which describes the configuration keys a service accepts: their
+    /// types, which keys are required, and the values and ranges they allow. This is the JSON
+    /// Schema shipped in the package's `config_schema.json`. Packages without a schema have no
+    /// interface, and any configuration which parses is accepted.
+    pub fn interface(&self) -> Option<&serde_json::Value> { self.schema.as_ref() }
+
     /// Updates the default layer of the configuration when a service
     /// is updated (because the new release may have changed the
     /// contents and / or structure of the configuration).
@@ -172,13 +199,16 @@ impl Cfg {
     pub fn update_defaults_from_package<P>(&mut self, package: &P) -> Result<bool>
         where P: PackageConfigPaths
     {
-        let incoming_defaults = {
+        let (incoming_defaults, incoming_schema) = {
             let pkg_root = match self.override_config_dir {
                 Some(ref path) => Cow::Borrowed(path),
                 None => Cow::Owned(package.default_config_dir()),
             };
-            Self::load_default(pkg_root.as_ref())?
+            (Self::load_default(pkg_root.as_ref())?, Self::load_schema(pkg_root.as_ref())?)
         };
+        // The schema is not part of the rendered configuration so a change to it alone does not
+        // require rebuilding templated contents.
+        self.schema = incoming_schema;
 
         if incoming_defaults != self.default {
             self.default = incoming_defaults;
@@ -262,6 +292,28 @@ impl Cfg {
         Self::load_toml_file(config_from, "default.toml")
     }
 
+    fn load_schema<T>(config_from: T) -> Result<Option<serde_json::Value>>
+        where T: AsRef<Path>
+    {
+        let path = config_from.as_ref().join(CONFIG_SCHEMA_FILE);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                debug!("Failed to open '{}', {}, {}",
+                       CONFIG_SCHEMA_FILE,
+                       path.display(),
+                       e);
+                return Ok(None);
+            }
+        };
+        let schema = serde_json::from_reader(file).map_err(|e| {
+                                                      Error::ConfigSchema(format!("{}, {}",
+                                                                                  path.display(),
+                                                                                  e))
+                                                  })?;
+        Ok(Some(schema))
+    }
+
     fn determine_user_config_path<P: PackageConfigPaths>(package: &P) -> UserConfigPath {
         let recommended_dir = package.recommended_user_config_dir();
         let recommended_path = recommended_dir.join(USER_CONFIG_FILE);
@@ -945,6 +997,112 @@ mod test {
         assert_eq!(default_toml, toml::to_string(&cfg).unwrap());
     }
 
+    const CONFIG_SCHEMA: &str = r#"{
+        "type": "object",
+        "required": ["port"],
+        "properties": {
+            "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+            "log_level": { "enum": ["debug", "info", "warn", "error"] },
+            "datastore": {
+                "type": "object",
+                "properties": {
+                    "host": { "type": "string" }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn load_config_schema_from_package() {
+        let tmp = TempDir::new().expect("create temp dir");
+        let pkg = TestPkg::new(&tmp);
+        let cfg = Cfg::new(&pkg, None).expect("create config");
+        assert!(cfg.interface().is_none());
+
+        fs::write(pkg.default_config_dir().join(CONFIG_SCHEMA_FILE),
+                  CONFIG_SCHEMA).expect("write schema");
+        let cfg = Cfg::new(&pkg, None).expect("create config");
+        assert!(cfg.interface().is_some());
+    }
+
+    #[test]
+    fn load_malformed_config_schema_fails() {
+        let tmp = TempDir::new().expect("create temp dir");
+        let pkg = TestPkg::new(&tmp);
+        fs::write(pkg.default_config_dir().join(CONFIG_SCHEMA_FILE),
+                  "{ not json").expect("write schema");
+        match Cfg::new(&pkg, None) {
+            Err(Error::ConfigSchema(_)) => (),
+            other => panic!("Expected Error::ConfigSchema, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_accepts_config_matching_schema() {
+        let schema: serde_json::Value = serde_json::from_str(CONFIG_SCHEMA).unwrap();
+        let cfg = toml_from_str(
+                                r#"
+            port = 8080
+            log_level = "info"
+            [datastore]
+            host = "localhost"
+            "#,
+        );
+        assert_eq!(Cfg::validate(&schema, &cfg).unwrap(), None);
+    }
+
+    #[test]
+    fn validate_reports_each_invalid_key() {
+        let schema: serde_json::Value = serde_json::from_str(CONFIG_SCHEMA).unwrap();
+        let cfg = toml_from_str(
+                                r#"
+            log_level = "loud"
+            [datastore]
+            host = 5432
+            "#,
+        );
+        let errors = Cfg::validate(&schema, &cfg).unwrap()
+                                                 .expect("config should be invalid");
+        assert_eq!(errors.len(), 3);
+        for key in &["port: ", "log_level: ", "datastore.host: "] {
+            assert!(errors.iter().any(|e| e.starts_with(key)),
+                    "no error for {} in {:?}",
+                    key,
+                    errors);
+        }
+
+        let cfg = toml_from_str("port = 70000");
+        let errors = Cfg::validate(&schema, &cfg).unwrap()
+                                                 .expect("config should be invalid");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("port: "));
+    }
+
+    #[test]
+    fn validate_gossip_merges_partial_config_with_other_layers() {
+        let tmp = TempDir::new().expect("create temp dir");
+        let pkg = TestPkg::new(&tmp);
+        fs::write(pkg.default_config_dir().join(CONFIG_SCHEMA_FILE),
+                  CONFIG_SCHEMA).expect("write schema");
+        write_toml(&pkg.default_config_dir().join("default.toml"),
+                   "port = 8080\nlog_level = \"info\"");
+        let mut cfg = Cfg::new(&pkg, None).expect("create config");
+        cfg.set_gossip(1, toml_from_str("port = 9000"));
+
+        // `port` is required, but is still provided by default.toml
+        let partial = toml_from_str("log_level = \"debug\"");
+        assert_eq!(Cfg::validate(cfg.interface().unwrap(), &partial).unwrap()
+                                                                    .map(|e| e.len()),
+                   Some(1));
+        assert_eq!(cfg.validate_gossip(&partial).unwrap(), None);
+
+        let errors = cfg.validate_gossip(&toml_from_str("port = 70000"))
+                        .unwrap()
+                        .expect("config should be invalid");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("port: "));
+    }
+
     // env_key: the name of the environment variable the config should
     //     be read from
     // package_name: the name of the package that would read
@@ -10,32 +10,41 @@ use handlebars::{Handlebars,
 use serde_json::Value as Json;
 use std::collections::BTreeMap;
 
+/// Iterates over the service group members which have the boolean field `field` set, such as
+/// the `alive` members.
 #[derive(Clone, Copy)]
-pub struct EachAliveHelper;
+pub struct EachMemberHelper {
+    name:  &'static str,
+    field: &'static str,
+}
 
-impl HelperDef for EachAliveHelper {
+impl EachMemberHelper {
+    fn matches(&self, member: &serde_json::Map<String, Json>) -> bool {
+        member.get(self.field)
+              .and_then(Json::as_bool)
+              .unwrap_or(false)
+    }
+}
+
+impl HelperDef for EachMemberHelper {
     fn call(&self, h: &Helper<'_>, r: &Handlebars, rc: &mut RenderContext<'_>) -> RenderResult<()> {
         let value = h.param(0)
-                     .ok_or_else(|| RenderError::new("Param not found for helper \"eachAlive\""))?;
+                     .ok_or_else(|| {
+                         RenderError::new(format!("Param not found for helper \"{}\"", self.name))
+                     })?;
         if let Some(template) = h.template() {
             rc.promote_local_vars();
             let local_path_root = value.path_root()
                                        .map(|p| format!("{}/{}", rc.get_path(), p));
             let rendered = match (value.value().is_truthy(), value.value()) {
                 (true, &Json::Array(ref list)) => {
-                    let alive_members: Vec<Json> = list.iter()
-                                                       .filter_map(|m| {
-                                                           m.as_object().and_then(|m| {
-                                if m.contains_key("alive") && m["alive"].as_bool().unwrap() {
-                                    Some(to_json(&m))
-                                } else {
-                                    None
-                                }
-                            })
-                                                       })
-                                                       .collect();
-                    let len = alive_members.len();
-                    for (i, alive_member) in alive_members.iter().enumerate() {
+                    let members: Vec<Json> = list.iter()
+                                                 .filter_map(Json::as_object)
+                                                 .filter(|m| self.matches(m))
+                                                 .map(to_json)
+                                                 .collect();
+                    let len = members.len();
+                    for (i, member) in members.iter().enumerate() {
                         let mut local_rc = rc.derive();
                         local_rc.set_local_var("@first".to_string(), to_json(&(i == 0usize)));
                         local_rc.set_local_var("@last".to_string(), to_json(&(i == len - 1)));
@@ -43,7 +52,7 @@ impl HelperDef for EachAliveHelper {
 
                         if let Some(block_param) = h.block_param() {
                             let mut map = BTreeMap::new();
-                            map.insert(block_param.to_string(), to_json(alive_member));
+                            map.insert(block_param.to_string(), to_json(member));
                             local_rc.push_block_context(&map)?;
                         }
 
@@ -57,7 +66,7 @@ impl HelperDef for EachAliveHelper {
                 }
                 (true, &Json::Object(ref obj)) => {
                     let mut first: bool = true;
-                    if !obj.contains_key("alive") || !obj["alive"].as_bool().unwrap() {
+                    if !self.matches(obj) {
                         return Ok(());
                     }
                     for k in obj.keys() {
@@ -117,4 +126,8 @@ impl HelperDef for EachAliveHelper {
     }
 }
 
-pub static EACH_ALIVE: EachAliveHelper = EachAliveHelper;
+pub static EACH_ALIVE: EachMemberHelper = EachMemberHelper { name:  "eachAlive",
+                                                             field: "alive", };
+
+pub static EACH_HEALTHY: EachMemberHelper = EachMemberHelper { name:  "eachHealthy",
+                                                               field: "healthy", };
@@ -301,6 +301,51 @@ impl PartialEq<EventStreamServerCertificate> for EventStreamServerCertificate {
     }
 }
 
+/// Where the syslog event stream sink sends its messages.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "&str", into = "String")]
+pub enum EventStreamSyslogAddress {
+    /// A local Unix datagram socket, such as `/dev/log`.
+    Unix(PathBuf),
+    /// A `host:port` pair of a syslog server listening for UDP datagrams.
+    Udp(String),
+}
+
+impl FromStr for EventStreamSyslogAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        if s.starts_with('/') {
+            return Ok(EventStreamSyslogAddress::Unix(PathBuf::from(s)));
+        }
+        match s.rsplitn(2, ':').collect::<Vec<_>>().as_slice() {
+            [port, host] if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(EventStreamSyslogAddress::Udp(s.to_string()))
+            }
+            _ => Err(Error::InvalidEventStreamSyslogAddress(s.to_string())),
+        }
+    }
+}
+
+impl std::convert::TryFrom<&str> for EventStreamSyslogAddress {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> { EventStreamSyslogAddress::from_str(s) }
+}
+
+impl fmt::Display for EventStreamSyslogAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventStreamSyslogAddress::Unix(path) => write!(f, "{}", path.display()),
+            EventStreamSyslogAddress::Udp(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+impl Into<String> for EventStreamSyslogAddress {
+    fn into(self) -> String { self.to_string() }
+}
+
 habitat_core::env_config_socketaddr!(#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
                                      pub GossipListenAddr,
                                      HAB_LISTEN_GOSSIP,
@@ -407,6 +452,23 @@ mod tests {
         fn cannot_parse_from_empty_string() { assert!("".parse::<EventStreamToken>().is_err()) }
     }
 
+    mod syslog_address {
+        use super::*;
+
+        #[test]
+        fn parses_socket_paths_and_udp_addresses() {
+            assert_eq!("/dev/log".parse::<EventStreamSyslogAddress>().unwrap(),
+                       EventStreamSyslogAddress::Unix(PathBuf::from("/dev/log")));
+            assert_eq!("logs.example.com:514".parse::<EventStreamSyslogAddress>()
+                                             .unwrap(),
+                       EventStreamSyslogAddress::Udp(String::from("logs.example.com:514")));
+            assert!("logs.example.com".parse::<EventStreamSyslogAddress>()
+                                      .is_err());
+            assert!(":514".parse::<EventStreamSyslogAddress>().is_err());
+            assert!("dev/log".parse::<EventStreamSyslogAddress>().is_err());
+        }
+    }
+
     mod gossip_listen_addr {
         use super::*;
         #[test]
@@ -910,6 +910,9 @@ impl DisplayProgress for ConsoleProgressBar {
         self.bar.show_tick = true;
         self.bar.message("    ");
         self.total = size;
+        // The size of a download shared with others grows as they start, so keep any progress
+        // already made.
+        self.bar.set(self.current);
     }
 
     fn finish(&mut self) {
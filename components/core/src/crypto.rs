@@ -118,10 +118,12 @@
 //! 1. The last line is left empty, meaning that 2 newline characters (`\n`) separate the header
 //!    from the payload
 //!
-//! The remainder of the file is a compressed tarball of the contents to be extracted on disk. At
-//! present, the tarball is compressed using `xz` but is considered an implementation detail. Also
-//! note unlike the format of keys, the compressed tarball is **not** Base64 encoded--it is the
-//! compressed tarball itself.
+//! The remainder of the file is a compressed tarball of the contents to be extracted on disk. A
+//! `HART-1` artifact's tarball is always compressed using `xz`, while a `HART-2` artifact's tarball
+//! may be compressed using either `xz` or `zstd`, which is detected from the tarball itself. Since
+//! older clients can only read `HART-1` artifacts, `xz`-compressed tarballs are still signed as
+//! `HART-1`. Also note unlike the format of keys, the compressed tarball is **not** Base64
+//! encoded--it is the compressed tarball itself.
 //!
 //! Note that the BLAKE2b hash functions use a digest length of 32 bytes (256 bits!). More details
 //! about the hashing strategy can be found in the [libsodium hashing
@@ -171,6 +173,12 @@
 //! tail -n +6 /tmp/somefile.hart | xzcat | tar x -C /
 //! ```
 //!
+//! or, for a `zstd`-compressed tarball:
+//!
+//! ```text
+//! tail -n +6 /tmp/somefile.hart | zstdcat | tar x -C /
+//! ```
+//!
 //! **Caution!** Working with Habitat artifacts in this manner this is not normally recommended and
 //! is **not** a supported workflow for working with Habitat artifacts--they are signed for very
 //! important reasons.
@@ -232,7 +240,10 @@ pub static SIG_HASH_TYPE: &str = "BLAKE2b";
 /// This environment variable allows you to override the fs::CACHE_KEY_PATH
 /// at runtime. This is useful for testing.
 pub static CACHE_KEY_PATH_ENV_VAR: &str = "HAB_CACHE_KEY_PATH";
-pub static HART_FORMAT_VERSION: &str = "HART-1";
+/// The newest artifact format version, whose tarball may be compressed with `xz` or `zstd`
+pub static HART_FORMAT_VERSION: &str = "HART-2";
+/// The original artifact format version, whose tarball is always compressed with `xz`
+pub static HART_1_FORMAT_VERSION: &str = "HART-1";
 pub static BOX_FORMAT_VERSION: &str = "BOX-1";
 pub static ANONYMOUS_BOX_FORMAT_VERSION: &str = "ANONYMOUS-BOX-1";
 /// Create secret key files with these permissions
@@ -1,4 +1,5 @@
-use std::{fs::File,
+use std::{fmt,
+          fs::File,
           io::{self,
                prelude::*,
                BufReader,
@@ -7,29 +8,96 @@ use std::{fs::File,
 
 use base64;
 use sodiumoxide::crypto::sign;
+use xz2::{bufread::XzDecoder,
+          write::XzEncoder};
 
 use super::{hash,
             keys::parse_name_with_rev,
             SigKeyPair,
+            HART_1_FORMAT_VERSION,
             HART_FORMAT_VERSION,
             SIG_HASH_TYPE};
 use crate::error::{Error,
                    Result};
 
-/// Generate and sign a package
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+// Artifacts are compressed once and decompressed on every install, so favor smaller tarballs over
+// faster compression.
+const XZ_LEVEL: u32 = 6;
+const ZSTD_LEVEL: i32 = 19;
+
+/// The compression of the tarball carried by an artifact.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression of the tarball at the start of `reader` without consuming any of
+    /// it. Returns `None` if the tarball isn't compressed.
+    pub fn detect<R: BufRead>(reader: &mut R) -> Result<Option<Self>> {
+        let buffer = reader.fill_buf()?;
+        if buffer.starts_with(XZ_MAGIC) {
+            Ok(Some(Compression::Xz))
+        } else if buffer.starts_with(ZSTD_MAGIC) {
+            Ok(Some(Compression::Zstd))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The oldest artifact format version able to carry a tarball with this compression.
+    pub fn format_version(self) -> &'static str {
+        match self {
+            Compression::Xz => HART_1_FORMAT_VERSION,
+            Compression::Zstd => HART_FORMAT_VERSION,
+        }
+    }
+
+    /// Decompress the tarball read from `reader`, which must have this compression.
+    pub fn decoder<'a, R: BufRead + 'a>(self, reader: R) -> Result<Box<dyn Read + 'a>> {
+        match self {
+            Compression::Xz => Ok(Box::new(XzDecoder::new(reader))),
+            Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Xz => write!(f, "xz"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+fn is_supported_format_version(format_version: &str) -> bool {
+    format_version == HART_FORMAT_VERSION || format_version == HART_1_FORMAT_VERSION
+}
+
+/// Generate and sign a package. The artifact gets the oldest format version able to carry the
+/// source archive's compression, so that `xz`-compressed packages remain readable by clients
+/// which only understand `HART-1`.
 pub fn sign<P1: ?Sized, P2: ?Sized>(src: &P1, dst: &P2, pair: &SigKeyPair) -> Result<()>
     where P1: AsRef<Path>,
           P2: AsRef<Path>
 {
     let hash = hash::hash_file(&src)?;
     debug!("File hash for {} = {}", src.as_ref().display(), &hash);
+    let format_version = {
+        let mut reader = BufReader::new(File::open(src)?);
+        Compression::detect(&mut reader)?.map_or(HART_1_FORMAT_VERSION, Compression::format_version)
+    };
 
     let signature = sign::sign(&hash.as_bytes(), pair.secret()?);
     let output_file = File::create(dst)?;
     let mut writer = BufWriter::new(&output_file);
     write!(writer,
            "{}\n{}\n{}\n{}\n\n",
-           HART_FORMAT_VERSION,
+           format_version,
            pair.name_with_rev(),
            SIG_HASH_TYPE,
            base64::encode(&signature))?;
@@ -38,6 +106,46 @@ pub fn sign<P1: ?Sized, P2: ?Sized>(src: &P1, dst: &P2, pair: &SigKeyPair) -> Re
     Ok(())
 }
 
+/// Write the tarball in the source archive `src`, which may be uncompressed or compressed with
+/// `xz` or `zstd`, to `dst` with `compression`.
+pub fn recompress<P1: ?Sized, P2: ?Sized>(src: &P1,
+                                          dst: &P2,
+                                          compression: Compression)
+                                          -> Result<()>
+    where P1: AsRef<Path>,
+          P2: AsRef<Path>
+{
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut tarball: Box<dyn Read> = match Compression::detect(&mut reader)? {
+        Some(current) => current.decoder(reader)?,
+        None => Box::new(reader),
+    };
+    let writer = BufWriter::new(File::create(dst)?);
+    let mut writer = match compression {
+        Compression::Xz => {
+            let mut encoder = XzEncoder::new(writer, XZ_LEVEL);
+            io::copy(&mut tarball, &mut encoder)?;
+            encoder.finish()?
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, ZSTD_LEVEL)?;
+            io::copy(&mut tarball, &mut encoder)?;
+            encoder.finish()?
+        }
+    };
+    writer.flush()?;
+    Ok(())
+}
+
+/// Detect the compression of the tarball in a .hart file, returning `None` if it is not
+/// compressed.
+pub fn payload_compression<P: ?Sized>(src: &P) -> Result<Option<Compression>>
+    where P: AsRef<Path>
+{
+    let mut reader = get_archive_reader(&src)?;
+    Compression::detect(&mut reader)
+}
+
 /// return a BufReader to the .tar bytestream, skipping the signed header
 pub fn get_archive_reader<P: AsRef<Path>>(src: &P) -> Result<BufReader<File>> {
     let f = File::open(src)?;
@@ -134,7 +242,7 @@ pub fn verify<P1: ?Sized, P2: ?Sized>(src: &P1, cache_key_path: &P2) -> Result<(
     let f = File::open(src)?;
     let mut reader = BufReader::new(f);
 
-    let _ = {
+    let format_version = {
         let mut buffer = String::new();
         match reader.read_line(&mut buffer) {
             Ok(0) => {
@@ -143,7 +251,7 @@ pub fn verify<P1: ?Sized, P2: ?Sized>(src: &P1, cache_key_path: &P2) -> Result<(
                                                        .to_string()));
             }
             Ok(_) => {
-                if buffer.trim() != HART_FORMAT_VERSION {
+                if !is_supported_format_version(buffer.trim()) {
                     let msg = format!("Unsupported format version: {}", &buffer.trim());
                     return Err(Error::CryptoError(msg));
                 }
@@ -204,6 +312,14 @@ pub fn verify<P1: ?Sized, P2: ?Sized>(src: &P1, cache_key_path: &P2) -> Result<(
                                                   .to_string()));
         }
     };
+    if format_version == HART_1_FORMAT_VERSION
+       && Compression::detect(&mut reader)? == Some(Compression::Zstd)
+    {
+        let msg = format!("Unsupported compression for {} artifact: {}",
+                          format_version,
+                          Compression::Zstd);
+        return Err(Error::CryptoError(msg));
+    }
     let expected_hash = match sign::verify(signature.as_slice(), pair.public()?) {
         Ok(signed_data) => String::from_utf8(signed_data).map_err(|_| {
                                Error::CryptoError("Error parsing artifact signature".to_string())
@@ -234,7 +350,7 @@ pub fn artifact_signer<P: AsRef<Path>>(src: &P) -> Result<String> {
                                                        .to_string()));
             }
             Ok(_) => {
-                if buffer.trim() != HART_FORMAT_VERSION {
+                if !is_supported_format_version(buffer.trim()) {
                     let msg = format!("Unsupported format version: {}", &buffer.trim());
                     return Err(Error::CryptoError(msg));
                 }
@@ -270,6 +386,7 @@ mod test {
     use super::{super::{keys::parse_name_with_rev,
                         test_support::*,
                         SigKeyPair,
+                        HART_1_FORMAT_VERSION,
                         HART_FORMAT_VERSION,
                         SIG_HASH_TYPE},
                 *};
@@ -285,6 +402,46 @@ mod test {
         verify(&dst, cache.path()).unwrap();
     }
 
+    #[test]
+    fn sign_and_verify_zstd() {
+        let cache = Builder::new().prefix("key_cache").tempdir().unwrap();
+        let pair = SigKeyPair::generate_pair_for_origin("unicorn");
+        pair.to_pair_files(cache.path()).unwrap();
+        let src = cache.path().join("src.tar.zst");
+        let dst = cache.path().join("signed.dat");
+
+        recompress(&fixture("signme.dat"), &src, Compression::Zstd).unwrap();
+        sign(&src, &dst, &pair).unwrap();
+        verify(&dst, cache.path()).unwrap();
+        assert_eq!(HART_FORMAT_VERSION,
+                   get_artifact_header(&dst).unwrap().format_version);
+        assert_eq!(Some(Compression::Zstd), payload_compression(&dst).unwrap());
+
+        let mut tarball = Vec::new();
+        Compression::Zstd.decoder(get_archive_reader(&dst).unwrap())
+                         .unwrap()
+                         .read_to_end(&mut tarball)
+                         .unwrap();
+        assert_eq!(fs::read(fixture("signme.dat")).unwrap(), tarball);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported compression for HART-1 artifact: zstd")]
+    fn verify_zstd_hart_1() {
+        let cache = Builder::new().prefix("key_cache").tempdir().unwrap();
+        let pair = SigKeyPair::generate_pair_for_origin("unicorn");
+        pair.to_pair_files(cache.path()).unwrap();
+        let src = cache.path().join("src.tar.zst");
+        let dst = cache.path().join("signed.dat");
+        recompress(&fixture("signme.dat"), &src, Compression::Zstd).unwrap();
+        sign(&src, &dst, &pair).unwrap();
+
+        let signed = fs::read(&dst).unwrap();
+        let hart_1 = [&b"HART-1"[..], &signed[HART_FORMAT_VERSION.len()..]].concat();
+        fs::write(&dst, hart_1).unwrap();
+        verify(&dst, cache.path()).unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "Secret key is required but not present for")]
     fn sign_missing_private_key() {
@@ -502,7 +659,7 @@ mod test {
         sign(&src, &dst, &pair).unwrap();
 
         let hart_header = get_artifact_header(&dst).unwrap();
-        assert_eq!(HART_FORMAT_VERSION, hart_header.format_version);
+        assert_eq!(HART_1_FORMAT_VERSION, hart_header.format_version);
         let (key_name, _rev) = parse_name_with_rev(&hart_header.key_name).unwrap();
         assert_eq!("unicorn", key_name);
         assert_eq!(SIG_HASH_TYPE, hart_header.hash_type);
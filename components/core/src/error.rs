@@ -85,6 +85,10 @@ pub enum Error {
     FullyQualifiedPackageIdentRequired(String),
     /// Occurs when a service binding cannot be successfully parsed.
     InvalidBinding(String),
+    /// Occurs when a built-in health check string cannot be successfully parsed.
+    InvalidHealthCheck(String),
+    /// Occurs when a rolling update max unavailable string cannot be successfully parsed.
+    InvalidMaxUnavailable(String),
     /// Occurs when a package identifier string cannot be successfully parsed.
     InvalidPackageIdent(String),
     /// Occurs when a package target string cannot be successfully parsed.
@@ -93,6 +97,8 @@ pub enum Error {
     InvalidPackageType(String),
     /// Occurs when a service group string cannot be successfully parsed.
     InvalidServiceGroup(String),
+    /// Occurs when a package version constraint string cannot be successfully parsed.
+    InvalidVersionConstraint(String),
     /// Occurs when an origin is in an invalid format
     InvalidOrigin(String),
     /// Occurs when an OsString path cannot be converted to a String
@@ -278,12 +284,28 @@ impl fmt::Display for Error {
                          architecture-platform (example: x86_64-linux)",
                         e)
             }
+            Error::InvalidHealthCheck(ref e) => {
+                format!("Invalid health check: {}. A valid health check is in the form \
+                         tcp://host:port, http(s)://host:port/path, or exec:command [args...]",
+                        e)
+            }
+            Error::InvalidMaxUnavailable(ref e) => {
+                format!("Invalid max unavailable: {}. A valid max unavailable is a positive count \
+                         (example: 3) or a percentage (example: 25%)",
+                        e)
+            }
             Error::InvalidPackageType(ref e) => format!("Invalid package type: {}.", e),
             Error::InvalidServiceGroup(ref e) => {
                 format!("Invalid service group: {}. A valid service group string is in the form \
                          service.group (example: redis.production)",
                         e)
             }
+            Error::InvalidVersionConstraint(ref e) => {
+                format!("Invalid version constraint: {:?}. A valid constraint is a \
+                         comma-separated list of versions, each optionally preceded by =, >, >=, \
+                         <, or <= (example: >=4.0,<5)",
+                        e)
+            }
             Error::InvalidOrigin(ref origin) => {
                 format!("Invalid origin: {}. Origins must begin with a lowercase letter or \
                          number. Allowed characters include lowercase letters, numbers, -, and _. \
@@ -320,6 +320,11 @@ pub fn svc_logs_path<T: AsRef<Path>>(service_name: T) -> PathBuf {
     svc_path(service_name).join("logs")
 }
 
+/// Returns the path to the file a given service's output is captured in.
+pub fn svc_log_file<T: AsRef<Path>>(service_name: T) -> PathBuf {
+    svc_logs_path(service_name).join("service.log")
+}
+
 /// Returns the path to a given service's pid file.
 pub fn svc_pid_file<T: AsRef<Path>>(service_name: T) -> PathBuf {
     svc_path(service_name).join("PID")
@@ -1,4 +1,5 @@
 pub mod archive;
+pub mod constraint;
 pub mod ident;
 pub mod install;
 pub mod list;
@@ -8,6 +9,7 @@ pub mod target;
 
 pub use self::{archive::{FromArchive,
                          PackageArchive},
+               constraint::VersionConstraint,
                ident::{FullyQualifiedPackageIdent,
                        Identifiable,
                        PackageIdent},
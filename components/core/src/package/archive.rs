@@ -3,7 +3,8 @@ use super::{metadata::{MetaFile,
             Identifiable,
             PackageIdent,
             PackageTarget};
-use crate::{crypto::{artifact,
+use crate::{crypto::{artifact::{self,
+                                Compression},
                      hash},
             error::{Error,
                     Result}};
@@ -371,11 +372,7 @@ impl PackageArchive {
     /// * If the package cannot be unpacked
     pub fn unpack(&self, fs_root_path: Option<&Path>) -> Result<()> {
         let root = fs_root_path.unwrap_or_else(|| Path::new("/"));
-        let tar_reader = artifact::get_archive_reader(&self.path)?;
-        let mut builder = reader::Builder::new();
-        builder.support_format(ReadFormat::Gnutar)?;
-        builder.support_filter(ReadFilter::Xz)?;
-        let mut reader = builder.open_stream(tar_reader)?;
+        let mut reader = self.tarball_reader()?;
         let writer = writer::Disk::new();
         let mut extract_options = ExtractOptions::new();
         extract_options.add(ExtractOption::Time);
@@ -387,6 +384,29 @@ impl PackageArchive {
         Ok(())
     }
 
+    /// Open the tarball in the artifact, which libarchive decompresses itself when it is
+    /// compressed with xz but which we decompress for it when it is compressed with zstd.
+    /// Uncompressed tarballs are read as they are.
+    fn tarball_reader(&self) -> Result<reader::StreamReader> {
+        let mut tar_reader = artifact::get_archive_reader(&self.path)?;
+        let mut builder = reader::Builder::new();
+        builder.support_format(ReadFormat::Gnutar)?;
+        match Compression::detect(&mut tar_reader)? {
+            Some(Compression::Xz) => {
+                builder.support_filter(ReadFilter::Xz)?;
+                Ok(builder.open_stream(tar_reader)?)
+            }
+            Some(Compression::Zstd) => {
+                builder.support_filter(ReadFilter::None)?;
+                Ok(builder.open_stream(Compression::Zstd.decoder(tar_reader)?)?)
+            }
+            None => {
+                builder.support_filter(ReadFilter::None)?;
+                Ok(builder.open_stream(tar_reader)?)
+            }
+        }
+    }
+
     fn read_deps(&mut self, file: MetaFile) -> Result<Vec<PackageIdent>> {
         let mut deps: Vec<PackageIdent> = vec![];
 
@@ -418,11 +438,7 @@ impl PackageArchive {
         }
         let mut metadata = Metadata::new();
         let mut matched_count = 0u8;
-        let tar_reader = artifact::get_archive_reader(&self.path)?;
-        let mut builder = reader::Builder::new();
-        builder.support_format(ReadFormat::Gnutar)?;
-        builder.support_filter(ReadFilter::Xz)?;
-        let mut reader = builder.open_stream(tar_reader)?;
+        let mut reader = self.tarball_reader()?;
         loop {
             let mut matched_type: Option<MetaFile> = None;
             if let Some(entry) = reader.next_header() {
@@ -484,7 +500,10 @@ pub trait FromArchive: Sized {
 mod test {
     use super::{super::target,
                 *};
-    use std::path::PathBuf;
+    use crate::crypto::SigKeyPair;
+    use std::{fs::File,
+              io,
+              path::PathBuf};
 
     #[test]
     fn reading_artifact_metadata() {
@@ -501,6 +520,45 @@ mod test {
 
     pub fn fixtures() -> PathBuf { root().join("fixtures") }
 
+    #[test]
+    fn reading_and_unpacking_zstd_artifact() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let xz_tarball = tmpdir.path().join("possums.tar.xz");
+        let zstd_tarball = tmpdir.path().join("possums.tar.zst");
+        let signed = tmpdir.path().join("possums.hart");
+        let source = fixtures().join("happyhumans-possums-8.1.4-20160427165340-x86_64-linux.hart");
+        io::copy(&mut artifact::get_archive_reader(&source).unwrap(),
+                 &mut File::create(&xz_tarball).unwrap()).unwrap();
+        artifact::recompress(&xz_tarball, &zstd_tarball, Compression::Zstd).unwrap();
+        let pair = SigKeyPair::generate_pair_for_origin("happyhumans");
+        artifact::sign(&zstd_tarball, &signed, &pair).unwrap();
+
+        let mut hart = PackageArchive::new(signed);
+        assert_eq!(hart.ident().unwrap().to_string(),
+                   "happyhumans/possums/8.1.4/20160427165340");
+        let root = tmpdir.path().join("root");
+        hart.unpack(Some(&root)).unwrap();
+        assert!(root.join("hab/pkgs/happyhumans/possums/8.1.4/20160427165340")
+                    .is_dir());
+    }
+
+    #[test]
+    fn reading_uncompressed_artifact() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let tarball = tmpdir.path().join("possums.tar");
+        let signed = tmpdir.path().join("possums.hart");
+        let source = fixtures().join("happyhumans-possums-8.1.4-20160427165340-x86_64-linux.hart");
+        io::copy(&mut Compression::Xz.decoder(artifact::get_archive_reader(&source).unwrap())
+                                     .unwrap(),
+                 &mut File::create(&tarball).unwrap()).unwrap();
+        let pair = SigKeyPair::generate_pair_for_origin("happyhumans");
+        artifact::sign(&tarball, &signed, &pair).unwrap();
+
+        let mut hart = PackageArchive::new(signed);
+        assert_eq!(hart.ident().unwrap().to_string(),
+                   "happyhumans/possums/8.1.4/20160427165340");
+    }
+
     #[test]
     fn reading_artifact_deps() {
         let mut hart = PackageArchive::new(fixtures().join("happyhumans-possums-8.1.\
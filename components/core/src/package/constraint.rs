@@ -0,0 +1,180 @@
+//! Constraints on the version of a package.
+//!
+//! A constraint follows the name of a package after an `@`, and is a comma-separated list of
+//! comparisons, all of which a version must satisfy:
+//!
+//! ```text
+//! core/redis@>=4.0,<5
+//! ```
+//!
+//! Versions are compared with `version_sort`, so a pre-release such as `5.0.0-rc1` sorts before
+//! `5.0.0` and satisfies `<5`. A comparison without an operator requires an equal version.
+
+use super::ident::version_sort;
+use crate::error::Error;
+use serde::{Deserialize,
+            Deserializer,
+            Serialize,
+            Serializer};
+use std::{cmp::Ordering,
+          fmt,
+          result,
+          str::FromStr};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Operator {
+    Equal,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Operator {
+    // Longer operators come first so that `>=` isn't read as `>` followed by `=1.0`.
+    const ALL: [(&'static str, Operator); 5] = [(">=", Operator::GreaterOrEqual),
+                                                ("<=", Operator::LessOrEqual),
+                                                (">", Operator::Greater),
+                                                ("<", Operator::Less),
+                                                ("=", Operator::Equal)];
+
+    fn allows(self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterOrEqual => ordering != Ordering::Less,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessOrEqual => ordering != Ordering::Greater,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::Equal => "=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Comparison {
+    operator: Operator,
+    version:  String,
+}
+
+impl Comparison {
+    fn matches(&self, version: &str) -> bool {
+        version_sort(version, &self.version).map(|ordering| self.operator.allows(ordering))
+                                            .unwrap_or(false)
+    }
+}
+
+/// A set of comparisons the version of a package must satisfy.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VersionConstraint(Vec<Comparison>);
+
+impl VersionConstraint {
+    /// Returns true if `version` satisfies every comparison in the constraint. Versions which
+    /// cannot be compared, such as `master`, never match.
+    pub fn matches(&self, version: &str) -> bool {
+        self.0.iter().all(|comparison| comparison.matches(version))
+    }
+}
+
+impl FromStr for VersionConstraint {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let mut comparisons = Vec::new();
+        for comparison in value.split(',').map(str::trim) {
+            let (operator, version) =
+                Operator::ALL.iter()
+                             .find(|(prefix, _)| comparison.starts_with(prefix))
+                             .map(|(prefix, operator)| (*operator, &comparison[prefix.len()..]))
+                             .unwrap_or((Operator::Equal, comparison));
+            let version = version.trim();
+            if !version.starts_with(|c: char| c.is_ascii_digit())
+               || version_sort(version, version).is_err()
+            {
+                return Err(Error::InvalidVersionConstraint(value.to_string()));
+            }
+            comparisons.push(Comparison { operator,
+                                          version: version.to_string() });
+        }
+        Ok(VersionConstraint(comparisons))
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparisons = self.0
+                              .iter()
+                              .map(|c| format!("{}{}", c.operator.as_str(), c.version))
+                              .collect::<Vec<_>>();
+        write!(f, "{}", comparisons.join(","))
+    }
+}
+
+impl Serialize for VersionConstraint {
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionConstraint {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_every_comparison() {
+        let constraint: VersionConstraint = ">=1.4, <2".parse().unwrap();
+        assert!(constraint.matches("1.4"));
+        assert!(constraint.matches("1.4.0"));
+        assert!(constraint.matches("1.10.2"));
+        assert!(!constraint.matches("1.3.9"));
+        assert!(!constraint.matches("2.0.0"));
+        assert!(constraint.matches("2.0.0-rc1"));
+        assert!(!constraint.matches("master"));
+
+        let exact: VersionConstraint = "4.0.9".parse().unwrap();
+        assert!(exact.matches("4.0.9"));
+        assert!(!exact.matches("4.0.10"));
+        let exclusive: VersionConstraint = ">1,<=3".parse().unwrap();
+        assert!(!exclusive.matches("1.0"));
+        assert!(exclusive.matches("3"));
+    }
+
+    #[test]
+    fn rejects_invalid_constraints() {
+        for value in &["", ">=", "<5,", ">=master", "~>1.0"] {
+            match value.parse::<VersionConstraint>() {
+                Err(Error::InvalidVersionConstraint(_)) => (),
+                other => panic!("Expected an error parsing {:?}, got {:?}", value, other),
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        let constraint: VersionConstraint = " >=4.0 , <5".parse().unwrap();
+        assert_eq!(constraint.to_string(), ">=4.0,<5");
+        assert_eq!(constraint.to_string().parse::<VersionConstraint>().unwrap(),
+                   constraint);
+        assert_eq!("4.0".parse::<VersionConstraint>().unwrap().to_string(),
+                   "=4.0");
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{error::{Error,
                     Result},
-            package::PackageTarget};
+            package::{PackageTarget,
+                      VersionConstraint}};
 use regex::Regex;
 use serde_derive::{Deserialize,
                    Serialize};
@@ -18,10 +19,14 @@ lazy_static::lazy_static! {
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Hash)]
 pub struct PackageIdent {
-    pub origin:  String,
-    pub name:    String,
-    pub version: Option<String>,
-    pub release: Option<String>,
+    pub origin:     String,
+    pub name:       String,
+    pub version:    Option<String>,
+    pub release:    Option<String>,
+    /// The versions an unversioned identifier may resolve to, such as `>=4.0,<5` in
+    /// `core/redis@>=4.0,<5`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<VersionConstraint>,
 }
 
 pub trait Identifiable: fmt::Display {
@@ -30,6 +35,8 @@ pub trait Identifiable: fmt::Display {
     fn version(&self) -> Option<&str>;
     fn release(&self) -> Option<&str>;
 
+    fn constraint(&self) -> Option<&VersionConstraint> { None }
+
     fn fully_qualified(&self) -> bool { self.version().is_some() && self.release().is_some() }
 
     fn valid(&self) -> bool {
@@ -41,6 +48,12 @@ pub trait Identifiable: fmt::Display {
         if self.origin() != other.origin() || self.name() != other.name() {
             return false;
         }
+        if let Some(constraint) = other.constraint() {
+            match self.version() {
+                Some(version) if constraint.matches(version) => (),
+                _ => return false,
+            }
+        }
         if self.version().is_some() {
             if other.version().is_none() {
                 return true;
@@ -68,10 +81,11 @@ impl PackageIdent {
                                 version: Option<T>,
                                 release: Option<T>)
                                 -> Self {
-        PackageIdent { origin:  origin.into(),
-                       name:    name.into(),
-                       version: version.map(Into::into),
-                       release: release.map(Into::into), }
+        PackageIdent { origin:     origin.into(),
+                       name:       name.into(),
+                       version:    version.map(Into::into),
+                       release:    release.map(Into::into),
+                       constraint: None, }
     }
 
     pub fn archive_name(&self) -> Result<String> {
@@ -164,6 +178,8 @@ impl Identifiable for PackageIdent {
     fn version(&self) -> Option<&str> { self.version.as_deref() }
 
     fn release(&self) -> Option<&str> { self.release.as_deref() }
+
+    fn constraint(&self) -> Option<&VersionConstraint> { self.constraint.as_ref() }
 }
 
 // It does not make sense for `PackageIdent` to implement `Default`. This should be removed.
@@ -187,6 +203,8 @@ impl fmt::Display for PackageIdent {
                    self.origin,
                    self.name,
                    self.version.as_ref().unwrap())
+        } else if let Some(ref constraint) = self.constraint {
+            write!(f, "{}/{}@{}", self.origin, self.name, constraint)
         } else {
             write!(f, "{}/{}", self.origin, self.name)
         }
@@ -201,14 +219,23 @@ impl FromStr for PackageIdent {
     type Err = Error;
 
     fn from_str(value: &str) -> result::Result<Self, Self::Err> {
-        let items: Vec<&str> = value.split('/').collect();
+        let (ident, constraint) = match value.find('@') {
+            Some(at) => (&value[..at], Some(value[at + 1..].parse()?)),
+            None => (value, None),
+        };
+        let items: Vec<&str> = ident.split('/').collect();
         let (origin, name, ver, rel) = match items.len() {
             2 => (items[0], items[1], None, None),
+            // A constraint only narrows down which version an unversioned ident resolves to
+            3 | 4 if constraint.is_some() => {
+                return Err(Error::InvalidPackageIdent(value.to_string()))
+            }
             3 => (items[0], items[1], Some(items[2]), None),
             4 => (items[0], items[1], Some(items[2]), Some(items[3])),
             _ => return Err(Error::InvalidPackageIdent(value.to_string())),
         };
-        Ok(PackageIdent::new(origin, name, ver, rel))
+        Ok(PackageIdent { constraint,
+                          ..PackageIdent::new(origin, name, ver, rel) })
     }
 }
 
@@ -301,10 +328,11 @@ pub struct FullyQualifiedPackageIdent(PackageIdent);
 impl FullyQualifiedPackageIdent {
     /// Creates a new fully qualified package identifier
     pub fn new<T: Into<String>>(origin: T, name: T, version: T, release: T) -> Self {
-        let ident = PackageIdent { origin:  origin.into(),
-                                   name:    name.into(),
-                                   version: Some(version.into()),
-                                   release: Some(release.into()), };
+        let ident = PackageIdent { origin:     origin.into(),
+                                   name:       name.into(),
+                                   version:    Some(version.into()),
+                                   release:    Some(release.into()),
+                                   constraint: None, };
         FullyQualifiedPackageIdent(ident)
     }
 
@@ -745,6 +773,34 @@ mod tests {
         assert!(!invalid2.valid());
     }
 
+    #[test]
+    fn package_ident_with_constraint() {
+        let ident = PackageIdent::from_str("core/redis@>=4.0,<5").unwrap();
+        assert_eq!(ident.version, None);
+        assert_eq!(ident.constraint,
+                   Some(VersionConstraint::from_str(">=4.0,<5").unwrap()));
+        assert_eq!(ident.to_string(), "core/redis@>=4.0,<5");
+
+        assert!(PackageIdent::from_str("core/redis@").is_err());
+        assert!(PackageIdent::from_str("core/redis/4.0.9@<5").is_err());
+        assert!(PackageIdent::from_str("core/redis@~4").is_err());
+    }
+
+    #[test]
+    fn package_ident_satisfies_constraint() {
+        let constrained = PackageIdent::from_str("core/redis@>=4.0,<5").unwrap();
+        let matching = PackageIdent::from_str("core/redis/4.0.14/20190319155852").unwrap();
+        let too_new = PackageIdent::from_str("core/redis/5.0.7/20200121151542").unwrap();
+        let unversioned = PackageIdent::from_str("core/redis").unwrap();
+        let other = PackageIdent::from_str("core/nginx/4.1.0/20190115184823").unwrap();
+
+        assert!(matching.satisfies(&constrained));
+        assert!(!too_new.satisfies(&constrained));
+        assert!(!unversioned.satisfies(&constrained));
+        assert!(!other.satisfies(&constrained));
+        assert!(too_new.satisfies(&unversioned));
+    }
+
     #[test]
     fn check_origin_name() {
         assert!(super::is_valid_origin_name("foo"));
@@ -941,6 +941,30 @@ core/bar=pub:core/publish sub:core/subscribe
         assert_eq!(active_target, loaded.target().unwrap());
     }
 
+    #[test]
+    fn load_with_constrained_ident_loads_latest_matching_version() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let active_target = PackageTarget::active_target();
+        for ident_s in &["dream-theater/systematic-chaos/1.1.1/20180704142702",
+                         "dream-theater/systematic-chaos/1.9.0/20180704142702",
+                         "dream-theater/systematic-chaos/2.0.0/20180704142702"]
+        {
+            let pkg_install = testing_package_install(ident_s, fs_root.path());
+            write_metafile(&pkg_install, MetaFile::Target, &active_target);
+        }
+
+        let ident = PackageIdent::from_str("dream-theater/systematic-chaos@>=1.2,<2").unwrap();
+        let loaded = PackageInstall::load(&ident, Some(fs_root.path())).unwrap();
+        assert_eq!(loaded.ident().to_string(),
+                   "dream-theater/systematic-chaos/1.9.0/20180704142702");
+
+        let ident = PackageIdent::from_str("dream-theater/systematic-chaos@>2").unwrap();
+        match PackageInstall::load(&ident, Some(fs_root.path())) {
+            Err(Error::PackageNotFound(ref err_ident)) => assert_eq!(&ident, err_ident),
+            other => panic!("Expected Error::PackageNotFound, got {:?}", other),
+        }
+    }
+
     #[test]
     fn load_with_missing_target_returns_package_not_found_err() {
         let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
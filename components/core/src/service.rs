@@ -299,6 +299,254 @@ impl From<HealthCheckInterval> for Duration {
     fn from(h: HealthCheckInterval) -> Self { h.0 }
 }
 
+/// A health check which the Supervisor runs itself, in place of a service's `health-check` hook.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HealthCheck {
+    /// The service is healthy if a TCP connection to `address` can be opened.
+    Tcp {
+        address:      String,
+        #[serde(default = "HealthCheck::default_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// The service is healthy if a GET request to `url` responds with `status`, or with any
+    /// successful status if `status` is not set.
+    Http {
+        url:          String,
+        #[serde(default)]
+        status:       Option<u16>,
+        #[serde(default = "HealthCheck::default_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// Run `command`. Its exit code is interpreted the same way as a `health-check` hook's.
+    Exec {
+        command:      Vec<String>,
+        #[serde(default = "HealthCheck::default_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+impl HealthCheck {
+    const DEFAULT_TIMEOUT_SECS: u64 = 5;
+
+    fn default_timeout_secs() -> u64 { Self::DEFAULT_TIMEOUT_SECS }
+
+    /// How long to wait for the check to complete before considering it failed.
+    pub fn timeout(&self) -> Duration {
+        match self {
+            HealthCheck::Tcp { timeout_secs, .. }
+            | HealthCheck::Http { timeout_secs, .. }
+            | HealthCheck::Exec { timeout_secs, .. } => Duration::from_secs(*timeout_secs),
+        }
+    }
+}
+
+impl fmt::Display for HealthCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthCheck::Tcp { address, .. } => write!(f, "tcp://{}", address),
+            HealthCheck::Http { url, .. } => write!(f, "{}", url),
+            HealthCheck::Exec { command, .. } => write!(f, "exec:{}", command.join(" ")),
+        }
+    }
+}
+
+/// Parse the short form of a health check used on the command line: `tcp://host:port`,
+/// `http://host:port/path` (or `https://`), or `exec:command [args...]`. Checks parsed this way
+/// use the default timeout, and HTTP checks accept any successful status.
+impl FromStr for HealthCheck {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let value = value.trim();
+        let timeout_secs = Self::DEFAULT_TIMEOUT_SECS;
+        let check = if value.starts_with("tcp://") {
+            let address = &value["tcp://".len()..];
+            match address.rfind(':') {
+                Some(i) if i > 0 && address[i + 1..].parse::<u16>().is_ok() => {
+                    Some(HealthCheck::Tcp { address: address.to_string(),
+                                            timeout_secs })
+                }
+                _ => None,
+            }
+        } else if value.starts_with("http://") || value.starts_with("https://") {
+            Some(HealthCheck::Http { url: value.to_string(),
+                                     status: None,
+                                     timeout_secs })
+        } else if value.starts_with("exec:") {
+            let command: Vec<String> = value["exec:".len()..].split_whitespace()
+                                                             .map(str::to_string)
+                                                             .collect();
+            if command.is_empty() {
+                None
+            } else {
+                Some(HealthCheck::Exec { command,
+                                         timeout_secs })
+            }
+        } else {
+            None
+        };
+        check.ok_or_else(|| Error::InvalidHealthCheck(value.to_string()))
+    }
+}
+
+/// How the Supervisor restarts a service whose process exits unexpectedly. Restarts are delayed by
+/// an exponential backoff, and a service which exits more than `max_restarts` times within
+/// `window_secs` is considered to be crash looping and is no longer restarted. By default there is
+/// no limit, so giving up on a service is opt-in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// The number of restarts allowed within the window. Zero allows any number of restarts.
+    pub max_restarts:     u32,
+    pub window_secs:      u64,
+    /// The delay before the first restart. It doubles with each further exit within the window.
+    pub backoff_secs:     u64,
+    pub max_backoff_secs: u64,
+}
+
+impl RestartPolicy {
+    /// The period over which exits are counted.
+    pub fn window(&self) -> Duration { Duration::from_secs(self.window_secs) }
+
+    /// How long to wait before restarting a service which has exited `exits` times within the
+    /// window.
+    pub fn backoff(&self, exits: usize) -> Duration {
+        let doublings = exits.saturating_sub(1).min(32) as u32;
+        let secs = self.backoff_secs
+                       .saturating_mul(2u64.saturating_pow(doublings))
+                       .min(self.max_backoff_secs);
+        Duration::from_secs(secs)
+    }
+
+    /// Returns true if a service which has exited `exits` times within the window should no
+    /// longer be restarted.
+    pub fn is_crash_loop(&self, exits: usize) -> bool {
+        self.max_restarts > 0 && exits > self.max_restarts as usize
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self { max_restarts:     0,
+               window_secs:      600,
+               backoff_secs:     1,
+               max_backoff_secs: 60, }
+    }
+}
+
+/// The number of members of a service group that may update concurrently during a rolling
+/// update, either as an absolute count or as a percentage of the alive members.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MaxUnavailable {
+    Count(u32),
+    Percent(u32),
+}
+
+impl MaxUnavailable {
+    /// Resolve to a number of members given the number of alive members in the service group.
+    /// Percentages round down, but at least one member is always allowed to update.
+    pub fn members(self, alive_members: usize) -> usize {
+        let members = match self {
+            MaxUnavailable::Count(count) => count as usize,
+            MaxUnavailable::Percent(percent) => alive_members * percent as usize / 100,
+        };
+        members.max(1)
+    }
+}
+
+impl Default for MaxUnavailable {
+    fn default() -> Self { MaxUnavailable::Count(1) }
+}
+
+impl fmt::Display for MaxUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaxUnavailable::Count(count) => write!(f, "{}", count),
+            MaxUnavailable::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl FromStr for MaxUnavailable {
+    type Err = Error;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let value = value.trim();
+        let parsed = if value.ends_with('%') {
+            value[..value.len() - 1].parse::<u32>()
+                                    .ok()
+                                    .filter(|p| *p > 0 && *p <= 100)
+                                    .map(MaxUnavailable::Percent)
+        } else {
+            value.parse::<u32>()
+                 .ok()
+                 .filter(|c| *c > 0)
+                 .map(MaxUnavailable::Count)
+        };
+        parsed.ok_or_else(|| Error::InvalidMaxUnavailable(value.to_string()))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for MaxUnavailable {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        struct MaxUnavailableVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for MaxUnavailableVisitor {
+            type Value = MaxUnavailable;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter,
+                       "a positive count (example 3) or a percentage (example \"25%\")")
+            }
+
+            fn visit_str<E>(self, s: &str) -> std::result::Result<Self::Value, E>
+                where E: serde::de::Error
+            {
+                MaxUnavailable::from_str(s).map_err(|_| {
+                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(s), &self)
+                })
+            }
+
+            fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E>
+                where E: serde::de::Error
+            {
+                match v {
+                    1..=0xFFFF_FFFF => Ok(MaxUnavailable::Count(v as u32)),
+                    _ => {
+                        Err(serde::de::Error::invalid_value(serde::de::Unexpected::Unsigned(v),
+                                                            &self))
+                    }
+                }
+            }
+
+            fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E>
+                where E: serde::de::Error
+            {
+                match v {
+                    1..=0xFFFF_FFFF => Ok(MaxUnavailable::Count(v as u32)),
+                    _ => {
+                        Err(serde::de::Error::invalid_value(serde::de::Unexpected::Signed(v),
+                                                            &self))
+                    }
+                }
+            }
+        }
+
+        deserializer.deserialize_any(MaxUnavailableVisitor)
+    }
+}
+
+impl serde::Serialize for MaxUnavailable {
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -544,6 +792,126 @@ mod test {
                    format!("{}", HealthCheckInterval::from_str("5").unwrap()));
     }
 
+    #[test]
+    fn max_unavailable_from_str() {
+        assert_eq!(MaxUnavailable::from_str("3").unwrap(),
+                   MaxUnavailable::Count(3));
+        assert_eq!(MaxUnavailable::from_str("25%").unwrap(),
+                   MaxUnavailable::Percent(25));
+        assert!(MaxUnavailable::from_str("0").is_err());
+        assert!(MaxUnavailable::from_str("0%").is_err());
+        assert!(MaxUnavailable::from_str("101%").is_err());
+        assert!(MaxUnavailable::from_str("-1").is_err());
+        assert!(MaxUnavailable::from_str("lots").is_err());
+    }
+
+    #[test]
+    fn max_unavailable_display_round_trips() {
+        for s in &["1", "12", "1%", "50%", "100%"] {
+            assert_eq!(*s, MaxUnavailable::from_str(s).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn max_unavailable_toml_deserialize() {
+        #[derive(Deserialize)]
+        struct Data {
+            count:   MaxUnavailable,
+            percent: MaxUnavailable,
+        }
+        let toml = r#"
+            count = 3
+            percent = "25%"
+            "#;
+        let data: Data = toml::from_str(toml).unwrap();
+
+        assert_eq!(data.count, MaxUnavailable::Count(3));
+        assert_eq!(data.percent, MaxUnavailable::Percent(25));
+    }
+
+    #[test]
+    fn max_unavailable_members() {
+        assert_eq!(MaxUnavailable::default().members(60), 1);
+        assert_eq!(MaxUnavailable::Count(5).members(60), 5);
+        assert_eq!(MaxUnavailable::Percent(25).members(60), 15);
+        assert_eq!(MaxUnavailable::Percent(25).members(3), 1);
+        assert_eq!(MaxUnavailable::Percent(100).members(7), 7);
+    }
+
+    #[test]
+    fn health_check_from_str() {
+        assert_eq!(HealthCheck::from_str("tcp://localhost:5432").unwrap(),
+                   HealthCheck::Tcp { address:      "localhost:5432".to_string(),
+                                      timeout_secs: 5, });
+        assert_eq!(HealthCheck::from_str("http://localhost:8080/health").unwrap(),
+                   HealthCheck::Http { url:          "http://localhost:8080/health".to_string(),
+                                       status:       None,
+                                       timeout_secs: 5, });
+        assert_eq!(HealthCheck::from_str("exec:pg_isready -q").unwrap(),
+                   HealthCheck::Exec { command:      vec!["pg_isready".to_string(),
+                                                          "-q".to_string()],
+                                       timeout_secs: 5, });
+        assert!(HealthCheck::from_str("tcp://localhost").is_err());
+        assert!(HealthCheck::from_str("tcp://:5432").is_err());
+        assert!(HealthCheck::from_str("exec:").is_err());
+        assert!(HealthCheck::from_str("ping localhost").is_err());
+    }
+
+    #[test]
+    fn health_check_toml_deserialize() {
+        #[derive(Deserialize)]
+        struct Data {
+            check: HealthCheck,
+        }
+        let toml = r#"
+            [check]
+            type = "http"
+            url = "https://localhost/health"
+            status = 204
+            "#;
+        let data: Data = toml::from_str(toml).unwrap();
+
+        assert_eq!(data.check,
+                   HealthCheck::Http { url:          "https://localhost/health".to_string(),
+                                       status:       Some(204),
+                                       timeout_secs: 5, });
+        assert_eq!(data.check.timeout(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn restart_policy_backoff_doubles_up_to_the_maximum() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(5), Duration::from_secs(16));
+        assert_eq!(policy.backoff(7), Duration::from_secs(60));
+        assert_eq!(policy.backoff(1000), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn restart_policy_crash_loop() {
+        let policy = RestartPolicy { max_restarts: 5,
+                                     ..RestartPolicy::default() };
+        assert!(!policy.is_crash_loop(5));
+        assert!(policy.is_crash_loop(6));
+    }
+
+    #[test]
+    fn restart_policy_default_never_crash_loops() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.max_restarts, 0);
+        assert!(!policy.is_crash_loop(1000));
+    }
+
+    #[test]
+    fn restart_policy_toml_deserialize_defaults_missing_fields() {
+        let policy: RestartPolicy = toml::from_str("max_restarts = 3").unwrap();
+        assert_eq!(policy,
+                   RestartPolicy { max_restarts: 3,
+                                   ..RestartPolicy::default() });
+    }
+
     /// This ensures that we can safely transition from the old
     /// application/environment formulation of service group
     /// names. Once this has been in the wild for a while, we can
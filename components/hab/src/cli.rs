@@ -24,9 +24,15 @@ use habitat_core::{crypto::{keys::PairType,
                              Identifiable,
                              PackageIdent,
                              PackageTarget},
-                   service::{HealthCheckInterval,
+                   service::{HealthCheck,
+                             HealthCheckInterval,
+                             MaxUnavailable,
                              ServiceGroup},
                    ChannelIdent};
+use habitat_sup_client::{CTL_CLIENT_CERTIFICATE_ENVVAR,
+                         CTL_CLIENT_KEY_ENVVAR,
+                         CTL_SERVER_CA_CERTIFICATE_ENVVAR,
+                         CTL_SERVER_NAME_ENVVAR};
 use habitat_sup_protocol;
 use std::{path::Path,
           result,
@@ -104,6 +110,10 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                     "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
                 (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
                     "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+                (arg: arg_ctl_server_ca_certificate())
+                (arg: arg_ctl_server_name())
+                (arg: arg_ctl_client_certificate())
+                (arg: arg_ctl_client_key())
             )
         )
         (@subcommand file =>
@@ -122,6 +132,10 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                 (@arg USER: -u --user +takes_value "Name of the user key")
                 (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
                     "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+                (arg: arg_ctl_server_ca_certificate())
+                (arg: arg_ctl_server_name())
+                (arg: arg_ctl_client_certificate())
+                (arg: arg_ctl_client_key())
                 (arg: arg_cache_key_path())
             )
         )
@@ -608,6 +622,21 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                 )
 
             )
+            (@subcommand lock =>
+                (about: "Writes a lockfile of the exact artifacts of a package and its \
+                    dependencies")
+                (@arg PKG_IDENT: +required +takes_value {valid_ident}
+                    "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
+                (@arg LOCKFILE: -f --file +takes_value default_value[habitat.lock]
+                    "The path to write the lockfile to")
+                (@arg BLDR_URL: -u --url +takes_value {valid_url}
+                    "Specify an alternate Builder endpoint. If not specified, the value will \
+                     be taken from the HAB_BLDR_URL environment variable if defined. (default: \
+                     https://bldr.habitat.sh)")
+                (@arg CHANNEL: --channel -c +takes_value default_value[stable] env(ChannelIdent::ENVVAR)
+                    "Resolve the package from the specified release channel")
+                (@arg AUTH_TOKEN: -z --auth +takes_value "Authentication token for Builder")
+            )
             (@subcommand provides =>
                 (about: "Search installed Habitat packages for a given file")
                 (@arg FILE: +required +takes_value
@@ -637,6 +666,9 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                 (@arg DEST: +required +takes_value
                     "The destination path to the signed Habitat Artifact \
                     (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)")
+                (@arg ZSTD: --zstd
+                    "Recompress the archive with Zstandard, creating a HART-2 artifact which \
+                    installs faster but which older clients cannot read")
                 (arg: arg_cache_key_path())
             )
             (@subcommand uninstall =>
@@ -853,11 +885,16 @@ pub fn get(feature_flags: FeatureFlag) -> App<'static, 'static> {
                     (arg: arg_cache_key_path())
                 )
             )
+            (subcommand: sub_svc_canary().aliases(&["c", "ca", "can", "cana", "canar"]))
             (subcommand: sub_svc_load().aliases(&["l", "lo", "loa"]))
+            (subcommand: sub_svc_logs().aliases(&["log"]))
+            (subcommand: sub_svc_reload().aliases(&["rel", "relo", "reloa"]))
+            (subcommand: sub_svc_restart().aliases(&["rest", "resta", "restar"]))
             (subcommand: sub_svc_start().aliases(&["star"]))
             (subcommand: sub_svc_status().aliases(&["stat", "statu"]))
             (subcommand: sub_svc_stop().aliases(&["sto"]))
             (subcommand: sub_svc_unload().aliases(&["u", "un", "unl", "unlo", "unloa"]))
+            (subcommand: sub_svc_update().aliases(&["up", "upd", "upda", "updat"]))
         )
         (@subcommand studio =>
             (about: "Commands relating to Habitat Studios")
@@ -950,6 +987,7 @@ pub fn sup_commands(feature_flags: FeatureFlag) -> App<'static, 'static> {
     (@setting VersionlessSubcommands)
     (@setting ArgRequiredElseHelp)
     (@setting SubcommandRequiredElseHelp)
+    (subcommand: sub_sup_apply().aliases(&["ap", "app", "appl"]))
     (subcommand: sub_sup_bash().aliases(&["b", "ba", "bas"]))
     (subcommand: sub_sup_depart().aliases(&["d", "de", "dep", "depa", "depart"]))
     (subcommand: sub_sup_run(feature_flags).aliases(&["r", "ru"]))
@@ -988,6 +1026,43 @@ fn arg_cache_key_path() -> Arg<'static, 'static> {
                                     .help("Cache for creating and searching for encryption keys")
 }
 
+fn arg_ctl_server_ca_certificate() -> Arg<'static, 'static> {
+    Arg::with_name("CTL_SERVER_CA_CERTIFICATE").long("ctl-server-ca-certificate")
+                                               .takes_value(true)
+                                               .env(CTL_SERVER_CA_CERTIFICATE_ENVVAR)
+                                               .requires("CTL_SERVER_NAME")
+                                               .help("The CA certificate used to verify a remote \
+                                                      Supervisor's Control Gateway over TLS")
+}
+
+fn arg_ctl_server_name() -> Arg<'static, 'static> {
+    Arg::with_name("CTL_SERVER_NAME").long("ctl-server-name")
+                                     .takes_value(true)
+                                     .env(CTL_SERVER_NAME_ENVVAR)
+                                     .requires("CTL_SERVER_CA_CERTIFICATE")
+                                     .help("The name to verify the remote Supervisor's Control \
+                                            Gateway certificate against")
+}
+
+fn arg_ctl_client_certificate() -> Arg<'static, 'static> {
+    Arg::with_name("CTL_CLIENT_CERTIFICATE").long("ctl-client-certificate")
+                                            .takes_value(true)
+                                            .env(CTL_CLIENT_CERTIFICATE_ENVVAR)
+                                            .requires_all(&["CTL_CLIENT_KEY",
+                                                            "CTL_SERVER_CA_CERTIFICATE"])
+                                            .help("The certificate presented to a remote \
+                                                   Supervisor's Control Gateway over TLS")
+}
+
+fn arg_ctl_client_key() -> Arg<'static, 'static> {
+    Arg::with_name("CTL_CLIENT_KEY").long("ctl-client-key")
+                                    .takes_value(true)
+                                    .env(CTL_CLIENT_KEY_ENVVAR)
+                                    .requires("CTL_CLIENT_CERTIFICATE")
+                                    .help("The private key of the certificate presented to a \
+                                           remote Supervisor's Control Gateway")
+}
+
 fn arg_target() -> Arg<'static, 'static> {
     Arg::with_name("PKG_TARGET").takes_value(true)
                                 .validator(valid_target)
@@ -1058,9 +1133,11 @@ fn sub_pkg_install(feature_flags: FeatureFlag) -> App<'static, 'static> {
                          https://bldr.habitat.sh)")
         (@arg CHANNEL: --channel -c +takes_value default_value[stable] env(ChannelIdent::ENVVAR)
             "Install from the specified release channel")
-        (@arg PKG_IDENT_OR_ARTIFACT: +required +multiple +takes_value
+        (@arg PKG_IDENT_OR_ARTIFACT: required_unless[LOCKED] +multiple +takes_value
             "One or more Habitat package identifiers (ex: acme/redis) and/or filepaths \
             to a Habitat Artifact (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)")
+        (@arg LOCKED: --locked +takes_value {file_exists} conflicts_with[PKG_IDENT_OR_ARTIFACT]
+            "Install exactly the packages in a lockfile written by 'hab pkg lock'")
         (@arg BINLINK: -b --binlink
             "Binlink all binaries from installed package(s) into BINLINK_DIR")
         (@arg BINLINK_DIR: --("binlink-dir") +takes_value {non_empty} env(BINLINK_DIR_ENVVAR)
@@ -1093,6 +1170,10 @@ fn sub_config_apply() -> App<'static, 'static> {
     (@arg USER: -u --user +takes_value "Name of a user key to use for encryption")
     (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
         "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+    (arg: arg_ctl_server_ca_certificate())
+    (arg: arg_ctl_server_name())
+    (arg: arg_ctl_client_certificate())
+    (arg: arg_ctl_client_key())
     (arg: arg_cache_key_path())
     )
 }
@@ -1100,6 +1181,23 @@ fn sub_config_apply() -> App<'static, 'static> {
 // the following sup related functions are
 // public due to their utilization in `hab-sup`
 // for consistency, all supervisor related clap subcommands are defined in this module
+fn sub_sup_apply() -> App<'static, 'static> {
+    clap_app!(@subcommand apply =>
+        (about: "Load, update, and unload services so that a Supervisor runs exactly the \
+            services listed in a manifest")
+        (@arg MANIFEST: +required +takes_value {file_exists}
+            "Path to a TOML manifest with a [[service]] table for each service")
+        (@arg DRY_RUN: --("dry-run") "Show the changes applying the manifest would make without \
+            making them")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
+    )
+}
+
 fn sub_sup_depart() -> App<'static, 'static> {
     clap_app!(@subcommand depart =>
         (about: "Depart a Supervisor from the gossip ring; kicking and banning the target \
@@ -1107,6 +1205,10 @@ fn sub_sup_depart() -> App<'static, 'static> {
         (@arg MEMBER_ID: +required +takes_value "The member-id of the Supervisor to depart")
         (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
             "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
     )
 }
 
@@ -1153,6 +1255,39 @@ fn sub_sup_term() -> App<'static, 'static> {
     )
 }
 
+fn sub_svc_canary() -> App<'static, 'static> {
+    clap_app!(@subcommand canary =>
+        (about: "Promote or abort the canary stage of a service's rolling update")
+        (@arg DECISION: +required +takes_value possible_value[promote abort]
+            "Whether to promote the update to the rest of the service group or abort it")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident}
+            "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
+    )
+}
+
+fn sub_svc_logs() -> App<'static, 'static> {
+    clap_app!(@subcommand logs =>
+        (about: "Show the output of a loaded Habitat service")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident}
+            "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
+        (@arg FOLLOW: -f --follow "Keep showing output as the service writes it")
+        (@arg LINES: -n --lines +takes_value default_value("10") {valid_numeric::<u64>}
+            "The number of lines of existing output to show")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
+    )
+}
+
 fn sub_svc_start() -> App<'static, 'static> {
     clap_app!(@subcommand start =>
         (about: "Start a loaded, but stopped, Habitat service")
@@ -1160,6 +1295,10 @@ fn sub_svc_start() -> App<'static, 'static> {
             "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
         (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
             "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
     )
 }
 
@@ -1171,6 +1310,10 @@ fn sub_svc_status() -> App<'static, 'static> {
         (@arg PKG_IDENT: +takes_value {valid_ident} "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
         (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
         "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
     )
 }
 
@@ -1187,10 +1330,46 @@ fn sub_svc_stop() -> App<'static, 'static> {
             "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
         (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
             "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
     );
     add_shutdown_timeout_option(sub)
 }
 
+fn sub_svc_restart() -> App<'static, 'static> {
+    let sub = clap_app!(@subcommand restart =>
+        (about: "Restart a running Habitat service")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident}
+            "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
+    );
+    add_shutdown_timeout_option(sub)
+}
+
+fn sub_svc_reload() -> App<'static, 'static> {
+    clap_app!(@subcommand reload =>
+        (about: "Reload a running Habitat service without restarting it by running its reload \
+            hook")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident}
+            "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
+        (@arg SIGNAL: --signal -s +takes_value possible_value[HUP INT QUIT USR1 USR2 ALRM TERM]
+            "Send this signal to the service's process instead of running its reload hook")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
+    )
+}
+
 fn sub_svc_load() -> App<'static, 'static> {
     let mut sub = clap_app!(@subcommand load =>
         (about: "Load a service to be started and supervised by Habitat from a package \
@@ -1220,6 +1399,10 @@ fn sub_svc_load() -> App<'static, 'static> {
             was previously loaded and running this operation will also restart the service")
         (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
             "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
     );
 
     // The clap_app macro does not allow "-" in possible values
@@ -1227,7 +1410,8 @@ fn sub_svc_load() -> App<'static, 'static> {
                                             .long("strategy")
                                             .takes_value(true)
                                             .default_value("none")
-                                            .possible_values(&["none", "at-once", "rolling"])
+                                            .possible_values(&["none", "at-once", "rolling",
+                                                               "canary"])
                                             .validator(valid_update_strategy)
                                             .help("The update strategy"));
 
@@ -1240,6 +1424,31 @@ fn sub_svc_load() -> App<'static, 'static> {
                                                     .help(UPDATE_CONDITION_HELP)
                                                     .long_help(UPDATE_CONDITION_LONG_HELP));
 
+    // There is no default value so that reloading a service with --force keeps its setting
+    sub = sub.arg(Arg::with_name("MAX_UNAVAILABLE").long("max-unavailable")
+                                                   .takes_value(true)
+                                                   .validator(valid_max_unavailable)
+                                                   .help("The number (ex: 3) or percentage (ex: \
+                                                          25%) of members which may update \
+                                                          concurrently during a rolling update \
+                                                          [default: 1]"));
+
+    sub = sub.arg(Arg::with_name("CANARIES").long("canaries")
+                                            .takes_value(true)
+                                            .validator(valid_canaries)
+                                            .help("The number of followers which update during \
+                                                   the canary stage of a canary update \
+                                                   [default: 1]"));
+
+    sub = sub.arg(Arg::with_name("HEALTH_CHECK").long("health-check")
+                                                .takes_value(true)
+                                                .validator(valid_health_check)
+                                                .help("A health check for the Supervisor to run \
+                                                       in place of the health-check hook (ex: \
+                                                       tcp://localhost:5432, \
+                                                       http://localhost:8080/health, \
+                                                       exec:pg_isready -q)"));
+
     // The clap_app macro does not support numbers in default_value
     sub = sub.arg(Arg::with_name("HEALTH_CHECK_INTERVAL").short("i")
                                                          .long("health-check-interval")
@@ -1249,6 +1458,30 @@ fn sub_svc_load() -> App<'static, 'static> {
                                                          .help("The interval in seconds on \
                                                                 which to run health checks"));
 
+    sub = sub.arg(Arg::with_name("MAX_RESTARTS").long("max-restarts")
+                                                .takes_value(true)
+                                                .validator(valid_numeric::<u32>)
+                                                .help("The number of times the service may be \
+                                                       restarted within the restart window \
+                                                       before the Supervisor gives up on it, or \
+                                                       0 for no limit [default: 0]"));
+    sub = sub.arg(Arg::with_name("RESTART_WINDOW").long("restart-window")
+                                                  .takes_value(true)
+                                                  .validator(valid_numeric::<u32>)
+                                                  .help("The period in seconds over which \
+                                                         restarts are counted [default: 600]"));
+    sub = sub.arg(Arg::with_name("RESTART_BACKOFF").long("restart-backoff")
+                                                   .takes_value(true)
+                                                   .validator(valid_numeric::<u32>)
+                                                   .help("The delay in seconds before the first \
+                                                          restart, doubling with each further \
+                                                          restart [default: 1]"));
+    sub = sub.arg(Arg::with_name("MAX_RESTART_BACKOFF").long("max-restart-backoff")
+                                                       .takes_value(true)
+                                                       .validator(valid_numeric::<u32>)
+                                                       .help("The longest delay in seconds \
+                                                              before a restart [default: 60]"));
+
     if cfg!(windows) {
         sub = sub.arg(Arg::with_name("PASSWORD").long("password")
                                                 .takes_value(true)
@@ -1266,7 +1499,106 @@ fn sub_svc_unload() -> App<'static, 'static> {
             "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
         (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
             "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
+    );
+    add_shutdown_timeout_option(sub)
+}
+
+fn sub_svc_update() -> App<'static, 'static> {
+    let mut sub = clap_app!(@subcommand update =>
+        (about: "Change the settings of a loaded service. Settings which are not given are left \
+            as they are. The service is only restarted if its binds or binding mode change")
+        (@arg PKG_IDENT: +required +takes_value {valid_ident}
+            "A package identifier (ex: core/redis, core/busybox-static/1.42.2)")
+        (@arg CHANNEL: --channel +takes_value
+            "Receive updates from the specified release channel")
+        (@arg BLDR_URL: -u --url +takes_value {valid_url}
+            "Receive updates from an alternate Builder endpoint")
+        (@arg BIND: --bind +takes_value +multiple
+            "One or more service groups to bind to a configuration, replacing the current binds")
+        (@arg BINDING_MODE: --("binding-mode") +takes_value {valid_binding_mode} possible_value[strict relaxed]
+             "Governs how the presence or absence of binds affects service startup")
+        (@arg REMOTE_SUP: --("remote-sup") -r +takes_value
+            "Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]")
+        (arg: arg_ctl_server_ca_certificate())
+        (arg: arg_ctl_server_name())
+        (arg: arg_ctl_client_certificate())
+        (arg: arg_ctl_client_key())
     );
+
+    // The clap_app macro does not allow "-" in possible values
+    sub = sub.arg(Arg::with_name("STRATEGY").short("s")
+                                            .long("strategy")
+                                            .takes_value(true)
+                                            .possible_values(&["none", "at-once", "rolling",
+                                                               "canary"])
+                                            .validator(valid_update_strategy)
+                                            .help("The update strategy"));
+
+    // The clap_app macro does not allow "-" in possible values
+    sub = sub.arg(Arg::with_name("UPDATE_CONDITION").long("update-condition")
+                                                    .takes_value(true)
+                                                    .possible_values(&["latest", "track-channel"])
+                                                    .validator(valid_update_condition)
+                                                    .help(UPDATE_CONDITION_HELP)
+                                                    .long_help(UPDATE_CONDITION_LONG_HELP));
+
+    sub = sub.arg(Arg::with_name("MAX_UNAVAILABLE").long("max-unavailable")
+                                                   .takes_value(true)
+                                                   .validator(valid_max_unavailable)
+                                                   .help("The number (ex: 3) or percentage (ex: \
+                                                          25%) of members which may update \
+                                                          concurrently during a rolling update"));
+
+    sub = sub.arg(Arg::with_name("CANARIES").long("canaries")
+                                            .takes_value(true)
+                                            .validator(valid_canaries)
+                                            .help("The number of followers which update during \
+                                                   the canary stage of a canary update"));
+
+    sub = sub.arg(Arg::with_name("HEALTH_CHECK").long("health-check")
+                                                .takes_value(true)
+                                                .validator(valid_health_check)
+                                                .help("A health check for the Supervisor to run \
+                                                       in place of the health-check hook (ex: \
+                                                       tcp://localhost:5432, \
+                                                       http://localhost:8080/health, \
+                                                       exec:pg_isready -q)"));
+
+    sub = sub.arg(Arg::with_name("HEALTH_CHECK_INTERVAL").short("i")
+                                                         .long("health-check-interval")
+                                                         .takes_value(true)
+                                                         .validator(valid_health_check_interval)
+                                                         .help("The interval in seconds on \
+                                                                which to run health checks"));
+
+    sub = sub.arg(Arg::with_name("MAX_RESTARTS").long("max-restarts")
+                                                .takes_value(true)
+                                                .validator(valid_numeric::<u32>)
+                                                .help("The number of times the service may be \
+                                                       restarted within the restart window \
+                                                       before the Supervisor gives up on it, or \
+                                                       0 for no limit"));
+    sub = sub.arg(Arg::with_name("RESTART_WINDOW").long("restart-window")
+                                                  .takes_value(true)
+                                                  .validator(valid_numeric::<u32>)
+                                                  .help("The period in seconds over which \
+                                                         restarts are counted"));
+    sub = sub.arg(Arg::with_name("RESTART_BACKOFF").long("restart-backoff")
+                                                   .takes_value(true)
+                                                   .validator(valid_numeric::<u32>)
+                                                   .help("The delay in seconds before the first \
+                                                          restart, doubling with each further \
+                                                          restart"));
+    sub = sub.arg(Arg::with_name("MAX_RESTART_BACKOFF").long("max-restart-backoff")
+                                                       .takes_value(true)
+                                                       .validator(valid_numeric::<u32>)
+                                                       .help("The longest delay in seconds \
+                                                              before a restart"));
+
     add_shutdown_timeout_option(sub)
 }
 
@@ -1340,6 +1672,34 @@ fn valid_numeric<T: FromStr>(val: String) -> result::Result<(), String> {
     }
 }
 
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_max_unavailable(val: String) -> result::Result<(), String> {
+    match MaxUnavailable::from_str(&val) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("'{}' is not a valid value for max unavailable: {}", val, e)),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_canaries(val: String) -> result::Result<(), String> {
+    match val.parse::<u32>() {
+        Ok(canaries) if canaries > 0 => Ok(()),
+        _ => {
+            Err(format!("'{}' is not a valid number of canaries, which \
+                         must be at least 1",
+                        val))
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_health_check(val: String) -> result::Result<(), String> {
+    match HealthCheck::from_str(&val) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("'{}' is not a valid value for health check: {}", val, e)),
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
 fn valid_health_check_interval(val: String) -> result::Result<(), String> {
     match HealthCheckInterval::from_str(&val) {
@@ -1374,7 +1734,8 @@ fn valid_ident(val: String) -> result::Result<(), String> {
         Ok(_) => Ok(()),
         Err(_) => {
             Err(format!("'{}' is not valid. Package identifiers have the \
-                         form origin/name[/version[/release]]",
+                         form origin/name[/version[/release]] or \
+                         origin/name@constraint",
                         &val))
         }
     }
@@ -1580,6 +1941,47 @@ mod tests {
             assert!(matches.is_ok());
         }
 
+        #[test]
+        fn app_and_env_options_required_if_json_sink_options() {
+            for sink in &[["--event-stream-file", "/hab/sup/default/events.json"],
+                          ["--event-stream-webhook", "https://events.example.com"],
+                          ["--event-stream-syslog", "/dev/log"]]
+            {
+                let matches = sub_sup_run(no_feature_flags()).get_matches_from_safe(vec!["run",
+                                                                                         sink[0],
+                                                                                         sink[1]]);
+                assert!(matches.is_err());
+                let error = matches.unwrap_err();
+                assert_eq!(error.kind, clap::ErrorKind::MissingRequiredArgument);
+                let matches = sub_sup_run(no_feature_flags()).get_matches_from_safe(vec![
+                    "run",
+                    "--event-stream-application",
+                    "MY_APP",
+                    "--event-stream-environment",
+                    "MY_ENV",
+                    sink[0],
+                    sink[1],
+                ]);
+                assert!(matches.is_ok());
+            }
+        }
+
+        #[test]
+        fn syslog_option_must_be_a_socket_path_or_address() {
+            let matches = sub_sup_run(no_feature_flags()).get_matches_from_safe(vec![
+                "run",
+                "--event-stream-application",
+                "MY_APP",
+                "--event-stream-environment",
+                "MY_ENV",
+                "--event-stream-syslog",
+                "syslog.example.com",
+            ]);
+            assert!(matches.is_err());
+            let error = matches.unwrap_err();
+            assert_eq!(error.kind, clap::ErrorKind::ValueValidation);
+        }
+
         #[test]
         fn app_option_must_take_a_value() {
             let matches = sub_sup_run(no_feature_flags()).get_matches_from_safe(vec![
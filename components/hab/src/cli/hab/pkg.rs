@@ -288,8 +288,14 @@ pub enum Pkg {
         channel:               String,
         /// One or more Habitat package identifiers (ex: acme/redis) and/or filepaths to a Habitat
         /// Artifact (ex: /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)
-        #[structopt(name = "PKG_IDENT_OR_ARTIFACT", required = true)]
+        #[structopt(name = "PKG_IDENT_OR_ARTIFACT", required_unless = "LOCKED")]
         pkg_ident_or_artifact: Vec<String>,
+        /// Install exactly the packages in a lockfile written by 'hab pkg lock'
+        #[structopt(name = "LOCKED",
+                    long = "locked",
+                    validator = file_exists,
+                    conflicts_with = "PKG_IDENT_OR_ARTIFACT")]
+        locked:                Option<PathBuf>,
         /// Binlink all binaries from installed package(s) into BINLINK_DIR
         #[structopt(name = "BINLINK", short = "b", long = "binlink")]
         binlink:               bool,
@@ -319,6 +325,28 @@ pub enum Pkg {
     },
     /// List all versions of installed packages
     List(List),
+    /// Writes a lockfile of the exact artifacts of a package and its dependencies
+    Lock {
+        #[structopt(flatten)]
+        pkg_ident:  PkgIdent,
+        /// The path to write the lockfile to
+        #[structopt(name = "LOCKFILE",
+                    short = "f",
+                    long = "file",
+                    default_value = "habitat.lock")]
+        lockfile:   PathBuf,
+        #[structopt(flatten)]
+        bldr_url:   BldrUrl,
+        /// Resolve the package from the specified release channel
+        #[structopt(name = "CHANNEL",
+                    short = "c",
+                    long = "channel",
+                    default_value = "stable",
+                    env = ChannelIdent::ENVVAR)]
+        channel:    String,
+        #[structopt(flatten)]
+        auth_token: AuthToken,
+    },
     /// Prints the path to a specific installed release of a package
     Path {
         #[structopt(flatten)]
@@ -379,6 +407,10 @@ pub enum Pkg {
         /// /home/acme-redis-3.0.7-21120102031201-x86_64-linux.hart)
         #[structopt(name = "DEST")]
         dest:           PathBuf,
+        /// Recompress the archive with Zstandard, creating a HART-2 artifact which installs
+        /// faster but which older clients cannot read
+        #[structopt(name = "ZSTD", long = "zstd")]
+        zstd:           bool,
         #[structopt(flatten)]
         cache_key_path: CacheKeyPath,
     },
@@ -5,7 +5,8 @@ use super::{svc::{ConfigOptSharedLoad,
                    ConfigOptCacheKeyPath,
                    ConfigOptRemoteSup,
                    RemoteSup}};
-use crate::VERSION;
+use crate::{cli::file_exists,
+            VERSION};
 use configopt::{self,
                 configopt_fields,
                 ConfigOpt};
@@ -15,6 +16,7 @@ use habitat_common::{cli::{RING_ENVVAR,
                      types::{EventStreamConnectMethod,
                              EventStreamMetaPair,
                              EventStreamServerCertificate,
+                             EventStreamSyslogAddress,
                              EventStreamToken,
                              GossipListenAddr,
                              HttpListenAddr,
@@ -32,6 +34,7 @@ use std::{fmt,
           str::FromStr};
 use structopt::{clap::AppSettings,
                 StructOpt};
+use url::Url;
 
 #[derive(ConfigOpt, StructOpt)]
 #[structopt(name = "hab",
@@ -43,6 +46,19 @@ use structopt::{clap::AppSettings,
         )]
 #[allow(clippy::large_enum_variant)]
 pub enum Sup {
+    /// Load, update, and unload services so that a Supervisor runs exactly the services listed in
+    /// a manifest
+    #[structopt(no_version)]
+    Apply {
+        /// Path to a TOML manifest with a [[service]] table for each service
+        #[structopt(name = "MANIFEST", validator = file_exists)]
+        manifest:   PathBuf,
+        /// Show the changes applying the manifest would make without making them
+        #[structopt(name = "DRY_RUN", long = "dry-run")]
+        dry_run:    bool,
+        #[structopt(flatten)]
+        remote_sup: RemoteSup,
+    },
     /// Start an interactive Bash-like shell
     #[structopt(usage = "hab sup bash", no_version)]
     Bash,
@@ -203,6 +219,29 @@ pub struct SupRun {
     #[structopt(long = "ca-certs",
                 requires_all = &["CERT_FILE", "KEY_FILE"])]
     pub ca_cert_file: Option<PathBuf>,
+    /// The server certificates for Control Gateway TLS encryption
+    ///
+    /// Read server certificates from CTL_SERVER_CERTIFICATE. This should contain PEM-format
+    /// certificates in the right order. The first certificate should certify CTL_SERVER_KEY.
+    #[structopt(long = "ctl-server-certificate",
+                env = "HAB_CTL_SERVER_CERTIFICATE",
+                requires = "CTL_SERVER_KEY")]
+    pub ctl_server_certificate: Option<PathBuf>,
+    /// The private key for Control Gateway TLS encryption
+    ///
+    /// Read the private key from CTL_SERVER_KEY. This should be an RSA private key in PEM format.
+    #[structopt(long = "ctl-server-key",
+                env = "HAB_CTL_SERVER_KEY",
+                requires = "CTL_SERVER_CERTIFICATE")]
+    pub ctl_server_key: Option<PathBuf>,
+    /// The CA certificate for Control Gateway client authentication
+    ///
+    /// Read the CA certificate from CTL_CLIENT_CA_CERTIFICATE. Clients of the Control Gateway
+    /// must present a certificate signed by this CA.
+    #[structopt(long = "ctl-client-ca-certificate",
+                env = "HAB_CTL_CLIENT_CA_CERTIFICATE",
+                requires_all = &["CTL_SERVER_CERTIFICATE", "CTL_SERVER_KEY"])]
+    pub ctl_client_ca_certificate: Option<PathBuf>,
     /// Load a Habitat package as part of the Supervisor startup
     ///
     /// The package can be specified by a package identifier (ex: core/redis) or filepath to a
@@ -269,6 +308,35 @@ pub struct SupRun {
     /// The certificate should be in PEM format.
     #[structopt(long = "event-stream-server-certificate")]
     pub event_stream_server_certificate: Option<EventStreamServerCertificate>,
+    /// The most megabytes of events to buffer on disk while the event stream is disconnected
+    ///
+    /// Buffered events are sent in order once the connection is restored. When the buffer is
+    /// full, the oldest events are dropped. Set to '0' to drop events while disconnected.
+    #[structopt(long = "event-stream-spool-size", default_value = "10")]
+    pub event_stream_spool_size: u64,
+    /// The path to a file to append events to, one JSON object per line
+    ///
+    /// This enables the event stream and requires EVENT_STREAM_APPLICATION and
+    /// EVENT_STREAM_ENVIRONMENT also be set.
+    #[structopt(long = "event-stream-file",
+                requires_all = &["EVENT_STREAM_APPLICATION", "EVENT_STREAM_ENVIRONMENT"])]
+    pub event_stream_file: Option<PathBuf>,
+    /// The URL of an HTTP endpoint to POST each event to as JSON
+    ///
+    /// Failed deliveries are retried several times before the event is dropped. Events that
+    /// arrive while too many are waiting to be delivered are also dropped. This enables the event
+    /// stream and requires EVENT_STREAM_APPLICATION and EVENT_STREAM_ENVIRONMENT also be set.
+    #[structopt(long = "event-stream-webhook",
+                requires_all = &["EVENT_STREAM_APPLICATION", "EVENT_STREAM_ENVIRONMENT"])]
+    pub event_stream_webhook: Option<Url>,
+    /// Send each event as JSON to syslog
+    ///
+    /// Either the absolute path to a local syslog socket (ex: /dev/log) or the host:port of a
+    /// syslog server accepting UDP. This enables the event stream and requires
+    /// EVENT_STREAM_APPLICATION and EVENT_STREAM_ENVIRONMENT also be set.
+    #[structopt(long = "event-stream-syslog",
+                requires_all = &["EVENT_STREAM_APPLICATION", "EVENT_STREAM_ENVIRONMENT"])]
+    pub event_stream_syslog: Option<EventStreamSyslogAddress>,
     /// Automatically cleanup old packages
     ///
     /// The Supervisor will automatically cleanup old packages only keeping the
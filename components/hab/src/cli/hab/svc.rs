@@ -4,13 +4,17 @@ use super::util::{CacheKeyPath,
                   ConfigOptRemoteSup,
                   PkgIdent,
                   RemoteSup};
+use crate::cli::{valid_canaries,
+                 valid_health_check};
 use configopt::ConfigOpt;
 use habitat_core::{os::process::ShutdownTimeout,
                    package::PackageIdent,
-                   service::{ServiceBind,
+                   service::{MaxUnavailable,
+                             ServiceBind,
                              ServiceGroup},
                    ChannelIdent};
-use habitat_sup_protocol::types::UpdateCondition;
+use habitat_sup_protocol::types::{CanaryDecision,
+                                  UpdateCondition};
 use structopt::StructOpt;
 use url::Url;
 
@@ -19,12 +23,61 @@ use url::Url;
 #[structopt(no_version)]
 #[allow(clippy::large_enum_variant)]
 pub enum Svc {
+    /// Promote or abort the canary stage of a service's rolling update
+    Canary {
+        /// Whether to promote the update to the rest of the service group or abort it
+        #[structopt(name = "DECISION", possible_values = CanaryDecision::VARIANTS)]
+        decision:   CanaryDecision,
+        #[structopt(flatten)]
+        pkg_ident:  PkgIdent,
+        #[structopt(flatten)]
+        remote_sup: RemoteSup,
+    },
     Key(Key),
     /// Load a service to be started and supervised by Habitat from a package identifier. If an
     /// installed package doesn't satisfy the given package identifier, a suitable package will be
     /// installed from Builder.
     #[structopt(no_version)]
     Load(Load),
+    /// Show the output of a loaded Habitat service
+    Logs {
+        #[structopt(flatten)]
+        pkg_ident:  PkgIdent,
+        /// Keep showing output as the service writes it
+        #[structopt(name = "FOLLOW", short = "f", long = "follow")]
+        follow:     bool,
+        /// The number of lines of existing output to show
+        #[structopt(name = "LINES", short = "n", long = "lines", default_value = "10")]
+        lines:      u64,
+        #[structopt(flatten)]
+        remote_sup: RemoteSup,
+    },
+    /// Reload a running Habitat service without restarting it by running its reload hook
+    Reload {
+        #[structopt(flatten)]
+        pkg_ident:  PkgIdent,
+        /// Send this signal to the service's process instead of running its reload hook
+        #[structopt(name = "SIGNAL",
+                    short = "s",
+                    long = "signal",
+                    possible_values = &["HUP", "INT", "QUIT", "USR1", "USR2", "ALRM", "TERM"])]
+        signal:     Option<String>,
+        #[structopt(flatten)]
+        remote_sup: RemoteSup,
+    },
+    /// Restart a running Habitat service
+    Restart {
+        #[structopt(flatten)]
+        pkg_ident:        PkgIdent,
+        #[structopt(flatten)]
+        remote_sup:       RemoteSup,
+        /// The delay in seconds after sending the shutdown signal to wait before killing the
+        /// service process
+        ///
+        /// The default value is set in the packages plan file.
+        #[structopt(name = "SHUTDOWN_TIMEOUT", long = "shutdown-timeout")]
+        shutdown_timeout: Option<ShutdownTimeout>,
+    },
     /// Start a loaded, but stopped, Habitat service.
     Start {
         #[structopt(flatten)]
@@ -67,6 +120,9 @@ pub enum Svc {
         #[structopt(name = "SHUTDOWN_TIMEOUT", long = "shutdown-timeout")]
         shutdown_timeout: Option<ShutdownTimeout>,
     },
+    /// Change the settings of a loaded service. Settings which are not given are left as they
+    /// are. The service is only restarted if its binds or binding mode change.
+    Update(Update),
 }
 
 #[derive(ConfigOpt, StructOpt)]
@@ -118,7 +174,7 @@ pub struct SharedLoad {
     #[structopt(long = "strategy",
                 short = "s",
                 default_value = "none",
-                possible_values = &["none", "at-once", "rolling"])]
+                possible_values = &["none", "at-once", "rolling", "canary"])]
     pub strategy:              habitat_sup_protocol::types::UpdateStrategy,
     /// The condition dictating when this service should update
     ///
@@ -134,6 +190,14 @@ pub struct SharedLoad {
                 default_value = UpdateCondition::Latest.as_str(),
                 possible_values = UpdateCondition::VARIANTS)]
     pub update_condition:      UpdateCondition,
+    /// The number (ex: 3) or percentage (ex: 25%) of members which may update concurrently during
+    /// a rolling update [default: 1]
+    #[structopt(long = "max-unavailable")]
+    pub max_unavailable:       Option<MaxUnavailable>,
+    /// The number of followers which update during the canary stage of a canary update [default:
+    /// 1]
+    #[structopt(long = "canaries", validator = valid_canaries)]
+    pub canaries:              Option<u32>,
     /// One or more service groups to bind to a configuration
     #[structopt(long = "bind")]
     #[serde(default)]
@@ -152,6 +216,26 @@ pub struct SharedLoad {
     // based format.
     #[structopt(long = "health-check-interval", short = "i", default_value = "30")]
     pub health_check_interval: u64,
+    /// A health check for the Supervisor to run in place of the service's health-check hook (ex:
+    /// tcp://localhost:5432, http://localhost:8080/health, exec:pg_isready -q)
+    // Kept as a `String` so that it can be given in the same short form in a config file as on the
+    // command line.
+    #[structopt(long = "health-check", validator = valid_health_check)]
+    pub health_check:          Option<String>,
+    /// The number of times the service may be restarted within the restart window before the
+    /// Supervisor gives up on it, or 0 for no limit [default: 0]
+    #[structopt(long = "max-restarts")]
+    pub max_restarts:          Option<u32>,
+    /// The period in seconds over which restarts are counted [default: 600]
+    #[structopt(long = "restart-window")]
+    pub restart_window:        Option<u32>,
+    /// The delay in seconds before the first restart, doubling with each further restart
+    /// [default: 1]
+    #[structopt(long = "restart-backoff")]
+    pub restart_backoff:       Option<u32>,
+    /// The longest delay in seconds before a restart [default: 60]
+    #[structopt(long = "max-restart-backoff")]
+    pub max_restart_backoff:   Option<u32>,
     /// The delay in seconds after sending the shutdown signal to wait before killing the service
     /// process
     ///
@@ -196,3 +280,64 @@ pub struct Load {
     #[serde(flatten)]
     shared_load: SharedLoad,
 }
+
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(no_version, rename_all = "screamingsnake")]
+#[allow(dead_code)]
+pub struct Update {
+    #[structopt(flatten)]
+    pkg_ident:             PkgIdent,
+    /// Receive updates from the specified release channel
+    #[structopt(long = "channel")]
+    channel:               Option<ChannelIdent>,
+    /// Receive updates from an alternate Builder endpoint
+    #[structopt(name = "BLDR_URL", short = "u", long = "url")]
+    bldr_url:              Option<Url>,
+    /// One or more service groups to bind to a configuration, replacing the current binds
+    #[structopt(long = "bind")]
+    bind:                  Vec<ServiceBind>,
+    /// Governs how the presence or absence of binds affects service startup
+    #[structopt(long = "binding-mode", possible_values = &["strict", "relaxed"])]
+    binding_mode:          Option<habitat_sup_protocol::types::BindingMode>,
+    /// The update strategy
+    #[structopt(long = "strategy",
+                short = "s",
+                possible_values = &["none", "at-once", "rolling", "canary"])]
+    strategy:              Option<habitat_sup_protocol::types::UpdateStrategy>,
+    /// The condition dictating when this service should update
+    #[structopt(long = "update-condition", possible_values = UpdateCondition::VARIANTS)]
+    update_condition:      Option<UpdateCondition>,
+    /// The number (ex: 3) or percentage (ex: 25%) of members which may update concurrently during
+    /// a rolling update
+    #[structopt(long = "max-unavailable")]
+    max_unavailable:       Option<MaxUnavailable>,
+    /// The number of followers which update during the canary stage of a canary update
+    #[structopt(long = "canaries", validator = valid_canaries)]
+    canaries:              Option<u32>,
+    /// The interval in seconds on which to run health checks
+    #[structopt(long = "health-check-interval", short = "i")]
+    health_check_interval: Option<u64>,
+    /// A health check for the Supervisor to run in place of the service's health-check hook (ex:
+    /// tcp://localhost:5432, http://localhost:8080/health, exec:pg_isready -q)
+    #[structopt(long = "health-check", validator = valid_health_check)]
+    health_check:          Option<String>,
+    /// The number of times the service may be restarted within the restart window before the
+    /// Supervisor gives up on it, or 0 for no limit
+    #[structopt(long = "max-restarts")]
+    max_restarts:          Option<u32>,
+    /// The period in seconds over which restarts are counted
+    #[structopt(long = "restart-window")]
+    restart_window:        Option<u32>,
+    /// The delay in seconds before the first restart, doubling with each further restart
+    #[structopt(long = "restart-backoff")]
+    restart_backoff:       Option<u32>,
+    /// The longest delay in seconds before a restart
+    #[structopt(long = "max-restart-backoff")]
+    max_restart_backoff:   Option<u32>,
+    /// The delay in seconds after sending the shutdown signal to wait before killing the service
+    /// process
+    #[structopt(long = "shutdown-timeout")]
+    shutdown_timeout:      Option<ShutdownTimeout>,
+    #[structopt(flatten)]
+    remote_sup:            RemoteSup,
+}
@@ -74,6 +74,30 @@ pub struct RemoteSup {
     /// Address to a remote Supervisor's Control Gateway [default: 127.0.0.1:9632]
     #[structopt(name = "REMOTE_SUP", long = "remote-sup", short = "r")]
     remote_sup: Option<SocketAddr>,
+    /// The CA certificate used to verify a remote Supervisor's Control Gateway over TLS
+    #[structopt(name = "CTL_SERVER_CA_CERTIFICATE",
+                long = "ctl-server-ca-certificate",
+                env = "HAB_CTL_SERVER_CA_CERTIFICATE",
+                requires = "CTL_SERVER_NAME")]
+    ctl_server_ca_certificate: Option<PathBuf>,
+    /// The name to verify the remote Supervisor's Control Gateway certificate against
+    #[structopt(name = "CTL_SERVER_NAME",
+                long = "ctl-server-name",
+                env = "HAB_CTL_SERVER_NAME",
+                requires = "CTL_SERVER_CA_CERTIFICATE")]
+    ctl_server_name: Option<String>,
+    /// The certificate presented to a remote Supervisor's Control Gateway over TLS
+    #[structopt(name = "CTL_CLIENT_CERTIFICATE",
+                long = "ctl-client-certificate",
+                env = "HAB_CTL_CLIENT_CERTIFICATE",
+                requires_all = &["CTL_CLIENT_KEY", "CTL_SERVER_CA_CERTIFICATE"])]
+    ctl_client_certificate: Option<PathBuf>,
+    /// The private key of the certificate presented to a remote Supervisor's Control Gateway
+    #[structopt(name = "CTL_CLIENT_KEY",
+                long = "ctl-client-key",
+                env = "HAB_CTL_CLIENT_KEY",
+                requires = "CTL_CLIENT_CERTIFICATE")]
+    ctl_client_key: Option<PathBuf>,
 }
 
 pub fn socket_addr_with_default_port<S: AsRef<str>>(addr: S,
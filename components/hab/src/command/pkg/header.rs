@@ -15,6 +15,11 @@ pub fn start(ui: &mut UI, src: &Path) -> Result<()> {
         println!("Key Name       : {}", header.key_name);
         println!("Hash Type      : {}", header.hash_type);
         println!("Raw Signature  : {}", header.signature_raw);
+        match artifact::payload_compression(src) {
+            Ok(Some(compression)) => println!("Compression    : {}", compression),
+            Ok(None) => println!("Compression    : none"),
+            Err(_) => ui.warn("Failed to detect package compression.")?,
+        }
     } else {
         ui.warn("Failed to read package header.")?;
     }
@@ -1,21 +1,45 @@
-use std::path::Path;
+use std::{fs,
+          path::Path};
 
-use crate::{common::ui::{Status,
+use crate::{common::ui::{Glyph,
+                         Status,
                          UIWriter,
                          UI},
-            hcore::crypto::{artifact,
+            hcore::crypto::{artifact::{self,
+                                       Compression},
                             SigKeyPair}};
 
 use crate::error::Result;
 
-pub fn start(ui: &mut UI, origin: &SigKeyPair, src: &Path, dst: &Path) -> Result<()> {
+/// Sign the archive at `src`, recompressing its tarball with `compression` first if one is given.
+pub fn start(ui: &mut UI,
+             origin: &SigKeyPair,
+             src: &Path,
+             dst: &Path,
+             compression: Option<Compression>)
+             -> Result<()> {
     ui.begin(format!("Signing {}", src.display()))?;
+    if let Some(compression) = compression {
+        let tarball = dst.with_extension(format!("tar.{}.tmp", compression));
+        ui.status(Status::Custom(Glyph::FingerPoint, String::from("Compressing")),
+                  format!("{} with {}", src.display(), compression))?;
+        artifact::recompress(src, &tarball, compression)?;
+        let signed = sign(ui, origin, &tarball, dst);
+        fs::remove_file(&tarball)?;
+        signed?;
+    } else {
+        sign(ui, origin, src, dst)?;
+    }
+    ui.end(format!("Signed artifact {}.", dst.display()))?;
+    Ok(())
+}
+
+fn sign(ui: &mut UI, origin: &SigKeyPair, src: &Path, dst: &Path) -> Result<()> {
     ui.status(Status::Signing,
               format!("{} with {} to create {}",
                       src.display(),
                       &origin.name_with_rev(),
                       dst.display()))?;
     artifact::sign(src, dst, origin)?;
-    ui.end(format!("Signed artifact {}.", dst.display()))?;
     Ok(())
 }
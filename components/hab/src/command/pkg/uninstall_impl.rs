@@ -19,7 +19,8 @@ use habitat_core::{error as herror,
 use habitat_sup_client::{SrvClient,
                          SrvClientError};
 use habitat_sup_protocol;
-use std::{fs,
+use std::{convert::TryFrom,
+          fs,
           path::Path,
           str::FromStr};
 
@@ -251,7 +252,7 @@ async fn supervisor_services() -> Result<Vec<PackageIdent>> {
             "ServiceStatus" => {
                 let m = reply.parse::<habitat_sup_protocol::types::ServiceStatus>()
                              .map_err(SrvClientError::Decode)?;
-                out.push(m.ident.into());
+                out.push(PackageIdent::try_from(m.ident)?);
             }
             "NetOk" => (),
             "NetErr" => {
@@ -12,6 +12,11 @@ pub fn start(ui: &mut UI, src: &Path, cache: &Path) -> Result<()> {
     let (name_with_rev, hash) = artifact::verify(src, cache)?;
     ui.status(Status::Verified,
               format!("checksum {} signed with {}", &hash, &name_with_rev))?;
+    let compression = match artifact::payload_compression(src)? {
+        Some(compression) => format!("tarball compressed with {}", compression),
+        None => String::from("uncompressed tarball"),
+    };
+    ui.status(Status::Verified, compression)?;
     ui.end(format!("Verified artifact {}.", &src.display()))?;
     Ok(())
 }
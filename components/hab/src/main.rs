@@ -30,10 +30,11 @@ use hab::{cli::{self,
 use habitat_api_client::BuildOnUpload;
 use habitat_common::{self as common,
                      cli::cache_key_path_from_matches,
-                     command::package::install::{InstallHookMode,
-                                                 InstallMode,
-                                                 InstallSource,
-                                                 LocalPackageUsage},
+                     command::package::{install::{InstallHookMode,
+                                                  InstallMode,
+                                                  InstallSource,
+                                                  LocalPackageUsage},
+                                        lockfile::Lockfile},
                      output,
                      types::ListenCtlAddr,
                      ui::{Status,
@@ -43,7 +44,8 @@ use habitat_common::{self as common,
                      FeatureFlag};
 #[cfg(windows)]
 use habitat_core::crypto::dpapi::encrypt;
-use habitat_core::{crypto::{init,
+use habitat_core::{crypto::{artifact::Compression,
+                            init,
                             keys::PairType,
                             BoxKeyPair,
                             SigKeyPair},
@@ -60,7 +62,8 @@ use habitat_core::{crypto::{init,
                    url::{bldr_url_from_env,
                          default_bldr_url},
                    ChannelIdent};
-use habitat_sup_client::{SrvClient,
+use habitat_sup_client::{CtlTlsConfig,
+                         SrvClient,
                          SrvClientError};
 use habitat_sup_protocol::{self as sup_proto,
                            codec::*,
@@ -263,6 +266,7 @@ async fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                 ("hash", Some(m)) => sub_pkg_hash(m)?,
                 ("install", Some(m)) => sub_pkg_install(ui, m, feature_flags).await?,
                 ("list", Some(m)) => sub_pkg_list(m)?,
+                ("lock", Some(m)) => sub_pkg_lock(ui, m).await?,
                 ("path", Some(m)) => sub_pkg_path(m)?,
                 ("provides", Some(m)) => sub_pkg_provides(m)?,
                 ("search", Some(m)) => sub_pkg_search(m).await?,
@@ -307,8 +311,13 @@ async fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                         _ => unreachable!(),
                     }
                 }
+                ("canary", Some(m)) => sub_svc_canary(m).await?,
                 ("load", Some(m)) => sub_svc_load(m).await?,
+                ("logs", Some(m)) => sub_svc_logs(m).await?,
+                ("reload", Some(m)) => sub_svc_reload(m).await?,
+                ("restart", Some(m)) => sub_svc_restart(m).await?,
                 ("unload", Some(m)) => sub_svc_unload(m).await?,
+                ("update", Some(m)) => sub_svc_update(m).await?,
                 ("start", Some(m)) => sub_svc_start(m).await?,
                 ("stop", Some(m)) => sub_svc_stop(m).await?,
                 ("status", Some(m)) => sub_svc_status(m).await?,
@@ -317,6 +326,7 @@ async fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
         }
         ("sup", Some(m)) => {
             match m.subcommand() {
+                ("apply", Some(m)) => sub_sup_apply(m).await?,
                 ("depart", Some(m)) => sub_sup_depart(m).await?,
                 ("secret", Some(m)) => {
                     match m.subcommand() {
@@ -888,7 +898,6 @@ async fn sub_pkg_install(ui: &mut UI,
                          -> Result<()> {
     let url = bldr_url_from_matches(&m)?;
     let channel = channel_from_matches_or_default(m);
-    let install_sources = install_sources_from_matches(m)?;
     let token = maybe_auth_token(&m);
     let install_mode =
         if feature_flags.contains(FeatureFlag::OFFLINE_INSTALL) && m.is_present("OFFLINE") {
@@ -912,7 +921,23 @@ async fn sub_pkg_install(ui: &mut UI,
 
     init()?;
 
-    for install_source in install_sources.iter() {
+    if let Some(lockfile) = m.value_of("LOCKED") {
+        let lockfile = Lockfile::read(Path::new(lockfile))?;
+        let artifact_cache_path = cache_artifact_path(Some(&*FS_ROOT_PATH));
+        let pkg_install = common::command::package::install::start_locked(ui,
+                                                                          &url,
+                                                                          &lockfile,
+                                                                          PRODUCT,
+                                                                          VERSION,
+                                                                          &*FS_ROOT_PATH,
+                                                                          &artifact_cache_path,
+                                                                          token.as_deref(),
+                                                                          &install_mode,
+                                                                          install_hook_mode).await?;
+        return binlink_installed_package(ui, m, pkg_install.ident());
+    }
+
+    for install_source in install_sources_from_matches(m)?.iter() {
         let pkg_install =
             common::command::package::install::start(ui,
                                                      &url,
@@ -926,19 +951,43 @@ async fn sub_pkg_install(ui: &mut UI,
                                                      &install_mode,
                                                      &local_package_usage,
                                                      install_hook_mode).await?;
+        binlink_installed_package(ui, m, pkg_install.ident())?;
+    }
+    Ok(())
+}
 
-        if let Some(dest_dir) = binlink_dest_dir_from_matches(m) {
-            let force = m.is_present("FORCE");
-            command::pkg::binlink::binlink_all_in_pkg(ui,
-                                                      pkg_install.ident(),
-                                                      &dest_dir,
-                                                      &FS_ROOT_PATH,
-                                                      force)?;
-        }
+fn binlink_installed_package(ui: &mut UI, m: &ArgMatches<'_>, ident: &PackageIdent) -> Result<()> {
+    if let Some(dest_dir) = binlink_dest_dir_from_matches(m) {
+        let force = m.is_present("FORCE");
+        command::pkg::binlink::binlink_all_in_pkg(ui, ident, &dest_dir, &FS_ROOT_PATH, force)?;
     }
     Ok(())
 }
 
+async fn sub_pkg_lock(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
+    let url = bldr_url_from_matches(&m)?;
+    let channel = channel_from_matches_or_default(m);
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?; // Required via clap
+    let path = Path::new(m.value_of("LOCKFILE").unwrap()); // Defaulted via clap
+    let token = maybe_auth_token(&m);
+
+    init()?;
+
+    let lockfile =
+        common::command::package::install::lock(ui,
+                                                &url,
+                                                &channel,
+                                                (&ident, PackageTarget::active_target()),
+                                                PRODUCT,
+                                                VERSION,
+                                                &*FS_ROOT_PATH,
+                                                &cache_artifact_path(Some(&*FS_ROOT_PATH)),
+                                                token.as_deref()).await?;
+    lockfile.write(path)?;
+    ui.status(Status::Created, format!("lockfile {}", path.display()))?;
+    Ok(())
+}
+
 fn sub_pkg_path(m: &ArgMatches<'_>) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
 
@@ -980,7 +1029,13 @@ fn sub_pkg_sign(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
                                                &cache_key_path,
                                                Some(&PairType::Secret))?;
 
-    command::pkg::sign::start(ui, &pair, &src, &dst)
+    let compression = if m.is_present("ZSTD") {
+        Some(Compression::Zstd)
+    } else {
+        None
+    };
+
+    command::pkg::sign::start(ui, &pair, &src, &dst, compression)
 }
 
 async fn sub_pkg_bulkupload(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
@@ -1106,6 +1161,7 @@ async fn sub_pkg_channels(ui: &mut UI, m: &ArgMatches<'_>) -> Result<()> {
 async fn sub_svc_set(m: &ArgMatches<'_>) -> Result<()> {
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let service_group = ServiceGroup::from_str(m.value_of("SERVICE_GROUP").unwrap())?;
     let mut ui = ui();
@@ -1152,7 +1208,9 @@ async fn sub_svc_set(m: &ArgMatches<'_>) -> Result<()> {
                         .map(ToString::to_string)
                         .unwrap_or_else(|| "UNKNOWN".to_string()),))?;
     ui.status(Status::Creating, "service configuration")?;
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, validate).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, validate).await?;
+    let mut invalid = false;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         match reply.message_id() {
@@ -1163,15 +1221,26 @@ async fn sub_svc_set(m: &ArgMatches<'_>) -> Result<()> {
                 match ErrCode::from_i32(m.code) {
                     Some(ErrCode::InvalidPayload) => {
                         ui.warn(m)?;
+                        invalid = true;
                     }
+                    // The peer does not run the service, so the configuration is applied without
+                    // being validated.
+                    Some(ErrCode::NotFound) => ui.warn(m.msg)?,
                     _ => return Err(SrvClientError::from(m).into()),
                 }
             }
             _ => return Err(SrvClientError::from(io::Error::from(io::ErrorKind::UnexpectedEof)).into()),
         }
     }
+    if invalid {
+        return Err(Error::ArgumentError("Configuration does not match the \
+                                         service's schema, nothing was \
+                                         applied"
+                                                 .to_string()));
+    }
     ui.status(Status::Applying, format!("via peer {}", listen_ctl_addr))?;
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, set).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, set).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         match reply.message_id() {
@@ -1192,10 +1261,12 @@ async fn sub_svc_config(m: &ArgMatches<'_>) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let mut msg = sup_proto::ctl::SvcGetDefaultCfg::default();
     msg.ident = Some(ident.into());
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         match reply.message_id() {
@@ -1217,11 +1288,13 @@ async fn sub_svc_config(m: &ArgMatches<'_>) -> Result<()> {
 async fn sub_svc_load(m: &ArgMatches<'_>) -> Result<()> {
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let mut msg = svc_load_from_input(m)?;
     let ident: PackageIdent = m.value_of("PKG_IDENT").unwrap().parse()?;
     msg.ident = Some(ident.into());
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         handle_ctl_reply(&reply)?;
@@ -1233,13 +1306,87 @@ async fn sub_svc_unload(m: &ArgMatches<'_>) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let timeout_in_seconds =
         parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
 
     let msg = sup_proto::ctl::SvcUnload { ident: Some(ident.into()),
                                           timeout_in_seconds };
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        handle_ctl_reply(&reply)?;
+    }
+    Ok(())
+}
+
+async fn sub_svc_update(m: &ArgMatches<'_>) -> Result<()> {
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
+    let secret_key = config::ctl_secret_key(&cfg)?;
+    // Only send the settings which were given, so everything else is left as it is. Unlike
+    // `svc_load_from_input`, the Builder URL is not taken from the environment.
+    let mut msg = sup_proto::ctl::SvcUpdate::default();
+    msg.ident = Some(ident.into());
+    msg.bldr_url = m.value_of("BLDR_URL").map(str::to_string);
+    msg.bldr_channel = channel_from_matches(m).map(|c| c.to_string());
+    msg.binds = get_binds_from_input(m)?;
+    msg.binding_mode = get_binding_mode_from_input(m).map(|v| v as i32);
+    msg.update_strategy = get_strategy_from_input(m).map(|v| v as i32);
+    msg.update_condition = get_update_condition_from_input(m).map(|v| v as i32);
+    msg.max_unavailable = m.value_of("MAX_UNAVAILABLE").map(str::to_string);
+    msg.canaries = parse_optional_arg("CANARIES", m);
+    msg.health_check_interval = get_health_check_interval_from_input(m);
+    msg.health_check = m.value_of("HEALTH_CHECK").map(str::to_string);
+    msg.max_restarts = parse_optional_arg("MAX_RESTARTS", m);
+    msg.restart_window = parse_optional_arg("RESTART_WINDOW", m);
+    msg.restart_backoff = parse_optional_arg("RESTART_BACKOFF", m);
+    msg.max_restart_backoff = parse_optional_arg("MAX_RESTART_BACKOFF", m);
+    msg.shutdown_timeout =
+        parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        handle_ctl_reply(&reply)?;
+    }
+    Ok(())
+}
+
+async fn sub_svc_canary(m: &ArgMatches<'_>) -> Result<()> {
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
+    let decision = sup_proto::types::CanaryDecision::from_str(m.value_of("DECISION").unwrap())?;
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
+    let secret_key = config::ctl_secret_key(&cfg)?;
+    let msg = sup_proto::ctl::SvcCanary { ident:    Some(ident.into()),
+                                          decision: Some(decision as i32), };
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        handle_ctl_reply(&reply)?;
+    }
+    Ok(())
+}
+
+async fn sub_svc_logs(m: &ArgMatches<'_>) -> Result<()> {
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
+    let secret_key = config::ctl_secret_key(&cfg)?;
+    let lines = value_t!(m, "LINES", u64).unwrap();
+    let msg = sup_proto::ctl::SvcLogs { ident:  Some(ident.into()),
+                                        follow: Some(m.is_present("FOLLOW")),
+                                        lines:  Some(lines), };
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         handle_ctl_reply(&reply)?;
@@ -1251,10 +1398,12 @@ async fn sub_svc_start(m: &ArgMatches<'_>) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let mut msg = sup_proto::ctl::SvcStart::default();
     msg.ident = Some(ident.into());
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         handle_ctl_reply(&reply)?;
@@ -1265,6 +1414,7 @@ async fn sub_svc_start(m: &ArgMatches<'_>) -> Result<()> {
 async fn sub_svc_status(m: &ArgMatches<'_>) -> Result<()> {
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let mut msg = sup_proto::ctl::SvcStatus::default();
     if let Some(pkg) = m.value_of("PKG_IDENT") {
@@ -1272,7 +1422,8 @@ async fn sub_svc_status(m: &ArgMatches<'_>) -> Result<()> {
     }
 
     let mut out = TabWriter::new(io::stdout());
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     // Ensure there is at least one result from the server otherwise produce an error
     if let Some(message_result) = response.next().await {
         let reply = message_result?;
@@ -1292,13 +1443,52 @@ async fn sub_svc_stop(m: &ArgMatches<'_>) -> Result<()> {
     let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let timeout_in_seconds =
         parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
 
     let msg = sup_proto::ctl::SvcStop { ident: Some(ident.into()),
                                         timeout_in_seconds };
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        handle_ctl_reply(&reply)?;
+    }
+    Ok(())
+}
+
+async fn sub_svc_restart(m: &ArgMatches<'_>) -> Result<()> {
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
+    let secret_key = config::ctl_secret_key(&cfg)?;
+    let timeout_in_seconds =
+        parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
+
+    let msg = sup_proto::ctl::SvcRestart { ident: Some(ident.into()),
+                                           timeout_in_seconds };
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        handle_ctl_reply(&reply)?;
+    }
+    Ok(())
+}
+
+async fn sub_svc_reload(m: &ArgMatches<'_>) -> Result<()> {
+    let ident = PackageIdent::from_str(m.value_of("PKG_IDENT").unwrap())?;
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
+    let secret_key = config::ctl_secret_key(&cfg)?;
+    let msg = sup_proto::ctl::SvcReload { ident:  Some(ident.into()),
+                                          signal: m.value_of("SIGNAL").map(str::to_string), };
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         handle_ctl_reply(&reply)?;
@@ -1310,6 +1500,7 @@ async fn sub_file_put(m: &ArgMatches<'_>) -> Result<()> {
     let service_group = ServiceGroup::from_str(m.value_of("SERVICE_GROUP").unwrap())?;
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let mut ui = ui();
     let mut msg = sup_proto::ctl::SvcFilePut::default();
@@ -1351,7 +1542,8 @@ async fn sub_file_put(m: &ArgMatches<'_>) -> Result<()> {
     }
     ui.status(Status::Applying, format!("via peer {}", listen_ctl_addr))
       .unwrap();
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         match reply.message_id() {
@@ -1373,9 +1565,27 @@ async fn sub_file_put(m: &ArgMatches<'_>) -> Result<()> {
     Ok(())
 }
 
+async fn sub_sup_apply(m: &ArgMatches<'_>) -> Result<()> {
+    let cfg = config::load()?;
+    let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
+    let secret_key = config::ctl_secret_key(&cfg)?;
+    let mut msg = sup_proto::ctl::SupApply::default();
+    msg.manifest = Some(std::fs::read_to_string(m.value_of("MANIFEST").unwrap())?);
+    msg.dry_run = Some(m.is_present("DRY_RUN"));
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        handle_ctl_reply(&reply)?;
+    }
+    Ok(())
+}
+
 async fn sub_sup_depart(m: &ArgMatches<'_>) -> Result<()> {
     let cfg = config::load()?;
     let listen_ctl_addr = listen_ctl_addr_from_input(m)?;
+    let ctl_tls = ctl_tls_config_from_input(m);
     let secret_key = config::ctl_secret_key(&cfg)?;
     let mut ui = ui();
     let mut msg = sup_proto::ctl::SupDepart::default();
@@ -1386,7 +1596,8 @@ async fn sub_sup_depart(m: &ArgMatches<'_>) -> Result<()> {
       .unwrap();
     ui.status(Status::Applying, format!("via peer {}", listen_ctl_addr))
       .unwrap();
-    let mut response = SrvClient::request(&listen_ctl_addr, &secret_key, msg).await?;
+    let mut response =
+        SrvClient::request_with_tls(&listen_ctl_addr, &ctl_tls, &secret_key, msg).await?;
     while let Some(message_result) = response.next().await {
         let reply = message_result?;
         match reply.message_id() {
@@ -1957,6 +2168,14 @@ fn listen_ctl_addr_from_input(m: &ArgMatches<'_>) -> Result<ListenCtlAddr> {
      .map_or(Ok(ListenCtlAddr::default()), resolve_listen_ctl_addr)
 }
 
+fn ctl_tls_config_from_input(m: &ArgMatches<'_>) -> CtlTlsConfig {
+    let path = |name: &str| m.value_of(name).map(PathBuf::from);
+    CtlTlsConfig { server_ca_certificate: path("CTL_SERVER_CA_CERTIFICATE"),
+                   server_name:           m.value_of("CTL_SERVER_NAME").map(str::to_string),
+                   client_certificate:    path("CTL_CLIENT_CERTIFICATE"),
+                   client_key:            path("CTL_CLIENT_KEY"), }
+}
+
 fn resolve_listen_ctl_addr(input: &str) -> Result<ListenCtlAddr> {
     let listen_ctl_addr = if input.find(':').is_some() {
         input.to_string()
@@ -2031,6 +2250,13 @@ fn svc_load_from_input(m: &ArgMatches) -> Result<sup_proto::ctl::SvcLoad> {
     msg.topology = get_topology_from_input(m).map(|v| v as i32);
     msg.update_strategy = get_strategy_from_input(m).map(|v| v as i32);
     msg.update_condition = get_update_condition_from_input(m).map(|v| v as i32);
+    msg.max_unavailable = m.value_of("MAX_UNAVAILABLE").map(str::to_string);
+    msg.canaries = parse_optional_arg("CANARIES", m);
+    msg.health_check = m.value_of("HEALTH_CHECK").map(str::to_string);
+    msg.max_restarts = parse_optional_arg("MAX_RESTARTS", m);
+    msg.restart_window = parse_optional_arg("RESTART_WINDOW", m);
+    msg.restart_backoff = parse_optional_arg("RESTART_BACKOFF", m);
+    msg.max_restart_backoff = parse_optional_arg("MAX_RESTART_BACKOFF", m);
     msg.shutdown_timeout =
         parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
     Ok(msg)
@@ -180,6 +180,9 @@ impl LauncherCli {
     /// `username` and `groupname` are string names, while `uid` and
     /// `gid` are numeric IDs. Newer versions of the Launcher can
     /// accept either, but prefer numeric IDs.
+    ///
+    /// The service's output is captured in `log_path`. Older
+    /// Launchers ignore it.
     pub fn spawn(&self,
                  id: &str,
                  bin: &Path,
@@ -188,7 +191,8 @@ impl LauncherCli {
                             groupname,
                             gid, }: UserInfo,
                  password: Option<&str>,
-                 env: Env)
+                 env: Env,
+                 log_path: &Path)
                  -> Result<Pid> {
         // On Windows, we only expect user to be Some.
         //
@@ -203,7 +207,8 @@ impl LauncherCli {
                                     svc_group_id: gid,
                                     svc_password: password.map(str::to_string),
                                     env,
-                                    id: id.to_string() };
+                                    id: id.to_string(),
+                                    log_path: Some(log_path.to_string_lossy().into_owned()) };
 
         Self::send(&self.tx, &msg)?;
         let reply = Self::recv::<protocol::SpawnOk>(&self.rx)?;
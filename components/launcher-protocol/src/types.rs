@@ -134,6 +134,7 @@ pub struct Spawn {
     pub env:          BTreeMap<String, String>,
     pub svc_user_id:  Option<u32>,
     pub svc_group_id: Option<u32>,
+    pub log_path:     Option<String>,
 }
 
 impl LauncherMessage for Spawn {
@@ -149,7 +150,8 @@ impl LauncherMessage for Spawn {
                    svc_password: proto.svc_password,
                    env:          BTreeMap::from_iter(proto.env.into_iter()),
                    svc_user_id:  proto.svc_user_id,
-                   svc_group_id: proto.svc_group_id, })
+                   svc_group_id: proto.svc_group_id,
+                   log_path:     proto.log_path, })
     }
 }
 
@@ -162,7 +164,8 @@ impl From<Spawn> for generated::Spawn {
                            svc_password: value.svc_password,
                            env:          HashMap::from_iter(value.env.into_iter()),
                            svc_user_id:  value.svc_user_id,
-                           svc_group_id: value.svc_group_id, }
+                           svc_group_id: value.svc_group_id,
+                           log_path:     value.log_path, }
     }
 }
 
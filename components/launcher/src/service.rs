@@ -3,8 +3,9 @@ use crate::protocol;
 use core::os::process::windows_child::{ChildStderr,
                                        ChildStdout,
                                        ExitStatus};
-use habitat_common::output::{self,
-                             StructuredOutput};
+use habitat_common::{output::{self,
+                              StructuredOutput},
+                     service_log::RotatingLog};
 #[cfg(unix)]
 use std::process::{ChildStderr,
                    ChildStdout,
@@ -14,6 +15,8 @@ use std::{fmt,
                BufRead,
                BufReader,
                Read},
+          sync::{Arc,
+                 Mutex},
           thread};
 
 pub use crate::sys::service::*;
@@ -29,16 +32,29 @@ impl Service {
                stdout: Option<ChildStdout>,
                stderr: Option<ChildStderr>)
                -> Self {
+        let log = match spawn.log_path {
+            Some(ref path) => {
+                match RotatingLog::open(path) {
+                    Ok(log) => Some(Arc::new(Mutex::new(log))),
+                    Err(e) => {
+                        error!("Unable to open log file {} for {}: {}", path, spawn.id, e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
         if let Some(stdout) = stdout {
             let id = spawn.id.to_string();
+            let log = log.clone();
             thread::Builder::new().name(format!("{}-out", spawn.id))
-                                  .spawn(move || pipe_stdout(stdout, &id))
+                                  .spawn(move || pipe_stdout(stdout, &id, log))
                                   .ok();
         }
         if let Some(stderr) = stderr {
             let id = spawn.id.to_string();
             thread::Builder::new().name(format!("{}-err", spawn.id))
-                                  .spawn(move || pipe_stderr(stderr, &id))
+                                  .spawn(move || pipe_stderr(stderr, &id, log))
                                   .ok();
         }
         Service { args: spawn,
@@ -69,13 +85,14 @@ impl fmt::Debug for Service {
 }
 
 /// Consume output from a child process until EOF, then finish
-fn pipe_stdout<T>(out: T, id: &str)
+fn pipe_stdout<T>(out: T, id: &str, log: Option<Arc<Mutex<RotatingLog>>>)
     where T: Read
 {
     let mut reader = BufReader::new(out);
     let mut buffer = String::new();
     while reader.read_line(&mut buffer).unwrap() > 0 {
         let content = &buffer.trim_end_matches('\n');
+        write_log(log.as_ref(), id, content);
         let so = StructuredOutput::succinct(&id, "O", output::get_format(), content);
         if let Err(e) = so.println() {
             println!("printing output: '{}' to stdout resulted in error: {}",
@@ -86,13 +103,14 @@ fn pipe_stdout<T>(out: T, id: &str)
 }
 
 /// Consume standard error from a child process until EOF, then finish
-fn pipe_stderr<T>(err: T, id: &str)
+fn pipe_stderr<T>(err: T, id: &str, log: Option<Arc<Mutex<RotatingLog>>>)
     where T: Read
 {
     let mut reader = BufReader::new(err);
     let mut buffer = String::new();
     while reader.read_line(&mut buffer).unwrap() > 0 {
         let content = &buffer.trim_end_matches('\n');
+        write_log(log.as_ref(), id, content);
         let so = StructuredOutput::succinct(&id, "E", output::get_format(), content);
         if let Err(e) = so.eprintln() {
             eprintln!("printing output: '{}' to stderr resulted in error: {}",
@@ -101,3 +119,13 @@ fn pipe_stderr<T>(err: T, id: &str)
         buffer.clear();
     }
 }
+
+/// Append a line of a child process's output to its log file, if it has one
+fn write_log(log: Option<&Arc<Mutex<RotatingLog>>>, id: &str, content: &str) {
+    if let Some(log) = log {
+        let mut log = log.lock().expect("Service log lock poisoned");
+        if let Err(e) = log.write_line(content) {
+            error!("Unable to write to log file for {}: {}", id, e);
+        }
+    }
+}
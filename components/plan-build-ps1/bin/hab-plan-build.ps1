@@ -1193,8 +1193,8 @@ function Invoke-DefaultInstall {
 }
 
 # Copy the `./config` directory, relative to the Plan, to `$pkg_prefix\config`.
-# Do the same with `default.toml`. Delegates most of the implementation to the
-# `Invoke-DefaultBuildConfig` function.
+# Do the same with `default.toml` and its optional `config_schema.json`.
+# Delegates most of the implementation to the `Invoke-DefaultBuildConfig` function.
 function Invoke-BuildConfig {
     Invoke-DefaultBuildConfig
 }
@@ -1217,6 +1217,10 @@ function Invoke-DefaultBuildConfig {
         Write-BuildLine "Writing default.toml"
         Copy-Item "$PLAN_CONTEXT/default.toml" $pkg_prefix
     }
+    if (Test-Path "$PLAN_CONTEXT/config_schema.json") {
+        Write-BuildLine "Writing config_schema.json"
+        Copy-Item "$PLAN_CONTEXT/config_schema.json" $pkg_prefix
+    }
 }
 
 # Write out the `$pkg_prefix\run` file. If a file named `hooks\run`
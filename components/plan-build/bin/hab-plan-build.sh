@@ -1964,8 +1964,8 @@ _build_metadata() {
 }
 
 # Copy the `./config` directory, relative to the Plan, to `$pkg_prefix/config`.
-# Do the same with `default.toml`. Delegates most of the implementation to the
-# `do_default_build_config()` function.
+# Do the same with `default.toml` and its optional `config_schema.json`.
+# Delegates most of the implementation to the `do_default_build_config()` function.
 do_build_config() {
   do_default_build_config
   return $?
@@ -1983,6 +1983,9 @@ do_default_build_config() {
   if [[ -f "$PLAN_CONTEXT/default.toml" ]]; then
     cp "$PLAN_CONTEXT/default.toml" "$pkg_prefix"
   fi
+  if [[ -f "$PLAN_CONTEXT/config_schema.json" ]]; then
+    cp "$PLAN_CONTEXT/config_schema.json" "$pkg_prefix"
+  fi
   return 0
 }
 
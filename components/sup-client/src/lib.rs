@@ -42,18 +42,40 @@ use futures::{sink::SinkExt,
               stream::{Stream,
                        StreamExt}};
 use habitat_common as common;
-use std::{error,
+use std::{env,
+          error,
           fmt,
-          io,
-          path::PathBuf,
+          fs::File,
+          io::{self,
+               BufReader},
+          path::{Path,
+                 PathBuf},
+          sync::Arc,
           time::Duration};
 use tokio::{net::TcpStream,
             time};
+use tokio_rustls::{rustls::{internal::pemfile,
+                            ClientConfig},
+                   webpki::DNSNameRef,
+                   TlsConnector};
 use tokio_util::codec::Framed;
 
 /// Time to wait in milliseconds for a client connection to timeout.
 pub const REQ_TIMEOUT: u64 = 10_000;
 
+/// The name of the user to authenticate to the CtlGateway as. If unset, the secret key is the
+/// Supervisor's shared secret.
+pub const CTL_USER_ENVVAR: &str = "HAB_CTL_USER";
+/// Path to the CA certificate used to verify a CtlGateway speaking TLS. Setting this makes every
+/// request connect over TLS.
+pub const CTL_SERVER_CA_CERTIFICATE_ENVVAR: &str = "HAB_CTL_SERVER_CA_CERTIFICATE";
+/// The name the CtlGateway's certificate was issued for. Required when connecting over TLS.
+pub const CTL_SERVER_NAME_ENVVAR: &str = "HAB_CTL_SERVER_NAME";
+/// Path to the certificate presented to a CtlGateway which requires client authentication.
+pub const CTL_CLIENT_CERTIFICATE_ENVVAR: &str = "HAB_CTL_CLIENT_CERTIFICATE";
+/// Path to the private key for `CTL_CLIENT_CERTIFICATE_ENVVAR`.
+pub const CTL_CLIENT_KEY_ENVVAR: &str = "HAB_CTL_CLIENT_KEY";
+
 /// Error types returned by a [`SrvClient`].
 #[derive(Debug)]
 pub enum SrvClientError {
@@ -71,6 +93,8 @@ pub enum SrvClientError {
     NetErr(NetErr),
     /// A parse error from an Invalid Color string
     ParseColor(termcolor::ParseColorError),
+    /// The TLS settings for connecting to the remote are invalid.
+    Tls(String),
 }
 
 impl error::Error for SrvClientError {}
@@ -101,6 +125,7 @@ impl fmt::Display for SrvClientError {
             SrvClientError::Io(ref err) => format!("{}", err),
             SrvClientError::NetErr(ref err) => format!("{}", err),
             SrvClientError::ParseColor(ref err) => format!("{}", err),
+            SrvClientError::Tls(ref msg) => format!("Unable to connect over TLS: {}", msg),
         };
         write!(f, "{}", content)
     }
@@ -127,13 +152,40 @@ impl From<termcolor::ParseColorError> for SrvClientError {
     fn from(err: termcolor::ParseColorError) -> Self { SrvClientError::ParseColor(err) }
 }
 
+/// How to connect to a CtlGateway which speaks TLS. Without a `server_ca_certificate`, the
+/// connection is not encrypted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CtlTlsConfig {
+    /// The CA certificate used to verify the CtlGateway
+    pub server_ca_certificate: Option<PathBuf>,
+    /// The name the CtlGateway's certificate was issued for
+    pub server_name:           Option<String>,
+    /// The certificate presented to a CtlGateway which requires client authentication
+    pub client_certificate:    Option<PathBuf>,
+    /// The private key for `client_certificate`
+    pub client_key:            Option<PathBuf>,
+}
+
+impl CtlTlsConfig {
+    /// Read the TLS settings from `CTL_SERVER_CA_CERTIFICATE_ENVVAR` and related variables.
+    pub fn from_env() -> Self {
+        let path = |var: &str| env::var_os(var).map(PathBuf::from);
+        CtlTlsConfig { server_ca_certificate: path(CTL_SERVER_CA_CERTIFICATE_ENVVAR),
+                       server_name:           env::var(CTL_SERVER_NAME_ENVVAR).ok(),
+                       client_certificate:    path(CTL_CLIENT_CERTIFICATE_ENVVAR),
+                       client_key:            path(CTL_CLIENT_KEY_ENVVAR), }
+    }
+}
+
 /// Client for connecting and communicating with a server speaking SrvProtocol.
 ///
 /// See module doc for usage.
 pub struct SrvClient;
 
 impl SrvClient {
-    /// Connect to the remote server with the given secret_key and make a request.
+    /// Connect to the remote server with the given secret_key and make a request. The client
+    /// authenticates as the user named by `CTL_USER_ENVVAR`, if set, and connects over TLS as
+    /// configured by `CTL_SERVER_CA_CERTIFICATE_ENVVAR` and related variables.
     ///
     /// Returns a stream of `SrvMessage`'s representing the server response.
     pub async fn request(
@@ -141,13 +193,24 @@ impl SrvClient {
         secret_key: &str,
         request: impl Into<SrvMessage> + fmt::Debug)
         -> Result<impl Stream<Item = Result<SrvMessage, io::Error>>, SrvClientError> {
-        let socket = TcpStream::connect(address.as_ref()).await?;
+        Self::request_with_tls(address, &CtlTlsConfig::from_env(), secret_key, request).await
+    }
+
+    /// Like `request`, but connect over TLS as configured by `tls` instead of the environment.
+    pub async fn request_with_tls(
+        address: &ListenCtlAddr,
+        tls: &CtlTlsConfig,
+        secret_key: &str,
+        request: impl Into<SrvMessage> + fmt::Debug)
+        -> Result<impl Stream<Item = Result<SrvMessage, io::Error>>, SrvClientError> {
+        let socket = Self::connect(address, tls).await?;
         let mut socket = Framed::new(socket, SrvCodec::new());
         let mut current_transaction = SrvTxn::default();
 
         // Send the handshake message to the server
         let mut handshake = protocol::ctl::Handshake::default();
         handshake.secret_key = Some(String::from(secret_key));
+        handshake.user = env::var(CTL_USER_ENVVAR).ok();
         let mut message = SrvMessage::from(handshake);
         message.set_transaction(current_transaction);
         socket.send(message).await?;
@@ -173,6 +236,71 @@ impl SrvClient {
         Ok(socket)
     }
 
+    /// Open a connection to the remote server. The connection is wrapped in TLS if `tls` names a
+    /// server CA certificate.
+    async fn connect(address: &ListenCtlAddr,
+                     tls: &CtlTlsConfig)
+                     -> Result<Box<dyn SrvTransport>, SrvClientError> {
+        let socket = TcpStream::connect(address.as_ref()).await?;
+        let ca_cert_path = match &tls.server_ca_certificate {
+            Some(path) => path,
+            None => return Ok(Box::new(socket)),
+        };
+        let server_name = match &tls.server_name {
+            Some(server_name) => server_name,
+            None => {
+                let msg = "A server name is required to connect over TLS";
+                return Err(SrvClientError::Tls(msg.to_string()));
+            }
+        };
+        let dns_name = DNSNameRef::try_from_ascii_str(server_name).map_err(|_| {
+                           SrvClientError::Tls(format!("'{}' is not a valid server name",
+                                                       server_name))
+                       })?;
+        let config = Self::tls_config(ca_cert_path, tls)?;
+        let socket = TlsConnector::from(Arc::new(config)).connect(dns_name, socket)
+                                                         .await?;
+        Ok(Box::new(socket))
+    }
+
+    fn tls_config(ca_cert_path: &Path, tls: &CtlTlsConfig) -> Result<ClientConfig, SrvClientError> {
+        let mut config = ClientConfig::new();
+        let ca_file = &mut BufReader::new(File::open(ca_cert_path)?);
+        match config.root_store.add_pem_file(ca_file) {
+            Ok((added, _)) if added > 0 => (),
+            _ => {
+                let msg = format!("Invalid CA certificate file {}", ca_cert_path.display());
+                return Err(SrvClientError::Tls(msg));
+            }
+        }
+
+        match (&tls.client_certificate, &tls.client_key) {
+            (Some(cert_path), Some(key_path)) => {
+                let invalid_cert = || {
+                    SrvClientError::Tls(format!("Invalid certificate file {}", cert_path.display()))
+                };
+                let invalid_key = || {
+                    SrvClientError::Tls(format!("Invalid private key file {}", key_path.display()))
+                };
+                let cert_file = &mut BufReader::new(File::open(cert_path)?);
+                let cert_chain = pemfile::certs(cert_file).ok()
+                                                          .filter(|c| !c.is_empty())
+                                                          .ok_or_else(invalid_cert)?;
+                let key_file = &mut BufReader::new(File::open(key_path)?);
+                let key = pemfile::rsa_private_keys(key_file).ok()
+                                                             .and_then(|mut k| k.pop())
+                                                             .ok_or_else(invalid_key)?;
+                config.set_single_client_cert(cert_chain, key);
+            }
+            (None, None) => (),
+            _ => {
+                let msg = "A client certificate and its private key must be given together";
+                return Err(SrvClientError::Tls(msg.to_string()));
+            }
+        }
+        Ok(config)
+    }
+
     pub fn read_secret_key() -> Result<String, SrvClientError> {
         let mut buf = String::new();
         protocol::read_secret_key(protocol::sup_root(None), &mut buf)
@@ -53,7 +53,8 @@ use std::{fmt,
           io::{self,
                Cursor},
           str};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead,
+                AsyncWrite};
 use tokio_util::codec::{Decoder,
                         Encoder,
                         Framed};
@@ -71,9 +72,15 @@ const RESPONSE_MASK: u32 = 0x1;
 const COMPLETE_OFFSET: u32 = 30;
 const COMPLETE_MASK: u32 = 0x1;
 
-/// A `TcpStream` framed with `SrvCodec`. This is the base socket connection that the CtlGateway
-/// client and server speak.
-pub type SrvStream = Framed<TcpStream, SrvCodec>;
+/// A connection the CtlGateway client and server can speak `SrvProtocol` over. This is either a
+/// plain `TcpStream` or a `TcpStream` wrapped in TLS.
+pub trait SrvTransport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T> SrvTransport for T where T: AsyncRead + AsyncWrite + Send + Unpin {}
+
+/// A `SrvTransport` framed with `SrvCodec`. This is the base socket connection that the
+/// CtlGateway client and server speak.
+pub type SrvStream = Framed<Box<dyn SrvTransport>, SrvCodec>;
 
 // The type of the transaction id.
 pub type TxnId = u32;
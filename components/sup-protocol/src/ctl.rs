@@ -24,6 +24,10 @@ impl message::MessageStatic for SupDepart {
     const MESSAGE_ID: &'static str = "SupDepart";
 }
 
+impl message::MessageStatic for SupApply {
+    const MESSAGE_ID: &'static str = "SupApply";
+}
+
 impl message::MessageStatic for SvcFilePut {
     const MESSAGE_ID: &'static str = "SvcFilePut";
 }
@@ -44,6 +48,10 @@ impl message::MessageStatic for SvcLoad {
     const MESSAGE_ID: &'static str = "SvcLoad";
 }
 
+impl message::MessageStatic for SvcUpdate {
+    const MESSAGE_ID: &'static str = "SvcUpdate";
+}
+
 impl message::MessageStatic for SvcUnload {
     const MESSAGE_ID: &'static str = "SvcUnload";
 }
@@ -56,6 +64,22 @@ impl message::MessageStatic for SvcStop {
     const MESSAGE_ID: &'static str = "SvcStop";
 }
 
+impl message::MessageStatic for SvcRestart {
+    const MESSAGE_ID: &'static str = "SvcRestart";
+}
+
+impl message::MessageStatic for SvcReload {
+    const MESSAGE_ID: &'static str = "SvcReload";
+}
+
+impl message::MessageStatic for SvcCanary {
+    const MESSAGE_ID: &'static str = "SvcCanary";
+}
+
+impl message::MessageStatic for SvcLogs {
+    const MESSAGE_ID: &'static str = "SvcLogs";
+}
+
 impl message::MessageStatic for SvcStatus {
     const MESSAGE_ID: &'static str = "SvcStatus";
 }
@@ -12,7 +12,8 @@ use crate::{core::{self,
             net::{self,
                   ErrCode,
                   NetErr}};
-use std::{fmt,
+use std::{convert::TryFrom,
+          fmt,
           str::FromStr};
 
 include!(concat!(env!("OUT_DIR"), "/sup.types.rs"));
@@ -63,7 +64,12 @@ impl fmt::Display for PackageIdent {
                 write!(f, "{}/{}/{}/{}", self.origin, self.name, version, release,)
             }
             (Some(ref version), None) => write!(f, "{}/{}/{}", self.origin, self.name, version,),
-            (None, Some(_)) | (None, None) => write!(f, "{}/{}", self.origin, self.name),
+            (None, _) => {
+                match self.constraint.as_ref() {
+                    Some(constraint) => write!(f, "{}/{}@{}", self.origin, self.name, constraint),
+                    None => write!(f, "{}/{}", self.origin, self.name),
+                }
+            }
         }
     }
 }
@@ -73,6 +79,7 @@ impl fmt::Display for ProcessState {
         let state = match *self {
             ProcessState::Down => "down",
             ProcessState::Up => "up",
+            ProcessState::CrashLoop => "crash-loop",
         };
         write!(f, "{}", state)
     }
@@ -113,10 +120,11 @@ impl FromStr for ProcessState {
         match value.to_lowercase().as_ref() {
             "0" => Ok(ProcessState::Down),
             "1" => Ok(ProcessState::Up),
+            "2" => Ok(ProcessState::CrashLoop),
             _ => {
                 Err(net::err(ErrCode::InvalidPayload,
                              format!("Invalid process state \"{:?}\", must \
-                                      be `up` or `down`.",
+                                      be `up`, `down`, or `crash-loop`.",
                                      value)))
             }
         }
@@ -194,16 +202,30 @@ impl From<core::service::HealthCheckInterval> for HealthCheckInterval {
 
 impl From<package::PackageIdent> for PackageIdent {
     fn from(ident: package::PackageIdent) -> Self {
-        Self { origin:  ident.origin,
-               name:    ident.name,
-               version: ident.version,
-               release: ident.release, }
+        Self { origin:     ident.origin,
+               name:       ident.name,
+               version:    ident.version,
+               release:    ident.release,
+               constraint: ident.constraint.map(|c| c.to_string()), }
     }
 }
 
-impl Into<package::PackageIdent> for PackageIdent {
-    fn into(self) -> package::PackageIdent {
-        package::PackageIdent::new(self.origin, self.name, self.version, self.release)
+impl TryFrom<PackageIdent> for package::PackageIdent {
+    type Error = NetErr;
+
+    fn try_from(ident: PackageIdent) -> Result<Self, Self::Error> {
+        let constraint = match ident.constraint {
+            Some(constraint) => {
+                Some(constraint.parse()
+                               .map_err(|e| net::err(ErrCode::InvalidPayload, e))?)
+            }
+            None => None,
+        };
+        Ok(package::PackageIdent { constraint,
+                                   ..package::PackageIdent::new(ident.origin,
+                                                                ident.name,
+                                                                ident.version,
+                                                                ident.release) })
     }
 }
 
@@ -295,6 +317,7 @@ impl UpdateStrategy {
             UpdateStrategy::None => "none",
             UpdateStrategy::AtOnce => "at-once",
             UpdateStrategy::Rolling => "rolling",
+            UpdateStrategy::Canary => "canary",
         }
     }
 }
@@ -307,6 +330,7 @@ impl FromStr for UpdateStrategy {
             "none" => Ok(UpdateStrategy::None),
             "at-once" => Ok(UpdateStrategy::AtOnce),
             "rolling" => Ok(UpdateStrategy::Rolling),
+            "canary" => Ok(UpdateStrategy::Canary),
             _ => Err(net::err(ErrCode::InvalidPayload, "Invalid update strategy.")),
         }
     }
@@ -343,6 +367,33 @@ impl fmt::Display for UpdateCondition {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.as_str()) }
 }
 
+impl CanaryDecision {
+    pub const VARIANTS: &'static [&'static str] = &["promote", "abort"];
+
+    pub fn as_str(&self) -> &str {
+        match *self {
+            CanaryDecision::Promote => "promote",
+            CanaryDecision::Abort => "abort",
+        }
+    }
+}
+
+impl FromStr for CanaryDecision {
+    type Err = NetErr;
+
+    fn from_str(decision: &str) -> Result<Self, Self::Err> {
+        match decision {
+            "promote" => Ok(CanaryDecision::Promote),
+            "abort" => Ok(CanaryDecision::Abort),
+            _ => Err(net::err(ErrCode::InvalidPayload, "Invalid canary decision.")),
+        }
+    }
+}
+
+impl fmt::Display for CanaryDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.as_str()) }
+}
+
 #[cfg(test)]
 mod test {
     use toml;
@@ -433,6 +484,39 @@ mod test {
         assert_eq!("at-once", strategy.to_string())
     }
 
+    #[test]
+    fn update_strategy_canary_round_trip() {
+        let strategy = UpdateStrategy::from_str("canary").unwrap();
+
+        assert_eq!(strategy, UpdateStrategy::Canary);
+        assert_eq!("canary", strategy.to_string())
+    }
+
+    #[test]
+    fn canary_decision_from_str() {
+        assert_eq!(CanaryDecision::from_str("promote").unwrap(),
+                   CanaryDecision::Promote);
+        assert_eq!(CanaryDecision::from_str("abort").unwrap(),
+                   CanaryDecision::Abort);
+        assert!(CanaryDecision::from_str("maybe").is_err());
+    }
+
+    #[test]
+    fn package_ident_constraint_conversion() {
+        let ident = PackageIdent { origin:     "core".to_string(),
+                                   name:       "redis".to_string(),
+                                   version:    None,
+                                   release:    None,
+                                   constraint: Some(">=1.4, <2".to_string()), };
+        let converted = package::PackageIdent::try_from(ident.clone()).unwrap();
+        assert!(converted.constraint.unwrap().matches("1.10.2"));
+
+        let invalid = PackageIdent { constraint: Some(">=".to_string()),
+                                     ..ident };
+        let err = package::PackageIdent::try_from(invalid).unwrap_err();
+        assert_eq!(err.code, ErrCode::InvalidPayload as i32);
+    }
+
     #[test]
     fn update_strategy_toml_deserialize() {
         #[derive(Deserialize)]
@@ -50,6 +50,22 @@ fn raml2html_cmd(dst: PathBuf, src: PathBuf) -> io::Result<ExitStatus> {
 
 fn generate_event_protobufs() {
     let mut config = prost_build::Config::new();
+    // Events are also rendered as JSON for the event sinks that do not speak protobuf. The
+    // well-known types and enum fields need a little help to serialize legibly.
+    config.type_attribute(".", "#[derive(Serialize)]");
+    for field in &[".chef.habitat.supervisor.event.EventMetadata.occurred_at"] {
+        config.field_attribute(field, "#[serde(serialize_with = \"serialize_timestamp\")]");
+    }
+    for field in &[".chef.habitat.supervisor.event.ServiceCrashLoopEvent.window",
+                   ".chef.habitat.supervisor.event.HealthCheckEvent.execution",
+                   ".chef.habitat.supervisor.event.HealthCheckEvent.interval"]
+    {
+        config.field_attribute(field, "#[serde(serialize_with = \"serialize_duration\")]");
+    }
+    config.field_attribute(".chef.habitat.supervisor.event.UpdateConfig.strategy",
+                           "#[serde(serialize_with = \"serialize_update_strategy\")]");
+    config.field_attribute(".chef.habitat.supervisor.event.HealthCheckEvent.result",
+                           "#[serde(serialize_with = \"serialize_health_check_result\")]");
     config.compile_protos(&["protocols/event.proto"], &["protocols/"])
           .unwrap()
 }
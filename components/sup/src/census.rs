@@ -1,4 +1,6 @@
-use crate::error::Error;
+use crate::{error::Error,
+            event,
+            manager::service::HealthCheckResult};
 use habitat_butterfly::{member::{Health,
                                  Member,
                                  MemberList,
@@ -6,7 +8,10 @@ use habitat_butterfly::{member::{Health,
                         rumor::{election::{Election as ElectionRumor,
                                            ElectionStatus as ElectionStatusRumor,
                                            ElectionUpdate as ElectionUpdateRumor},
-                                service::{Service as ServiceRumor,
+                                service::{Canary,
+                                          CanaryDecision,
+                                          Rollout,
+                                          Service as ServiceRumor,
                                           SysInfo},
                                 service_config::ServiceConfig as ServiceConfigRumor,
                                 service_file::ServiceFile as ServiceFileRumor,
@@ -44,6 +49,9 @@ pub struct CensusRing {
     last_membership_counter: usize,
     last_service_config_counter: usize,
     last_service_file_counter: usize,
+    /// The last seen health of every other member of the ring.
+    #[serde(skip)]
+    member_health: HashMap<MemberId, Health>,
 }
 
 impl CensusRing {
@@ -62,7 +70,8 @@ impl CensusRing {
                      last_election_update_counter: 0,
                      last_membership_counter: 0,
                      last_service_config_counter: 0,
-                     last_service_file_counter: 0, }
+                     last_service_file_counter: 0,
+                     member_health: HashMap::new(), }
     }
 
     /// # Locking (see locking.md)
@@ -145,6 +154,9 @@ impl CensusRing {
         }
 
         member_list.with_memberships_mlr(|Membership { member, health }| {
+                       if self.member_health_changed(&member.id, health) {
+                           event::member_health_changed(&member, health);
+                       }
                        for group in self.census_groups.values_mut() {
                            if let Some(census_member) = group.find_member_mut(&member.id) {
                                census_member.update_from_member(&member);
@@ -156,6 +168,19 @@ impl CensusRing {
                    .ok();
     }
 
+    /// Records the health of a member, returning `true` if it changed since the member was last
+    /// seen, or if the member is first seen in a state other than alive. The health of the local
+    /// member is not tracked.
+    fn member_health_changed(&mut self, member_id: &str, health: Health) -> bool {
+        if member_id == self.local_member_id {
+            return false;
+        }
+        match self.member_health.insert(member_id.to_string(), health) {
+            Some(previous) => previous != health,
+            None => health != Health::Alive,
+        }
+    }
+
     /// # Locking (see locking.md)
     /// * `RumorStore::list` (read)
     fn update_from_election_store_rsr(&mut self, election_rumors: &RumorStore<ElectionRumor>) {
@@ -402,6 +427,83 @@ impl CensusGroup {
         None
     }
 
+    /// Return the alive peer whose update gives this member its turn in a rolling update where
+    /// up to `max_unavailable` members update concurrently. This is the peer `max_unavailable`
+    /// places to your left in the ordered members list, or the update leader if it is reached
+    /// first. Returns None if there is no update leader or you have no alive peers.
+    pub fn update_turn_peer(&self, max_unavailable: usize) -> Option<&CensusMember> {
+        match (self.me(), self.update_leader()) {
+            (Some(me), Some(leader)) => {
+                Self::update_turn_peer_impl(self.population.values(), me, leader, max_unavailable)
+            }
+            _ => None,
+        }
+    }
+
+    fn update_turn_peer_impl<'a>(members: impl Iterator<Item = &'a CensusMember>,
+                                 me: &CensusMember,
+                                 leader: &CensusMember,
+                                 max_unavailable: usize)
+                                 -> Option<&'a CensusMember> {
+        let alive_members: Vec<&CensusMember> = members.filter(|cm| cm.alive()).collect();
+        let position = alive_members.iter()
+                                    .position(|cm| cm.member_id == me.member_id)?;
+        let len = alive_members.len();
+        let mut peer = None;
+        for offset in 1..=max_unavailable.min(len - 1) {
+            let member = alive_members[(position + len - offset) % len];
+            peer = Some(member);
+            if member.member_id == leader.member_id {
+                break;
+            }
+        }
+        peer
+    }
+
+    /// Return the place of this member in the order in which followers take their turns in a
+    /// rolling update: 0 for the update leader, 1 for the alive peer to its right in the ordered
+    /// members list and so on. Returns None if there is no update leader or you are not alive.
+    pub fn update_follower_position(&self) -> Option<usize> {
+        match (self.me(), self.update_leader()) {
+            (Some(me), Some(leader)) => {
+                Self::update_follower_position_impl(self.population.values(), me, leader)
+            }
+            _ => None,
+        }
+    }
+
+    fn update_follower_position_impl<'a>(members: impl Iterator<Item = &'a CensusMember>,
+                                         me: &CensusMember,
+                                         leader: &CensusMember)
+                                         -> Option<usize> {
+        let alive_members: Vec<&CensusMember> = members.filter(|cm| cm.alive()).collect();
+        let position = |member: &CensusMember| {
+            alive_members.iter()
+                         .position(|cm| cm.member_id == member.member_id)
+        };
+        let (me_position, leader_position) = (position(me)?, position(leader)?);
+        let len = alive_members.len();
+        Some((me_position + len - leader_position) % len)
+    }
+
+    /// Return the operator decision on the canary stage of a rolling update to `pkg` made on any
+    /// alive member. An abort takes precedence over a promotion.
+    pub fn canary_decision(&self, pkg: &PackageIdent) -> Option<CanaryDecision> {
+        let pkg = pkg.to_string();
+        let mut decision = None;
+        for canary in self.members()
+                          .filter(|cm| cm.alive())
+                          .filter_map(|cm| cm.canary.as_ref())
+                          .filter(|canary| canary.pkg == pkg)
+        {
+            match canary.decision {
+                CanaryDecision::Abort => return Some(CanaryDecision::Abort),
+                CanaryDecision::Promote => decision = Some(CanaryDecision::Promote),
+            }
+        }
+        decision
+    }
+
     fn update_from_service_rumors(&mut self, rumors: &HashMap<String, ServiceRumor>) {
         for (member_id, service_rumor) in rumors.iter() {
             // Yeah - we are ourself - we're alive.
@@ -580,6 +682,9 @@ pub struct CensusMember {
     pub suspect: bool,
     pub confirmed: bool,
     pub departed: bool,
+    pub health_check: HealthCheckResult,
+    pub canary: Option<Canary>,
+    pub rollout: Option<Rollout>,
     // Maps must be represented last in a serializable struct for the current version of the toml
     // crate. Additionally, this deserialization method is required to correct any ordering issues
     // with the table being serialized - https://docs.rs/toml/0.4.0/toml/ser/fn.tables_last.html
@@ -600,6 +705,9 @@ impl CensusMember {
             Err(err) => warn!("Received a bad package ident from gossip data, err={}", err),
         };
         self.sys = rumor.sys.clone();
+        self.health_check = rumor.health.into();
+        self.canary = rumor.canary.clone();
+        self.rollout = rumor.rollout.clone();
         self.cfg = toml::from_slice(&rumor.cfg).unwrap_or_default();
     }
 
@@ -662,6 +770,18 @@ impl CensusMember {
     pub fn confirmed(&self) -> bool { self.confirmed }
 
     pub fn departed(&self) -> bool { self.departed }
+
+    /// Is this member alive and not failing its most recent health check? Members whose health
+    /// is `Unknown`, such as members of services without a health check or members running
+    /// Supervisors which do not gossip their health, are healthy as long as they are alive.
+    pub fn healthy(&self) -> bool {
+        match self.health_check {
+            HealthCheckResult::Ok | HealthCheckResult::Warning | HealthCheckResult::Unknown => {
+                self.alive
+            }
+            HealthCheckResult::Critical => false,
+        }
+    }
 }
 
 /// This data structure just wraps the CensusMember and allows us to tweak the serialization logic.
@@ -688,7 +808,7 @@ impl<'a> Serialize for CensusMemberProxy<'a> {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
         where S: Serializer
     {
-        let mut strukt = serializer.serialize_struct("census_member", 24)?;
+        let mut strukt = serializer.serialize_struct("census_member", 26)?;
         strukt.serialize_field("member_id", &self.member_id)?;
         strukt.serialize_field("pkg", &self.pkg)?;
 
@@ -715,6 +835,8 @@ impl<'a> Serialize for CensusMemberProxy<'a> {
         strukt.serialize_field("suspect", &self.suspect)?;
         strukt.serialize_field("confirmed", &self.confirmed)?;
         strukt.serialize_field("departed", &self.departed)?;
+        strukt.serialize_field("health_check", &self.health_check)?;
+        strukt.serialize_field("healthy", &self.healthy())?;
         strukt.serialize_field("cfg", &self.cfg)?;
         strukt.end()
     }
@@ -767,6 +889,28 @@ mod tests {
         assert_eq!(members.next().unwrap().member_id, "member-b");
     }
 
+    #[test]
+    fn healthy_members_are_alive_and_not_failing_health_checks() {
+        let mut member = test_census_member("member-a", Health::Alive);
+        assert_eq!(member.health_check, HealthCheckResult::Unknown);
+        assert!(member.healthy(),
+                "members without a health check are healthy");
+
+        member.health_check = HealthCheckResult::Warning;
+        assert!(member.healthy());
+
+        member.health_check = HealthCheckResult::Critical;
+        assert!(!member.healthy());
+
+        let mut member = test_census_member("member-b", Health::Suspect);
+        member.health_check = HealthCheckResult::Ok;
+        assert!(!member.healthy());
+
+        let json = serde_json::to_value(&CensusMemberProxy::new(&member)).unwrap();
+        assert_eq!(json["health_check"], "Ok");
+        assert_eq!(json["healthy"], false);
+    }
+
     #[test]
     fn census_ring_proxy_conforms_to_the_schema() {
         let (ring, ..) = test_census_ring();
@@ -870,6 +1014,9 @@ mod tests {
                        suspect: health == Health::Suspect,
                        confirmed: health == Health::Confirmed,
                        departed: health == Health::Departed,
+                       health_check: HealthCheckResult::Unknown,
+                       canary: None,
+                       rollout: None,
                        cfg: toml::value::Table::new() }
     }
 
@@ -896,6 +1043,40 @@ mod tests {
         assert!(active_members.next().is_none());
     }
 
+    #[test]
+    fn canary_decision_prefers_abort_and_ignores_other_packages() {
+        let pkg: PackageIdent = "habitat-testing/test_service/1.0.0/20200101000000".parse()
+                                                                                   .unwrap();
+        let canary = |decision| {
+            Some(Canary { pkg: pkg.to_string(),
+                          decision })
+        };
+        let mut promoter = test_census_member("promoter", Health::Alive);
+        promoter.canary = canary(CanaryDecision::Promote);
+        let mut aborter = test_census_member("aborter", Health::Alive);
+        aborter.canary = canary(CanaryDecision::Abort);
+        let mut other = test_census_member("other", Health::Alive);
+        other.canary =
+            Some(Canary { pkg:      "habitat-testing/test_service/0.9.0/20190101000000".to_string(),
+                          decision: CanaryDecision::Abort, });
+
+        let sg: ServiceGroup =
+            "test-service.default".parse()
+                                  .expect("This should be a valid service group");
+        let mut census_group = CensusGroup::new(sg, &"promoter".to_string());
+        for member in vec![promoter, other] {
+            census_group.population
+                        .insert(member.member_id.clone(), member);
+        }
+        assert_eq!(census_group.canary_decision(&pkg),
+                   Some(CanaryDecision::Promote));
+
+        census_group.population
+                    .insert(aborter.member_id.clone(), aborter);
+        assert_eq!(census_group.canary_decision(&pkg),
+                   Some(CanaryDecision::Abort));
+    }
+
     fn assert_eq_member_ids(cm: Option<&CensusMember>, id: Option<&str>) {
         assert_eq!(cm.map(|cm| cm.member_id.as_str()), id);
     }
@@ -971,4 +1152,97 @@ mod tests {
         assert_eq_member_ids(CensusGroup::previous_peer_impl(members.iter(), &me),
                              Some("left_of_me_with_wrapping"));
     }
+
+    #[test]
+    fn update_turn_peer_one_at_a_time_is_previous_peer() {
+        let me = test_census_member("me", Health::Alive);
+        let leader = test_census_member("leader", Health::Alive);
+        let members = vec![leader.clone(),
+                           test_census_member("left_of_me", Health::Alive),
+                           me.clone(),];
+        assert_eq_member_ids(CensusGroup::update_turn_peer_impl(members.iter(), &me, &leader, 1),
+                             Some("left_of_me"));
+    }
+
+    #[test]
+    fn update_turn_peer_skips_concurrently_updating_peers() {
+        let me = test_census_member("me", Health::Alive);
+        let leader = test_census_member("leader", Health::Alive);
+        let members = vec![leader.clone(),
+                           test_census_member("3_left_of_me", Health::Alive),
+                           test_census_member("2_left_of_me", Health::Confirmed),
+                           test_census_member("left_of_me", Health::Alive),
+                           me.clone(),];
+        assert_eq_member_ids(CensusGroup::update_turn_peer_impl(members.iter(), &me, &leader, 2),
+                             Some("3_left_of_me"));
+    }
+
+    #[test]
+    fn update_turn_peer_stops_at_leader() {
+        let me = test_census_member("me", Health::Alive);
+        let leader = test_census_member("leader", Health::Alive);
+        let members = vec![test_census_member("right_of_me_with_wrapping", Health::Alive),
+                           leader.clone(),
+                           test_census_member("left_of_me", Health::Alive),
+                           me.clone(),];
+        assert_eq_member_ids(CensusGroup::update_turn_peer_impl(members.iter(), &me, &leader, 3),
+                             Some("leader"));
+    }
+
+    #[test]
+    fn member_health_changes_are_detected_for_other_members() {
+        let mut ring = CensusRing::new("me");
+        assert!(!ring.member_health_changed("them", Health::Alive));
+        assert!(!ring.member_health_changed("them", Health::Alive));
+        assert!(ring.member_health_changed("them", Health::Suspect));
+        assert!(ring.member_health_changed("them", Health::Confirmed));
+        assert!(!ring.member_health_changed("me", Health::Alive));
+        assert!(!ring.member_health_changed("me", Health::Suspect));
+    }
+
+    #[test]
+    fn members_first_seen_in_trouble_are_changes() {
+        let mut ring = CensusRing::new("me");
+        assert!(ring.member_health_changed("suspect", Health::Suspect));
+        assert!(!ring.member_health_changed("suspect", Health::Suspect));
+        assert!(ring.member_health_changed("departed", Health::Departed));
+        assert!(!ring.member_health_changed("me", Health::Departed));
+    }
+
+    #[test]
+    fn update_turn_peer_with_only_me() {
+        let me = test_census_member("me", Health::Alive);
+        let members = vec![me.clone()];
+        assert_eq_member_ids(CensusGroup::update_turn_peer_impl(members.iter(), &me, &me, 4),
+                             None);
+    }
+
+    #[test]
+    fn update_follower_position_of_the_leader_is_zero() {
+        let me = test_census_member("me", Health::Alive);
+        let members = vec![test_census_member("left_of_me", Health::Alive), me.clone()];
+        assert_eq!(CensusGroup::update_follower_position_impl(members.iter(), &me, &me),
+                   Some(0));
+    }
+
+    #[test]
+    fn update_follower_position_skips_dead_members_and_wraps_around() {
+        let me = test_census_member("me", Health::Alive);
+        let leader = test_census_member("leader", Health::Alive);
+        let members = vec![test_census_member("right_of_leader", Health::Alive),
+                           me.clone(),
+                           test_census_member("left_of_leader", Health::Confirmed),
+                           leader.clone(),];
+        assert_eq!(CensusGroup::update_follower_position_impl(members.iter(), &me, &leader),
+                   Some(2));
+    }
+
+    #[test]
+    fn update_follower_position_without_an_alive_leader() {
+        let me = test_census_member("me", Health::Alive);
+        let leader = test_census_member("leader", Health::Confirmed);
+        let members = vec![leader.clone(), me.clone()];
+        assert_eq!(CensusGroup::update_follower_position_impl(members.iter(), &me, &leader),
+                   None);
+    }
 }
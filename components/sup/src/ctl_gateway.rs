@@ -9,6 +9,7 @@
 pub mod acceptor;
 pub mod handler;
 pub mod server;
+pub mod users;
 
 use crate::error::{Error,
                    Result};
@@ -100,6 +101,10 @@ impl CtlRequest {
     /// Returns true if the request is transactional and false if not.
     pub fn transactional(&self) -> bool { self.transaction.is_some() && self.tx.is_some() }
 
+    /// Returns true if replies can no longer reach the client, for example because it
+    /// disconnected.
+    pub fn is_closed(&self) -> bool { self.tx.as_ref().map_or(true, |tx| tx.is_closed()) }
+
     fn send_msg<T>(&mut self, msg: T, complete: bool)
         where T: Into<habitat_sup_protocol::codec::SrvMessage> + fmt::Debug
     {
@@ -8,7 +8,9 @@
 //! mpsc channel, [`CtlSender`], to [`CtlReceiver`]. A new mpsc pair is created for each
 //! transactional request where the sending half is given to a [`ctl_gateway.CtlRequest`].
 
-use super::{CtlRequest,
+use super::{users::{Identity,
+                    Users},
+            CtlRequest,
             REQ_TIMEOUT};
 use crate::manager::{action::ActionSender,
                      commands,
@@ -24,6 +26,7 @@ use habitat_sup_protocol::{self as protocol,
                            codec::{SrvCodec,
                                    SrvMessage,
                                    SrvStream,
+                                   SrvTransport,
                                    SrvTxn},
                            net::{self,
                                  ErrCode,
@@ -34,7 +37,8 @@ use prometheus::{HistogramTimer,
                  HistogramVec,
                  IntCounterVec};
 use prost;
-use std::{error,
+use std::{collections::VecDeque,
+          error,
           fmt,
           io,
           net::SocketAddr,
@@ -42,11 +46,16 @@ use std::{error,
           sync::{Arc,
                  Mutex},
           time::Duration};
-use tokio::{net::TcpListener,
+use tokio::{net::{TcpListener,
+                  TcpStream},
             task,
             time};
+use tokio_rustls::{rustls::ServerConfig,
+                   TlsAcceptor};
 use tokio_util::codec::Decoder;
 
+static LOGKEY: &str = "AG";
+
 lazy_static! {
     static ref RPC_CALLS: IntCounterVec = register_int_counter_vec!("hab_sup_rpc_call_total",
                                                                     "Total number of RPC calls",
@@ -146,6 +155,26 @@ struct Client {
 }
 
 impl Client {
+    /// Establish TLS with the client if the server requires it, then serve the client over the
+    /// resulting connection.
+    async fn accept(self,
+                    tcp_stream: TcpStream,
+                    tls_acceptor: Option<TlsAcceptor>)
+                    -> Result<(), HandlerError> {
+        let transport: Box<dyn SrvTransport> = match tls_acceptor {
+            Some(tls_acceptor) => {
+                let accept_with_timeout = time::timeout(Duration::from_millis(REQ_TIMEOUT),
+                                                        tls_acceptor.accept(tcp_stream));
+                Box::new(accept_with_timeout.await.map_err(|_| {
+                                                        io::Error::new(io::ErrorKind::TimedOut,
+                                                                       "client timed out")
+                                                    })??)
+            }
+            None => Box::new(tcp_stream),
+        };
+        self.serve(SrvCodec::new().framed(transport)).await
+    }
+
     /// Serve the client from the given framed socket stream.
     pub async fn serve(self, mut socket: SrvStream) -> Result<(), HandlerError> {
         let mgr_sender = self.state
@@ -155,20 +184,23 @@ impl Client {
                              .clone();
         let handshake_with_timeout = time::timeout(Duration::from_millis(REQ_TIMEOUT),
                                                    self.handshake(&mut socket));
-        handshake_with_timeout.await
-                              .map_err(|_| {
-                                  io::Error::new(io::ErrorKind::TimedOut, "client timed out")
-                              })??;
-        SrvHandler::new(socket, mgr_sender).await
+        let identity =
+            handshake_with_timeout.await
+                                  .map_err(|_| {
+                                      io::Error::new(io::ErrorKind::TimedOut, "client timed out")
+                                  })??;
+        SrvHandler::new(socket, mgr_sender, identity).await
     }
 
     /// Initiate a handshake with the connected client before allowing future requests. A failed
     /// handshake will close the connection.
-    async fn handshake(&self, socket: &mut SrvStream) -> Result<(), HandlerError> {
+    ///
+    /// Returns the identity the client authenticated as.
+    async fn handshake(&self, socket: &mut SrvStream) -> Result<Identity, HandlerError> {
         let message = socket.next()
                             .await
                             .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))??;
-        let success = if message.message_id() != "Handshake" {
+        let identity = if message.message_id() != "Handshake" {
             debug!("No handshake");
             return Err(HandlerError::from(io::Error::from(io::ErrorKind::ConnectionAborted)));
         } else if !message.is_transaction() {
@@ -176,14 +208,16 @@ impl Client {
         } else {
             match message.parse::<protocol::ctl::Handshake>() {
                 Ok(decoded) => {
-                    trace!("Received handshake, {:?}", decoded);
-                    let secret_key = self.state
-                                         .lock()
-                                         .expect("SrvState mutex poisoned")
-                                         .secret_key
-                                         .to_string();
+                    trace!("Received handshake, {:?}", decoded.user);
+                    let state = self.state.lock().expect("SrvState mutex poisoned");
                     let decoded_key = decoded.secret_key.unwrap_or_default();
-                    crypto::secure_eq(decoded_key, secret_key)
+                    match decoded.user {
+                        Some(user) => state.users.authenticate(&user, &decoded_key),
+                        None if crypto::secure_eq(decoded_key, &state.secret_key) => {
+                            state.users.shared_secret_identity()
+                        }
+                        None => None,
+                    }
                 }
                 Err(err) => {
                     warn!("Handshake error, {:?}", err);
@@ -191,14 +225,14 @@ impl Client {
                 }
             }
         };
-        let mut reply = if success {
+        let mut reply = if identity.is_some() {
             SrvMessage::from(net::ok())
         } else {
             SrvMessage::from(net::err(ErrCode::Unauthorized, "secret key mismatch"))
         };
         reply.reply_for(message.transaction().unwrap(), true);
         socket.send(reply).await?;
-        Ok(())
+        identity.ok_or_else(|| HandlerError::from(io::Error::from(io::ErrorKind::PermissionDenied)))
     }
 }
 
@@ -213,10 +247,13 @@ struct SrvHandler {
     ctl_receiver: CtlReceiver,
     ctl_sender:   CtlSender,
     timer:        Option<HistogramTimer>,
+    identity:     Identity,
+    /// Messages the client sent while a reply was in progress, handled once it is complete.
+    pending:      VecDeque<SrvMessage>,
 }
 
 impl SrvHandler {
-    fn new(io: SrvStream, mgr_sender: MgrSender) -> Self {
+    fn new(io: SrvStream, mgr_sender: MgrSender, identity: Identity) -> Self {
         let (ctl_sender, ctl_receiver) = mpsc::unbounded();
 
         SrvHandler { io,
@@ -224,15 +261,37 @@ impl SrvHandler {
                      mgr_sender,
                      ctl_receiver,
                      ctl_sender,
-                     timer: None }
+                     timer: None,
+                     identity,
+                     pending: VecDeque::new() }
     }
 
     /// # Locking (see locking.md)
     /// * `GatewayState::inner` (read)
     /// * `ManagerServices::inner` (read)
     async fn command_from_message_gsr_msr(msg: &SrvMessage,
-                                          ctl_sender: CtlSender)
+                                          ctl_sender: CtlSender,
+                                          identity: Identity)
                                           -> std::result::Result<CtlCommand, HandlerError> {
+        if !identity.is_authorized(msg.message_id()) {
+            outputln!("Denied {} for {} ({})",
+                      msg.message_id(),
+                      identity.name,
+                      identity.role);
+            let err = net::err(ErrCode::Unauthorized,
+                               format!("{} is not authorized to send {}",
+                                       identity.name,
+                                       msg.message_id()));
+            return Ok(CtlCommand::new(ctl_sender,
+                                      msg.transaction(),
+                                      move |_state, _req, _action_sender| {
+                                          Err(err.clone())
+                                      }));
+        }
+        outputln!("Accepted {} for {} ({})",
+                  msg.message_id(),
+                  identity.name,
+                  identity.role);
         match msg.message_id() {
             "SvcGetDefaultCfg" => {
                 let m = msg.parse::<protocol::ctl::SvcGetDefaultCfg>()
@@ -258,7 +317,7 @@ impl SrvHandler {
                 Ok(CtlCommand::new(ctl_sender,
                                    msg.transaction(),
                                    move |state, req, _action_sender| {
-                                       commands::service_cfg_set(state, req, m.clone())
+                                       commands::service_cfg_set_msr(state, req, m.clone())
                                    }))
             }
             "SvcValidateCfg" => {
@@ -267,7 +326,7 @@ impl SrvHandler {
                 Ok(CtlCommand::new(ctl_sender,
                                    msg.transaction(),
                                    move |state, req, _action_sender| {
-                                       commands::service_cfg_validate(state, req, m.clone())
+                                       commands::service_cfg_validate_msr(state, req, m.clone())
                                    }))
             }
             "SvcLoad" => {
@@ -288,6 +347,15 @@ impl SrvHandler {
                                        })
                                    }))
             }
+            "SvcUpdate" => {
+                let m = msg.parse::<protocol::ctl::SvcUpdate>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, _action_sender| {
+                                       commands::service_update(state, req, m.clone())
+                                   }))
+            }
             "SvcUnload" => {
                 let m = msg.parse::<protocol::ctl::SvcUnload>()
                            .map_err(HandlerError::from)?;
@@ -318,6 +386,51 @@ impl SrvHandler {
                                        commands::service_stop(state, req, m.clone(), &action_sender)
                                    }))
             }
+            "SvcRestart" => {
+                let m = msg.parse::<protocol::ctl::SvcRestart>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, action_sender| {
+                                       commands::service_restart(state,
+                                                                 req,
+                                                                 m.clone(),
+                                                                 &action_sender)
+                                   }))
+            }
+            "SvcReload" => {
+                let m = msg.parse::<protocol::ctl::SvcReload>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, action_sender| {
+                                       commands::service_reload_msr(state,
+                                                                    req,
+                                                                    m.clone(),
+                                                                    &action_sender)
+                                   }))
+            }
+            "SvcCanary" => {
+                let m = msg.parse::<protocol::ctl::SvcCanary>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, action_sender| {
+                                       commands::service_canary(state,
+                                                                req,
+                                                                m.clone(),
+                                                                &action_sender)
+                                   }))
+            }
+            "SvcLogs" => {
+                let m = msg.parse::<protocol::ctl::SvcLogs>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, _action_sender| {
+                                       commands::service_logs(state, req, m.clone())
+                                   }))
+            }
             "SvcStatus" => {
                 let m = msg.parse::<protocol::ctl::SvcStatus>()
                            .map_err(HandlerError::from)?;
@@ -336,6 +449,23 @@ impl SrvHandler {
                                        commands::supervisor_depart(state, req, m.clone())
                                    }))
             }
+            "SupApply" => {
+                let m = msg.parse::<protocol::ctl::SupApply>()
+                           .map_err(HandlerError::from)?;
+                Ok(CtlCommand::new(ctl_sender,
+                                   msg.transaction(),
+                                   move |state, req, action_sender| {
+                                       // See "SvcLoad" for why it is safe to block here.
+                                       task::block_in_place(|| {
+                                           executor::block_on(commands::supervisor_apply(
+                                               state,
+                                               req,
+                                               m.clone(),
+                                               &action_sender,
+                                           ))
+                                       })
+                                   }))
+            }
             _ => {
                 warn!("Unhandled message, {}", msg.message_id());
                 Err(HandlerError::from(io::Error::from(io::ErrorKind::InvalidData)))
@@ -361,53 +491,79 @@ impl Future for SrvHandler {
         loop {
             match self.state {
                 SrvHandlerState::Receiving => {
-                    match ready!(self.io.poll_next_unpin(cx)) {
+                    let msg = match self.pending.pop_front() {
+                        Some(msg) => msg,
                         None => {
-                            break;
-                        }
-                        Some(Ok(msg)) => {
-                            self.start_timer(&msg.message_id());
-                            trace!("OnMessage, {}", msg.message_id());
-
-                            let fut =
-                                Self::command_from_message_gsr_msr(&msg, self.ctl_sender.clone());
-                            tokio::pin!(fut);
-                            let cmd = match futures::ready!(fut.poll_unpin(cx)) {
-                                Ok(cmd) => cmd,
-                                Err(_) => {
+                            match ready!(self.io.poll_next_unpin(cx)) {
+                                None => {
                                     break;
                                 }
-                            };
-                            if let Err(err) = futures::ready!(self.mgr_sender.poll_ready(cx)) {
-                                return Poll::Ready(Err(HandlerError::from(err)));
-                            }
-                            match self.mgr_sender.start_send(cmd) {
-                                Ok(()) => {
-                                    self.state = SrvHandlerState::Sending;
-                                    continue;
-                                }
-                                Err(err) => {
-                                    // An error here means that the
-                                    // receiving end of this channel went
-                                    // away.
-                                    //
-                                    // Most often, this will be because
-                                    // we're in the middle of an orderly
-                                    // shutdown and no longer wish to
-                                    // process incoming commands.
-                                    warn!("ManagerReceiver err: {}", err);
+                                Some(Ok(msg)) => msg,
+                                Some(Err(err)) => {
+                                    error!("SrvHandler failed to receive message, err: {}", err);
                                     return Poll::Ready(Err(HandlerError::from(err)));
                                 }
                             }
                         }
-                        Some(Err(err)) => {
-                            error!("SrvHandler failed to receive message, err: {}", err);
+                    };
+                    self.start_timer(&msg.message_id());
+                    trace!("OnMessage, {}", msg.message_id());
+
+                    let fut = Self::command_from_message_gsr_msr(&msg,
+                                                                 self.ctl_sender.clone(),
+                                                                 self.identity.clone());
+                    tokio::pin!(fut);
+                    let cmd = match futures::ready!(fut.poll_unpin(cx)) {
+                        Ok(cmd) => cmd,
+                        Err(_) => {
+                            break;
+                        }
+                    };
+                    if let Err(err) = futures::ready!(self.mgr_sender.poll_ready(cx)) {
+                        return Poll::Ready(Err(HandlerError::from(err)));
+                    }
+                    match self.mgr_sender.start_send(cmd) {
+                        Ok(()) => {
+                            self.state = SrvHandlerState::Sending;
+                            continue;
+                        }
+                        Err(err) => {
+                            // An error here means that the
+                            // receiving end of this channel went
+                            // away.
+                            //
+                            // Most often, this will be because
+                            // we're in the middle of an orderly
+                            // shutdown and no longer wish to
+                            // process incoming commands.
+                            warn!("ManagerReceiver err: {}", err);
                             return Poll::Ready(Err(HandlerError::from(err)));
                         }
                     }
                 }
                 SrvHandlerState::Sending => {
-                    match futures::ready!(self.ctl_receiver.poll_next_unpin(cx)) {
+                    let reply = match self.ctl_receiver.poll_next_unpin(cx) {
+                        Poll::Ready(reply) => reply,
+                        Poll::Pending => {
+                            // Watch for the client going away while it waits on replies, such
+                            // as when it follows a service's output. Returning drops our
+                            // receiver, which tells the command to stop.
+                            match ready!(self.io.poll_next_unpin(cx)) {
+                                None => {
+                                    debug!("Client disconnected before its reply was complete");
+                                    return Poll::Ready(Ok(()));
+                                }
+                                Some(Ok(msg)) => {
+                                    debug!("Queueing {} until the reply in progress is complete",
+                                           msg.message_id());
+                                    self.pending.push_back(msg);
+                                    continue;
+                                }
+                                Some(Err(err)) => return Poll::Ready(Err(HandlerError::from(err))),
+                            }
+                        }
+                    };
+                    match reply {
                         Some(msg) => {
                             trace!("MgrSender -> SrvHandler, {:?}", msg);
                             if msg.is_complete() {
@@ -441,7 +597,15 @@ impl Future for SrvHandler {
                         timer.observe_duration();
                     }
                     trace!("OnMessage complete");
-                    break;
+                    if self.pending.is_empty() {
+                        break;
+                    }
+                    // Handle the messages the client sent while we were replying, each with a
+                    // reply channel of its own.
+                    let (ctl_sender, ctl_receiver) = mpsc::unbounded();
+                    self.ctl_sender = ctl_sender;
+                    self.ctl_receiver = ctl_receiver;
+                    self.state = SrvHandlerState::Receiving;
                 }
             }
         }
@@ -450,7 +614,8 @@ impl Future for SrvHandler {
 }
 
 enum SrvHandlerState {
-    /// Handler is Receiving/Waiting for message from client.
+    /// Handler is Receiving/Waiting for message from client, or taking the next message the client
+    /// sent while a reply was in progress.
     Receiving,
     /// Handler has sent a request to the Manager and is streaming replies back to the client
     /// socket.
@@ -461,15 +626,24 @@ enum SrvHandlerState {
 
 struct SrvState {
     secret_key: String,
+    users:      Users,
     mgr_sender: MgrSender,
 }
 
 /// Start a new thread which will run the CtlGateway server.
 ///
-/// New connections will be authenticated using `secret_key`. Messages from the main thread
-/// will be sent over the channel `mgr_sender`.
-pub async fn run(listen_addr: SocketAddr, secret_key: String, mgr_sender: MgrSender) {
+/// New connections will be authenticated as one of `users` or with `secret_key`, and are only
+/// authorized to send the messages their role allows. Messages from the main thread
+/// will be sent over the channel `mgr_sender`. If `tls_config` is given, every connection must
+/// complete a TLS handshake before the SrvProtocol handshake.
+pub async fn run(listen_addr: SocketAddr,
+                 secret_key: String,
+                 users: Users,
+                 tls_config: Option<ServerConfig>,
+                 mgr_sender: MgrSender) {
+    let tls_acceptor = tls_config.map(|c| TlsAcceptor::from(Arc::new(c)));
     let state = SrvState { secret_key,
+                           users,
                            mgr_sender };
     let state = Arc::new(Mutex::new(state));
     let mut listner =
@@ -486,10 +660,10 @@ pub async fn run(listen_addr: SocketAddr, secret_key: String, mgr_sender: MgrSen
                         continue;
                     }
                 };
-                let io = SrvCodec::new().framed(tcp_stream);
                 let client = Client { state: Arc::clone(&state), };
+                let tls_acceptor = tls_acceptor.clone();
                 tokio::spawn(async move {
-                    let res = client.serve(io).await;
+                    let res = client.accept(tcp_stream, tls_acceptor).await;
                     debug!("DISCONNECTED from {:?} with result {:?}", addr, res);
                 });
             }
@@ -497,3 +671,168 @@ pub async fn run(listen_addr: SocketAddr, secret_key: String, mgr_sender: MgrSen
         }
     }
 }
+
+/// Send `msg` to the Manager over `mgr_sender` on behalf of `identity`, just as if it had been
+/// received from a CtlGateway client, and wait for every reply to it. This lets other gateways
+/// share the CtlGateway's commands.
+///
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+pub async fn dispatch_gsr_msr(mgr_sender: &MgrSender,
+                              mut msg: SrvMessage,
+                              identity: Identity)
+                              -> Result<Vec<SrvMessage>, HandlerError> {
+    // Commands only reply to transactional messages.
+    msg.set_transaction(SrvTxn::from(1));
+    let (ctl_sender, mut ctl_receiver) = mpsc::unbounded();
+    let cmd = SrvHandler::command_from_message_gsr_msr(&msg, ctl_sender, identity).await?;
+    mgr_sender.unbounded_send(cmd)
+              .map_err(|e| HandlerError::from(e.into_send_error()))?;
+    let mut replies = Vec::new();
+    while let Some(reply) = ctl_receiver.next().await {
+        let complete = reply.is_complete();
+        replies.push(reply);
+        if complete {
+            break;
+        }
+    }
+    Ok(replies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::{self,
+                         TLSConfig};
+    use habitat_common::types::ListenCtlAddr;
+    use habitat_sup_client::{CtlTlsConfig,
+                             SrvClient};
+    use std::{net::{Ipv4Addr,
+                    TcpListener as StdTcpListener},
+              path::PathBuf};
+
+    const SECRET_KEY: &str = "ctl-secret";
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+                                                 .join("fixtures")
+                                                 .join("ctl-gateway")
+                                                 .join(name)
+    }
+
+    /// Start a CtlGateway and wait until it accepts connections.
+    async fn start_gateway(tls_config: Option<ServerConfig>) -> (ListenCtlAddr, MgrReceiver) {
+        let port = StdTcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap()
+                                                                 .local_addr()
+                                                                 .unwrap()
+                                                                 .port();
+        let addr = ListenCtlAddr::new(Ipv4Addr::LOCALHOST, port);
+        let (mgr_sender, mgr_receiver) = mpsc::unbounded();
+        tokio::spawn(run(*addr.as_ref(),
+                         SECRET_KEY.to_string(),
+                         Users::default(),
+                         tls_config,
+                         mgr_sender));
+        while TcpStream::connect(addr.as_ref()).await.is_err() {
+            time::delay_for(Duration::from_millis(10)).await;
+        }
+        (addr, mgr_receiver)
+    }
+
+    /// Start a CtlGateway which requires clients to present a certificate signed by the test CA
+    /// and wait until it accepts connections.
+    async fn start_tls_gateway() -> (ListenCtlAddr, MgrReceiver) {
+        let tls_config =
+            manager::tls_config(&TLSConfig { cert_path:    fixture("server.pem"),
+                                             key_path:     fixture("server-key.pem"),
+                                             ca_cert_path: Some(fixture("ca.pem")), }).unwrap();
+        start_gateway(Some(tls_config)).await
+    }
+
+    /// Connect to a CtlGateway without TLS and complete the handshake.
+    async fn connect(addr: &ListenCtlAddr) -> SrvStream {
+        let socket: Box<dyn SrvTransport> =
+            Box::new(TcpStream::connect(addr.as_ref()).await.unwrap());
+        let mut socket = SrvCodec::new().framed(socket);
+        let mut handshake = protocol::ctl::Handshake::default();
+        handshake.secret_key = Some(SECRET_KEY.to_string());
+        let mut message = SrvMessage::from(handshake);
+        message.set_transaction(SrvTxn::default());
+        socket.send(message).await.unwrap();
+        let reply = socket.next().await.unwrap().unwrap();
+        assert_eq!(reply.message_id(), "NetOk");
+        socket
+    }
+
+    async fn next_command(mgr_receiver: &mut MgrReceiver) -> CtlCommand {
+        let cmd = time::timeout(Duration::from_secs(10), mgr_receiver.next()).await;
+        cmd.expect("timed out waiting for a command").unwrap()
+    }
+
+    fn client_tls_config() -> CtlTlsConfig {
+        CtlTlsConfig { server_ca_certificate: Some(fixture("ca.pem")),
+                       server_name:           Some("localhost".to_string()),
+                       client_certificate:    Some(fixture("client.pem")),
+                       client_key:            Some(fixture("client-key.pem")), }
+    }
+
+    #[tokio::test]
+    async fn requests_round_trip_over_tls() {
+        let (addr, mut mgr_receiver) = start_tls_gateway().await;
+        let status = protocol::ctl::SvcStatus::default();
+        let response =
+            SrvClient::request_with_tls(&addr, &client_tls_config(), SECRET_KEY, status).await;
+        assert!(response.is_ok(), "TLS request failed");
+        let cmd = time::timeout(Duration::from_secs(10), mgr_receiver.next()).await
+                                                                             .unwrap();
+        assert!(cmd.is_some());
+    }
+
+    #[tokio::test]
+    async fn rejects_clients_which_fail_the_tls_handshake() {
+        let (addr, mut mgr_receiver) = start_tls_gateway().await;
+
+        let mut without_certificate = client_tls_config();
+        without_certificate.client_certificate = None;
+        without_certificate.client_key = None;
+        let mut wrong_server_name = client_tls_config();
+        wrong_server_name.server_name = Some("example.com".to_string());
+        let mut without_tls = client_tls_config();
+        without_tls.server_ca_certificate = None;
+
+        for tls in &[without_certificate, wrong_server_name, without_tls] {
+            let status = protocol::ctl::SvcStatus::default();
+            let response = SrvClient::request_with_tls(&addr, tls, SECRET_KEY, status).await;
+            assert!(response.is_err(), "Request with {:?} should fail", tls);
+        }
+        assert!(mgr_receiver.try_next().is_err());
+    }
+
+    #[tokio::test]
+    async fn handles_requests_sent_while_a_reply_is_in_progress() {
+        let (addr, mut mgr_receiver) = start_gateway(None).await;
+        let mut socket = connect(&addr).await;
+
+        let mut txn = SrvTxn::default();
+        for _ in 0..2 {
+            txn.increment();
+            let mut message = SrvMessage::from(protocol::ctl::SvcStatus::default());
+            message.set_transaction(txn);
+            socket.send(message).await.unwrap();
+        }
+
+        let mut first = next_command(&mut mgr_receiver).await;
+        // Give the second request time to arrive while the first reply is in progress.
+        time::delay_for(Duration::from_millis(100)).await;
+        first.req.reply_complete(net::ok());
+        let reply = socket.next().await.unwrap().unwrap();
+        assert_eq!(reply.transaction().unwrap().id(), 1);
+
+        let mut second = next_command(&mut mgr_receiver).await;
+        second.req.reply_complete(net::ok());
+        let reply = socket.next().await.unwrap().unwrap();
+        assert_eq!(reply.transaction().unwrap().id(), 2);
+        assert!(reply.is_complete());
+    }
+}
@@ -0,0 +1,240 @@
+//! Named CtlGateway credentials and the roles they are authorized for.
+//!
+//! Users are read from `CTL_USERS.toml` in the Supervisor's state directory:
+//!
+//! ```toml
+//! [[user]]
+//! name = "alice"
+//! secret = "<output of `hab sup secret generate`>"
+//! role = "operator"
+//! ```
+//!
+//! A client authenticating with the shared `CTL_SECRET` instead of a named user is treated as an
+//! admin, so existing tooling keeps working. Once named users are in place, the top-level
+//! `shared_secret_role` setting limits the shared secret to a narrower role, or disables it with
+//! `"none"`.
+
+use crate::error::{Error,
+                   Result};
+use habitat_core::crypto;
+use std::{fmt,
+          fs,
+          io,
+          path::{Path,
+                 PathBuf}};
+
+const CTL_USERS_FILENAME: &str = "CTL_USERS.toml";
+
+/// The name recorded for clients authenticating with the shared `CTL_SECRET`.
+pub const SHARED_SECRET_USER: &str = "<CTL_SECRET>";
+
+/// What a CtlGateway client is authorized to do. Each role may do everything the roles before it
+/// may do.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    /// Query services and their configuration.
+    ReadOnly,
+    /// Start, stop, update, and configure services that are already loaded.
+    Operator,
+    /// Load and unload services, apply manifests, and depart Supervisors.
+    Admin,
+}
+
+impl Role {
+    /// The least privileged role allowed to send the message `message_id`.
+    pub fn required_for(message_id: &str) -> Self {
+        match message_id {
+            "SvcGetDefaultCfg" | "SvcLogs" | "SvcStatus" | "SvcValidateCfg" => Role::ReadOnly,
+            "SvcFilePut" | "SvcSetCfg" | "SvcStart" | "SvcStop" | "SvcRestart" | "SvcReload"
+            | "SvcUpdate" | "SvcCanary" => Role::Operator,
+            _ => Role::Admin,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            Role::ReadOnly => "read-only",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+/// A client which completed the CtlGateway handshake.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identity {
+    pub name: String,
+    pub role: Role,
+}
+
+impl Identity {
+    /// Returns true if this client may send the message `message_id`.
+    pub fn is_authorized(&self, message_id: &str) -> bool {
+        self.role >= Role::required_for(message_id)
+    }
+}
+
+/// The role of clients authenticating with the shared `CTL_SECRET`, or `None` if the shared
+/// secret is not accepted.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum SharedSecretRole {
+    None,
+    ReadOnly,
+    Operator,
+    Admin,
+}
+
+impl Default for SharedSecretRole {
+    fn default() -> Self { SharedSecretRole::Admin }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct User {
+    name:   String,
+    secret: String,
+    role:   Role,
+}
+
+/// The named credentials accepted by the CtlGateway.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Users {
+    #[serde(default)]
+    shared_secret_role: SharedSecretRole,
+    #[serde(default, rename = "user")]
+    users:              Vec<User>,
+}
+
+impl Users {
+    /// Read the users from the state directory `sup_root`. A missing file means there are no
+    /// named users.
+    pub fn load<T>(sup_root: T) -> Result<Self>
+        where T: AsRef<Path>
+    {
+        let path = users_path(sup_root);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(Error::CtlUsersIo(path, e)),
+        };
+        toml::from_str(&contents).map_err(|e| Error::CtlUsersParse(path, e))
+    }
+
+    /// Look up the identity of a client presenting `secret` as `name`.
+    pub fn authenticate(&self, name: &str, secret: &str) -> Option<Identity> {
+        self.users
+            .iter()
+            .find(|user| user.name == name)
+            .filter(|user| crypto::secure_eq(secret, &user.secret))
+            .map(|user| {
+                Identity { name: user.name.clone(),
+                           role: user.role, }
+            })
+    }
+
+    /// The identity of a client presenting the shared `CTL_SECRET`, or `None` if the shared secret
+    /// is disabled.
+    pub fn shared_secret_identity(&self) -> Option<Identity> {
+        let role = match self.shared_secret_role {
+            SharedSecretRole::None => return None,
+            SharedSecretRole::ReadOnly => Role::ReadOnly,
+            SharedSecretRole::Operator => Role::Operator,
+            SharedSecretRole::Admin => Role::Admin,
+        };
+        Some(Identity { name: SHARED_SECRET_USER.to_string(),
+                        role })
+    }
+}
+
+fn users_path<T>(sup_root: T) -> PathBuf
+    where T: AsRef<Path>
+{
+    sup_root.as_ref().join(CTL_USERS_FILENAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const USERS: &str = r#"
+[[user]]
+name = "alice"
+secret = "alice-secret"
+role = "read-only"
+
+[[user]]
+name = "bob"
+secret = "bob-secret"
+role = "admin"
+"#;
+
+    fn users(contents: &str) -> Users {
+        let tmpdir = TempDir::new().unwrap();
+        fs::write(users_path(tmpdir.path()), contents).unwrap();
+        Users::load(tmpdir.path()).unwrap()
+    }
+
+    #[test]
+    fn missing_users_file_has_no_users() {
+        let tmpdir = TempDir::new().unwrap();
+        let users = Users::load(tmpdir.path()).unwrap();
+        assert!(users.authenticate("alice", "").is_none());
+    }
+
+    #[test]
+    fn authenticates_users_by_name_and_secret() {
+        let users = users(USERS);
+        assert_eq!(users.authenticate("alice", "alice-secret"),
+                   Some(Identity { name: "alice".to_string(),
+                                   role: Role::ReadOnly, }));
+        assert_eq!(users.authenticate("bob", "bob-secret").map(|i| i.role),
+                   Some(Role::Admin));
+        assert!(users.authenticate("alice", "bob-secret").is_none());
+        assert!(users.authenticate("carol", "alice-secret").is_none());
+    }
+
+    #[test]
+    fn shared_secret_is_an_admin_by_default() {
+        let users = users(USERS);
+        assert_eq!(users.shared_secret_identity(),
+                   Some(Identity { name: SHARED_SECRET_USER.to_string(),
+                                   role: Role::Admin, }));
+    }
+
+    #[test]
+    fn shared_secret_role_can_be_limited_or_disabled() {
+        let limited = users(&format!("shared_secret_role = \"read-only\"\n{}", USERS));
+        assert_eq!(limited.shared_secret_identity().map(|i| i.role),
+                   Some(Role::ReadOnly));
+
+        let disabled = users(&format!("shared_secret_role = \"none\"\n{}", USERS));
+        assert!(disabled.shared_secret_identity().is_none());
+        assert_eq!(disabled.authenticate("bob", "bob-secret").map(|i| i.role),
+                   Some(Role::Admin));
+    }
+
+    #[test]
+    fn roles_authorize_messages() {
+        let identity = |role| {
+            Identity { name: "alice".to_string(),
+                       role }
+        };
+        assert!(identity(Role::ReadOnly).is_authorized("SvcStatus"));
+        assert!(identity(Role::ReadOnly).is_authorized("SvcLogs"));
+        assert!(!identity(Role::ReadOnly).is_authorized("SvcStop"));
+        assert!(identity(Role::Operator).is_authorized("SvcStop"));
+        assert!(identity(Role::Operator).is_authorized("SvcRestart"));
+        assert!(!identity(Role::ReadOnly).is_authorized("SvcReload"));
+        assert!(!identity(Role::Operator).is_authorized("SvcUnload"));
+        assert!(!identity(Role::Operator).is_authorized("SupDepart"));
+        assert!(!identity(Role::Operator).is_authorized("SupApply"));
+        assert!(identity(Role::Admin).is_authorized("SupDepart"));
+    }
+}
@@ -48,6 +48,9 @@ pub enum Error {
     TestBootFail,
     ButterflyError(habitat_butterfly::error::Error),
     CtlSecretIo(PathBuf, io::Error),
+    CtlUsersIo(PathBuf, io::Error),
+    CtlUsersParse(PathBuf, toml::de::Error),
+    DuplicateManifestService(String),
     APIClient(habitat_api_client::Error),
     EnvJoinPathsError(env::JoinPathsError),
     ExecCommandNotFound(String),
@@ -59,6 +62,7 @@ pub enum Error {
     HabitatCore(habitat_core::Error),
     InvalidBinds(Vec<String>),
     InvalidCertFile(PathBuf),
+    InvalidEventStreamSpoolSize(u64),
     InvalidHealthCheckResult(i32),
     InvalidKeyFile(PathBuf),
     InvalidKeyParameter(String),
@@ -68,6 +72,7 @@ pub enum Error {
     Io(io::Error),
     TaskJoin(JoinError),
     Launcher(habitat_launcher_client::Error),
+    ManifestParse(toml::de::Error),
     MissingRequiredBind(Vec<String>),
     MissingRequiredIdent,
     NameLookup(io::Error),
@@ -163,6 +168,17 @@ impl fmt::Display for Error {
                         path.display(),
                         err)
             }
+            Error::CtlUsersIo(ref path, ref err) => {
+                format!("IoError while reading ctl users, {}, {}",
+                        path.display(),
+                        err)
+            }
+            Error::CtlUsersParse(ref path, ref err) => {
+                format!("Unable to parse ctl users, {}, {}", path.display(), err)
+            }
+            Error::DuplicateManifestService(ref name) => {
+                format!("The manifest lists the {} service more than once", name)
+            }
             Error::ExecCommandNotFound(ref c) => {
                 format!("`{}' was not found on the filesystem or in PATH", c)
             }
@@ -176,6 +192,10 @@ impl fmt::Display for Error {
             Error::GroupNotFound(ref e) => format!("No GID for group '{}' could be found", e),
             Error::InvalidBinds(ref e) => format!("Invalid bind(s), {}", e.join(", ")),
             Error::InvalidCertFile(ref path) => format!("Invalid cert file: {}", path.display()),
+            Error::InvalidEventStreamSpoolSize(size) => {
+                format!("Invalid event stream spool size: {} megabytes is too large",
+                        size)
+            }
             Error::InvalidHealthCheckResult(code) => {
                 format!("Invalid health check result: {}", code)
             }
@@ -189,6 +209,7 @@ impl fmt::Display for Error {
             Error::Io(ref err) => err.to_string(),
             Error::TaskJoin(ref err) => err.to_string(),
             Error::Launcher(ref err) => err.to_string(),
+            Error::ManifestParse(ref err) => format!("Unable to parse the manifest, {}", err),
             Error::MissingRequiredBind(ref e) => {
                 format!("Missing required bind(s), {}", e.join(", "))
             }
@@ -1,38 +1,67 @@
 //! Main interface for a stream of events the Supervisor can send out
 //! in the course of its operations.
 //!
-//! Currently, the Supervisor is able to send events to a [NATS][1]
-//! server. The `init_stream` function must be called
-//! before sending events to initialize the publishing thread in the
-//! background. Thereafter, you can pass "event" structs to the
-//! `event` function, which will publish the event to the stream.
+//! Events can be sent to any combination of sinks:
 //!
-//! All events are published under the "habitat" subject.
+//! * a [NATS][1] server, such as the one in Chef Automate, as protobuf messages
+//! * a local file, as lines of JSON
+//! * an HTTP endpoint, as a JSON `POST` per event
+//! * syslog, as JSON in the body of each message
+//!
+//! The `init` function must be called before sending events to
+//! start the background publishing tasks. Thereafter, the event
+//! functions in this module publish their event to every sink.
+//!
+//! All events are published under the "habitat" subject. Events
+//! rendered as JSON carry that subject alongside the event itself.
 //!
 //! [1]:https://github.com/nats-io/nats-server
 
 mod error;
+mod file_sink;
 mod nats_message_stream;
+mod spool;
+mod syslog_sink;
 mod types;
+mod webhook_sink;
 
 pub(crate) use self::types::ServiceMetadata;
-use self::types::{EventMessage,
-                  EventMetadata,
-                  HealthCheckEvent,
-                  ServiceStartedEvent,
-                  ServiceStoppedEvent,
-                  ServiceUpdateStartedEvent};
+use self::{file_sink::FileSink,
+           syslog_sink::SyslogSink,
+           types::{ElectionLostEvent,
+                   ElectionWonEvent,
+                   EventMessage,
+                   EventMetadata,
+                   HealthCheckEvent,
+                   HookFailedEvent,
+                   MemberConfirmedEvent,
+                   MemberDepartedEvent,
+                   MemberSuspectEvent,
+                   ServiceConfigAppliedEvent,
+                   ServiceCrashLoopEvent,
+                   ServiceFileUpdatedEvent,
+                   ServiceLoadedEvent,
+                   ServiceStartedEvent,
+                   ServiceStoppedEvent,
+                   ServiceUnloadedEvent,
+                   ServiceUpdateFailedEvent,
+                   ServiceUpdateFinishedEvent,
+                   ServiceUpdateStartedEvent,
+                   SupervisorUpdateEvent},
+           webhook_sink::WebhookSink};
 use crate::manager::{service::{HealthCheckHookStatus,
                                HealthCheckResult,
-                               ProcessOutput,
                                Service,
                                StandardStreams},
                      sys::Sys};
 pub use error::{Error,
                 Result};
+use habitat_butterfly::member::{Health,
+                                Member};
 use habitat_common::types::{EventStreamConnectMethod,
                             EventStreamMetadata,
                             EventStreamServerCertificate,
+                            EventStreamSyslogAddress,
                             EventStreamToken};
 use habitat_core::{package::ident::PackageIdent,
                    service::HealthCheckInterval};
@@ -43,7 +72,10 @@ use rants::{Address,
             Subject};
 use state::Storage;
 use std::{net::SocketAddr,
+          path::{Path,
+                 PathBuf},
           time::Duration};
+use url::Url;
 
 lazy_static! {
     // TODO (CM): When const fn support lands in stable, we can ditch
@@ -58,25 +90,63 @@ lazy_static! {
         "habitat.event.service_update_started".parse().expect("valid NATS subject");
     static ref HEALTHCHECK_SUBJECT: Subject =
         "habitat.event.healthcheck".parse().expect("valid NATS subject");
+    static ref SERVICE_CRASH_LOOP_SUBJECT: Subject =
+        "habitat.event.service_crash_loop".parse().expect("valid NATS subject");
+    static ref SERVICE_LOADED_SUBJECT: Subject =
+        "habitat.event.service_loaded".parse().expect("valid NATS subject");
+    static ref SERVICE_UNLOADED_SUBJECT: Subject =
+        "habitat.event.service_unloaded".parse().expect("valid NATS subject");
+    static ref SERVICE_UPDATE_FINISHED_SUBJECT: Subject =
+        "habitat.event.service_update_finished".parse().expect("valid NATS subject");
+    static ref SERVICE_UPDATE_FAILED_SUBJECT: Subject =
+        "habitat.event.service_update_failed".parse().expect("valid NATS subject");
+    static ref SERVICE_CONFIG_APPLIED_SUBJECT: Subject =
+        "habitat.event.service_config_applied".parse().expect("valid NATS subject");
+    static ref SERVICE_FILE_UPDATED_SUBJECT: Subject =
+        "habitat.event.service_file_updated".parse().expect("valid NATS subject");
+    static ref ELECTION_WON_SUBJECT: Subject =
+        "habitat.event.election_won".parse().expect("valid NATS subject");
+    static ref ELECTION_LOST_SUBJECT: Subject =
+        "habitat.event.election_lost".parse().expect("valid NATS subject");
+    static ref MEMBER_SUSPECT_SUBJECT: Subject =
+        "habitat.event.member_suspect".parse().expect("valid NATS subject");
+    static ref MEMBER_CONFIRMED_SUBJECT: Subject =
+        "habitat.event.member_confirmed".parse().expect("valid NATS subject");
+    static ref MEMBER_DEPARTED_SUBJECT: Subject =
+        "habitat.event.member_departed".parse().expect("valid NATS subject");
+    static ref HOOK_FAILED_SUBJECT: Subject =
+        "habitat.event.hook_failed".parse().expect("valid NATS subject");
+    static ref SUPERVISOR_UPDATE_SUBJECT: Subject =
+        "habitat.event.supervisor_update".parse().expect("valid NATS subject");
 
-    /// Reference to the event stream.
-    static ref NATS_MESSAGE_STREAM: Storage<NatsMessageStream> = Storage::new();
+    /// References to every sink of the event stream.
+    static ref EVENT_SINKS: Storage<Vec<EventSink>> = Storage::new();
     /// Core information that is shared between all events.
     static ref EVENT_CORE: Storage<EventCore> = Storage::new();
 }
 
-/// Starts a new task for sending events to a NATS Streaming
-/// server. Stashes the handle to the stream, as well as the core
-/// event information that will be a part of all events, in a global
-/// static reference for access later.
-pub async fn init(sys: &Sys, fqdn: String, config: EventStreamConfig) -> Result<()> {
+/// Starts the tasks for sending events to each configured sink.
+/// Stashes the handles to the sinks, as well as the core event
+/// information that will be a part of all events, in a global static
+/// reference for access later.
+///
+/// Events that cannot be published to NATS while it is unreachable
+/// are spooled in `spool_path`.
+pub async fn init(sys: &Sys,
+                  fqdn: String,
+                  config: EventStreamConfig,
+                  spool_path: &Path)
+                  -> Result<()> {
     // Only initialize once
     if !initialized() {
         let supervisor_id = sys.member_id.clone();
         let ip_address = sys.gossip_listen();
         let event_core = EventCore::new(&supervisor_id, ip_address, &fqdn, &config);
-        let stream = NatsMessageStream::new(&supervisor_id, config).await?;
-        NATS_MESSAGE_STREAM.set(stream);
+        let mut sinks = Vec::with_capacity(config.sinks.len());
+        for sink in config.sinks {
+            sinks.push(EventSink::new(&supervisor_id, &fqdn, spool_path, sink).await?);
+        }
+        EVENT_SINKS.set(sinks);
         EVENT_CORE.set(event_core);
     }
     Ok(())
@@ -88,14 +158,75 @@ pub async fn init(sys: &Sys, fqdn: String, config: EventStreamConfig) -> Result<
 // layout so this can be avoided.
 #[derive(Clone, Debug, PartialEq)]
 pub struct EventStreamConfig {
-    pub environment:        String,
-    pub application:        String,
-    pub site:               Option<String>,
-    pub meta:               EventStreamMetadata,
+    pub environment: String,
+    pub application: String,
+    pub site:        Option<String>,
+    pub meta:        EventStreamMetadata,
+    pub sinks:       Vec<EventSinkConfig>,
+}
+
+/// Where events are sent, and how.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventSinkConfig {
+    /// Publish protobuf messages to a NATS server.
+    Nats(NatsConfig),
+    /// Append JSON lines to a file.
+    File(PathBuf),
+    /// `POST` JSON to an HTTP endpoint.
+    Webhook(Url),
+    /// Send JSON in syslog messages.
+    Syslog(EventStreamSyslogAddress),
+}
+
+/// The options for connecting to a NATS server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NatsConfig {
     pub token:              EventStreamToken,
     pub url:                Address,
     pub connect_method:     EventStreamConnectMethod,
     pub server_certificate: Option<EventStreamServerCertificate>,
+    /// The most bytes of events to spool on disk while the server is
+    /// unreachable. Zero disables spooling.
+    pub spool_size:         u64,
+}
+
+/// A handle to a running event sink.
+enum EventSink {
+    Nats(NatsMessageStream),
+    File(FileSink),
+    Webhook(WebhookSink),
+    Syslog(SyslogSink),
+}
+
+impl EventSink {
+    async fn new(supervisor_id: &str,
+                 fqdn: &str,
+                 spool_path: &Path,
+                 config: EventSinkConfig)
+                 -> Result<Self> {
+        let sink = match config {
+            EventSinkConfig::Nats(config) => {
+                EventSink::Nats(NatsMessageStream::new(supervisor_id, config, spool_path).await?)
+            }
+            EventSinkConfig::File(path) => EventSink::File(FileSink::new(path).await?),
+            EventSinkConfig::Webhook(url) => EventSink::Webhook(WebhookSink::new(url)?),
+            EventSinkConfig::Syslog(address) => {
+                EventSink::Syslog(SyslogSink::new(address, fqdn).await?)
+            }
+        };
+        Ok(sink)
+    }
+
+    fn send(&self, subject: &'static Subject, event: &impl EventMessage) {
+        match self {
+            EventSink::Nats(stream) => {
+                stream.send(NatsMessage::new(subject.clone(), event.to_bytes()))
+            }
+            EventSink::File(sink) => sink.send(event.to_json(&subject.to_string())),
+            EventSink::Webhook(sink) => sink.send(event.to_json(&subject.to_string())),
+            EventSink::Syslog(sink) => sink.send(event.to_json(&subject.to_string())),
+        }
+    }
 }
 
 /// Send an event for the start of a Service.
@@ -127,6 +258,160 @@ pub fn service_update_started(service: &Service, update: &PackageIdent) {
     }
 }
 
+/// Send an event when the Supervisor gives up on restarting a Service whose process keeps exiting.
+pub fn service_crash_loop(service: &Service, exits: usize, window: Duration) {
+    if initialized() {
+        publish(&SERVICE_CRASH_LOOP_SUBJECT,
+                ServiceCrashLoopEvent { event_metadata:   None,
+                                        service_metadata: Some(service.to_service_metadata()),
+                                        exits:            exits as u32,
+                                        window:           Some(window.into()), });
+    }
+}
+
+/// Send an event when a Service is loaded.
+pub fn service_loaded(metadata: ServiceMetadata) {
+    if initialized() {
+        publish(&SERVICE_LOADED_SUBJECT,
+                ServiceLoadedEvent { event_metadata:   None,
+                                     service_metadata: Some(metadata), });
+    }
+}
+
+/// Send an event when a Service is unloaded.
+pub fn service_unloaded(metadata: ServiceMetadata) {
+    if initialized() {
+        publish(&SERVICE_UNLOADED_SUBJECT,
+                ServiceUnloadedEvent { event_metadata:   None,
+                                       service_metadata: Some(metadata), });
+    }
+}
+
+/// Send an event when a Service has restarted with the package it
+/// was updating to.
+pub fn service_update_finished(service: &Service) {
+    if initialized() {
+        let service_metadata = Some(service.to_service_metadata());
+        publish(&SERVICE_UPDATE_FINISHED_SUBJECT,
+                ServiceUpdateFinishedEvent { event_metadata: None,
+                                             service_metadata });
+    }
+}
+
+/// Send an event when a Service could not restart with the package it
+/// was updating to. `metadata` describes the Service as it was before
+/// the update.
+pub fn service_update_failed(metadata: ServiceMetadata, update: &PackageIdent, error: String) {
+    if initialized() {
+        publish(&SERVICE_UPDATE_FAILED_SUBJECT,
+                ServiceUpdateFailedEvent { event_metadata: None,
+                                           service_metadata: Some(metadata),
+                                           update_package_ident: update.to_string(),
+                                           error });
+    }
+}
+
+/// Send an event when a Service applies gossiped configuration.
+pub fn service_config_applied(service: &Service, incarnation: u64) {
+    if initialized() {
+        publish(&SERVICE_CONFIG_APPLIED_SUBJECT,
+                ServiceConfigAppliedEvent { event_metadata: None,
+                                            service_metadata:
+                                                Some(service.to_service_metadata()),
+                                            incarnation });
+    }
+}
+
+/// Send an event when a Service writes a gossiped file.
+pub fn service_file_updated(service: &Service, filename: &str, incarnation: u64) {
+    if initialized() {
+        publish(&SERVICE_FILE_UPDATED_SUBJECT,
+                ServiceFileUpdatedEvent { event_metadata: None,
+                                          service_metadata: Some(service.to_service_metadata()),
+                                          filename: filename.to_string(),
+                                          incarnation });
+    }
+}
+
+/// Send an event when this Supervisor is elected the leader of a
+/// Service's group.
+pub fn election_won(service: &Service) {
+    if initialized() {
+        publish(&ELECTION_WON_SUBJECT,
+                ElectionWonEvent { event_metadata:   None,
+                                   service_metadata: Some(service.to_service_metadata()), });
+    }
+}
+
+/// Send an event when another Supervisor is elected the leader of a
+/// Service's group.
+pub fn election_lost(service: &Service, leader_member_id: &str) {
+    if initialized() {
+        publish(&ELECTION_LOST_SUBJECT,
+                ElectionLostEvent { event_metadata:   None,
+                                    service_metadata: Some(service.to_service_metadata()),
+                                    leader_member_id: leader_member_id.to_string(), });
+    }
+}
+
+/// Send an event when another member of the ring changes to a health
+/// other than alive.
+pub fn member_health_changed(member: &Member, health: Health) {
+    if initialized() {
+        let member_id = member.id.clone();
+        let member_address = member.address.clone();
+        match health {
+            Health::Alive => {}
+            Health::Suspect => {
+                publish(&MEMBER_SUSPECT_SUBJECT,
+                        MemberSuspectEvent { event_metadata: None,
+                                             member_id,
+                                             member_address })
+            }
+            Health::Confirmed => {
+                publish(&MEMBER_CONFIRMED_SUBJECT,
+                        MemberConfirmedEvent { event_metadata: None,
+                                               member_id,
+                                               member_address })
+            }
+            Health::Departed => {
+                publish(&MEMBER_DEPARTED_SUBJECT,
+                        MemberDepartedEvent { event_metadata: None,
+                                              member_id,
+                                              member_address })
+            }
+        }
+    }
+}
+
+/// Send an event when a Service's hook fails, either by exiting with
+/// a failing `exit_status` or because of an `error` running it.
+///
+/// Takes metadata directly, like `health_check`, because most hooks
+/// are run asynchronously.
+pub fn hook_failed(metadata: ServiceMetadata,
+                   hook: &str,
+                   exit_status: Option<i32>,
+                   error: Option<String>) {
+    if initialized() {
+        publish(&HOOK_FAILED_SUBJECT,
+                HookFailedEvent { event_metadata: None,
+                                  service_metadata: Some(metadata),
+                                  hook: hook.to_string(),
+                                  exit_status,
+                                  error });
+    }
+}
+
+/// Send an event when the Supervisor restarts to update itself.
+pub fn supervisor_update(update: &PackageIdent) {
+    if initialized() {
+        publish(&SUPERVISOR_UPDATE_SUBJECT,
+                SupervisorUpdateEvent { event_metadata:       None,
+                                        update_package_ident: update.to_string(), });
+    }
+}
+
 // Takes metadata directly, rather than a `&Service` like other event
 // functions, because of how the asynchronous health checking
 // currently works. Revisit when async/await + Pin is all stabilized.
@@ -137,12 +422,8 @@ pub fn health_check(metadata: ServiceMetadata,
     if initialized() {
         let health_check_result: types::HealthCheckResult = health_check_result.into();
         let maybe_duration = health_check_hook_status.maybe_duration();
-        let maybe_process_output = health_check_hook_status.maybe_process_output();
-        let exit_status = maybe_process_output.as_ref()
-                                              .and_then(|o| o.exit_status().code());
-        let StandardStreams { stdout, stderr } =
-            maybe_process_output.map(ProcessOutput::standard_streams)
-                                .unwrap_or_default();
+        let (exit_status, StandardStreams { stdout, stderr }) =
+            health_check_hook_status.into_output();
 
         let prost_interval = ProstDuration::from(Duration::from(health_check_interval));
 
@@ -200,7 +481,7 @@ impl EventCore {
 /// Internal helper function to know whether or not to go to the trouble of
 /// creating event structures. If the event stream hasn't been
 /// initialized, then we shouldn't need to do anything.
-fn initialized() -> bool { NATS_MESSAGE_STREAM.try_get().is_some() }
+fn initialized() -> bool { EVENT_SINKS.try_get().is_some() }
 
 /// Publish an event. This is the main interface that client code will
 /// use.
@@ -208,7 +489,7 @@ fn initialized() -> bool { NATS_MESSAGE_STREAM.try_get().is_some() }
 /// If `init_stream` has not been called already, this function will
 /// be a no-op.
 fn publish(subject: &'static Subject, mut event: impl EventMessage) {
-    if let Some(stream) = NATS_MESSAGE_STREAM.try_get() {
+    if let Some(sinks) = EVENT_SINKS.try_get() {
         // TODO (CM): Yeah... this is looking pretty gross. The
         // intention is to be able to timestamp the events right as
         // they go out.
@@ -225,37 +506,133 @@ fn publish(subject: &'static Subject, mut event: impl EventMessage) {
                                                  Some(std::time::SystemTime::now().into()),
                                              ..EVENT_CORE.get().to_event_metadata() });
 
-        let packet = NatsMessage::new(subject, event.to_bytes());
-        stream.send(packet);
+        for sink in sinks {
+            sink.send(subject, &event);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{nats_message_stream::NatsMessageStream,
+                types::{UpdateConfig,
+                        UpdateStrategy},
                 *};
-    use crate::prost::Message;
+    use crate::{manager::service::ProcessOutput,
+                prost::Message};
     use futures::{channel::mpsc as futures_mpsc,
                   stream::StreamExt};
     #[cfg(windows)]
     use habitat_core::os::process::windows_child::ExitStatus;
     use habitat_core::service::HealthCheckInterval;
+    use std::sync::{Mutex,
+                    MutexGuard};
     #[cfg(unix)]
     use std::{os::unix::process::ExitStatusExt,
               process::ExitStatus};
 
+    #[test]
+    fn events_render_as_json() {
+        let timestamp = prost_types::Timestamp { seconds: 1_500_000_000,
+                                                 nanos:   0, };
+        let event_metadata = EventMetadata { supervisor_id: String::from("supervisor_id"),
+                                             occurred_at: Some(timestamp),
+                                             ..EventMetadata::default() };
+        let update_config = UpdateConfig { strategy: UpdateStrategy::Rolling as i32,
+                                           channel:  String::from("stable"), };
+        let service_metadata = ServiceMetadata { update_config: Some(update_config),
+                                                 ..ServiceMetadata::default() };
+        let window = Duration::from_secs(600);
+        let event = ServiceCrashLoopEvent { event_metadata:   Some(event_metadata),
+                                            service_metadata: Some(service_metadata),
+                                            exits:            5,
+                                            window:           Some(window.into()), };
+        let json: serde_json::Value =
+            serde_json::from_str(&event.to_json("habitat.event.service_crash_loop")).unwrap();
+        assert_eq!(json["subject"], "habitat.event.service_crash_loop");
+        assert_eq!(json["event"]["event_metadata"]["supervisor_id"],
+                   "supervisor_id");
+        assert_eq!(json["event"]["event_metadata"]["occurred_at"],
+                   "2017-07-14T02:40:00Z");
+        assert_eq!(json["event"]["service_metadata"]["update_config"]["strategy"],
+                   "Rolling");
+        assert_eq!(json["event"]["exits"], 5);
+        assert_eq!(json["event"]["window"], "600s");
+    }
+
+    lazy_static! {
+        /// The events published by tests. The event sinks can only be set once, so tests which
+        /// publish events take turns reading them from here.
+        static ref PUBLISHED: Mutex<futures_mpsc::Receiver<NatsMessage>> = {
+            let (tx, rx) = futures_mpsc::channel(16);
+            EVENT_SINKS.set(vec![EventSink::Nats(NatsMessageStream::from_parts(tx, None))]);
+            EVENT_CORE.set(EventCore { supervisor_id: String::from("supervisor_id"),
+                                       ip_address:    "127.0.0.1:8080".parse().unwrap(),
+                                       fqdn:          String::from("fqdn"),
+                                       application:   String::from("application"),
+                                       environment:   String::from("environment"),
+                                       site:          None,
+                                       meta:          EventStreamMetadata::default(), });
+            Mutex::new(rx)
+        };
+    }
+
+    fn published() -> MutexGuard<'static, futures_mpsc::Receiver<NatsMessage>> {
+        PUBLISHED.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[tokio::test]
+    async fn hook_failed_event() {
+        let mut rx = published();
+        hook_failed(ServiceMetadata::default(), "init", Some(3), None);
+        hook_failed(ServiceMetadata::default(),
+                    "post-run",
+                    None,
+                    Some(String::from("failed to run")));
+        let events = (&mut *rx).take(2).collect::<Vec<_>>().await;
+
+        assert_eq!(events[0].subject().to_string(), "habitat.event.hook_failed");
+        let event = HookFailedEvent::decode(events[0].payload()).unwrap();
+        assert_eq!(event.hook, "init");
+        assert_eq!(event.exit_status, Some(3));
+        assert_eq!(event.error, None);
+
+        let event = HookFailedEvent::decode(events[1].payload()).unwrap();
+        assert_eq!(event.hook, "post-run");
+        assert_eq!(event.exit_status, None);
+        assert_eq!(event.error, Some(String::from("failed to run")));
+    }
+
+    #[tokio::test]
+    async fn member_health_events() {
+        let mut rx = published();
+        let member = Member { address: String::from("10.0.0.1"),
+                              ..Member::default() };
+        member_health_changed(&member, Health::Alive);
+        member_health_changed(&member, Health::Suspect);
+        member_health_changed(&member, Health::Confirmed);
+        member_health_changed(&member, Health::Departed);
+        let events = (&mut *rx).take(3).collect::<Vec<_>>().await;
+
+        assert_eq!(events.iter()
+                         .map(|e| e.subject().to_string())
+                         .collect::<Vec<_>>(),
+                   vec!["habitat.event.member_suspect",
+                        "habitat.event.member_confirmed",
+                        "habitat.event.member_departed"]);
+        let event = MemberSuspectEvent::decode(events[0].payload()).unwrap();
+        assert_eq!(event.member_id, member.id);
+        assert_eq!(event.member_address, "10.0.0.1");
+        let event = MemberConfirmedEvent::decode(events[1].payload()).unwrap();
+        assert_eq!(event.member_id, member.id);
+        let event = MemberDepartedEvent::decode(events[2].payload()).unwrap();
+        assert_eq!(event.member_id, member.id);
+    }
+
     #[tokio::test]
     #[cfg(any(unix, windows))]
     async fn health_check_event() {
-        let (tx, rx) = futures_mpsc::unbounded();
-        NATS_MESSAGE_STREAM.set(NatsMessageStream(tx));
-        EVENT_CORE.set(EventCore { supervisor_id: String::from("supervisor_id"),
-                                   ip_address:    "127.0.0.1:8080".parse().unwrap(),
-                                   fqdn:          String::from("fqdn"),
-                                   application:   String::from("application"),
-                                   environment:   String::from("environment"),
-                                   site:          None,
-                                   meta:          EventStreamMetadata::default(), });
+        let mut rx = published();
         health_check(ServiceMetadata::default(),
                      HealthCheckResult::Ok,
                      HealthCheckHookStatus::NoHook,
@@ -288,7 +665,7 @@ mod tests {
                      HealthCheckResult::Unknown,
                      HealthCheckHookStatus::Ran(process_output, Duration::from_secs(15)),
                      HealthCheckInterval::default());
-        let events = rx.take(4).collect::<Vec<_>>().await;
+        let events = (&mut *rx).take(4).collect::<Vec<_>>().await;
 
         let event = HealthCheckEvent::decode(events[0].payload()).unwrap();
         assert_eq!(event.result, 0);
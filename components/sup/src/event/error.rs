@@ -5,6 +5,8 @@ use rants::{error::Error as RantsError,
             native_tls};
 use std::{error,
           fmt,
+          io,
+          path::PathBuf,
           result};
 
 pub type Result<T> = result::Result<T, Error>;
@@ -12,9 +14,13 @@ pub type Result<T> = result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
     ConnectNatsServer,
+    EventFile(PathBuf, io::Error),
+    EventSpool(PathBuf, io::Error),
     HabitatHttpClient(habitat_http_client::Error),
     NativeTls(native_tls::Error),
     Rants(RantsError),
+    SyslogConnect(String, io::Error),
+    Webhook(reqwest::Error),
 }
 
 // TODO (CM): I would have like to have derived Fail on our Error
@@ -29,9 +35,19 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::ConnectNatsServer => "Could not establish connection to NATS server".fmt(f),
+            Error::EventFile(path, e) => {
+                format!("Could not open event file '{}': {}", path.display(), e).fmt(f)
+            }
+            Error::EventSpool(path, e) => {
+                format!("Could not open event spool '{}': {}", path.display(), e).fmt(f)
+            }
             Error::HabitatHttpClient(_) => "{}".fmt(f),
             Error::NativeTls(e) => format!("{}", e).fmt(f),
             Error::Rants(e) => format!("{}", e).fmt(f),
+            Error::SyslogConnect(address, e) => {
+                format!("Could not connect to syslog at '{}': {}", address, e).fmt(f)
+            }
+            Error::Webhook(e) => format!("Could not create event webhook client: {}", e).fmt(f),
         }
     }
 }
@@ -40,9 +56,13 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Error::ConnectNatsServer => None,
+            Error::EventFile(_, ref e) => Some(e),
+            Error::EventSpool(_, ref e) => Some(e),
             Error::HabitatHttpClient(ref e) => Some(e),
             Error::Rants(ref e) => Some(e),
             Error::NativeTls(ref e) => Some(e),
+            Error::SyslogConnect(_, ref e) => Some(e),
+            Error::Webhook(ref e) => Some(e),
         }
     }
 }
@@ -0,0 +1,85 @@
+//! An event sink that appends each event to a local file as a line of JSON.
+
+use crate::event::{Error,
+                   Result};
+use futures::{channel::{mpsc as futures_mpsc,
+                        mpsc::UnboundedSender},
+              stream::StreamExt};
+use std::path::PathBuf;
+use tokio::{fs::OpenOptions,
+            io::AsyncWriteExt};
+
+/// A lightweight handle for the file sink. Events are written in the order they are sent by a
+/// background task, so a slow disk never blocks the Supervisor.
+pub struct FileSink(UnboundedSender<String>);
+
+impl FileSink {
+    pub async fn new(path: PathBuf) -> Result<FileSink> {
+        let mut file = OpenOptions::new().create(true)
+                                         .append(true)
+                                         .open(&path)
+                                         .await
+                                         .map_err(|e| Error::EventFile(path.clone(), e))?;
+
+        let (tx, mut rx) = futures_mpsc::unbounded::<String>();
+
+        tokio::spawn(async move {
+            while let Some(mut line) = rx.next().await {
+                line.push('\n');
+                let written = match file.write_all(line.as_bytes()).await {
+                    Ok(()) => file.flush().await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = written {
+                    error!("Failed to write event to '{}', err: {}", path.display(), e);
+                }
+            }
+        });
+
+        Ok(FileSink(tx))
+    }
+
+    /// Queues an event to be written
+    pub fn send(&self, event: String) {
+        trace!("Queueing event for file: {}", event);
+        if let Err(e) = self.0.unbounded_send(event) {
+            error!("Failed to queue event, err: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs,
+              time::Duration};
+    use tempfile::TempDir;
+    use tokio::time;
+
+    #[tokio::test]
+    async fn appends_each_event_as_a_line() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("events.json");
+        fs::write(&path, "{\"event\":0}\n").unwrap();
+        let sink = FileSink::new(path.clone()).await.unwrap();
+
+        sink.send(r#"{"event":1}"#.to_string());
+        sink.send(r#"{"event":2}"#.to_string());
+
+        let expected = "{\"event\":0}\n{\"event\":1}\n{\"event\":2}\n";
+        for _ in 0..100 {
+            if fs::read_to_string(&path).unwrap() == expected {
+                break;
+            }
+            time::delay_for(Duration::from_millis(10)).await;
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_file_cannot_be_opened() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("missing").join("events.json");
+        assert!(FileSink::new(path).await.is_err());
+    }
+}
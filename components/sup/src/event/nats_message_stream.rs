@@ -1,27 +1,56 @@
+use super::spool::Spool;
 use crate::event::{Error,
-                   EventStreamConfig,
+                   NatsConfig,
                    Result};
 use futures::{channel::{mpsc as futures_mpsc,
-                        mpsc::UnboundedSender},
+                        mpsc::{Receiver,
+                               Sender}},
               stream::StreamExt};
 use habitat_http_client;
+use prometheus::IntCounter;
 use rants::{error::Error as RantsError,
             native_tls::TlsConnector,
             Client,
             Subject};
-use tokio::time;
+use std::{collections::VecDeque,
+          path::Path,
+          sync::{Arc,
+                 Mutex},
+          time::Duration};
+use tokio::{sync::Mutex as AsyncMutex,
+            time};
+
+/// How often to try replaying spooled messages while no new messages arrive.
+const SPOOL_REPLAY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many messages can wait to be published before new messages overflow.
+const QUEUE_SIZE: usize = 1024;
+
+/// How many messages can wait in the overflow queue before the oldest of them are dropped.
+const OVERFLOW_SIZE: usize = 1024;
+
+lazy_static! {
+    static ref EVENTS_DROPPED: IntCounter =
+        register_int_counter!("hab_sup_event_stream_dropped_total",
+                              "Number of events that were never published to the event \
+                               stream").unwrap();
+    static ref EVENTS_REPLAYED: IntCounter =
+        register_int_counter!("hab_sup_event_stream_replayed_total",
+                              "Number of spooled events published after the event stream \
+                               reconnected").unwrap();
+}
 
 /// The subject and payload of a NATS message.
 #[derive(Debug)]
 pub struct NatsMessage {
-    subject: &'static Subject,
+    subject: Subject,
     payload: Vec<u8>,
 }
 
 impl NatsMessage {
-    pub fn new(subject: &'static Subject, payload: Vec<u8>) -> Self {
-        NatsMessage { subject, payload }
-    }
+    pub fn new(subject: Subject, payload: Vec<u8>) -> Self { NatsMessage { subject, payload } }
+
+    pub fn subject(&self) -> &Subject { &self.subject }
 
     pub fn payload(&self) -> &[u8] { self.payload.as_slice() }
 }
@@ -29,17 +58,42 @@ impl NatsMessage {
 /// A lightweight handle for the NATS message stream. All events are converted into a NatsMessage
 /// and sent into this stream to be published.
 ///
-/// An UnboundedSender should be ok here. Messages are continously processed even if the client is
-/// not currently connected.
-pub struct NatsMessageStream(pub(super) UnboundedSender<NatsMessage>);
+/// The queue of messages waiting to be published is bounded. If it fills up, new messages wait in
+/// a bounded overflow queue, which the publisher empties in order once it has caught up with the
+/// queue. Without a spool, messages that do not fit in the queue are dropped instead.
+pub struct NatsMessageStream {
+    sender:   Mutex<Sender<NatsMessage>>,
+    overflow: Option<Arc<Overflow>>,
+}
+
+/// Messages which did not fit in the queue, oldest first.
+type Overflow = Mutex<VecDeque<NatsMessage>>;
 
 impl NatsMessageStream {
-    pub async fn new(supervisor_id: &str, config: EventStreamConfig) -> Result<NatsMessageStream> {
-        let EventStreamConfig { url,
-                                token,
-                                connect_method,
-                                server_certificate,
-                                .. } = config;
+    pub async fn new(supervisor_id: &str,
+                     config: NatsConfig,
+                     spool_path: &Path)
+                     -> Result<NatsMessageStream> {
+        let NatsConfig { url,
+                         token,
+                         connect_method,
+                         server_certificate,
+                         spool_size, } = config;
+
+        // A spool size of zero disables spooling; messages are dropped while disconnected.
+        let spool = if spool_size > 0 {
+            let (spool, dropped) = match Spool::open(spool_path, spool_size).await {
+                Ok(opened) => opened,
+                Err(e) => return Err(Error::EventSpool(spool_path.to_path_buf(), e)),
+            };
+            EVENTS_DROPPED.inc_by(dropped as i64);
+            if !spool.is_empty() {
+                debug!("Found {} spooled events to replay", spool.len());
+            }
+            Some(Arc::new(AsyncMutex::new(spool)))
+        } else {
+            None
+        };
 
         let mut client = Client::new(vec![url]);
 
@@ -74,35 +128,297 @@ impl NatsMessageStream {
             tokio::spawn(async move { client.connect().await });
         }
 
-        let (tx, mut rx) = futures_mpsc::unbounded::<NatsMessage>();
+        let (tx, mut rx) = futures_mpsc::channel::<NatsMessage>(QUEUE_SIZE);
+        let overflow = Arc::new(Overflow::default());
+        let stream =
+            NatsMessageStream::from_parts(tx, spool.as_ref().map(|_| Arc::clone(&overflow)));
 
         // Spawn a task to handle publishing received messages
         tokio::spawn(async move {
-            while let Some(packet) = rx.next().await {
-                if let Err(e) = client.publish(packet.subject, packet.payload()).await {
-                    // We do not retry any messages. If we are not connected when the message is
-                    // processed or there is an error in publishing the message, the message will
-                    // never be sent.
-                    if let RantsError::NotConnected = e {
-                        trace!("Failed to publish message to subject '{}' because the client is \
-                                not connected",
-                               packet.subject);
-                    } else {
-                        error!("Failed to publish message to subject '{}', err: {}",
-                               packet.subject, e);
+            let mut replay_interval = time::interval(SPOOL_REPLAY_INTERVAL);
+            loop {
+                tokio::select! {
+                    packet = rx.next() => {
+                        match packet {
+                            Some(packet) => {
+                                send(&client, spool.as_deref(), packet).await;
+                                send_waiting(&client, spool.as_deref(), &mut rx, &overflow).await;
+                            }
+                            None => break,
+                        }
+                    }
+                    _ = replay_interval.tick() => {
+                        if let Some(spool) = &spool {
+                            replay(&client, &mut *spool.lock().await).await;
+                        }
                     }
                 }
             }
         });
 
-        Ok(NatsMessageStream(tx))
+        Ok(stream)
+    }
+
+    pub(super) fn from_parts(sender: Sender<NatsMessage>, overflow: Option<Arc<Overflow>>) -> Self {
+        NatsMessageStream { sender: Mutex::new(sender),
+                            overflow }
     }
 
     /// Queues a NATS message to be published
     pub fn send(&self, event_packet: NatsMessage) {
         trace!("Queueing message: {:?}", event_packet);
-        if let Err(e) = self.0.unbounded_send(event_packet) {
-            error!("Failed to queue message, err: {}", e);
+        let overflow = match &self.overflow {
+            Some(overflow) => overflow,
+            None => {
+                if let Err(e) = self.try_send(event_packet) {
+                    if e.is_full() {
+                        warn!("Dropping message to subject '{}' because the queue is full",
+                              e.into_inner().subject());
+                        EVENTS_DROPPED.inc();
+                    } else {
+                        error!("Failed to queue message, err: {}", e);
+                    }
+                }
+                return;
+            }
+        };
+        // Hold the overflow lock while queueing so the publisher cannot empty the overflow queue
+        // between our check and our send. While anything has overflowed, new messages join the
+        // back of the overflow queue, which keeps them in order.
+        let mut overflow = overflow.lock()
+                                   .expect("NATS message overflow lock poisoned");
+        let event_packet = if overflow.is_empty() {
+            match self.try_send(event_packet) {
+                Ok(()) => return,
+                Err(e) if e.is_full() => e.into_inner(),
+                Err(e) => {
+                    error!("Failed to queue message, err: {}", e);
+                    return;
+                }
+            }
+        } else {
+            event_packet
+        };
+        if overflow.len() >= OVERFLOW_SIZE {
+            if let Some(dropped) = overflow.pop_front() {
+                warn!("Dropping message to subject '{}' because the overflow queue is full",
+                      dropped.subject());
+                EVENTS_DROPPED.inc();
+            }
         }
+        overflow.push_back(event_packet);
+    }
+
+    fn try_send(&self,
+                event_packet: NatsMessage)
+                -> std::result::Result<(), futures_mpsc::TrySendError<NatsMessage>> {
+        self.sender
+            .lock()
+            .expect("NATS message sender lock poisoned")
+            .try_send(event_packet)
+    }
+}
+
+/// Publish or spool every message that is already waiting, without waiting for new ones. Messages
+/// in the overflow queue are newer than any in the queue, so the queue is emptied first.
+async fn send_waiting(client: &Client,
+                      spool: Option<&AsyncMutex<Spool>>,
+                      rx: &mut Receiver<NatsMessage>,
+                      overflow: &Overflow) {
+    loop {
+        let packet = match rx.try_next() {
+            Ok(Some(packet)) => packet,
+            _ => {
+                let packet = overflow.lock()
+                                     .expect("NATS message overflow lock poisoned")
+                                     .pop_front();
+                match packet {
+                    Some(packet) => packet,
+                    None => return,
+                }
+            }
+        };
+        send(client, spool, packet).await;
+    }
+}
+
+/// Publish a message, or spool it if the client is not connected. Messages are always published in
+/// order, so while anything is spooled new messages join the back of the spool.
+async fn send(client: &Client, spool: Option<&AsyncMutex<Spool>>, packet: NatsMessage) {
+    let mut spool = match spool {
+        Some(spool) => spool.lock().await,
+        None => {
+            if publish(client, &packet).await.is_err() {
+                EVENTS_DROPPED.inc();
+            }
+            return;
+        }
+    };
+    if spool.is_empty() {
+        match publish(client, &packet).await {
+            Err(RantsError::NotConnected) => {}
+            Err(_) => {
+                EVENTS_DROPPED.inc();
+                return;
+            }
+            Ok(()) => return,
+        }
+    }
+    push(&mut spool, &packet).await;
+    replay(client, &mut spool).await;
+}
+
+/// Add a message to the back of the spool.
+async fn push(spool: &mut Spool, packet: &NatsMessage) {
+    match spool.push(packet).await {
+        Ok(dropped) => EVENTS_DROPPED.inc_by(dropped as i64),
+        Err(e) => {
+            error!("Failed to spool message to subject '{}', err: {}",
+                   packet.subject(),
+                   e);
+            EVENTS_DROPPED.inc();
+        }
+    }
+}
+
+/// Publish spooled messages, oldest first, until the spool is empty or the client disconnects.
+async fn replay(client: &Client, spool: &mut Spool) {
+    loop {
+        let packet = match spool.front().await {
+            Ok(Some(packet)) => packet,
+            Ok(None) => return,
+            Err(e) => {
+                error!("Failed to read spooled message, dropping it, err: {}", e);
+                spool.pop_front().await;
+                EVENTS_DROPPED.inc();
+                continue;
+            }
+        };
+        match publish(client, &packet).await {
+            Err(RantsError::NotConnected) => return,
+            Err(_) => EVENTS_DROPPED.inc(),
+            Ok(()) => EVENTS_REPLAYED.inc(),
+        }
+        spool.pop_front().await;
+    }
+}
+
+async fn publish(client: &Client, packet: &NatsMessage) -> std::result::Result<(), RantsError> {
+    let result = client.publish(packet.subject(), packet.payload()).await;
+    if let Err(e) = &result {
+        if let RantsError::NotConnected = e {
+            trace!("Failed to publish message to subject '{}' because the client is not connected",
+                   packet.subject());
+        } else {
+            error!("Failed to publish message to subject '{}', err: {}",
+                   packet.subject(),
+                   e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn message(subject: &str, payload: &str) -> NatsMessage {
+        NatsMessage::new(subject.parse().unwrap(), payload.as_bytes().to_vec())
+    }
+
+    async fn spooled(spool: &AsyncMutex<Spool>) -> Vec<String> {
+        let mut spool = spool.lock().await;
+        let mut subjects = Vec::new();
+        while let Some(message) = spool.front().await.unwrap() {
+            subjects.push(message.subject().to_string());
+            spool.pop_front().await;
+        }
+        subjects
+    }
+
+    #[tokio::test]
+    async fn send_spools_messages_in_order_while_disconnected() {
+        let tmpdir = TempDir::new().unwrap();
+        let (spool, _) = Spool::open(tmpdir.path(), 1024).await.unwrap();
+        let spool = AsyncMutex::new(spool);
+        // The client is never connected.
+        let client = Client::new(vec!["127.0.0.1:4222".parse().unwrap()]);
+
+        send(&client, Some(&spool), message("habitat.event.one", "1")).await;
+        send(&client, Some(&spool), message("habitat.event.two", "2")).await;
+        replay(&client, &mut *spool.lock().await).await;
+        send(&client, Some(&spool), message("habitat.event.three", "3")).await;
+
+        assert_eq!(spooled(&spool).await,
+                   vec!["habitat.event.one",
+                        "habitat.event.two",
+                        "habitat.event.three"]);
+    }
+
+    #[tokio::test]
+    async fn overflows_in_order_when_the_queue_is_full() {
+        let tmpdir = TempDir::new().unwrap();
+        let (spool, _) = Spool::open(tmpdir.path(), 1024).await.unwrap();
+        let spool = AsyncMutex::new(spool);
+        // The client is never connected.
+        let client = Client::new(vec!["127.0.0.1:4222".parse().unwrap()]);
+        let overflow = Arc::new(Overflow::default());
+        // A channel with no buffer holds a single message for its one sender.
+        let (tx, mut rx) = futures_mpsc::channel(0);
+        let stream = NatsMessageStream::from_parts(tx, Some(Arc::clone(&overflow)));
+
+        stream.send(message("habitat.event.one", "1"));
+        stream.send(message("habitat.event.two", "2"));
+        stream.send(message("habitat.event.three", "3"));
+        let packet = rx.next().await.unwrap();
+        // The queue has room again, but this message must not jump ahead of the overflow.
+        stream.send(message("habitat.event.four", "4"));
+        assert_eq!(overflow.lock().unwrap().len(), 3);
+
+        send(&client, Some(&spool), packet).await;
+        send_waiting(&client, Some(&spool), &mut rx, &overflow).await;
+
+        assert!(overflow.lock().unwrap().is_empty());
+        assert_eq!(spooled(&spool).await,
+                   vec!["habitat.event.one",
+                        "habitat.event.two",
+                        "habitat.event.three",
+                        "habitat.event.four"]);
+    }
+
+    #[tokio::test]
+    async fn drops_the_oldest_messages_when_the_overflow_queue_is_full() {
+        let overflow = Arc::new(Overflow::default());
+        let (tx, _rx) = futures_mpsc::channel(0);
+        let stream = NatsMessageStream::from_parts(tx, Some(Arc::clone(&overflow)));
+        let dropped = EVENTS_DROPPED.get();
+
+        stream.send(message("habitat.event.queued", "0"));
+        for i in 0..=OVERFLOW_SIZE {
+            stream.send(message(&format!("habitat.event.{}", i), ""));
+        }
+
+        assert!(EVENTS_DROPPED.get() > dropped);
+        let overflow = overflow.lock().unwrap();
+        assert_eq!(overflow.len(), OVERFLOW_SIZE);
+        assert_eq!(overflow.front().unwrap().subject().to_string(),
+                   "habitat.event.1");
+    }
+
+    #[tokio::test]
+    async fn drops_messages_when_the_queue_is_full_and_spooling_is_disabled() {
+        let (tx, mut rx) = futures_mpsc::channel(0);
+        let stream = NatsMessageStream::from_parts(tx, None);
+        let dropped = EVENTS_DROPPED.get();
+
+        stream.send(message("habitat.event.one", "1"));
+        stream.send(message("habitat.event.two", "2"));
+
+        assert!(EVENTS_DROPPED.get() > dropped);
+        drop(stream);
+        assert_eq!(rx.next().await.unwrap().subject().to_string(),
+                   "habitat.event.one");
+        assert!(rx.next().await.is_none());
     }
 }
@@ -0,0 +1,221 @@
+//! A bounded, on-disk queue of NATS messages that could not be published.
+//!
+//! Each message is stored in its own file, named for its position in the queue, so the queue
+//! survives a Supervisor restart and can be replayed in order once the client reconnects. When
+//! the queue grows past its size limit, the oldest messages are dropped to make room.
+
+use super::nats_message_stream::NatsMessage;
+use std::{collections::VecDeque,
+          io,
+          path::{Path,
+                 PathBuf}};
+use tokio::fs;
+
+/// The extension of a message file that is still being written.
+const TMP_EXTENSION: &str = "tmp";
+
+#[derive(Debug)]
+struct Entry {
+    seq:  u64,
+    size: u64,
+}
+
+pub struct Spool {
+    path:     PathBuf,
+    max_size: u64,
+    size:     u64,
+    entries:  VecDeque<Entry>,
+    next_seq: u64,
+}
+
+impl Spool {
+    /// Open the spool in the directory `path`, creating it if necessary. Returns the spool and
+    /// the number of messages dropped because they no longer fit within `max_size` bytes.
+    pub async fn open<P>(path: P, max_size: u64) -> io::Result<(Self, usize)>
+        where P: Into<PathBuf>
+    {
+        let path = path.into();
+        fs::create_dir_all(&path).await?;
+
+        let mut entries = Vec::new();
+        let mut dir = fs::read_dir(&path).await?;
+        while let Some(dir_entry) = dir.next_entry().await? {
+            let seq = dir_entry.file_name()
+                               .to_str()
+                               .and_then(|name| name.parse::<u64>().ok());
+            match seq {
+                Some(seq) => {
+                    entries.push(Entry { seq,
+                                         size: dir_entry.metadata().await?.len() })
+                }
+                // Anything else is left over from a write that never finished.
+                None => fs::remove_file(dir_entry.path()).await?,
+            }
+        }
+        entries.sort_by_key(|entry| entry.seq);
+
+        let next_seq = entries.last().map_or(0, |entry| entry.seq + 1);
+        let mut spool = Spool { path,
+                                max_size,
+                                size: entries.iter().map(|entry| entry.size).sum(),
+                                entries: entries.into(),
+                                next_seq };
+        let dropped = spool.evict().await;
+        Ok((spool, dropped))
+    }
+
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Add a message to the back of the queue. Returns the number of messages dropped from the
+    /// front of the queue to make room for it.
+    pub async fn push(&mut self, message: &NatsMessage) -> io::Result<usize> {
+        let seq = self.next_seq;
+        let mut contents = message.subject().to_string().into_bytes();
+        contents.push(b'\n');
+        contents.extend_from_slice(message.payload());
+
+        let path = self.message_path(seq);
+        let tmp_path = path.with_extension(TMP_EXTENSION);
+        fs::write(&tmp_path, &contents).await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        self.next_seq += 1;
+        self.size += contents.len() as u64;
+        self.entries.push_back(Entry { seq,
+                                       size: contents.len() as u64 });
+        Ok(self.evict().await)
+    }
+
+    /// Read the message at the front of the queue.
+    pub async fn front(&self) -> io::Result<Option<NatsMessage>> {
+        let entry = match self.entries.front() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let contents = fs::read(self.message_path(entry.seq)).await?;
+        let newline = contents.iter()
+                              .position(|&b| b == b'\n')
+                              .ok_or_else(|| corrupt("missing subject"))?;
+        let subject = std::str::from_utf8(&contents[..newline]).map_err(|_| corrupt("subject"))?
+                                                               .parse()
+                                                               .map_err(|_| corrupt("subject"))?;
+        Ok(Some(NatsMessage::new(subject, contents[newline + 1..].to_vec())))
+    }
+
+    /// Remove the message at the front of the queue.
+    pub async fn pop_front(&mut self) {
+        if let Some(entry) = self.entries.pop_front() {
+            self.size -= entry.size;
+            let path = self.message_path(entry.seq);
+            if let Err(e) = fs::remove_file(&path).await {
+                error!("Failed to remove spooled event '{}', err: {}",
+                       path.display(),
+                       e);
+            }
+        }
+    }
+
+    /// Drop messages from the front of the queue until it fits within its size limit.
+    async fn evict(&mut self) -> usize {
+        let mut dropped = 0;
+        while self.size > self.max_size && !self.is_empty() {
+            self.pop_front().await;
+            dropped += 1;
+        }
+        dropped
+    }
+
+    fn message_path(&self, seq: u64) -> PathBuf { message_path(&self.path, seq) }
+}
+
+fn message_path(path: &Path, seq: u64) -> PathBuf { path.join(format!("{:020}", seq)) }
+
+fn corrupt(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+                   format!("spooled event has an invalid {}", what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn message(subject: &str, payload: &str) -> NatsMessage {
+        NatsMessage::new(subject.parse().unwrap(), payload.as_bytes().to_vec())
+    }
+
+    async fn pop(spool: &mut Spool) -> Option<(String, Vec<u8>)> {
+        let message = spool.front().await.unwrap()?;
+        spool.pop_front().await;
+        Some((message.subject().to_string(), message.payload().to_vec()))
+    }
+
+    #[tokio::test]
+    async fn replays_messages_in_order_across_restarts() {
+        let tmpdir = TempDir::new().unwrap();
+        let (mut spool, dropped) = Spool::open(tmpdir.path(), 1024).await.unwrap();
+        assert_eq!(dropped, 0);
+        assert_eq!(spool.push(&message("habitat.event.one", "1"))
+                        .await
+                        .unwrap(),
+                   0);
+        assert_eq!(spool.push(&message("habitat.event.two", "2"))
+                        .await
+                        .unwrap(),
+                   0);
+        assert_eq!(pop(&mut spool).await,
+                   Some(("habitat.event.one".to_string(), b"1".to_vec())));
+        spool.push(&message("habitat.event.three", "3"))
+             .await
+             .unwrap();
+
+        let (mut spool, dropped) = Spool::open(tmpdir.path(), 1024).await.unwrap();
+        assert_eq!(dropped, 0);
+        assert_eq!(spool.len(), 2);
+        assert_eq!(pop(&mut spool).await,
+                   Some(("habitat.event.two".to_string(), b"2".to_vec())));
+        assert_eq!(pop(&mut spool).await,
+                   Some(("habitat.event.three".to_string(), b"3".to_vec())));
+        assert_eq!(pop(&mut spool).await, None);
+    }
+
+    #[tokio::test]
+    async fn drops_the_oldest_messages_past_the_size_limit() {
+        let tmpdir = TempDir::new().unwrap();
+        // Each message is 20 bytes: a 17 byte subject, a newline, and a 2 byte payload.
+        let (mut spool, _) = Spool::open(tmpdir.path(), 50).await.unwrap();
+        assert_eq!(spool.push(&message("habitat.event.one", "01"))
+                        .await
+                        .unwrap(),
+                   0);
+        assert_eq!(spool.push(&message("habitat.event.two", "02"))
+                        .await
+                        .unwrap(),
+                   0);
+        assert_eq!(spool.push(&message("habitat.event.thr", "03"))
+                        .await
+                        .unwrap(),
+                   1);
+        assert_eq!(spool.len(), 2);
+        assert_eq!(pop(&mut spool).await.unwrap().1, b"02".to_vec());
+
+        spool.push(&message("habitat.event.fou", "04"))
+             .await
+             .unwrap();
+        let (spool, dropped) = Spool::open(tmpdir.path(), 20).await.unwrap();
+        assert_eq!(dropped, 1);
+        assert_eq!(spool.front().await.unwrap().unwrap().payload(), b"04");
+    }
+
+    #[tokio::test]
+    async fn removes_partially_written_messages() {
+        let tmpdir = TempDir::new().unwrap();
+        let partial = message_path(tmpdir.path(), 0).with_extension(TMP_EXTENSION);
+        std::fs::write(&partial, "habitat.event.one\n").unwrap();
+        let (spool, _) = Spool::open(tmpdir.path(), 1024).await.unwrap();
+        assert!(spool.is_empty());
+        assert!(!partial.exists());
+    }
+}
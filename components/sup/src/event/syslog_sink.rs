@@ -0,0 +1,134 @@
+//! An event sink that sends each event as JSON in an [RFC 5424][1] syslog message.
+//!
+//! [1]:https://tools.ietf.org/html/rfc5424
+
+use crate::event::{Error,
+                   Result};
+use chrono::{SecondsFormat,
+             Utc};
+use futures::{channel::{mpsc as futures_mpsc,
+                        mpsc::UnboundedSender},
+              stream::StreamExt};
+use habitat_common::types::EventStreamSyslogAddress;
+use std::{io,
+          net::{Ipv4Addr,
+                Ipv6Addr,
+                SocketAddr}};
+#[cfg(unix)]
+use tokio::net::UnixDatagram;
+use tokio::net::{self,
+                 UdpSocket};
+
+/// The `daemon` facility at `info` severity.
+const PRIORITY: u8 = 3 * 8 + 6;
+const APP_NAME: &str = "hab-sup";
+
+enum Socket {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+impl Socket {
+    async fn connect(address: &EventStreamSyslogAddress) -> io::Result<Socket> {
+        match address {
+            #[cfg(unix)]
+            EventStreamSyslogAddress::Unix(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(path)?;
+                Ok(Socket::Unix(socket))
+            }
+            #[cfg(not(unix))]
+            EventStreamSyslogAddress::Unix(_) => {
+                Err(io::Error::new(io::ErrorKind::Other,
+                                   "Unix sockets are not supported on this \
+                                    platform"))
+            }
+            EventStreamSyslogAddress::Udp(address) => {
+                let mut addresses = net::lookup_host(address.as_str()).await?;
+                let remote = addresses.next().ok_or_else(|| {
+                                                  io::Error::new(io::ErrorKind::AddrNotAvailable,
+                                                                 "address did not resolve")
+                                              })?;
+                let local = match remote {
+                    SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+                    SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+                };
+                let socket = UdpSocket::bind(local).await?;
+                socket.connect(remote).await?;
+                Ok(Socket::Udp(socket))
+            }
+        }
+    }
+
+    async fn send(&mut self, message: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Socket::Unix(socket) => socket.send(message).await,
+            Socket::Udp(socket) => socket.send(message).await,
+        }
+    }
+}
+
+/// A lightweight handle for the syslog sink. Events are sent by a background task. As with any
+/// syslog datagram, delivery is not guaranteed.
+pub struct SyslogSink(UnboundedSender<String>);
+
+impl SyslogSink {
+    pub async fn new(address: EventStreamSyslogAddress, hostname: &str) -> Result<SyslogSink> {
+        let mut socket =
+            Socket::connect(&address).await
+                                     .map_err(|e| Error::SyslogConnect(address.to_string(), e))?;
+        let hostname = hostname.to_string();
+
+        let (tx, mut rx) = futures_mpsc::unbounded::<String>();
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.next().await {
+                let message = format_message(&hostname, &event);
+                if let Err(e) = socket.send(message.as_bytes()).await {
+                    error!("Failed to send event to syslog at '{}', err: {}",
+                           address, e);
+                }
+            }
+        });
+
+        Ok(SyslogSink(tx))
+    }
+
+    /// Queues an event to be sent
+    pub fn send(&self, event: String) {
+        trace!("Queueing event for syslog: {}", event);
+        if let Err(e) = self.0.unbounded_send(event) {
+            error!("Failed to queue event, err: {}", e);
+        }
+    }
+}
+
+fn format_message(hostname: &str, event: &str) -> String {
+    format!("<{}>1 {} {} {} {} - - {}",
+            PRIORITY,
+            Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true),
+            hostname,
+            APP_NAME,
+            std::process::id(),
+            event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_carry_the_event_as_their_body() {
+        let message = format_message("sup.example.com", r#"{"subject":"s"}"#);
+        let parts = message.splitn(8, ' ').collect::<Vec<_>>();
+        assert_eq!(parts[0], "<30>1");
+        assert_eq!(parts[2], "sup.example.com");
+        assert_eq!(parts[3], "hab-sup");
+        assert_eq!(parts[4], std::process::id().to_string());
+        assert_eq!(parts[5], "-");
+        assert_eq!(parts[6], "-");
+        assert_eq!(parts[7], r#"{"subject":"s"}"#);
+    }
+}
@@ -4,8 +4,17 @@
 use super::EventCore;
 use crate::manager::service::{HealthCheckResult as DomainHealthCheckResult,
                               Service,
+                              ServiceSpec,
                               UpdateStrategy as DomainUpdateStrategy};
+use chrono::{SecondsFormat,
+             TimeZone,
+             Utc};
+use habitat_core::{package::PackageIdent,
+                   service::ServiceGroup,
+                   ChannelIdent};
 use prost::Message;
+use serde::{Serialize,
+            Serializer};
 
 include!(concat!(env!("OUT_DIR"), "/chef.habitat.supervisor.event.rs"));
 
@@ -34,29 +43,49 @@ impl Service {
         ServiceMetadata { package_ident: self.pkg.ident.to_string(),
                           spec_ident:    self.spec_ident.to_string(),
                           service_group: self.service_group.to_string(),
-                          update_config: self.update_config(), }
+                          update_config: update_config(self.update_strategy, &self.channel), }
     }
+}
 
-    /// `UpdateConfig` is a (currently protobuf-only) type that
-    /// encapsulates a channel and update strategy. Importantly, the
-    /// existing `UpdateStrategy::None` variant is essentially
-    /// converted to `Option::None`, whereas the other variants are
-    /// coupled with the channel from which the Supervisor pulls
-    /// updates.
-    fn update_config(&self) -> Option<UpdateConfig> {
-        let strategy = match self.update_strategy {
-            DomainUpdateStrategy::None => {
-                return None;
-            }
-            DomainUpdateStrategy::AtOnce => UpdateStrategy::AtOnce,
-            DomainUpdateStrategy::Rolling => UpdateStrategy::Rolling,
-        };
-
-        Some(UpdateConfig { strategy: strategy.into(),
-                            channel:  self.channel.to_string(), })
+impl ServiceSpec {
+    /// Create a protobuf metadata struct for events about a service
+    /// that is not running, such as one that has just been loaded.
+    pub fn to_service_metadata(&self,
+                               package_ident: &PackageIdent,
+                               organization: Option<&str>)
+                               -> ServiceMetadata {
+        let service_group = ServiceGroup::new(&self.ident.name, &self.group, organization)
+            .map(|service_group| service_group.to_string())
+            .unwrap_or_default();
+        ServiceMetadata { package_ident: package_ident.to_string(),
+                          spec_ident: self.ident.to_string(),
+                          service_group,
+                          update_config: update_config(self.update_strategy, &self.channel) }
     }
 }
 
+/// `UpdateConfig` is a (currently protobuf-only) type that
+/// encapsulates a channel and update strategy. Importantly, the
+/// existing `UpdateStrategy::None` variant is essentially
+/// converted to `Option::None`, whereas the other variants are
+/// coupled with the channel from which the Supervisor pulls
+/// updates.
+fn update_config(update_strategy: DomainUpdateStrategy,
+                 channel: &ChannelIdent)
+                 -> Option<UpdateConfig> {
+    let strategy = match update_strategy {
+        DomainUpdateStrategy::None => {
+            return None;
+        }
+        DomainUpdateStrategy::AtOnce => UpdateStrategy::AtOnce,
+        DomainUpdateStrategy::Rolling => UpdateStrategy::Rolling,
+        DomainUpdateStrategy::Canary => UpdateStrategy::Canary,
+    };
+
+    Some(UpdateConfig { strategy: strategy.into(),
+                        channel:  channel.to_string(), })
+}
+
 impl EventCore {
     /// Create a protobuf metadata struct for all event messages.
     pub(super) fn to_event_metadata(&self) -> EventMetadata {
@@ -72,7 +101,14 @@ impl EventCore {
     }
 }
 
-pub trait EventMessage: Message + Sized {
+/// An event as rendered for the sinks that do not speak protobuf.
+#[derive(Serialize)]
+struct JsonEvent<'a, T> {
+    subject: &'a str,
+    event:   &'a T,
+}
+
+pub trait EventMessage: Message + Serialize + Sized {
     /// All messages will have some top-level metadata about the
     /// Supervisor they come from. This function allows us to set it
     /// generically when we send the message out.
@@ -88,6 +124,14 @@ pub trait EventMessage: Message + Sized {
             .expect("UNEXPECTED PROST ERROR: encoded_len() was not long enough!");
         buf.to_vec()
     }
+
+    /// Convert a message to a single line of JSON, tagged with the
+    /// subject it would be published under in NATS.
+    fn to_json(&self, subject: &str) -> String {
+        serde_json::to_string(&JsonEvent { subject,
+                                           event: self }).expect("UNEXPECTED SERDE ERROR: events \
+                                                                  should always serialize to JSON")
+    }
 }
 
 macro_rules! event_msg_impl {
@@ -104,3 +148,62 @@ event_msg_impl!(ServiceStartedEvent);
 event_msg_impl!(ServiceStoppedEvent);
 event_msg_impl!(ServiceUpdateStartedEvent);
 event_msg_impl!(HealthCheckEvent);
+event_msg_impl!(ServiceCrashLoopEvent);
+event_msg_impl!(ServiceLoadedEvent);
+event_msg_impl!(ServiceUnloadedEvent);
+event_msg_impl!(ServiceUpdateFinishedEvent);
+event_msg_impl!(ServiceUpdateFailedEvent);
+event_msg_impl!(ServiceConfigAppliedEvent);
+event_msg_impl!(ServiceFileUpdatedEvent);
+event_msg_impl!(ElectionWonEvent);
+event_msg_impl!(ElectionLostEvent);
+event_msg_impl!(MemberSuspectEvent);
+event_msg_impl!(MemberConfirmedEvent);
+event_msg_impl!(MemberDepartedEvent);
+event_msg_impl!(HookFailedEvent);
+event_msg_impl!(SupervisorUpdateEvent);
+
+// The serializers below are referenced from the generated code; see
+// `build.rs`. Timestamps and durations follow the protobuf JSON
+// mapping, and enums are rendered by name rather than number.
+
+fn serialize_timestamp<S>(timestamp: &Option<prost_types::Timestamp>,
+                          serializer: S)
+                          -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    timestamp.as_ref()
+             .map(|t| {
+                 Utc.timestamp(t.seconds, t.nanos as u32)
+                    .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+             })
+             .serialize(serializer)
+}
+
+fn serialize_duration<S>(duration: &Option<prost_types::Duration>,
+                         serializer: S)
+                         -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    duration.as_ref()
+            .map(|d| format!("{}s", d.seconds as f64 + f64::from(d.nanos) / 1e9))
+            .serialize(serializer)
+}
+
+fn serialize_update_strategy<S>(strategy: &i32, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    match UpdateStrategy::from_i32(*strategy) {
+        Some(strategy) => strategy.serialize(serializer),
+        None => strategy.serialize(serializer),
+    }
+}
+
+fn serialize_health_check_result<S>(result: &i32, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    match HealthCheckResult::from_i32(*result) {
+        Some(result) => result.serialize(serializer),
+        None => result.serialize(serializer),
+    }
+}
@@ -0,0 +1,216 @@
+//! An event sink that POSTs each event as JSON to an HTTP endpoint.
+
+use crate::event::{Error,
+                   Result};
+use futures::{channel::{mpsc as futures_mpsc,
+                        mpsc::Sender},
+              stream::StreamExt};
+use prometheus::IntCounter;
+use reqwest::{header::CONTENT_TYPE,
+              Client};
+use std::{sync::Mutex,
+          time::Duration};
+use tokio::time;
+use url::Url;
+
+/// How long to wait for the endpoint to respond to a single request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How many times to try delivering an event before dropping it.
+const DELIVERY_ATTEMPTS: u32 = 5;
+/// How long to wait before retrying the first failed delivery. The wait doubles after every
+/// subsequent failure.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+/// How many events can wait to be delivered before new events are dropped.
+const QUEUE_SIZE: usize = 1024;
+
+lazy_static! {
+    static ref EVENTS_DROPPED: IntCounter =
+        register_int_counter!("hab_sup_event_webhook_dropped_total",
+                              "Number of events that were never delivered to the event \
+                               webhook").unwrap();
+}
+
+/// A lightweight handle for the webhook sink. Events are delivered one at a time in the order
+/// they are sent, so a retried event is never overtaken by a later one.
+///
+/// The queue of events waiting to be delivered is bounded. If it fills up, for example while the
+/// endpoint is down, new events are dropped.
+pub struct WebhookSink(Mutex<Sender<String>>);
+
+impl WebhookSink {
+    pub fn new(url: Url) -> Result<WebhookSink> {
+        let client = Client::builder().timeout(REQUEST_TIMEOUT)
+                                      .build()
+                                      .map_err(Error::Webhook)?;
+
+        let (tx, mut rx) = futures_mpsc::channel::<String>(QUEUE_SIZE);
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.next().await {
+                deliver(&client, &url, event, RETRY_DELAY).await;
+            }
+        });
+
+        Ok(WebhookSink::from_parts(tx))
+    }
+
+    fn from_parts(sender: Sender<String>) -> Self { WebhookSink(Mutex::new(sender)) }
+
+    /// Queues an event to be delivered
+    pub fn send(&self, event: String) {
+        trace!("Queueing event for webhook: {}", event);
+        let result = self.0
+                         .lock()
+                         .expect("Webhook event sender lock poisoned")
+                         .try_send(event);
+        if let Err(e) = result {
+            if e.is_full() {
+                warn!("Dropping event because the webhook queue is full");
+                EVENTS_DROPPED.inc();
+            } else {
+                error!("Failed to queue event, err: {}", e);
+            }
+        }
+    }
+}
+
+/// Deliver an event, retrying failures after `retry_delay` and then after twice as long as the
+/// previous wait, until it is delivered or there have been `DELIVERY_ATTEMPTS` attempts.
+async fn deliver(client: &Client, url: &Url, event: String, retry_delay: Duration) {
+    let mut delay = retry_delay;
+    for attempt in 1..=DELIVERY_ATTEMPTS {
+        let response = client.post(url.clone())
+                             .header(CONTENT_TYPE, "application/json")
+                             .body(event.clone())
+                             .send()
+                             .await
+                             .and_then(reqwest::Response::error_for_status);
+        match response {
+            Ok(_) => return,
+            Err(e) if attempt < DELIVERY_ATTEMPTS => {
+                debug!("Failed to deliver event to '{}' (attempt {} of {}), retrying in {:?}, \
+                        err: {}",
+                       url, attempt, DELIVERY_ATTEMPTS, delay, e);
+                time::delay_for(delay).await;
+                delay *= 2;
+            }
+            Err(e) => {
+                error!("Failed to deliver event to '{}' after {} attempts, dropping it, err: {}",
+                       url, DELIVERY_ATTEMPTS, e);
+                EVENTS_DROPPED.inc();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::{Read,
+                   Write},
+              net::TcpListener,
+              sync::mpsc,
+              thread};
+
+    /// Answer one request with each of `statuses` in turn, sending the body of each request it
+    /// receives to the returned channel.
+    fn serve(statuses: Vec<&'static str>) -> (Url, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/events", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut buf = [0; 1024];
+                let body = loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.push_str(&String::from_utf8_lossy(&buf[..n]));
+                    if let Some(end) = request.find("\r\n\r\n") {
+                        if request.len() >= end + 4 + content_length(&request[..end]) {
+                            break request[end + 4..].to_string();
+                        }
+                    }
+                    assert!(n > 0, "connection closed before the request was complete");
+                };
+                // Record the request before replying so it is seen by the time the reply is.
+                tx.send(body).unwrap();
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: \
+                                        close\r\n\r\n",
+                                       status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url.parse().unwrap(), rx)
+    }
+
+    fn content_length(head: &str) -> usize {
+        head.lines()
+            .filter_map(|line| {
+                let mut header = line.splitn(2, ':');
+                match (header.next(), header.next()) {
+                    (Some(name), Some(value)) if name.eq_ignore_ascii_case("content-length") => {
+                        value.trim().parse().ok()
+                    }
+                    _ => None,
+                }
+            })
+            .next()
+            .unwrap_or(0)
+    }
+
+    fn client() -> Client { Client::builder().timeout(REQUEST_TIMEOUT).build().unwrap() }
+
+    #[tokio::test]
+    async fn sends_events_to_the_endpoint_in_order() {
+        let (url, requests) = serve(vec!["200 OK", "204 No Content"]);
+        let sink = WebhookSink::new(url).unwrap();
+
+        sink.send(r#"{"event":1}"#.to_string());
+        sink.send(r#"{"event":2}"#.to_string());
+
+        let received = tokio::task::spawn_blocking(move || {
+                           requests.iter().take(2).collect::<Vec<_>>()
+                       }).await
+                         .unwrap();
+        assert_eq!(received, vec![r#"{"event":1}"#, r#"{"event":2}"#]);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_deliveries() {
+        let (url, requests) = serve(vec!["503 Service Unavailable",
+                                         "500 Internal Server Error",
+                                         "200 OK"]);
+        deliver(&client(), &url, "{}".to_string(), Duration::from_millis(1)).await;
+
+        assert_eq!(requests.try_iter().collect::<Vec<_>>(),
+                   vec!["{}", "{}", "{}"]);
+    }
+
+    #[tokio::test]
+    async fn drops_events_which_fail_every_attempt() {
+        let (url, requests) = serve(vec!["503 Service Unavailable"; DELIVERY_ATTEMPTS as usize]);
+        let dropped = EVENTS_DROPPED.get();
+
+        deliver(&client(), &url, "{}".to_string(), Duration::from_millis(1)).await;
+
+        assert_eq!(requests.try_iter().count(), DELIVERY_ATTEMPTS as usize);
+        assert!(EVENTS_DROPPED.get() > dropped);
+    }
+
+    #[tokio::test]
+    async fn drops_events_when_the_queue_is_full() {
+        // A channel with no buffer holds a single event for its one sender.
+        let (tx, mut rx) = futures_mpsc::channel(0);
+        let sink = WebhookSink::from_parts(tx);
+        let dropped = EVENTS_DROPPED.get();
+
+        sink.send("1".to_string());
+        sink.send("2".to_string());
+
+        assert!(EVENTS_DROPPED.get() > dropped);
+        drop(sink);
+        assert_eq!(rx.next().await.unwrap(), "1");
+        assert!(rx.next().await.is_none());
+    }
+}
@@ -1,6 +1,11 @@
-use crate::manager::{self,
-                     service::{HealthCheckHook,
-                               HealthCheckResult}};
+use crate::{ctl_gateway::{server::{self as ctl_server,
+                                   MgrSender},
+                          users::{Identity,
+                                  Role,
+                                  Users}},
+            manager::{self,
+                      service::{HealthCheckHook,
+                                HealthCheckResult}}};
 use actix_rt::System;
 use actix_web::{dev::{Body,
                       Service,
@@ -9,23 +14,41 @@ use actix_web::{dev::{Body,
                 http::{self,
                        StatusCode},
                 web::{self,
+                      Bytes,
                       Data,
-                      Path},
+                      Path,
+                      Query},
                 App,
                 Error,
+                HttpMessage,
+                HttpRequest,
                 HttpResponse,
                 HttpServer,
                 Scope};
-use futures::future::{ok,
-                      Either,
-                      Future};
+use futures::{future::{ok,
+                       Either,
+                       Future},
+              stream::StreamExt};
 use habitat_common::{self,
                      templating::hooks,
                      types::HttpListenAddr,
                      FeatureFlag};
 use habitat_core::{crypto,
                    env as henv,
+                   package::PackageIdent,
                    service::ServiceGroup};
+use habitat_sup_protocol::{self as protocol,
+                           codec::SrvMessage,
+                           ctl::ServiceBindList,
+                           net::{self,
+                                 ErrCode,
+                                 NetErr,
+                                 NetResult},
+                           types::{BindingMode,
+                                   HealthCheckInterval,
+                                   Topology,
+                                   UpdateCondition,
+                                   UpdateStrategy}};
 use manager::sync::GatewayState;
 
 use prometheus::{self,
@@ -41,6 +64,7 @@ use std::{self,
           cell::Cell,
           fs::File,
           io::Read,
+          str::FromStr,
           sync::{Arc,
                  Condvar,
                  Mutex},
@@ -53,6 +77,9 @@ pub const HTTP_THREAD_COUNT: usize = 2;
 /// Default listening port for the HTTPGateway listener.
 pub const DEFAULT_PORT: u16 = 9631;
 
+/// The name recorded for commands sent by clients presenting the gateway's authentication token.
+const TOKEN_USER: &str = "<HAB_SUP_GATEWAY_AUTH_TOKEN>";
+
 lazy_static! {
     static ref HTTP_GATEWAY_REQUESTS: CounterVec =
         register_counter_vec!("hab_sup_http_gateway_requests_total",
@@ -68,7 +95,8 @@ habitat_core::env_config!(
     /// This represents an environment variable that holds an authentication token for the supervisor's
     /// HTTP gateway. If the environment variable is present, then its value is the auth token and all
     /// of the HTTP endpoints will require its presence. If it's not present, then everything continues
-    /// to work unauthenticated.
+    /// to work unauthenticated. The token only grants read access: the endpoints which change the
+    /// Supervisor's services always require the credentials of a CtlGateway user.
     #[derive(Clone, Debug)]
     pub GatewayAuthenticationToken,
     Option<String>,
@@ -85,6 +113,97 @@ struct HealthCheckBody {
     stderr: String,
 }
 
+/// The result of a command sent to the Supervisor: everything it printed while running the
+/// command, and the errors it returned, if any.
+#[derive(Default, Serialize)]
+struct CommandBody {
+    output: String,
+    errors: Vec<String>,
+}
+
+/// A request to load a service. Settings which are not given take the same defaults as they do
+/// for `hab svc load`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LoadBody {
+    ident:                 String,
+    group:                 Option<String>,
+    bldr_url:              Option<String>,
+    channel:               Option<String>,
+    topology:              Option<String>,
+    update_strategy:       Option<String>,
+    update_condition:      Option<String>,
+    max_unavailable:       Option<String>,
+    canaries:              Option<u32>,
+    binds:                 Option<Vec<String>>,
+    binding_mode:          Option<String>,
+    health_check_interval: Option<u64>,
+    health_check:          Option<String>,
+    shutdown_timeout:      Option<u32>,
+    max_restarts:          Option<u32>,
+    restart_window:        Option<u32>,
+    restart_backoff:       Option<u32>,
+    max_restart_backoff:   Option<u32>,
+    force:                 bool,
+}
+
+impl LoadBody {
+    fn into_msg(self) -> NetResult<protocol::ctl::SvcLoad> {
+        let ident = PackageIdent::from_str(&self.ident).map_err(|e| {
+                                                           net::err(ErrCode::InvalidPayload,
+                                                                    e.to_string())
+                                                       })?;
+        let mut msg = protocol::ctl::SvcLoad::default();
+        msg.ident = Some(ident.into());
+        msg.group = self.group;
+        msg.bldr_url = self.bldr_url;
+        msg.bldr_channel = self.channel;
+        msg.topology = parse_optional::<Topology>(self.topology)?.map(|v| v as i32);
+        msg.update_strategy =
+            parse_optional::<UpdateStrategy>(self.update_strategy)?.map(|v| v as i32);
+        msg.update_condition =
+            parse_optional::<UpdateCondition>(self.update_condition)?.map(|v| v as i32);
+        msg.max_unavailable = self.max_unavailable;
+        msg.canaries = self.canaries;
+        if let Some(binds) = self.binds {
+            let mut list = ServiceBindList::default();
+            for bind in binds {
+                list.binds.push(bind.parse()?);
+            }
+            msg.binds = Some(list);
+        }
+        msg.binding_mode = parse_optional::<BindingMode>(self.binding_mode)?.map(|v| v as i32);
+        msg.health_check_interval = self.health_check_interval
+                                        .map(|seconds| HealthCheckInterval { seconds });
+        msg.health_check = self.health_check;
+        msg.shutdown_timeout = self.shutdown_timeout;
+        msg.max_restarts = self.max_restarts;
+        msg.restart_window = self.restart_window;
+        msg.restart_backoff = self.restart_backoff;
+        msg.max_restart_backoff = self.max_restart_backoff;
+        if self.force {
+            msg.force = Some(true);
+        }
+        Ok(msg)
+    }
+}
+
+fn parse_optional<T>(value: Option<String>) -> NetResult<Option<T>>
+    where T: FromStr<Err = NetErr>
+{
+    value.map(|v| v.parse()).transpose()
+}
+
+#[derive(Deserialize)]
+struct UnloadQuery {
+    shutdown_timeout: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct VersionQuery {
+    version: u64,
+}
+
 impl Into<StatusCode> for HealthCheckResult {
     fn into(self) -> StatusCode {
         match self {
@@ -98,21 +217,31 @@ impl Into<StatusCode> for HealthCheckResult {
 struct AppState {
     gateway_state:        Arc<GatewayState>,
     authentication_token: Option<String>,
+    users:                Users,
+    /// Whether the gateway is served over TLS, which CtlGateway users' secrets require
+    tls:                  bool,
     timer:                Cell<Option<HistogramTimer>>,
     feature_flags:        FeatureFlag,
+    mgr_sender:           MgrSender,
 }
 
 impl AppState {
     fn new(gs: Arc<GatewayState>,
            authentication_token: GatewayAuthenticationToken,
-           feature_flags: FeatureFlag)
+           users: Users,
+           tls: bool,
+           feature_flags: FeatureFlag,
+           mgr_sender: MgrSender)
            -> Self {
         AppState { gateway_state: gs,
                    // We'll unwrap to the inner type, since the
                    // GatewayAuthenticationToken type has done its job by this point.
                    authentication_token: authentication_token.0,
+                   users,
+                   tls,
                    timer: Cell::new(None),
-                   feature_flags }
+                   feature_flags,
+                   mgr_sender }
     }
 }
 
@@ -123,41 +252,61 @@ fn authentication_middleware<S>(req: ServiceRequest,
                                 -> impl Future<Output = Result<ServiceResponse<Body>, Error>>
     where S: Service<Request = ServiceRequest, Response = ServiceResponse<Body>, Error = Error>
 {
-    let current_token = &req.app_data::<AppState>()
-                            .expect("app data")
-                            .authentication_token;
-    let current_token = if let Some(t) = current_token {
-        t
-    } else {
-        debug!("No authentication token present. HTTP gateway starting in unauthenticated mode.");
-        return Either::Left(srv.call(req));
-    };
-
-    // From this point forward, we know that we have an
-    // authentication token in the state. Therefore, anything
-    // short of a fully formed Authorization header (yes,
-    // Authorization; HTTP is fun, kids!) containing a Bearer
-    // token that matches the value we have in our state, results
-    // in an Unauthorized response.
-    let hdr = match req.headers()
-                       .get(http::header::AUTHORIZATION)
-                       .ok_or("header missing")
-                       .and_then(|hv| hv.to_str().or(Err("can't convert to str")))
-    {
-        Ok(h) => h,
-        Err(e) => {
-            debug!("Error reading required Authorization header: {:?}.", e);
-            return Either::Right(ok(req.into_response(HttpResponse::Unauthorized().finish())));
+    let state = req.app_data::<AppState>().expect("app data");
+    let identity = req.headers()
+                      .get(http::header::AUTHORIZATION)
+                      .and_then(|hv| hv.to_str().ok())
+                      .and_then(|hdr| authenticate(&state, hdr));
+
+    // Clients presenting the authentication token or the credentials of a CtlGateway user are
+    // remembered, so the endpoints which change the Supervisor's services can authorize them.
+    // Without an authentication token in the state, every other client is let through as well.
+    // Otherwise, anything short of a fully formed Authorization header (yes, Authorization; HTTP
+    // is fun, kids!) containing valid credentials results in an Unauthorized response.
+    match (identity, &state.authentication_token) {
+        (Some(identity), _) => {
+            req.extensions_mut().insert(identity);
+            Either::Left(srv.call(req))
         }
-    };
-
-    let hdr_components: Vec<&str> = hdr.split_whitespace().collect();
-
-    match hdr_components.as_slice() {
-        ["Bearer", incoming_token] if crypto::secure_eq(current_token, incoming_token) => {
+        (None, None) => {
+            debug!("No authentication token present. HTTP gateway starting in unauthenticated \
+                    mode.");
             Either::Left(srv.call(req))
         }
-        _ => Either::Right(ok(req.into_response(HttpResponse::Unauthorized().finish()))),
+        (None, Some(_)) => {
+            debug!("Missing or invalid Authorization header");
+            Either::Right(ok(req.into_response(HttpResponse::Unauthorized().finish())))
+        }
+    }
+}
+
+/// The identity of a client sending the Authorization header `hdr`. Clients present either the
+/// authentication token as a `Bearer` token, which only grants read access, or the name and
+/// secret of a CtlGateway user with `Basic` authentication, which gives them the user's role.
+/// Users' secrets are only accepted over TLS, so that they are never sent in plain text.
+fn authenticate(state: &AppState, hdr: &str) -> Option<Identity> {
+    match hdr.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["Bearer", incoming_token] => {
+            state.authentication_token
+                 .as_ref()
+                 .filter(|current_token| crypto::secure_eq(current_token, incoming_token))
+                 .map(|_| {
+                     Identity { name: TOKEN_USER.to_string(),
+                                role: Role::ReadOnly, }
+                 })
+        }
+        ["Basic", _] if !state.tls => {
+            debug!("Refusing Basic credentials sent without TLS");
+            None
+        }
+        ["Basic", credentials] => {
+            let credentials = String::from_utf8(base64::decode(credentials).ok()?).ok()?;
+            let mut credentials = credentials.splitn(2, ':');
+            let name = credentials.next()?;
+            let secret = credentials.next()?;
+            state.users.authenticate(name, secret)
+        }
+        _ => None,
     }
 }
 
@@ -223,7 +372,9 @@ impl Server {
                tls_config: Option<ServerConfig>,
                gateway_state: Arc<GatewayState>,
                authentication_token: GatewayAuthenticationToken,
+               users: Users,
                feature_flags: FeatureFlag,
+               mgr_sender: MgrSender,
                control: Arc<(Mutex<ServerStartup>, Condvar)>) {
         thread::spawn(move || {
             let &(ref lock, ref cvar) = &*control;
@@ -237,11 +388,15 @@ impl Server {
                 Err(_) => HTTP_THREAD_COUNT,
             };
 
+            let tls = tls_config.is_some();
             let mut server = HttpServer::new(move || {
                                  let app_state =
                                      Data::new(AppState::new(gateway_state.clone(),
                                                              authentication_token.clone(),
-                                                             feature_flags));
+                                                             users.clone(),
+                                                             tls,
+                                                             feature_flags,
+                                                             mgr_sender.clone()));
                                  App::new().app_data(app_state)
                                            .wrap_fn(authentication_middleware)
                                            .wrap_fn(metrics_middleware)
@@ -288,15 +443,35 @@ fn services_routes() -> Scope {
                                   web::get().to(config_with_org_gsr))
                            .route("/{svc}/{group}/{org}/health",
                                   web::get().to(health_with_org_gsr))
+                           .route("/{svc}/{group}/config",
+                                  web::post().to(set_config_without_org_gsr_msr))
+                           // This must be routed before `/{svc}/{group}/{org}/config`, which
+                           // would otherwise match a file named "config".
+                           .route("/{svc}/{group}/files/{filename}",
+                                  web::post().to(put_file_without_org_gsr_msr))
+                           .route("/{svc}/{group}/{org}/config",
+                                  web::post().to(set_config_with_org_gsr_msr))
+                           .route("/{svc}/{group}/{org}/files/{filename}",
+                                  web::post().to(put_file_with_org_gsr_msr))
+}
+
+fn specs_routes() -> Scope {
+    web::scope("/specs").route("", web::post().to(load_gsr_msr))
+                        .route("/{origin}/{name}", web::delete().to(unload_gsr_msr))
+                        .route("/{origin}/{name}/start", web::post().to(start_gsr_msr))
+                        .route("/{origin}/{name}/stop", web::post().to(stop_gsr_msr))
 }
 
 fn routes() -> Scope {
     web::scope("/").route("", web::get().to(doc))
                    .service(services_routes())
+                   .service(specs_routes())
                    .service(web::resource("/butterfly").route(web::get().to(butterfly_gsr))
                                                        .wrap_fn(redact_http_middleware))
                    .service(web::resource("/census").route(web::get().to(census_gsr))
                                                     .wrap_fn(redact_http_middleware))
+                   .service(web::resource("/events").route(web::get().to(events_gsw))
+                                                    .wrap_fn(redact_http_middleware))
                    .route("/metrics", web::get().to(metrics))
 }
 
@@ -323,6 +498,18 @@ fn census_gsr(state: Data<AppState>) -> HttpResponse {
     json_response(data)
 }
 
+/// Stream changes to the census and to services as server-sent events.
+///
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (write)
+#[allow(clippy::needless_pass_by_value)]
+fn events_gsw(state: Data<AppState>) -> HttpResponse {
+    let changes = state.gateway_state.lock_gsw().subscribe();
+    let events = changes.map(|change| Ok::<_, Error>(Bytes::from(change.to_event())));
+    HttpResponse::Ok().content_type("text/event-stream")
+                      .streaming(events)
+}
+
 /// # Locking (see locking.md)
 /// * `GatewayState::inner` (read)
 #[allow(clippy::needless_pass_by_value)]
@@ -471,8 +658,262 @@ fn metrics() -> HttpResponse {
 }
 
 fn doc() -> HttpResponse { HttpResponse::Ok().content_type("text/html").body(APIDOCS) }
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn load_gsr_msr(req: HttpRequest,
+                      body: web::Json<LoadBody>,
+                      state: Data<AppState>)
+                      -> HttpResponse {
+    match body.into_inner().into_msg() {
+        Ok(msg) => dispatch_gsr_msr(&req, &state, msg).await,
+        Err(err) => error_response(err),
+    }
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn unload_gsr_msr(req: HttpRequest,
+                        path: Path<(String, String)>,
+                        query: Query<UnloadQuery>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    let mut msg = protocol::ctl::SvcUnload::default();
+    msg.ident = Some(ident_from_path(path).into());
+    msg.timeout_in_seconds = query.shutdown_timeout;
+    dispatch_gsr_msr(&req, &state, msg).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn start_gsr_msr(req: HttpRequest,
+                       path: Path<(String, String)>,
+                       state: Data<AppState>)
+                       -> HttpResponse {
+    let mut msg = protocol::ctl::SvcStart::default();
+    msg.ident = Some(ident_from_path(path).into());
+    dispatch_gsr_msr(&req, &state, msg).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn stop_gsr_msr(req: HttpRequest,
+                      path: Path<(String, String)>,
+                      query: Query<UnloadQuery>,
+                      state: Data<AppState>)
+                      -> HttpResponse {
+    let mut msg = protocol::ctl::SvcStop::default();
+    msg.ident = Some(ident_from_path(path).into());
+    msg.timeout_in_seconds = query.shutdown_timeout;
+    dispatch_gsr_msr(&req, &state, msg).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn set_config_with_org_gsr_msr(req: HttpRequest,
+                                     path: Path<(String, String, String)>,
+                                     query: Query<VersionQuery>,
+                                     cfg: Bytes,
+                                     state: Data<AppState>)
+                                     -> HttpResponse {
+    let (svc, group, org) = path.into_inner();
+    set_config_gsr_msr(&req, svc, group, Some(&org), query.version, cfg, &state).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn set_config_without_org_gsr_msr(req: HttpRequest,
+                                        path: Path<(String, String)>,
+                                        query: Query<VersionQuery>,
+                                        cfg: Bytes,
+                                        state: Data<AppState>)
+                                        -> HttpResponse {
+    let (svc, group) = path.into_inner();
+    set_config_gsr_msr(&req, svc, group, None, query.version, cfg, &state).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+async fn set_config_gsr_msr(req: &HttpRequest,
+                            svc: String,
+                            group: String,
+                            org: Option<&str>,
+                            version: u64,
+                            cfg: Bytes,
+                            state: &AppState)
+                            -> HttpResponse {
+    let service_group = match ServiceGroup::new(svc, group, org) {
+        Ok(sg) => sg,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    let mut msg = protocol::ctl::SvcSetCfg::default();
+    msg.service_group = Some(service_group.into());
+    msg.cfg = Some(cfg.to_vec());
+    msg.version = Some(version);
+    dispatch_gsr_msr(req, state, msg).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn put_file_with_org_gsr_msr(req: HttpRequest,
+                                   path: Path<(String, String, String, String)>,
+                                   query: Query<VersionQuery>,
+                                   content: Bytes,
+                                   state: Data<AppState>)
+                                   -> HttpResponse {
+    let (svc, group, org, filename) = path.into_inner();
+    put_file_gsr_msr(&req,
+                     svc,
+                     group,
+                     Some(&org),
+                     filename,
+                     query.version,
+                     content,
+                     &state).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn put_file_without_org_gsr_msr(req: HttpRequest,
+                                      path: Path<(String, String, String)>,
+                                      query: Query<VersionQuery>,
+                                      content: Bytes,
+                                      state: Data<AppState>)
+                                      -> HttpResponse {
+    let (svc, group, filename) = path.into_inner();
+    put_file_gsr_msr(&req,
+                     svc,
+                     group,
+                     None,
+                     filename,
+                     query.version,
+                     content,
+                     &state).await
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+async fn put_file_gsr_msr(req: &HttpRequest,
+                          svc: String,
+                          group: String,
+                          org: Option<&str>,
+                          filename: String,
+                          version: u64,
+                          content: Bytes,
+                          state: &AppState)
+                          -> HttpResponse {
+    let service_group = match ServiceGroup::new(svc, group, org) {
+        Ok(sg) => sg,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    let mut msg = protocol::ctl::SvcFilePut::default();
+    msg.service_group = Some(service_group.into());
+    msg.filename = Some(filename);
+    msg.content = Some(content.to_vec());
+    msg.version = Some(version);
+    dispatch_gsr_msr(req, state, msg).await
+}
 // End route handlers
 
+/// Send a command to the Manager on behalf of the client which sent `req`, just as the
+/// CtlGateway does, and respond with its output and errors.
+///
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+/// * `ManagerServices::inner` (read)
+async fn dispatch_gsr_msr<T>(req: &HttpRequest, state: &AppState, msg: T) -> HttpResponse
+    where T: Into<SrvMessage>
+{
+    // Without credentials, anyone who can reach the gateway could change the Supervisor's
+    // services, so commands are only accepted from clients the authentication middleware
+    // recognized.
+    let identity = match req.extensions().get::<Identity>() {
+        Some(identity) => identity.clone(),
+        None => {
+            return error_response(net::err(ErrCode::Unauthorized,
+                                           "The credentials of a CtlGateway user, sent over \
+                                            TLS, are required to change services over HTTP"));
+        }
+    };
+    let msg = msg.into();
+    if !identity.is_authorized(msg.message_id()) {
+        return error_response(net::err(ErrCode::Unauthorized,
+                                       format!("{} is not authorized to send {}",
+                                               identity.name,
+                                               msg.message_id())));
+    }
+    let replies = match ctl_server::dispatch_gsr_msr(&state.mgr_sender, msg, identity).await {
+        Ok(replies) => replies,
+        Err(e) => {
+            error!("Failed to dispatch HTTP gateway command, err: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let mut status = StatusCode::OK;
+    let mut body = CommandBody::default();
+    for reply in replies {
+        match reply.message_id() {
+            "ConsoleLine" => {
+                if let Ok(line) = reply.parse::<protocol::ctl::ConsoleLine>() {
+                    body.output.push_str(&line.line);
+                }
+            }
+            "NetErr" => {
+                if let Ok(err) = reply.parse::<NetErr>() {
+                    status = error_status(&err);
+                    body.errors.push(err.msg);
+                }
+            }
+            _ => (),
+        }
+    }
+    HttpResponse::build(status).json(&body)
+}
+
+fn error_response(err: NetErr) -> HttpResponse {
+    let body = CommandBody { output: String::new(),
+                             errors: vec![err.msg.clone()], };
+    HttpResponse::build(error_status(&err)).json(&body)
+}
+
+fn error_status(err: &NetErr) -> StatusCode {
+    match ErrCode::from_i32(err.code) {
+        Some(ErrCode::NotFound) => StatusCode::NOT_FOUND,
+        Some(ErrCode::Conflict) => StatusCode::CONFLICT,
+        Some(ErrCode::Unauthorized) => StatusCode::FORBIDDEN,
+        Some(ErrCode::NotSupported) => StatusCode::NOT_IMPLEMENTED,
+        Some(ErrCode::BadPayload) | Some(ErrCode::InvalidPayload) | Some(ErrCode::UpdateClient) => {
+            StatusCode::BAD_REQUEST
+        }
+        Some(ErrCode::EntityTooLarge) => StatusCode::PAYLOAD_TOO_LARGE,
+        Some(ErrCode::Internal) | Some(ErrCode::Io) | None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn ident_from_path(path: Path<(String, String)>) -> PackageIdent {
+    let (origin, name) = path.into_inner();
+    PackageIdent::new(origin, name, None, None)
+}
+
 fn service_from_services(service_group: &ServiceGroup, services_json: &str) -> Option<Json> {
     match serde_json::from_str(services_json) {
         Ok(Json::Array(services)) => {
@@ -485,11 +926,29 @@ fn service_from_services(service_group: &ServiceGroup, services_json: &str) -> O
 
 #[cfg(test)]
 mod tests {
-    use crate::test_helpers::*;
+    use super::{authentication_middleware,
+                error_status,
+                routes,
+                AppState,
+                GatewayAuthenticationToken,
+                LoadBody,
+                Topology};
+    use crate::{ctl_gateway::server::MgrReceiver,
+                manager::sync::GatewayState,
+                test_helpers::*};
+    use actix_web::{http::{header,
+                           StatusCode},
+                    test,
+                    web::Data,
+                    App};
+    use futures::{channel::mpsc,
+                  StreamExt};
     use habitat_butterfly::{member::Member,
                             server::{Server,
                                      ServerProxy,
                                      Suitability}};
+    use habitat_common::FeatureFlag;
+    use habitat_sup_protocol::net;
     use serde_json;
     use std::{fs::File,
               io::Read,
@@ -497,7 +956,67 @@ mod tests {
                     Ipv4Addr,
                     SocketAddr},
               path::PathBuf,
-              sync::Mutex};
+              sync::{Arc,
+                     Mutex}};
+
+    const TOKEN: &str = "gateway-token";
+
+    const USERS: &str = r#"
+[[user]]
+name = "alice"
+secret = "alice-secret"
+role = "operator"
+
+[[user]]
+name = "bob"
+secret = "bob-secret"
+role = "admin"
+"#;
+
+    fn app_state(token: Option<&str>, tls: bool) -> (Data<AppState>, MgrReceiver) {
+        let (mgr_sender, mgr_receiver) = mpsc::unbounded();
+        let state = AppState::new(Arc::new(GatewayState::default()),
+                                  GatewayAuthenticationToken(token.map(str::to_string)),
+                                  toml::from_str(USERS).unwrap(),
+                                  tls,
+                                  FeatureFlag::empty(),
+                                  mgr_sender);
+        (Data::new(state), mgr_receiver)
+    }
+
+    fn basic(name: &str, secret: &str) -> String {
+        format!("Basic {}", base64::encode(format!("{}:{}", name, secret)))
+    }
+
+    /// POST a request to load `core/redis` to a gateway with the given state.
+    async fn load(state: &Data<AppState>, authorization: Option<&str>) -> StatusCode {
+        let mut app = test::init_service(App::new().app_data(state.clone())
+                                                   .wrap_fn(authentication_middleware)
+                                                   .service(routes())).await;
+        let mut req = test::TestRequest::post().uri("/specs")
+                                               .header(header::CONTENT_TYPE, "application/json")
+                                               .set_payload(r#"{"ident": "core/redis"}"#);
+        if let Some(authorization) = authorization {
+            req = req.header(header::AUTHORIZATION, authorization);
+        }
+        test::call_service(&mut app, req.to_request()).await
+                                                      .status()
+    }
+
+    /// Load `core/redis` while standing in for the Manager, which completes the command.
+    async fn load_and_complete(state: &Data<AppState>,
+                               mgr_receiver: &mut MgrReceiver,
+                               authorization: &str)
+                               -> StatusCode {
+        let manager = async {
+            let mut cmd = mgr_receiver.next()
+                                      .await
+                                      .expect("No command was dispatched");
+            cmd.req.reply_complete(net::ok());
+        };
+        let (status, ()) = futures::join!(load(state, Some(authorization)), manager);
+        status
+    }
 
     fn validate_sample_file_against_schema(name: &str, schema: &str) {
         let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
@@ -600,6 +1119,85 @@ mod tests {
                                             "http_gateway_services_schema.json");
     }
 
+    #[test]
+    fn load_body_converts_to_svc_load() {
+        let body: LoadBody = serde_json::from_str(r#"{"ident": "core/redis/5.0.7",
+                                                      "topology": "leader",
+                                                      "binds": ["backend:postgres.default"],
+                                                      "health_check_interval": 10,
+                                                      "force": true}"#).unwrap();
+        let msg = body.into_msg().unwrap();
+        assert_eq!(msg.ident.unwrap().to_string(), "core/redis/5.0.7");
+        assert_eq!(msg.topology, Some(Topology::Leader as i32));
+        assert_eq!(msg.binds.unwrap().binds.len(), 1);
+        assert_eq!(msg.health_check_interval.unwrap().seconds, 10);
+        assert_eq!(msg.force, Some(true));
+        assert_eq!(msg.group, None);
+
+        let body: LoadBody =
+            serde_json::from_str(r#"{"ident": "core/redis", "topology": "swarm"}"#).unwrap();
+        assert_eq!(error_status(&body.into_msg().unwrap_err()),
+                   StatusCode::BAD_REQUEST);
+        assert!(serde_json::from_str::<LoadBody>(r#"{"ident": "core/redis", "colour": "red"}"#)
+                .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn load_rejects_missing_or_wrong_credentials() {
+        let (state, mut mgr_receiver) = app_state(Some(TOKEN), true);
+        assert_eq!(load(&state, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(load(&state, Some("Bearer wrong-token")).await,
+                   StatusCode::UNAUTHORIZED);
+        assert_eq!(load(&state, Some(TOKEN)).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(load(&state, Some(&basic("bob", "alice-secret"))).await,
+                   StatusCode::UNAUTHORIZED);
+        assert!(mgr_receiver.try_next().is_err());
+    }
+
+    #[actix_rt::test]
+    async fn load_refuses_user_credentials_without_tls() {
+        let (state, mut mgr_receiver) = app_state(Some(TOKEN), false);
+        assert_eq!(load(&state, Some(&basic("bob", "bob-secret"))).await,
+                   StatusCode::UNAUTHORIZED);
+
+        let (state, mut no_token_mgr_receiver) = app_state(None, false);
+        assert_eq!(load(&state, Some(&basic("bob", "bob-secret"))).await,
+                   StatusCode::FORBIDDEN);
+        assert!(mgr_receiver.try_next().is_err());
+        assert!(no_token_mgr_receiver.try_next().is_err());
+    }
+
+    #[actix_rt::test]
+    async fn load_is_forbidden_without_credentials_when_no_token_is_set() {
+        let (state, mut mgr_receiver) = app_state(None, true);
+        assert_eq!(load(&state, None).await, StatusCode::FORBIDDEN);
+        assert_eq!(load(&state, Some("Bearer any-token")).await,
+                   StatusCode::FORBIDDEN);
+        assert!(mgr_receiver.try_next().is_err());
+    }
+
+    #[actix_rt::test]
+    async fn load_is_forbidden_for_the_token_and_users_without_admin_rights() {
+        let (state, mut mgr_receiver) = app_state(Some(TOKEN), true);
+        assert_eq!(load(&state, Some(&format!("Bearer {}", TOKEN))).await,
+                   StatusCode::FORBIDDEN);
+        assert_eq!(load(&state, Some(&basic("alice", "alice-secret"))).await,
+                   StatusCode::FORBIDDEN);
+        assert!(mgr_receiver.try_next().is_err());
+    }
+
+    #[actix_rt::test]
+    async fn load_is_dispatched_for_admin_users() {
+        let (state, mut mgr_receiver) = app_state(Some(TOKEN), true);
+        assert_eq!(load_and_complete(&state, &mut mgr_receiver, &basic("bob", "bob-secret")).await,
+                   StatusCode::OK);
+
+        // Users are authorized without a token, too.
+        let (state, mut mgr_receiver) = app_state(None, true);
+        assert_eq!(load_and_complete(&state, &mut mgr_receiver, &basic("bob", "bob-secret")).await,
+                   StatusCode::OK);
+    }
+
     #[test]
     fn trivial_services_failure() {
         let failure = validate_string(r#"[{"lulz": true}]"#, "http_gateway_services_schema.json");
@@ -17,7 +17,9 @@ use crate::sup::{cli::cli,
                  command,
                  error::{Error,
                          Result},
-                 event::EventStreamConfig,
+                 event::{EventSinkConfig,
+                         EventStreamConfig,
+                         NatsConfig},
                  logger,
                  manager::{Manager,
                            ManagerConfig,
@@ -26,6 +28,7 @@ use crate::sup::{cli::cli,
                  util};
 use configopt::ConfigOpt;
 use hab::cli::hab::sup::SupRun;
+use habitat_butterfly::ring_keys::RingKeys;
 use habitat_common::{command::package::install::InstallSource,
                      liveliness_checker,
                      output::{self,
@@ -249,7 +252,7 @@ async fn sub_run_rsr_imlw_mlw_gsw_smw_rhw_msw(sup_run: SupRun,
         None
     };
 
-    manager.run_rsw_imlw_mlw_gsw_smw_rhw_msw(svc).await
+    manager.run_rsw_imlw_mlw_gsw_smw_rhw_msw_rkw(svc).await
 }
 
 async fn sub_sh() -> Result<()> { command::shell::sh().await }
@@ -274,7 +277,7 @@ fn sub_term() -> Result<()> {
 fn split_apart_sup_run(sup_run: SupRun,
                        feature_flags: FeatureFlag)
                        -> Result<(ManagerConfig, sup_proto::ctl::SvcLoad)> {
-    let ring_key = get_ring_key(&sup_run)?;
+    let ring_keys = get_ring_keys(&sup_run)?;
 
     let shared_load = sup_run.shared_load;
 
@@ -287,26 +290,42 @@ fn split_apart_sup_run(sup_run: SupRun,
     #[cfg(not(target_os = "windows"))]
     let password = None;
 
-    let event_stream_config = if sup_run.event_stream_url.is_some() {
+    let mut event_sinks = Vec::new();
+    if let Some(url) = sup_run.event_stream_url {
+        let token = sup_run.event_stream_token
+                           .expect("Required option for EventStream feature");
+        let spool_size =
+            sup_run.event_stream_spool_size
+                   .checked_mul(1024 * 1024)
+                   .ok_or(Error::InvalidEventStreamSpoolSize(sup_run.event_stream_spool_size))?;
+        let nats = NatsConfig { token,
+                                url: url.into(),
+                                connect_method: sup_run.event_stream_connect_timeout,
+                                server_certificate: sup_run.event_stream_server_certificate,
+                                spool_size };
+        event_sinks.push(EventSinkConfig::Nats(nats));
+    }
+    if let Some(path) = sup_run.event_stream_file {
+        event_sinks.push(EventSinkConfig::File(path));
+    }
+    if let Some(url) = sup_run.event_stream_webhook {
+        event_sinks.push(EventSinkConfig::Webhook(url));
+    }
+    if let Some(address) = sup_run.event_stream_syslog {
+        event_sinks.push(EventSinkConfig::Syslog(address));
+    }
+    let event_stream_config = if event_sinks.is_empty() {
+        None
+    } else {
         Some(EventStreamConfig { environment:
                                      sup_run.event_stream_environment
                                             .expect("Required option for EventStream feature"),
                                  application:
                                      sup_run.event_stream_application
                                             .expect("Required option for EventStream feature"),
-                                 site:               sup_run.event_stream_site,
-                                 meta:               sup_run.event_meta.into(),
-                                 token:
-                                     sup_run.event_stream_token
-                                            .expect("Required option for EventStream feature"),
-                                 url:
-                                     sup_run.event_stream_url
-                                            .expect("Required option for EventStream feature")
-                                            .into(),
-                                 connect_method:     sup_run.event_stream_connect_timeout,
-                                 server_certificate: sup_run.event_stream_server_certificate, })
-    } else {
-        None
+                                 site:        sup_run.event_stream_site,
+                                 meta:        sup_run.event_meta.into(),
+                                 sinks:       event_sinks, })
     };
 
     let tls_config = if let Some(key_file) = sup_run.key_file {
@@ -320,6 +339,18 @@ fn split_apart_sup_run(sup_run: SupRun,
         None
     };
 
+    let ctl_tls_config = if let Some(key_path) = sup_run.ctl_server_key {
+        let cert_path =
+            sup_run.ctl_server_certificate
+                   .expect("`ctl_server_certificate` should always have a value if \
+                            `ctl_server_key` has a value.");
+        Some(TLSConfig { key_path,
+                         cert_path,
+                         ca_cert_path: sup_run.ctl_client_ca_certificate })
+    } else {
+        None
+    };
+
     let cfg = ManagerConfig { auto_update: sup_run.auto_update,
                               custom_state_path: None, // remove entirely?
                               cache_key_path: sup_run.cache_key_path.cache_key_path,
@@ -328,7 +359,7 @@ fn split_apart_sup_run(sup_run: SupRun,
                               http_disable: sup_run.http_disable,
                               organization: sup_run.organization,
                               gossip_permanent: sup_run.permanent_peer,
-                              ring_key,
+                              ring_keys,
                               gossip_peers: sup_run.peer,
                               watch_peer_file: sup_run.peer_watch_file
                                                       .map(|p| p.to_string_lossy().to_string()),
@@ -340,6 +371,7 @@ fn split_apart_sup_run(sup_run: SupRun,
                               ctl_listen: sup_run.listen_ctl,
                               http_listen: sup_run.listen_http,
                               tls_config,
+                              ctl_tls_config,
                               feature_flags,
                               event_stream_config,
                               keep_latest_packages: sup_run.keep_latest_packages,
@@ -383,6 +415,13 @@ fn split_apart_sup_run(sup_run: SupRun,
     msg.topology = shared_load.topology.map(i32::from);
     msg.update_strategy = Some(shared_load.strategy as i32);
     msg.update_condition = Some(shared_load.update_condition as i32);
+    msg.max_unavailable = shared_load.max_unavailable.map(|m| m.to_string());
+    msg.canaries = shared_load.canaries;
+    msg.health_check = shared_load.health_check;
+    msg.max_restarts = shared_load.max_restarts;
+    msg.restart_window = shared_load.restart_window;
+    msg.restart_backoff = shared_load.restart_backoff;
+    msg.max_restart_backoff = shared_load.max_restart_backoff;
     msg.shutdown_timeout = shared_load.shutdown_timeout.map(u32::from);
 
     Ok((cfg, msg))
@@ -391,18 +430,19 @@ fn split_apart_sup_run(sup_run: SupRun,
 // Various CLI Parsing Functions
 ////////////////////////////////////////////////////////////////////////
 
-fn get_ring_key(sup_run: &SupRun) -> Result<Option<SymKey>> {
+/// Load every revision of the ring key from the key cache. The Supervisor reloads them while it
+/// runs, so that it can decrypt messages sent with a revision imported after it started. It
+/// encrypts with the revision given by `--ring-key`, or with the newest cached revision of the
+/// ring named by `--ring`.
+fn get_ring_keys(sup_run: &SupRun) -> Result<Option<RingKeys>> {
     let cache_key_path = &sup_run.cache_key_path.cache_key_path;
     match &sup_run.ring {
-        Some(val) => {
-            let key = SymKey::get_latest_pair_for(val, cache_key_path)?;
-            Ok(Some(key))
-        }
+        Some(val) => Ok(Some(RingKeys::load(val, cache_key_path)?)),
         None => {
             match &sup_run.ring_key {
                 Some(val) => {
                     let (key, _) = SymKey::write_file_from_str(val, cache_key_path)?;
-                    Ok(Some(key))
+                    Ok(Some(RingKeys::load_with_encryption_key(key, cache_key_path)?))
                 }
                 None => Ok(None),
             }
@@ -700,8 +740,9 @@ mod test {
                 .expect("Could not write key pair");
             let config = config_from_cmd_str("hab-sup run --ring foobar");
 
-            assert_eq!(config.ring_key
+            assert_eq!(config.ring_keys
                              .expect("No ring key on manager config")
+                             .encryption_key_rkr()
                              .name_with_rev(),
                        pair.name_with_rev());
         }
@@ -713,6 +754,9 @@ mod test {
             lock.set(key_cache.path());
 
             env::set_var("HAB_CACHE_KEY_PATH", key_cache.path());
+            // A newer cached revision must not override the one given on the command line
+            SymKey::generate_pair_for_ring("foobar").to_pair_files(key_cache.path())
+                                                    .unwrap();
             let cmd_vec = vec![
                                "hab-sup",
                                "run",
@@ -724,8 +768,9 @@ RCFaO84j41GmrzWddxMdsXpGdn3iuIy7Mw3xYrjPLsE="#,
             ];
             let config = config_from_cmd_vec(cmd_vec);
 
-            assert_eq!(config.ring_key
+            assert_eq!(config.ring_keys
                              .expect("No ring key on manager config")
+                             .encryption_key_rkr()
                              .name_with_rev(),
                        "foobar-20160504220722");
         }
@@ -770,10 +815,11 @@ gpoVMSncu2jMIDZX63IkQII=
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      None,
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  None,
                                        keep_latest_packages: None,
@@ -803,6 +849,13 @@ gpoVMSncu2jMIDZX63IkQII=
                                                  health_check_interval:
                                                      Some(health_check_interval),
                                                  shutdown_timeout:        None,
+                                                 max_unavailable:         None,
+                                                 canaries:                None,
+                                                 health_check:            None,
+                                                 max_restarts:            None,
+                                                 restart_window:          None,
+                                                 restart_backoff:         None,
+                                                 max_restart_backoff:     None,
                                                  update_condition:
                                                      Some(UpdateCondition::Latest.into()), },
                        service_load);
@@ -833,19 +886,30 @@ gpoVMSncu2jMIDZX63IkQII=
             let ca_cert_path_str = ca_cert_path.to_str().unwrap();
             File::create(&ca_cert_path).unwrap();
 
-            let args = format!("hab-sup run --listen-gossip=1.2.3.4:4321 \
-                                --listen-http=5.5.5.5:11111 --http-disable \
-                                --listen-ctl=7.8.9.1:12 --org=MY_ORG --peer 1.1.1.1:1111 \
-                                2.2.2.2:2222 3.3.3.3 --permanent-peer --ring tester \
-                                --cache-key-path={} --auto-update --key={} --certs={} --ca-certs \
-                                {} --keep-latest-packages=5 --sys-ip-address 7.8.9.0",
-                               temp_dir_str, key_path_str, cert_path_str, ca_cert_path_str);
+            let args =
+                format!("hab-sup run --listen-gossip=1.2.3.4:4321 --listen-http=5.5.5.5:11111 \
+                         --http-disable --listen-ctl=7.8.9.1:12 --org=MY_ORG --peer 1.1.1.1:1111 \
+                         2.2.2.2:2222 3.3.3.3 --permanent-peer --ring tester --cache-key-path={} \
+                         --auto-update --key={} --certs={} --ca-certs {} --ctl-server-key={} \
+                         --ctl-server-certificate={} --ctl-client-ca-certificate={} \
+                         --keep-latest-packages=5 --sys-ip-address 7.8.9.0",
+                        temp_dir_str,
+                        key_path_str,
+                        cert_path_str,
+                        ca_cert_path_str,
+                        key_path_str,
+                        cert_path_str,
+                        ca_cert_path_str);
 
             let gossip_peers = vec!["1.1.1.1:1111".parse().unwrap(),
                                     "2.2.2.2:2222".parse().unwrap(),
                                     format!("3.3.3.3:{}", GossipListenAddr::DEFAULT_PORT).parse()
                                                                                          .unwrap()];
 
+            let tls_config = TLSConfig { cert_path,
+                                         key_path,
+                                         ca_cert_path: Some(ca_cert_path) };
+
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update: true,
                                        custom_state_path: None,
@@ -861,13 +925,11 @@ gpoVMSncu2jMIDZX63IkQII=
                                        http_disable: true,
                                        gossip_peers,
                                        gossip_permanent: true,
-                                       ring_key: Some(sym_key),
+                                       ring_keys: Some(sym_key.into()),
                                        organization: Some(String::from("MY_ORG")),
                                        watch_peer_file: None,
-                                       tls_config: Some(TLSConfig { cert_path,
-                                                                    key_path,
-                                                                    ca_cert_path:
-                                                                        Some(ca_cert_path) }),
+                                       tls_config: Some(tls_config.clone()),
+                                       ctl_tls_config: Some(tls_config),
                                        feature_flags: FeatureFlag::empty(),
                                        event_stream_config: None,
                                        keep_latest_packages: Some(5),
@@ -896,10 +958,11 @@ gpoVMSncu2jMIDZX63IkQII=
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      None,
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  None,
                                        keep_latest_packages: None,
@@ -927,10 +990,11 @@ gpoVMSncu2jMIDZX63IkQII=
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      Some(String::from("/some/path")),
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  None,
                                        keep_latest_packages: None,
@@ -991,26 +1055,46 @@ gpoVMSncu2jMIDZX63IkQII=
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      None,
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  Some(EventStreamConfig {
                                         environment: String::from("MY_ENV"),
                                         application: String::from("MY_APP"),
                                         site: Some(String::from("my_site")),
                                         meta: meta.into(),
-                                        token: "some_token".parse().unwrap(),
-                                        url: "127.0.0.1:3456".parse().unwrap(),
-                                        connect_method: EventStreamConnectMethod::Timeout {secs: 5},
-                                        server_certificate: Some(certificate_path_str.parse().unwrap()),
+                                        sinks: vec![EventSinkConfig::Nats(NatsConfig {
+                                            token: "some_token".parse().unwrap(),
+                                            url: "127.0.0.1:3456".parse().unwrap(),
+                                            connect_method: EventStreamConnectMethod::Timeout {secs: 5},
+                                            server_certificate: Some(certificate_path_str.parse().unwrap()),
+                                            spool_size: 10 * 1024 * 1024,
+                                        })],
                                        }),
                                        keep_latest_packages: None,
                                        sys_ip:               habitat_core::util::sys::ip().unwrap(), },
                        config,);
         }
 
+        #[test]
+        fn test_hab_sup_run_cli_event_stream_spool_size_overflow() {
+            let args = format!("hab-sup run --event-stream-application=MY_APP \
+                                --event-stream-environment=MY_ENV --event-stream-url \
+                                127.0.0.1:3456 --event-stream-token some_token \
+                                --event-stream-spool-size {}",
+                               u64::max_value());
+            let sup_run = sup_run_from_cmd_str(&args);
+            match split_apart_sup_run(sup_run, no_feature_flags()) {
+                Err(Error::InvalidEventStreamSpoolSize(size)) => {
+                    assert_eq!(size, u64::max_value())
+                }
+                other => panic!("Expected an invalid spool size, got {:?}", other.map(|_| ())),
+            }
+        }
+
         #[test]
         fn test_hab_sup_run_cli_svc() {
             let temp_dir = TempDir::new().expect("Could not create tempdir");
@@ -1020,7 +1104,8 @@ gpoVMSncu2jMIDZX63IkQII=
                                 two:service2.default --binding-mode relaxed --url http://my_url.com \
                                 --config-from={} --group MyGroup --topology leader \
                                 --strategy rolling --update-condition track-channel --health-check-interval 17 \
-                                --shutdown-timeout=12 core/redis",
+                                --shutdown-timeout=12 --max-unavailable 25% --canaries 2 \
+                                core/redis",
                                temp_dir_str);
 
             let mut binds = ServiceBindList::default();
@@ -1053,6 +1138,13 @@ gpoVMSncu2jMIDZX63IkQII=
                                                  health_check_interval:
                                                      Some(health_check_interval),
                                                  shutdown_timeout:        Some(12),
+                                                 max_unavailable:         Some(String::from("25%")),
+                                                 canaries:                Some(2),
+                                                 health_check:            None,
+                                                 max_restarts:            None,
+                                                 restart_window:          None,
+                                                 restart_backoff:         None,
+                                                 max_restart_backoff:     None,
                                                  update_condition:
                                                      Some(UpdateCondition::TrackChannel.into()), },
                        service_load);
@@ -1164,13 +1256,14 @@ sys_ip_address = "7.8.9.0"
                                        http_disable: true,
                                        gossip_peers,
                                        gossip_permanent: true,
-                                       ring_key: Some(sym_key),
+                                       ring_keys: Some(sym_key.into()),
                                        organization: Some(String::from("MY_ORG")),
                                        watch_peer_file: None,
                                        tls_config: Some(TLSConfig { cert_path,
                                                                     key_path,
                                                                     ca_cert_path:
                                                                         Some(ca_cert_path) }),
+                                       ctl_tls_config: None,
                                        feature_flags: FeatureFlag::empty(),
                                        event_stream_config: None,
                                        keep_latest_packages: Some(5),
@@ -1208,10 +1301,11 @@ sys_ip_address = "7.8.9.0"
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      None,
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  None,
                                        keep_latest_packages: None,
@@ -1248,10 +1342,11 @@ sys_ip_address = "7.8.9.0"
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      Some(String::from("/some/path")),
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  None,
                                        keep_latest_packages: None,
@@ -1322,6 +1417,7 @@ event_stream_site = "my_site"
 event_stream_token = "some_token"
 event_meta = ["key1=val1", "key2=val2", "keyA=valA"]
 event_stream_server_certificate = "{}"
+event_stream_spool_size = 5
 "#,
                                           certificate_path_str.replace("\\", "/")
             );
@@ -1349,20 +1445,24 @@ event_stream_server_certificate = "{}"
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:         None,
                                        watch_peer_file:      None,
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  Some(EventStreamConfig {
                                         environment: String::from("MY_ENV"),
                                         application: String::from("MY_APP"),
                                         site: Some(String::from("my_site")),
                                         meta: meta.into(),
-                                        token: "some_token".parse().unwrap(),
-                                        url: "127.0.0.1:3456".parse().unwrap(),
-                                        connect_method: EventStreamConnectMethod::Timeout {secs: 5},
-                                        server_certificate: Some(certificate_path_str.parse().unwrap()),
+                                        sinks: vec![EventSinkConfig::Nats(NatsConfig {
+                                            token: "some_token".parse().unwrap(),
+                                            url: "127.0.0.1:3456".parse().unwrap(),
+                                            connect_method: EventStreamConnectMethod::Timeout {secs: 5},
+                                            server_certificate: Some(certificate_path_str.parse().unwrap()),
+                                            spool_size: 5 * 1024 * 1024,
+                                        })],
                                        }),
                                        keep_latest_packages: None,
                                        sys_ip:               habitat_core::util::sys::ip().unwrap(), },
@@ -1388,6 +1488,10 @@ strategy = "at-once"
 update_condition = "track-channel"
 health_check_interval = 17
 shutdown_timeout = 12
+max_unavailable = 3
+canaries = 2
+health_check = "tcp://localhost:6379"
+max_restarts = 3
 pkg_ident_or_artifact = "core/redis"
 "#,
                                           temp_dir_str.replace("\\", "/")
@@ -1429,6 +1533,14 @@ pkg_ident_or_artifact = "core/redis"
                                                  health_check_interval:
                                                      Some(health_check_interval),
                                                  shutdown_timeout:        Some(12),
+                                                 max_unavailable:         Some(String::from("3")),
+                                                 canaries:                Some(2),
+                                                 health_check:
+                                                     Some(String::from("tcp://localhost:6379")),
+                                                 max_restarts:            Some(3),
+                                                 restart_window:          None,
+                                                 restart_backoff:         None,
+                                                 max_restart_backoff:     None,
                                                  update_condition:
                                                      Some(UpdateCondition::TrackChannel.into()), },
                        service_load);
@@ -1532,11 +1644,12 @@ organization = "MY_ORG_FROM_SECOND_CONFG"
                                        http_disable:         false,
                                        gossip_peers:         vec![],
                                        gossip_permanent:     false,
-                                       ring_key:             None,
+                                       ring_keys:            None,
                                        organization:
                                            Some(String::from("MY_ORG_FROM_SECOND_CONFG")),
                                        watch_peer_file:      None,
                                        tls_config:           None,
+                                       ctl_tls_config:       None,
                                        feature_flags:        FeatureFlag::empty(),
                                        event_stream_config:  None,
                                        keep_latest_packages: None,
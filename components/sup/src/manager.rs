@@ -1,9 +1,11 @@
 pub(crate) mod action;
+pub(crate) mod changes;
 pub mod service;
 #[macro_use]
 mod debug;
 pub mod commands;
 mod file_watcher;
+pub mod manifest;
 mod peer_watcher;
 mod self_updater;
 mod service_updater;
@@ -14,6 +16,8 @@ mod user_config_watcher;
 
 use self::{action::{ShutdownInput,
                     SupervisorAction},
+           changes::{Change,
+                     Snapshot},
            peer_watcher::PeerWatcher,
            self_updater::{SelfUpdater,
                           SUP_PKG_IDENT},
@@ -29,7 +33,8 @@ use self::{action::{ShutdownInput,
            spec_watcher::SpecWatcher,
            sys::Sys,
            user_config_watcher::UserConfigWatcher};
-use crate::{census::{CensusRing,
+use crate::{census::{CensusGroup,
+                     CensusRing,
                      CensusRingProxy},
             ctl_gateway::{self,
                           acceptor::CtlAcceptor,
@@ -37,7 +42,8 @@ use crate::{census::{CensusRing,
             error::{Error,
                     Result},
             event::{self,
-                    EventStreamConfig},
+                    EventStreamConfig,
+                    ServiceMetadata},
             http_gateway,
             util::pkg,
             VERSION};
@@ -48,6 +54,10 @@ use futures::{channel::{mpsc as fut_mpsc,
               prelude::*,
               stream::FuturesUnordered};
 use habitat_butterfly::{member::Member,
+                        ring_keys::{RingKeyReloadInterval,
+                                    RingKeys},
+                        rumor::service::{Canary,
+                                         CanaryDecision},
                         server::{timing::Timing,
                                  ServerProxy,
                                  Suitability}};
@@ -58,16 +68,15 @@ use habitat_common::{liveliness_checker,
                              ListenCtlAddr},
                      FeatureFlag};
 #[cfg(unix)]
-use habitat_core::os::{process::{ShutdownSignal,
-                                 Signal},
+use habitat_core::os::{process::ShutdownSignal,
                        signals};
-use habitat_core::{crypto::SymKey,
-                   env,
+use habitat_core::{env,
                    env::Config,
                    fs::FS_ROOT_PATH,
                    os::process::{self,
                                  Pid,
-                                 ShutdownTimeout},
+                                 ShutdownTimeout,
+                                 Signal},
                    package::{Identifiable,
                              PackageIdent,
                              PackageInstall},
@@ -77,7 +86,8 @@ use habitat_core::{crypto::SymKey,
 use habitat_launcher_client::{LauncherCli,
                               LAUNCHER_LOCK_CLEAN_ENV,
                               LAUNCHER_PID_ENV};
-use habitat_sup_protocol::{self};
+use habitat_sup_protocol::{self,
+                           ctl::SvcLoad};
 use parking_lot::{Mutex,
                   RwLock};
 use prometheus::{HistogramVec,
@@ -197,6 +207,8 @@ enum ServiceOperation {
         to_stop:  ServiceSpec,
         to_start: ServiceSpec,
     },
+    /// Apply changes to the spec of a running service which don't require restarting it.
+    Update(ServiceSpec),
 }
 
 /// A Supervisor can stop in a handful of ways.
@@ -242,10 +254,11 @@ impl ShutdownConfig {
 pub struct FsCfg {
     pub sup_root: PathBuf,
 
-    data_path:      PathBuf,
-    specs_path:     PathBuf,
-    member_id_file: PathBuf,
-    proc_lock_file: PathBuf,
+    data_path:        PathBuf,
+    specs_path:       PathBuf,
+    event_spool_path: PathBuf,
+    member_id_file:   PathBuf,
+    proc_lock_file:   PathBuf,
 }
 
 impl FsCfg {
@@ -255,6 +268,7 @@ impl FsCfg {
         let sup_root = sup_root.into();
         FsCfg { specs_path: sup_root.join("specs"),
                 data_path: sup_root.join("data"),
+                event_spool_path: sup_root.join("event_spool"),
                 member_id_file: sup_root.join(MEMBER_ID_FILE),
                 proc_lock_file: sup_root.join(PROC_LOCK_FILE),
                 sup_root }
@@ -274,10 +288,11 @@ pub struct ManagerConfig {
     pub http_disable:         bool,
     pub gossip_peers:         Vec<SocketAddr>,
     pub gossip_permanent:     bool,
-    pub ring_key:             Option<SymKey>,
+    pub ring_keys:            Option<RingKeys>,
     pub organization:         Option<String>,
     pub watch_peer_file:      Option<String>,
     pub tls_config:           Option<TLSConfig>,
+    pub ctl_tls_config:       Option<TLSConfig>,
     pub feature_flags:        FeatureFlag,
     pub event_stream_config:  Option<EventStreamConfig>,
     /// If this field is `Some`, keep the indicated number of latest packages and uninstall all
@@ -299,10 +314,10 @@ impl ManagerConfig {
         habitat_sup_protocol::sup_root(self.custom_state_path.as_ref())
     }
 
+    fn specs_path(&self) -> PathBuf { self.sup_root().join("specs") }
+
     fn spec_path_for(&self, ident: &PackageIdent) -> PathBuf {
-        self.sup_root()
-            .join("specs")
-            .join(ServiceSpec::ident_file(ident))
+        self.specs_path().join(ServiceSpec::ident_file(ident))
     }
 
     pub fn save_spec_for(&self, spec: &ServiceSpec) -> Result<()> {
@@ -317,6 +332,9 @@ impl ManagerConfig {
         // should we check for Err ?
         ServiceSpec::from_file(&spec_file).ok()
     }
+
+    /// Return the specs of every loaded service.
+    pub fn specs(&self) -> Result<Vec<ServiceSpec>> { Ok(SpecDir::new(self.specs_path())?.specs()) }
 }
 
 /// Once a formerly-busy service is no longer doing something
@@ -380,6 +398,21 @@ impl ReconciliationFlag {
     fn toggle_if_set(&self) -> bool { self.0.compare_and_swap(true, false, Ordering::Relaxed) }
 }
 
+/// A service which was stopped to update it, and has not yet been
+/// started again.
+struct PendingUpdate {
+    /// The package the service is updating to.
+    ident:    PackageIdent,
+    /// The service as it was before the update.
+    metadata: ServiceMetadata,
+}
+
+impl PendingUpdate {
+    fn failed(self, error: String) {
+        event::service_update_failed(self.metadata, &self.ident, error)
+    }
+}
+
 /// This struct encapsulates the shared state for the supervisor. It's worth noting that if there's
 /// something you want the CtlGateway to be able to operate on, it needs to be put in here. This
 /// state gets shared with all the CtlGateway handlers.
@@ -430,6 +463,51 @@ pub(crate) mod sync {
         pub fn set_health_of(&mut self, service_group: ServiceGroup, value: HealthCheckResult) {
             self.0.health_check_data.insert(service_group, value);
         }
+
+        /// Drop the subscribers to the /events endpoint which have disconnected, and return
+        /// whether any are left. Without subscribers, the snapshot is dropped too; whoever
+        /// subscribes next is sent the current state along with the next snapshot.
+        pub fn retain_subscribers(&mut self) -> bool {
+            self.0
+                .subscribers
+                .retain(|subscriber| !subscriber.is_closed());
+            if self.0.subscribers.is_empty() {
+                self.0.snapshot = None;
+                false
+            } else {
+                true
+            }
+        }
+
+        /// Replace the snapshot of the state streamed by the /events endpoint, sending the
+        /// changes since the previous snapshot to every subscriber.
+        pub fn set_snapshot(&mut self, snapshot: Snapshot) {
+            let changes = match self.0.snapshot {
+                Some(ref previous) => snapshot.changes_since(previous),
+                None => snapshot.changes_since(&Snapshot::default()),
+            };
+            if !changes.is_empty() {
+                // A subscriber whose receiver was dropped has disconnected.
+                self.0.subscribers.retain(|subscriber| {
+                                      changes.iter()
+                                             .all(|c| subscriber.unbounded_send(c.clone()).is_ok())
+                                  });
+            }
+            self.0.snapshot = Some(snapshot);
+        }
+
+        /// Subscribe to the changes streamed by the /events endpoint. The first changes received
+        /// describe the current state.
+        pub fn subscribe(&mut self) -> fut_mpsc::UnboundedReceiver<Change> {
+            let (tx, rx) = fut_mpsc::unbounded();
+            if let Some(ref snapshot) = self.0.snapshot {
+                for change in snapshot.changes_since(&Snapshot::default()) {
+                    tx.unbounded_send(change).ok();
+                }
+            }
+            self.0.subscribers.push(tx);
+            rx
+        }
     }
 
     /// All the data that is ultimately served from the Supervisor's HTTP
@@ -460,6 +538,10 @@ pub(crate) mod sync {
         /// Data returned by /services/<SERVICE_NAME>/<GROUP_NAME>/health
         /// endpoint
         health_check_data: HashMap<ServiceGroup, HealthCheckResult>,
+        /// The state last streamed by the /events endpoint, while it has subscribers
+        snapshot:          Option<Snapshot>,
+        /// Clients of the /events endpoint
+        subscribers:       Vec<fut_mpsc::UnboundedSender<Change>>,
     }
 
     type ManagerServicesInner = HashMap<PackageIdent, Service>;
@@ -563,6 +645,9 @@ pub struct Manager {
     ///
     /// Feel free to refactor to something different!
     service_states:      HashMap<PackageIdent, SystemTime>,
+    /// The services that are being restarted for an update, by spec
+    /// ident, so we can report how the update went once they start.
+    pending_updates:     HashMap<PackageIdent, PendingUpdate>,
 
     /// Collects the identifiers of all services that are currently
     /// doing something asynchronously (like shutting down, or running
@@ -577,7 +662,7 @@ pub struct Manager {
     // that point, we might need / want to change from a HashSet to
     // something else (maybe a HashMap?) in order to cleanly manage
     // the different operations.
-    busy_services:                Arc<Mutex<HashSet<PackageIdent>>>,
+    busy_services: Arc<Mutex<HashSet<PackageIdent>>>,
     services_need_reconciliation: ReconciliationFlag,
 
     feature_flags: FeatureFlag,
@@ -646,7 +731,7 @@ impl Manager {
         let server = habitat_butterfly::Server::new(sys.gossip_listen(),
                                                     sys.gossip_listen(),
                                                     member,
-                                                    cfg.ring_key,
+                                                    cfg.ring_keys,
                                                     None,
                                                     Some(&fs_cfg.data_path),
                                                     suitability_lookup)?;
@@ -675,7 +760,7 @@ impl Manager {
             let fqdn = habitat_core::os::net::fqdn().unwrap_or_else(|| sys.hostname.clone());
             outputln!("Event FQDN {}", fqdn);
 
-            event::init(&sys, fqdn, config).await?;
+            event::init(&sys, fqdn, config, &fs_cfg.event_spool_path).await?;
         }
 
         let pid_source = ServicePidSource::determine_source(&launcher);
@@ -698,6 +783,7 @@ impl Manager {
                      fs_cfg: Arc::new(fs_cfg),
                      organization: cfg.organization,
                      service_states: HashMap::new(),
+                     pending_updates: HashMap::new(),
                      sys: Arc::new(sys),
                      http_disable: cfg.http_disable,
                      busy_services: Arc::default(),
@@ -806,6 +892,7 @@ impl Manager {
     /// * `ManagerServices::inner` (read)
     async fn add_service_rsw_mlw_rhw_msr(&mut self, spec: ServiceSpec) {
         let ident = spec.ident.clone();
+        let pending_update = self.pending_updates.remove(&ident);
         let service = match Service::new(self.sys.clone(),
                                          spec,
                                          self.fs_cfg.clone(),
@@ -820,6 +907,9 @@ impl Manager {
             }
             Err(err) => {
                 outputln!("Unable to start {}, {}", ident, err);
+                if let Some(update) = pending_update {
+                    update.failed(err.to_string());
+                }
                 // Remove the spec file so it does not look like this service is loaded.
                 self.remove_spec_file(&ident).ok();
                 return;
@@ -835,6 +925,9 @@ impl Manager {
                 Path::new(&*FS_ROOT_PATH),
             ).await {
                 outputln!("Failed to run install hook for {}, {}", ident, err);
+                if let Some(update) = pending_update {
+                    update.failed(err.to_string());
+                }
                 return;
             }
         }
@@ -847,6 +940,9 @@ impl Manager {
                        the current user write access to it",
                       service.pkg.svc_path.display());
             outputln!("{} failed to start", ident);
+            if let Some(update) = pending_update {
+                update.failed(e.to_string());
+            }
             return;
         }
 
@@ -860,6 +956,9 @@ impl Manager {
             outputln!("Unable to start UserConfigWatcher for {}: {}",
                       service.spec_ident,
                       e);
+            if let Some(update) = pending_update {
+                update.failed(e.to_string());
+            }
             return;
         }
 
@@ -868,6 +967,14 @@ impl Manager {
         self.service_updater.lock().add(&service);
 
         event::service_started(&service);
+        if let Some(update) = pending_update {
+            if service.pkg.ident == update.ident {
+                event::service_update_finished(&service);
+            } else {
+                let error = format!("Started {} instead", service.pkg.ident);
+                update.failed(error);
+            }
+        }
 
         self.state
             .services
@@ -887,13 +994,16 @@ impl Manager {
     /// * `Server::member` (write)
     /// * `RumorHeat::inner` (write)
     /// * `ManagerServices::inner` (write)
+    /// * `RingKeys::keys` (write)
     #[allow(clippy::cognitive_complexity)]
-    pub async fn run_rsw_imlw_mlw_gsw_smw_rhw_msw(mut self,
-                                                  svc: Option<habitat_sup_protocol::ctl::SvcLoad>)
-                                                  -> Result<()> {
+    pub async fn run_rsw_imlw_mlw_gsw_smw_rhw_msw_rkw(mut self,
+                                                      svc: Option<SvcLoad>)
+                                                      -> Result<()> {
         let main_hist = RUN_LOOP_DURATION.with_label_values(&["sup"]);
         let service_hist = RUN_LOOP_DURATION.with_label_values(&["service"]);
         let mut next_cpu_measurement = Instant::now();
+        let ring_key_reload_interval: Duration = RingKeyReloadInterval::configured_value().into();
+        let mut next_ring_key_reload = Instant::now() + ring_key_reload_interval;
         let mut cpu_start = ProcessTime::now();
 
         // TODO (CM): consider bundling up these disparate channel
@@ -932,8 +1042,19 @@ impl Manager {
         let http_listen_addr = self.sys.http_listen();
         let ctl_listen_addr = self.sys.ctl_listen();
         let ctl_secret_key = ctl_gateway::readgen_secret_key(&self.fs_cfg.sup_root)?;
+        let ctl_users = ctl_gateway::users::Users::load(&self.fs_cfg.sup_root)?;
+        let ctl_tls_config = self.state
+                                 .cfg
+                                 .ctl_tls_config
+                                 .as_ref()
+                                 .map(tls_config)
+                                 .transpose()?;
         outputln!("Starting ctl-gateway on {}", &ctl_listen_addr);
-        tokio::spawn(ctl_gateway::server::run(ctl_listen_addr, ctl_secret_key, mgr_sender));
+        tokio::spawn(ctl_gateway::server::run(ctl_listen_addr,
+                                              ctl_secret_key,
+                                              ctl_users.clone(),
+                                              ctl_tls_config,
+                                              mgr_sender.clone()));
         debug!("ctl-gateway started");
 
         if self.http_disable {
@@ -964,7 +1085,9 @@ impl Manager {
                                       tls_server_config,
                                       self.state.gateway_state.clone(),
                                       http_gateway::GatewayAuthenticationToken::configured_value(),
+                                      ctl_users,
                                       self.feature_flags,
+                                      mgr_sender,
                                       pair.clone());
 
             // Only cleanup supervisor packages if we are running the latest installed version. It
@@ -1071,6 +1194,7 @@ impl Manager {
             if let Some(package) = self.check_for_updated_supervisor().await {
                 outputln!("Supervisor shutting down for automatic update to {}",
                           package);
+                event::supervisor_update(package.ident());
                 break ShutdownMode::Restarting;
             }
 
@@ -1088,9 +1212,23 @@ impl Manager {
                     }
                     SupervisorAction::UnloadService { service_spec,
                                                       shutdown_input, } => {
+                        self.pending_updates.remove(&service_spec.ident);
+                        event::service_unloaded(self.unloaded_service_metadata_msr(&service_spec));
                         self.remove_spec_file(&service_spec.ident).ok();
                         self.stop_service_gsw_msw(&service_spec.ident, &shutdown_input);
                     }
+                    SupervisorAction::RestartService { service_spec,
+                                                       shutdown_input, } => {
+                        self.restart_service_gsw_msw(&service_spec.ident, &shutdown_input);
+                    }
+                    SupervisorAction::ReloadService { service_spec,
+                                                      signal, } => {
+                        self.reload_service_msr(&service_spec.ident, signal);
+                    }
+                    SupervisorAction::DecideCanary { service_spec,
+                                                     decision, } => {
+                        self.decide_canary_rsw_mlw_rhw_msr(&service_spec.ident, decision);
+                    }
                 }
             }
 
@@ -1145,10 +1283,13 @@ impl Manager {
                 // this var goes out of scope
                 #[allow(unused_variables)]
                 let service_timer = service_hist.start_timer();
-                if service.tick(&self.census_ring.read(), &self.launcher) {
+                let rollout_changed = self.rollout_changed_since_last_gossip_rsr(&service);
+                if service.tick(&self.census_ring.read(), &self.launcher) || rollout_changed {
                     self.gossip_latest_service_rumor_rsw_mlw_rhw(&service);
                 }
+                service.record_metrics();
             }
+            self.publish_changes_gsw_msr();
 
             // This is really only needed until everything is running
             // in futures.
@@ -1170,6 +1311,11 @@ impl Manager {
                 next_cpu_measurement = Instant::now() + Duration::from_secs(1);
                 cpu_start = ProcessTime::now();
             }
+
+            if Instant::now() >= next_ring_key_reload {
+                self.reload_ring_keys_rkw();
+                next_ring_key_reload = Instant::now() + ring_key_reload_interval;
+            }
         }; // end main loop
 
         // When we make it down here, we've broken out of the main
@@ -1217,6 +1363,24 @@ impl Manager {
         }
     }
 
+    /// The event metadata for a service that is being unloaded. If
+    /// the service is not running, the package it would run is
+    /// reported.
+    ///
+    /// # Locking (see locking.md)
+    /// * `ManagerServices::inner` (read)
+    fn unloaded_service_metadata_msr(&self, spec: &ServiceSpec) -> ServiceMetadata {
+        if let Some(service) = self.state.services.lock_msr().get(&spec.ident) {
+            return service.to_service_metadata();
+        }
+        let fs_root_path = Path::new(&*FS_ROOT_PATH);
+        let package_ident = match PackageInstall::load(&spec.ident, Some(fs_root_path)) {
+            Ok(package) => package.ident().clone(),
+            Err(_) => spec.ident.clone(),
+        };
+        spec.to_service_metadata(&package_ident, self.organization.as_deref())
+    }
+
     async fn check_for_updated_supervisor(&mut self) -> Option<PackageInstall> {
         if let Some(ref mut self_updater) = self.self_updater {
             return self_updater.updated().await;
@@ -1240,6 +1404,10 @@ impl Manager {
             if let Some(new_ident) = service_updater.has_update(&service.service_group) {
                 outputln!("Restarting {} with package {}", ident, new_ident);
                 event::service_update_started(&service, &new_ident);
+                self.pending_updates
+                    .insert(ident.clone(),
+                            PendingUpdate { ident:    new_ident.clone(),
+                                            metadata: service.to_service_metadata(), });
                 // The supervisor always runs the latest package on disk. When we have an update
                 // ensure that the lastest package on disk is the package we updated to.
                 idents_to_restart_and_latest_desired_on_restart.push((ident.clone(),
@@ -1281,12 +1449,103 @@ impl Manager {
                               .map_rumor(&self.sys.member_id, |rumor| rumor.incarnation + 1)
                               .unwrap_or(1);
 
+        let mut rumor = service.to_rumor(incarnation);
+        {
+            let service_updater = self.service_updater.lock();
+            rumor.canary = service_updater.canary(&service.service_group);
+            rumor.rollout = service_updater.rollout(&service.service_group);
+        }
+        self.butterfly.insert_service_rsw_mlw_rhw(rumor);
+    }
+
+    /// Returns `true` if the state of the rolling update of a service led by this Supervisor
+    /// differs from the one in the last service rumor it gossiped.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RumorStore::list` (read)
+    fn rollout_changed_since_last_gossip_rsr(&self, service: &Service) -> bool {
+        let rollout = self.service_updater.lock().rollout(&service.service_group);
         self.butterfly
-            .insert_service_rsw_mlw_rhw(service.to_rumor(incarnation));
+            .service_store
+            .lock_rsr()
+            .service_group(&service.service_group)
+            .map_rumor(&self.sys.member_id, |rumor| rumor.rollout != rollout)
+            .unwrap_or(false)
+    }
+
+    /// Record an operator decision on the canary stage of the rolling update of a service and
+    /// share it with the ring. The decision applies to the package the update leader is rolling
+    /// out.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RumorStore::list` (write)
+    /// * `MemberList::entries` (write)
+    /// * `RumorHeat::inner` (write)
+    /// * `ManagerServices::inner` (read)
+    fn decide_canary_rsw_mlw_rhw_msr(&self,
+                                     ident: &PackageIdent,
+                                     decision: habitat_sup_protocol::types::CanaryDecision) {
+        let services = self.state.services.lock_msr();
+        let service = match services.services().find(|s| s.spec_ident == *ident) {
+            Some(service) => service,
+            None => {
+                warn!("Tried to decide the canary of '{}', but it is not running",
+                      ident);
+                return;
+            }
+        };
+        let pkg = self.census_ring
+                      .read()
+                      .census_group_for(&service.service_group)
+                      .and_then(CensusGroup::update_leader)
+                      .map(|leader| leader.pkg.to_string());
+        let pkg = match pkg {
+            Some(pkg) => pkg,
+            None => {
+                warn!("Tried to decide the canary of '{}', but its update leader is not known",
+                      service.service_group);
+                return;
+            }
+        };
+        outputln!("Recording canary decision '{}' for the rolling update of {} to {}",
+                  decision,
+                  service.service_group,
+                  pkg);
+        let decision = match decision {
+            habitat_sup_protocol::types::CanaryDecision::Promote => CanaryDecision::Promote,
+            habitat_sup_protocol::types::CanaryDecision::Abort => CanaryDecision::Abort,
+        };
+        self.service_updater
+            .lock()
+            .decide_canary(service.service_group.clone(), Canary { pkg, decision });
+        self.gossip_latest_service_rumor_rsw_mlw_rhw(service);
     }
 
     fn check_for_departure(&self) -> bool { self.butterfly.is_departed() }
 
+    /// Pick up ring key revisions that were imported into or removed from the key cache. The
+    /// ring keys are shared with the gossip server, which starts using them right away.
+    ///
+    /// # Locking (see locking.md)
+    /// * `RingKeys::keys` (write)
+    fn reload_ring_keys_rkw(&self) {
+        if let Some(ring_keys) = &self.state.cfg.ring_keys {
+            match ring_keys.reload_rkw() {
+                Ok(true) => {
+                    outputln!("Reloaded the {} ring key; encrypting with {}",
+                              ring_keys.name(),
+                              ring_keys.encryption_key_rkr().name_with_rev());
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    warn!("Failed to reload the {} ring key: {}",
+                          ring_keys.name(),
+                          err)
+                }
+            }
+        }
+    }
+
     /// # Locking (see locking.md)
     /// * `ManagerServices::inner` (read)
     fn check_for_changed_services_msr(&mut self) -> bool {
@@ -1404,6 +1663,22 @@ impl Manager {
         self.state.gateway_state.lock_gsw().set_services_data(json);
     }
 
+    /// Send the changes to the census and to services since the last pass of the main loop to
+    /// the clients of the HTTP gateway's /events endpoint.
+    ///
+    /// # Locking (see locking.md)
+    /// * `GatewayState::inner` (write)
+    /// * `ManagerServices::inner` (read)
+    fn publish_changes_gsw_msr(&self) {
+        // Taking a snapshot walks the whole census, which is wasted if nobody is listening.
+        if !self.state.gateway_state.lock_gsw().retain_subscribers() {
+            return;
+        }
+        let snapshot = Snapshot::new(&self.census_ring.read(),
+                                     self.state.services.lock_msr().services());
+        self.state.gateway_state.lock_gsw().set_snapshot(snapshot);
+    }
+
     /// Check if any elections need restarting.
     ///
     /// # Locking (see locking.md)
@@ -1421,6 +1696,10 @@ impl Manager {
     /// * `ManagerServices::inner` (write)
     fn stop_service_gsw_msw(&mut self, ident: &PackageIdent, shutdown_input: &ShutdownInput) {
         if let Some(service) = self.remove_service_from_state_msw(&ident) {
+            self.service_updater
+                .lock()
+                .forget_rollout(&service.service_group);
+            service.forget_exits();
             let future = self.stop_service_future_gsw(service, None, Some(shutdown_input));
             tokio::spawn(future);
         } else {
@@ -1429,6 +1708,43 @@ impl Manager {
         }
     }
 
+    /// Stop a service so that it is started again once our spec files are reconciled with what is
+    /// running. Unlike a restart due to an update, the service's previous exits are forgotten so
+    /// that a crash looping service gets a fresh start.
+    ///
+    /// # Locking (see locking.md)
+    /// * `GatewayState::inner` (write)
+    /// * `ManagerServices::inner` (write)
+    fn restart_service_gsw_msw(&mut self, ident: &PackageIdent, shutdown_input: &ShutdownInput) {
+        if let Some(service) = self.remove_service_from_state_msw(&ident) {
+            outputln!("Restarting {}", ident);
+            service.forget_exits();
+            let future = self.stop_service_future_gsw(service, None, Some(shutdown_input));
+            tokio::spawn(future);
+        } else {
+            warn!("Tried to restart '{}', but couldn't find it in our list of running services!",
+                  ident);
+        }
+    }
+
+    /// # Locking (see locking.md)
+    /// * `ManagerServices::inner` (read)
+    fn reload_service_msr(&self, ident: &PackageIdent, signal: Option<Signal>) {
+        match self.state
+                  .services
+                  .lock_msr()
+                  .services()
+                  .find(|service| service.spec_ident == *ident)
+        {
+            Some(service) => service.reload(signal),
+            None => {
+                warn!("Tried to reload '{}', but couldn't find it in our list of running \
+                       services!",
+                      ident);
+            }
+        }
+    }
+
     /// Create a future for stopping a Service removing it from the manager. The Service is assumed
     /// to have been removed from the internal list of active services already (see, e.g.,
     /// restart_services_rsw_mlr_rhw_msw and remove_service_from_state).
@@ -1444,6 +1760,7 @@ impl Manager {
         let busy_services = Arc::clone(&self.busy_services);
         let services_need_reconciliation = self.services_need_reconciliation.clone();
         let shutdown_config = ShutdownConfig::new(shutdown_input, &service);
+        let spec_file = self.state.cfg.spec_path_for(&service.spec_ident);
 
         // JW TODO: Update service rumor to remove service from
         // cluster
@@ -1451,6 +1768,11 @@ impl Manager {
         let ident = service.spec_ident.clone();
         let stop_it = async move {
             service.stop_gsw(shutdown_config).await;
+            // A service without a spec file was unloaded. Otherwise it is only stopped or
+            // restarted, and keeps its metrics so that their counters add up across restarts.
+            if !spec_file.exists() {
+                service.remove_metrics();
+            }
             event::service_stopped(&service);
             user_config_watcher.remove(&service);
             service_updater.lock().remove(&service.service_group);
@@ -1602,6 +1924,31 @@ impl Manager {
                     // Execute the future synchronously
                     self.add_service_rsw_mlw_rhw_msr(spec).await;
                 }
+                ServiceOperation::Update(spec) => self.update_service_msw(&spec),
+            }
+        }
+    }
+
+    /// Apply changes to the spec of a running service which don't require restarting it. See
+    /// `ServiceSpec::can_apply_live`.
+    ///
+    /// # Locking (see locking.md)
+    /// * `ManagerServices::inner` (write)
+    fn update_service_msw(&self, spec: &ServiceSpec) {
+        let mut services = self.state.services.lock_msw();
+        match services.services()
+                      .find(|service| service.spec_ident == spec.ident)
+        {
+            Some(service) => {
+                outputln!("Updating {} without restarting it", spec.ident);
+                if service.apply_spec(spec) {
+                    self.service_updater.lock().add(service);
+                }
+            }
+            None => {
+                warn!("Tried to update '{}', but couldn't find it in our list of running \
+                       services!",
+                      spec.ident);
             }
         }
     }
@@ -1674,13 +2021,10 @@ impl Manager {
                               if running_spec == disk_spec {
                                   debug!("Reconciliation: '{}' unchanged", ident);
                                   None
+                              } else if running_spec.can_apply_live(&disk_spec) {
+                                  debug!("Reconciliation: '{}' queued for update", ident);
+                                  Some(ServiceOperation::Update(disk_spec))
                               } else {
-                                  // TODO (CM): In the future, this would be the
-                                  // place where we can evaluate what has changed
-                                  // between the spec-on-disk and our in-memory
-                                  // representation and potentially just bring our
-                                  // in-memory representation in line without having
-                                  // to restart the entire service.
                                   debug!("Reconciliation: '{}' queued for restart", ident);
                                   Some(ServiceOperation::Restart { to_stop:  running_spec,
                                                                    to_start: disk_spec, })
@@ -1744,7 +2088,7 @@ impl Manager {
 
 ////////////////////////////////////////////////////////////////////////
 
-fn tls_config(config: &TLSConfig) -> Result<rustls::ServerConfig> {
+pub(crate) fn tls_config(config: &TLSConfig) -> Result<rustls::ServerConfig> {
     let client_auth = match &config.ca_cert_path {
         Some(path) => {
             let mut root_store = RootCertStore::empty();
@@ -1974,10 +2318,11 @@ mod test {
                             http_disable:         false,
                             gossip_peers:         vec![],
                             gossip_permanent:     false,
-                            ring_key:             None,
+                            ring_keys:            None,
                             organization:         None,
                             watch_peer_file:      None,
                             tls_config:           None,
+                            ctl_tls_config:       None,
                             feature_flags:        FeatureFlag::empty(),
                             event_stream_config:  None,
                             keep_latest_packages: None,
@@ -2097,10 +2442,10 @@ mod test {
 
             let on_disk_spec = {
                 let mut s = running_spec.clone();
-                s.update_strategy = UpdateStrategy::AtOnce;
+                s.group = "prod".to_string();
                 s
             };
-            assert_ne!(running_spec.update_strategy, on_disk_spec.update_strategy);
+            assert_ne!(running_spec.group, on_disk_spec.group);
 
             let running = vec![running_spec];
             let on_disk = vec![on_disk_spec];
@@ -2112,8 +2457,8 @@ mod test {
                 ServiceOperation::Restart { to_stop: ref old,
                                             to_start: ref new, } => {
                     assert_eq!(old.ident, new.ident);
-                    assert_eq!(old.update_strategy, UpdateStrategy::None);
-                    assert_eq!(new.update_strategy, UpdateStrategy::AtOnce);
+                    assert_eq!(old.group, "default");
+                    assert_eq!(new.group, "prod");
                 }
                 ref other => {
                     panic!("Should have been a restart operation: got {:?}", other);
@@ -2121,6 +2466,23 @@ mod test {
             }
         }
 
+        #[test]
+        fn live_change_to_spec_on_disk_means_update() {
+            let running_spec = new_spec("core/foo");
+
+            let on_disk_spec = {
+                let mut s = running_spec.clone();
+                s.update_strategy = UpdateStrategy::AtOnce;
+                s
+            };
+
+            let running = vec![running_spec];
+            let on_disk = vec![on_disk_spec.clone()];
+
+            let operations = Manager::specs_to_operations(running, on_disk);
+            assert_eq!(operations, vec![ServiceOperation::Update(on_disk_spec)]);
+        }
+
         #[test]
         fn multiple_operations_can_be_determined_at_once() {
             // Nothing should happen with this; it's already how it
@@ -2140,7 +2502,7 @@ mod test {
             let svc_3_running = new_spec("core/baz");
             let svc_3_on_disk = {
                 let mut s = svc_3_running.clone();
-                s.update_strategy = UpdateStrategy::AtOnce;
+                s.group = "prod".to_string();
                 s
             };
 
@@ -2,7 +2,9 @@
 //! part of the Supervisor to another.
 
 use super::service::ServiceSpec;
-use habitat_core::os::process::ShutdownTimeout;
+use habitat_core::os::process::{ShutdownTimeout,
+                                Signal};
+use habitat_sup_protocol::types::CanaryDecision;
 use std::sync::mpsc;
 
 /// Defines the parameters by which a service process is to be shut
@@ -28,6 +30,18 @@ pub enum SupervisorAction {
         service_spec:   ServiceSpec,
         shutdown_input: ShutdownInput,
     },
+    RestartService {
+        service_spec:   ServiceSpec,
+        shutdown_input: ShutdownInput,
+    },
+    ReloadService {
+        service_spec: ServiceSpec,
+        signal:       Option<Signal>,
+    },
+    DecideCanary {
+        service_spec: ServiceSpec,
+        decision:     CanaryDecision,
+    },
 }
 
 pub type ActionSender = mpsc::Sender<SupervisorAction>;
@@ -43,3 +57,9 @@ impl Into<ShutdownInput> for habitat_sup_protocol::ctl::SvcStop {
         ShutdownInput { timeout: self.timeout_in_seconds.map(ShutdownTimeout::from), }
     }
 }
+
+impl Into<ShutdownInput> for habitat_sup_protocol::ctl::SvcRestart {
+    fn into(self) -> ShutdownInput {
+        ShutdownInput { timeout: self.timeout_in_seconds.map(ShutdownTimeout::from), }
+    }
+}
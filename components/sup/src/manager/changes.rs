@@ -0,0 +1,236 @@
+//! Incremental changes to the census and to the Supervisor's services, streamed to HTTP gateway
+//! clients as server-sent events.
+//!
+//! After each pass of its main loop, the Manager takes a `Snapshot` of the state it streams and
+//! hands it to the `GatewayState`, which compares it with the previous snapshot and sends every
+//! difference to its subscribers as a `Change`.
+
+use crate::{census::CensusRing,
+            manager::service::Service};
+use std::collections::BTreeMap;
+
+/// A single change to the census or to a service.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Change {
+    /// A member's health changed.
+    MemberHealth {
+        member_id: String,
+        health:    String,
+    },
+    /// A member left the census.
+    MemberRemoved { member_id: String },
+    /// A service group elected a new leader, or lost its leader.
+    Leader {
+        service_group: String,
+        member_id:     Option<String>,
+    },
+    /// A service group's configuration was given a new incarnation.
+    Config {
+        service_group: String,
+        incarnation:   u64,
+    },
+    /// The process of one of this Supervisor's services changed state.
+    ProcessState {
+        service_group: String,
+        state:         String,
+    },
+    /// One of this Supervisor's services was unloaded.
+    ServiceUnloaded { service_group: String },
+}
+
+impl Change {
+    /// The change as a server-sent event, named for the kind of change it is.
+    pub fn to_event(&self) -> String {
+        let name = match self {
+            Change::MemberHealth { .. } => "member_health",
+            Change::MemberRemoved { .. } => "member_removed",
+            Change::Leader { .. } => "leader",
+            Change::Config { .. } => "config",
+            Change::ProcessState { .. } => "process_state",
+            Change::ServiceUnloaded { .. } => "service_unloaded",
+        };
+        let data = serde_json::to_string(self).expect("Change::serialize failure");
+        format!("event: {}\ndata: {}\n\n", name, data)
+    }
+}
+
+/// The parts of the census and of the Supervisor's services which are streamed as changes.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    members:        BTreeMap<String, String>,
+    leaders:        BTreeMap<String, String>,
+    incarnations:   BTreeMap<String, u64>,
+    process_states: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    pub fn new<'a, I>(census_ring: &CensusRing, services: I) -> Self
+        where I: IntoIterator<Item = &'a Service>
+    {
+        let mut snapshot = Snapshot::default();
+        for group in census_ring.groups() {
+            let service_group = group.service_group.to_string();
+            for member in group.members() {
+                let health = if member.alive() {
+                    "alive"
+                } else if member.suspect() {
+                    "suspect"
+                } else if member.confirmed() {
+                    "confirmed"
+                } else {
+                    "departed"
+                };
+                snapshot.members
+                        .insert(member.member_id.clone(), health.to_string());
+            }
+            if let Some(leader) = group.leader() {
+                snapshot.leaders
+                        .insert(service_group.clone(), leader.member_id.clone());
+            }
+            if let Some(ref config) = group.service_config {
+                snapshot.incarnations
+                        .insert(service_group, config.incarnation);
+            }
+        }
+        for service in services {
+            snapshot.process_states
+                    .insert(service.service_group.to_string(),
+                            service.process_state().to_string());
+        }
+        snapshot
+    }
+
+    /// The changes which turn `previous` into this snapshot.
+    pub fn changes_since(&self, previous: &Snapshot) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (member_id, health) in &self.members {
+            if previous.members.get(member_id) != Some(health) {
+                changes.push(Change::MemberHealth { member_id: member_id.clone(),
+                                                    health:    health.clone(), });
+            }
+        }
+        for member_id in previous.members.keys() {
+            if !self.members.contains_key(member_id) {
+                changes.push(Change::MemberRemoved { member_id: member_id.clone(), });
+            }
+        }
+        for (service_group, member_id) in &self.leaders {
+            if previous.leaders.get(service_group) != Some(member_id) {
+                changes.push(Change::Leader { service_group: service_group.clone(),
+                                              member_id:     Some(member_id.clone()), });
+            }
+        }
+        for service_group in previous.leaders.keys() {
+            if !self.leaders.contains_key(service_group) {
+                changes.push(Change::Leader { service_group: service_group.clone(),
+                                              member_id:     None, });
+            }
+        }
+        for (service_group, incarnation) in &self.incarnations {
+            if previous.incarnations.get(service_group) != Some(incarnation) {
+                changes.push(Change::Config { service_group: service_group.clone(),
+                                              incarnation:   *incarnation, });
+            }
+        }
+        for (service_group, state) in &self.process_states {
+            if previous.process_states.get(service_group) != Some(state) {
+                changes.push(Change::ProcessState { service_group: service_group.clone(),
+                                                    state:         state.clone(), });
+            }
+        }
+        for service_group in previous.process_states.keys() {
+            if !self.process_states.contains_key(service_group) {
+                changes.push(Change::ServiceUnloaded { service_group: service_group.clone(), });
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(members: &[(&str, &str)],
+                leaders: &[(&str, &str)],
+                incarnations: &[(&str, u64)],
+                process_states: &[(&str, &str)])
+                -> Snapshot {
+        let strings = |pairs: &[(&str, &str)]| {
+            pairs.iter()
+                 .map(|(k, v)| (k.to_string(), v.to_string()))
+                 .collect()
+        };
+        Snapshot { members:        strings(members),
+                   leaders:        strings(leaders),
+                   incarnations:   incarnations.iter()
+                                               .map(|(k, v)| (k.to_string(), *v))
+                                               .collect(),
+                   process_states: strings(process_states), }
+    }
+
+    #[test]
+    fn lists_everything_as_changed_since_an_empty_snapshot() {
+        let current = snapshot(&[("a", "alive")],
+                               &[("redis.default", "a")],
+                               &[("redis.default", 2)],
+                               &[("redis.default", "up")]);
+        assert_eq!(current.changes_since(&Snapshot::default()),
+                   vec![Change::MemberHealth { member_id: "a".to_string(),
+                                               health:    "alive".to_string(), },
+                        Change::Leader { service_group: "redis.default".to_string(),
+                                         member_id:     Some("a".to_string()), },
+                        Change::Config { service_group: "redis.default".to_string(),
+                                         incarnation:   2, },
+                        Change::ProcessState { service_group: "redis.default".to_string(),
+                                               state:         "up".to_string(), },]);
+    }
+
+    #[test]
+    fn lists_only_what_changed() {
+        let previous = snapshot(&[("a", "alive"), ("b", "alive")],
+                                &[("redis.default", "a")],
+                                &[("redis.default", 2)],
+                                &[("redis.default", "up")]);
+        let current = snapshot(&[("a", "alive"), ("b", "suspect")],
+                               &[],
+                               &[("redis.default", 3)],
+                               &[("redis.default", "up")]);
+        assert_eq!(current.changes_since(&previous),
+                   vec![Change::MemberHealth { member_id: "b".to_string(),
+                                               health:    "suspect".to_string(), },
+                        Change::Leader { service_group: "redis.default".to_string(),
+                                         member_id:     None, },
+                        Change::Config { service_group: "redis.default".to_string(),
+                                         incarnation:   3, },]);
+        assert!(current.changes_since(&current).is_empty());
+    }
+
+    #[test]
+    fn lists_removed_members_and_unloaded_services() {
+        let previous = snapshot(&[("a", "alive"), ("b", "departed")],
+                                &[("redis.default", "a")],
+                                &[("redis.default", 2)],
+                                &[("redis.default", "up"), ("nginx.default", "up")]);
+        let current = snapshot(&[("a", "alive")],
+                               &[("redis.default", "a")],
+                               &[("redis.default", 2)],
+                               &[("redis.default", "up")]);
+        assert_eq!(current.changes_since(&previous),
+                   vec![Change::MemberRemoved { member_id: "b".to_string(), },
+                        Change::ServiceUnloaded { service_group: "nginx.default".to_string(), },]);
+    }
+
+    #[test]
+    fn formats_changes_as_server_sent_events() {
+        let change = Change::ProcessState { service_group: "redis.default".to_string(),
+                                            state:         "crash-loop".to_string(), };
+        assert_eq!(change.to_event(),
+                   "event: process_state\ndata: \
+                    {\"service_group\":\"redis.default\",\"state\":\"crash-loop\"}\n\n");
+        let change = Change::MemberRemoved { member_id: "b".to_string(), };
+        assert_eq!(change.to_event(),
+                   "event: member_removed\ndata: {\"member_id\":\"b\"}\n\n");
+    }
+}
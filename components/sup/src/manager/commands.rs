@@ -2,19 +2,27 @@
 
 use crate::{ctl_gateway::CtlRequest,
             error::Error,
+            event,
             manager::{action::{ActionSender,
+                               ShutdownInput,
                                SupervisorAction},
+                      manifest::{Manifest,
+                                 Operation},
                       service::{spec::ServiceSpec,
                                 DesiredState,
-                                ProcessState},
+                                ProcessState,
+                                Service,
+                                UpdateStrategy},
                       ManagerState},
             util};
 use habitat_butterfly as butterfly;
 use habitat_common::{command::package::install::InstallSource,
                      outputln,
+                     service_log,
                      templating::package::Pkg,
                      ui::UIWriter};
-use habitat_core::{package::{Identifiable,
+use habitat_core::{os::process::Signal,
+                   package::{Identifiable,
                              PackageIdent,
                              PackageTarget},
                    service::ServiceGroup};
@@ -23,22 +31,40 @@ use habitat_sup_protocol::{self as protocol,
                                  ErrCode,
                                  NetResult}};
 use serde_json;
-use std::{convert::TryFrom,
+use std::{collections::HashMap,
+          convert::TryFrom,
           fmt,
+          fs::{self,
+               File},
+          io::{self,
+               BufRead,
+               BufReader,
+               Seek,
+               SeekFrom},
+          path::Path,
           result,
+          str::FromStr,
+          thread,
           time::{Duration,
                  SystemTime}};
 use toml;
 
 static LOGKEY: &str = "CMD";
 
+/// The number of lines of existing output sent by `service_logs` if the client doesn't ask for a
+/// particular number.
+const DEFAULT_LOG_LINES: u64 = 10;
+
+/// How often `service_logs` checks a followed log file for new output.
+const LOG_FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
 /// # Locking (see locking.md)
 /// * `ManagerServices::inner` (read)
 pub fn service_cfg_msr(mgr: &ManagerState,
                        req: &mut CtlRequest,
                        opts: protocol::ctl::SvcGetDefaultCfg)
                        -> NetResult<()> {
-    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    let ident = PackageIdent::try_from(opts.ident.ok_or_else(err_update_client)?)?;
     let mut msg = protocol::types::ServiceCfg { format:
                                                     Some(protocol::types::service_cfg::Format::Toml
                                                          as i32),
@@ -56,11 +82,14 @@ pub fn service_cfg_msr(mgr: &ManagerState,
     Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", ident)))
 }
 
-pub fn service_cfg_validate(_mgr: &ManagerState,
-                            req: &mut CtlRequest,
-                            opts: protocol::ctl::SvcValidateCfg)
-                            -> NetResult<()> {
+/// # Locking (see locking.md)
+/// * `ManagerServices::inner` (read)
+pub fn service_cfg_validate_msr(mgr: &ManagerState,
+                                req: &mut CtlRequest,
+                                opts: protocol::ctl::SvcValidateCfg)
+                                -> NetResult<()> {
     let cfg = opts.cfg.ok_or_else(err_update_client)?;
+    let service_group: ServiceGroup = opts.service_group.ok_or_else(err_update_client)?.into();
     let format = opts.format
                      .and_then(protocol::types::service_cfg::Format::from_i32)
                      .unwrap_or_default();
@@ -72,50 +101,62 @@ pub fn service_cfg_validate(_mgr: &ManagerState,
                             format!("Configuration format {} not available.",
                                     format)));
     }
-    let _new_cfg: toml::value::Table = toml::from_slice(&cfg).map_err(|e| {
-                                                                 net::err(
+    let new_cfg: toml::value::Table = toml::from_slice(&cfg).map_err(|e| {
+                                                                net::err(
             ErrCode::BadPayload,
             format!("Unable to decode configuration as {}, {}", format, e),
         )
-                                                             })?;
+                                                            })?;
+    match cfg_errors_msr(mgr, &service_group, &new_cfg)? {
+        Some(errors) => {
+            for error in errors {
+                req.reply_partial(net::err(ErrCode::InvalidPayload, error));
+            }
+        }
+        None => req.reply_partial(net::err(ErrCode::NotFound, not_validated_msg(&service_group))),
+    }
     req.reply_complete(net::ok());
     Ok(())
-    // JW TODO: Hold off on validation until we can validate services which aren't currently
-    // loaded in the Supervisor but are known through rumor propagation.
-    // let service_group: ServiceGroup = opts.service_group.into();
-    // for service in mgr.services.read().unwrap().iter() {
-    //     if service.service_group != service_group {
-    //         continue;
-    //     }
-    //     if let Some(interface) = service.cfg.interface() {
-    //         match Cfg::validate(interface, &new_cfg) {
-    //             None => req.reply_complete(net::ok()),
-    //             Some(errors) => {
-    //                 for error in errors {
-    //                     req.reply_partial(net::err(ErrCode::InvalidPayload, error));
-    //                 }
-    //                 req.reply_complete(net::ok());
-    //             }
-    //         }
-    //         return Ok(());
-    //     } else {
-    //         // No interface, this service can't be configured.
-    //         return Err(net::err(
-    //             ErrCode::NotFound,
-    //             "Service has no configurable attributes.",
-    //         ));
-    //     }
-    // }
-    // Err(net::err(
-    //     ErrCode::NotFound,
-    //     format!("Service not loaded, {}", service_group),
-    // ))
 }
 
-pub fn service_cfg_set(mgr: &ManagerState,
-                       req: &mut CtlRequest,
-                       opts: protocol::ctl::SvcSetCfg)
-                       -> NetResult<()> {
+/// Validate `cfg` as the new gossip layer of `service_group` and return an error for each
+/// invalid key of the configuration it would result in.
+///
+/// Only services loaded by this Supervisor can be validated, so `None` is returned for a service
+/// group which is only known through rumor propagation. Configuration for a service whose package
+/// does not ship a configuration schema is always valid.
+///
+/// # Locking (see locking.md)
+/// * `ManagerServices::inner` (read)
+fn cfg_errors_msr(mgr: &ManagerState,
+                  service_group: &ServiceGroup,
+                  cfg: &toml::value::Table)
+                  -> NetResult<Option<Vec<String>>> {
+    let services = mgr.services.lock_msr();
+    match services.services()
+                  .find(|service| service.service_group == *service_group)
+    {
+        Some(service) => {
+            service.cfg
+                   .validate_gossip(cfg)
+                   .map(|errors| Some(errors.unwrap_or_default()))
+                   .map_err(|e| net::err(ErrCode::Internal, e.to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+fn not_validated_msg(service_group: &ServiceGroup) -> String {
+    format!("Configuration for {} was not validated because this Supervisor is not running it",
+            service_group)
+}
+
+/// # Locking (see locking.md)
+/// * `ManagerServices::inner` (read)
+pub fn service_cfg_set_msr(mgr: &ManagerState,
+                           req: &mut CtlRequest,
+                           opts: protocol::ctl::SvcSetCfg)
+                           -> NetResult<()> {
     let cfg = opts.cfg.ok_or_else(err_update_client)?;
     let is_encrypted = opts.is_encrypted.unwrap_or(false);
     let version = opts.version.ok_or_else(err_update_client)?;
@@ -123,12 +164,33 @@ pub fn service_cfg_set(mgr: &ManagerState,
     if cfg.len() > protocol::butterfly::MAX_SVC_CFG_SIZE {
         return Err(net::err(ErrCode::EntityTooLarge, "Configuration too large."));
     }
+    // Encrypted configuration can only be read by the members of the service group, so it is
+    // validated by the client with `SvcValidateCfg` before it is encrypted.
+    if !is_encrypted {
+        let new_cfg = toml::from_slice::<toml::value::Table>(&cfg).map_err(|e| {
+                          let msg = format!("Unable to decode configuration as toml, {}", e);
+                          net::err(ErrCode::BadPayload, msg)
+                      })?;
+        match cfg_errors_msr(mgr, &service_group, &new_cfg)? {
+            Some(errors) if !errors.is_empty() => {
+                for error in errors {
+                    req.reply_partial(net::err(ErrCode::InvalidPayload, error));
+                }
+                return Err(net::err(ErrCode::InvalidPayload,
+                                    format!("Configuration for {} does not match \
+                                             its schema",
+                                            service_group)));
+            }
+            Some(_) => (),
+            None => outputln!("{}", not_validated_msg(&service_group)),
+        }
+    }
     outputln!("Setting new configuration version {} for {}",
               version,
               service_group,);
     let mut client =
         match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
-                                             mgr.cfg.ring_key.clone())
+                                             mgr.cfg.ring_keys.clone())
         {
             Ok(client) => client,
             Err(err) => {
@@ -161,7 +223,7 @@ pub fn service_file_put(mgr: &ManagerState,
               service_group,);
     let mut client =
         match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
-                                             mgr.cfg.ring_key.clone())
+                                             mgr.cfg.ring_keys.clone())
         {
             Ok(client) => client,
             Err(err) => {
@@ -180,7 +242,7 @@ pub async fn service_load(mgr: &ManagerState,
                           req: &mut CtlRequest,
                           opts: protocol::ctl::SvcLoad)
                           -> NetResult<()> {
-    let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
+    let ident = PackageIdent::try_from(opts.ident.clone().ok_or_else(err_update_client)?)?;
     let source = InstallSource::Ident(ident.clone(), PackageTarget::active_target());
     let spec = if let Some(spec) = mgr.cfg.spec_for_ident(source.as_ref()) {
         // We've seen this service before. Thus `load` acts as a way to edit spec files from the
@@ -201,18 +263,53 @@ pub async fn service_load(mgr: &ManagerState,
     let package = util::pkg::satisfy_or_install(req, &source, &spec.bldr_url, &spec.channel).await?;
     spec.validate(&package)?;
     mgr.cfg.save_spec_for(&spec)?;
+    event::service_loaded(spec.to_service_metadata(package.ident(),
+                                                   mgr.cfg.organization.as_deref()));
 
     req.info(format!("The {} service was successfully loaded", spec.ident))?;
     req.reply_complete(net::ok());
     Ok(())
 }
 
+pub fn service_update(mgr: &ManagerState,
+                      req: &mut CtlRequest,
+                      opts: protocol::ctl::SvcUpdate)
+                      -> NetResult<()> {
+    let ident = PackageIdent::try_from(opts.ident.clone().ok_or_else(err_update_client)?)?;
+    let spec = match mgr.cfg.spec_for_ident(&ident) {
+        Some(spec) => spec,
+        None => return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", ident))),
+    };
+    let updated = spec.clone().merge_svc_update(opts)?;
+    if updated == spec {
+        req.info(format!("The {} service is already up to date", spec.ident))?;
+        req.reply_complete(net::ok());
+        return Ok(());
+    }
+    if let Some(package) = util::pkg::installed(&updated.ident) {
+        updated.validate(&package)?;
+    }
+    mgr.cfg.save_spec_for(&updated)?;
+
+    if spec.can_apply_live(&updated) {
+        req.info(format!("Supervisor updating {} without restarting it. See the Supervisor \
+                          output for more details.",
+                         spec.ident))?;
+    } else {
+        req.info(format!("Supervisor restarting {} to apply the update. See the Supervisor \
+                          output for more details.",
+                         spec.ident))?;
+    }
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
 pub fn service_unload(mgr: &ManagerState,
                       req: &mut CtlRequest,
                       opts: protocol::ctl::SvcUnload,
                       action_sender: &ActionSender)
                       -> NetResult<()> {
-    let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
+    let ident = PackageIdent::try_from(opts.ident.clone().ok_or_else(err_update_client)?)?;
     if let Some(service_spec) = mgr.cfg.spec_for_ident(&ident) {
         let shutdown_input = opts.into();
         let action = SupervisorAction::UnloadService { service_spec,
@@ -233,7 +330,7 @@ pub fn service_start(mgr: &ManagerState,
                      req: &mut CtlRequest,
                      opts: protocol::ctl::SvcStart)
                      -> NetResult<()> {
-    let ident = opts.ident.ok_or_else(err_update_client)?.into();
+    let ident = PackageIdent::try_from(opts.ident.ok_or_else(err_update_client)?)?;
     match mgr.cfg.spec_for_ident(&ident) {
         Some(mut spec) => {
             if spec.desired_state == DesiredState::Down {
@@ -260,7 +357,7 @@ pub fn service_stop(mgr: &ManagerState,
                     opts: protocol::ctl::SvcStop,
                     action_sender: &ActionSender)
                     -> NetResult<()> {
-    let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
+    let ident = PackageIdent::try_from(opts.ident.clone().ok_or_else(err_update_client)?)?;
     match mgr.cfg.spec_for_ident(&ident) {
         Some(service_spec) => {
             if service_spec.desired_state == DesiredState::Up {
@@ -285,6 +382,121 @@ pub fn service_stop(mgr: &ManagerState,
     Ok(())
 }
 
+pub fn service_restart(mgr: &ManagerState,
+                       req: &mut CtlRequest,
+                       opts: protocol::ctl::SvcRestart,
+                       action_sender: &ActionSender)
+                       -> NetResult<()> {
+    let ident = PackageIdent::try_from(opts.ident.clone().ok_or_else(err_update_client)?)?;
+    match mgr.cfg.spec_for_ident(&ident) {
+        Some(service_spec) => {
+            if service_spec.desired_state != DesiredState::Up {
+                return Err(net::err(ErrCode::InvalidPayload,
+                                    format!("Service {} is stopped; start it with \
+                                             `hab svc start`",
+                                            &ident)));
+            }
+            let shutdown_input = opts.into();
+            let action = SupervisorAction::RestartService { service_spec,
+                                                            shutdown_input };
+            send_action(action, action_sender)?;
+            req.info(format!("Supervisor restarting {}. See the Supervisor output for more \
+                              details.",
+                             &ident))?;
+        }
+        None => {
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &ident)));
+        }
+    };
+
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
+/// # Locking (see locking.md)
+/// * `ManagerServices::inner` (read)
+pub fn service_reload_msr(mgr: &ManagerState,
+                          req: &mut CtlRequest,
+                          opts: protocol::ctl::SvcReload,
+                          action_sender: &ActionSender)
+                          -> NetResult<()> {
+    let ident = PackageIdent::try_from(opts.ident.ok_or_else(err_update_client)?)?;
+    let signal = opts.signal
+                     .as_ref()
+                     .map(|signal| Signal::from_str(signal))
+                     .transpose()
+                     .map_err(|e| net::err(ErrCode::InvalidPayload, e.to_string()))?;
+    let service_spec = match mgr.cfg.spec_for_ident(&ident) {
+        Some(service_spec) => service_spec,
+        None => return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", ident))),
+    };
+    if cfg!(windows) && signal.is_some() {
+        return Err(net::err(ErrCode::InvalidPayload,
+                            "Sending signals to services is not supported on \
+                             Windows"));
+    }
+    if signal.is_none() {
+        let has_reload_hook = mgr.services
+                                 .lock_msr()
+                                 .services()
+                                 .find(|service| service.spec_ident == ident)
+                                 .map(Service::has_reload_hook);
+        match has_reload_hook {
+            Some(true) => (),
+            Some(false) => {
+                return Err(net::err(ErrCode::InvalidPayload,
+                                    format!("Service {} has no reload hook; pass a \
+                                             signal to send its process instead",
+                                            ident)));
+            }
+            None => {
+                return Err(net::err(ErrCode::NotFound, format!("Service not running, {}", ident)));
+            }
+        }
+    }
+
+    let action = SupervisorAction::ReloadService { service_spec,
+                                                   signal };
+    send_action(action, action_sender)?;
+    req.info(format!("Supervisor reloading {}. See the Supervisor output for more details.",
+                     &ident))?;
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
+pub fn service_canary(mgr: &ManagerState,
+                      req: &mut CtlRequest,
+                      opts: protocol::ctl::SvcCanary,
+                      action_sender: &ActionSender)
+                      -> NetResult<()> {
+    let ident = PackageIdent::try_from(opts.ident.ok_or_else(err_update_client)?)?;
+    let decision = opts.decision
+                       .and_then(protocol::types::CanaryDecision::from_i32)
+                       .ok_or_else(err_update_client)?;
+    match mgr.cfg.spec_for_ident(&ident) {
+        Some(service_spec) => {
+            if service_spec.update_strategy != UpdateStrategy::Canary {
+                return Err(net::err(ErrCode::InvalidPayload,
+                                    format!("Service {} does not use the canary \
+                                             update strategy",
+                                            &ident)));
+            }
+            let action = SupervisorAction::DecideCanary { service_spec,
+                                                          decision };
+            send_action(action, action_sender)?;
+            req.info(format!("Supervisor recording the canary decision '{}' for {}. See the \
+                              Supervisor output for more details.",
+                             decision, &ident))?;
+        }
+        None => {
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &ident)));
+        }
+    };
+
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
 pub fn supervisor_depart(mgr: &ManagerState,
                          req: &mut CtlRequest,
                          opts: protocol::ctl::SupDepart)
@@ -292,7 +504,7 @@ pub fn supervisor_depart(mgr: &ManagerState,
     let member_id = opts.member_id.ok_or_else(err_update_client)?;
     let mut client =
         match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
-                                             mgr.cfg.ring_key.clone())
+                                             mgr.cfg.ring_keys.clone())
         {
             Ok(client) => client,
             Err(err) => {
@@ -310,6 +522,118 @@ pub fn supervisor_depart(mgr: &ManagerState,
     }
 }
 
+/// Load, update, and unload services so the Supervisor runs exactly the services in a manifest.
+/// This only changes spec files; the Supervisor brings the services in line with them as usual.
+pub async fn supervisor_apply(mgr: &ManagerState,
+                              req: &mut CtlRequest,
+                              opts: protocol::ctl::SupApply,
+                              action_sender: &ActionSender)
+                              -> NetResult<()> {
+    let manifest = opts.manifest
+                       .ok_or_else(err_update_client)?
+                       .parse::<Manifest>()
+                       .map_err(|e| net::err(ErrCode::InvalidPayload, e))?;
+    let plan = manifest.plan(mgr.cfg.specs()?);
+    if plan.is_empty() {
+        req.info("The loaded services already match the manifest")?;
+        req.reply_complete(net::ok());
+        return Ok(());
+    }
+    for operation in &plan {
+        req.info(operation.to_string())?;
+    }
+    if opts.dry_run.unwrap_or(false) {
+        req.reply_complete(net::ok());
+        return Ok(());
+    }
+
+    // Install and validate every package before changing any spec, so that a manifest which
+    // can't be applied leaves the loaded services as they were.
+    let mut packages = HashMap::new();
+    for operation in &plan {
+        let spec = match operation {
+            Operation::Load(spec) | Operation::Update { to: spec, .. } => spec,
+            Operation::Unload(_) => continue,
+        };
+        let source = InstallSource::Ident(spec.ident.clone(), PackageTarget::active_target());
+        let package =
+            util::pkg::satisfy_or_install(req, &source, &spec.bldr_url, &spec.channel).await?;
+        spec.validate(&package)?;
+        packages.insert(spec.ident.clone(), package);
+    }
+
+    let changes = plan.len();
+    for operation in plan {
+        match operation {
+            Operation::Load(spec) => {
+                mgr.cfg.save_spec_for(&spec)?;
+                let package = &packages[&spec.ident];
+                event::service_loaded(spec.to_service_metadata(package.ident(),
+                                                               mgr.cfg.organization.as_deref()));
+            }
+            Operation::Update { to, .. } => mgr.cfg.save_spec_for(&to)?,
+            Operation::Unload(service_spec) => {
+                let action = SupervisorAction::UnloadService { service_spec,
+                                                               shutdown_input:
+                                                                   ShutdownInput::default() };
+                send_action(action, action_sender)?;
+            }
+        }
+    }
+    req.info(format!("Supervisor applying {} change(s). See the Supervisor output for more \
+                      details.",
+                     changes))?;
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
+/// Send the output captured for a loaded service. If the client asks to follow it, this keeps
+/// sending output as the service writes it until the client disconnects.
+pub fn service_logs(mgr: &ManagerState,
+                    req: &mut CtlRequest,
+                    opts: protocol::ctl::SvcLogs)
+                    -> NetResult<()> {
+    let ident = PackageIdent::try_from(opts.ident.ok_or_else(err_update_client)?)?;
+    let spec = match mgr.cfg.spec_for_ident(&ident) {
+        Some(spec) => spec,
+        None => return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", ident))),
+    };
+    let path = habitat_core::fs::svc_log_file(&spec.ident.name);
+    let lines = opts.lines.unwrap_or(DEFAULT_LOG_LINES) as usize;
+    let tail = match service_log::tail(&path, lines) {
+        Ok(tail) => tail,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(net::err(ErrCode::NotFound,
+                                format!("No output has been captured for {}",
+                                        ident)));
+        }
+        Err(e) => return Err(net::err(ErrCode::Internal, e.to_string())),
+    };
+    for line in tail {
+        reply_log_line(req, &line);
+    }
+    if opts.follow.unwrap_or(false) {
+        // Following lasts until the client disconnects, so it gets its own thread rather than
+        // holding up the gateway or the runtime's worker threads.
+        let mut req = req.clone();
+        thread::Builder::new().name(format!("svc-logs-{}", spec.ident.name))
+                              .spawn(move || {
+                                  match follow_log(&mut req, &path) {
+                                      Ok(()) => req.reply_complete(net::ok()),
+                                      Err(e) => {
+                                          req.reply_complete(net::err(ErrCode::Internal,
+                                                                      e.to_string()))
+                                      }
+                                  }
+                              })
+                              .map_err(|e| net::err(ErrCode::Internal, e.to_string()))?;
+        return Ok(());
+    }
+
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
 /// # Locking (see locking.md)
 /// * `GatewayState::inner` (read)
 pub fn service_status_gsr(mgr: &ManagerState,
@@ -320,6 +644,7 @@ pub fn service_status_gsr(mgr: &ManagerState,
         serde_json::from_str(mgr.gateway_state.lock_gsr().services_data()).map_err(Error::ServiceDeserializationError)?;
 
     if let Some(ident) = opts.ident {
+        let ident = PackageIdent::try_from(ident)?;
         for status in statuses {
             if status.pkg.ident.satisfies(&ident) {
                 let msg: protocol::types::ServiceStatus = status.into();
@@ -351,6 +676,81 @@ pub fn service_status_gsr(mgr: &ManagerState,
 // Private helper functions
 fn err_update_client() -> net::NetErr { net::err(ErrCode::UpdateClient, "client out of date") }
 
+/// Send a line of a service's output to the client without echoing it to the Supervisor's own
+/// output.
+fn reply_log_line(req: &mut CtlRequest, line: &str) {
+    let mut msg = protocol::ctl::ConsoleLine::default();
+    msg.line = format!("{}\n", line);
+    req.reply_partial(msg);
+}
+
+/// Send the lines appended to the log file `path` until the client disconnects.
+fn follow_log(req: &mut CtlRequest, path: &Path) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::End(0))?;
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    while !req.is_closed() {
+        if reader.read_line(&mut line)? > 0 {
+            // A line without a newline is still being written; finish reading it first.
+            if line.ends_with('\n') {
+                reply_log_line(req, line.trim_end_matches('\n'));
+                line.clear();
+            }
+            continue;
+        }
+        // The Launcher rotates a log by moving it aside and starting a new file. Once we've read
+        // everything from the old file, switch to the new one.
+        let position = reader.seek(SeekFrom::Current(0))?;
+        if is_rotated(reader.get_ref(), path, position) {
+            reader = BufReader::new(File::open(path)?);
+        } else {
+            thread::sleep(LOG_FOLLOW_INTERVAL);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` now names a different file than the open `file`, or the file at `path` is
+/// shorter than the `position` we've read up to, which means it was truncated or replaced.
+fn is_rotated(file: &File, path: &Path, position: u64) -> bool {
+    let current = match File::open(path) {
+        Ok(current) => current,
+        Err(_) => return false,
+    };
+    match (file_identity(file), file_identity(&current)) {
+        (Ok(open), Ok(current)) if open != current => true,
+        _ => {
+            current.metadata()
+                   .map(|m| m.len() < position)
+                   .unwrap_or(false)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_identity(file: &File) -> io::Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = file.metadata()?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+/// The volume serial number and file index together identify a file on Windows, like a device
+/// and inode number do on Unix.
+#[cfg(windows)]
+fn file_identity(file: &File) -> io::Result<(u64, u64)> {
+    use std::{mem,
+              os::windows::io::AsRawHandle};
+    use winapi::um::fileapi::{self,
+                              BY_HANDLE_FILE_INFORMATION};
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { mem::zeroed() };
+    if unsafe { fileapi::GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let index = u64::from(info.nFileIndexHigh) << 32 | u64::from(info.nFileIndexLow);
+    Ok((u64::from(info.dwVolumeSerialNumber), index))
+}
+
 #[derive(Deserialize)]
 struct ServiceStatus {
     pkg:           Pkg,
@@ -442,3 +842,50 @@ fn send_action(action: SupervisorAction, sender: &ActionSender) -> NetResult<()>
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn log_is_rotated_when_moved_aside() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("out.log");
+        fs::write(&path, "one\ntwo\n").unwrap();
+        let file = File::open(&path).unwrap();
+        assert!(!is_rotated(&file, &path, 8));
+
+        fs::rename(&path, tmpdir.path().join("out.log.1")).unwrap();
+        assert!(!is_rotated(&file, &path, 8), "nothing is at the path yet");
+
+        // The new file is longer than the old one, which a length comparison would miss.
+        fs::write(&path, "three\nfour\nfive\nsix\n").unwrap();
+        assert!(is_rotated(&file, &path, 8));
+    }
+
+    #[test]
+    fn log_is_rotated_when_truncated() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("out.log");
+        fs::write(&path, "one\ntwo\n").unwrap();
+        let file = File::open(&path).unwrap();
+        assert!(!is_rotated(&file, &path, 8));
+
+        fs::write(&path, "three\n").unwrap();
+        assert!(is_rotated(&file, &path, 8));
+    }
+
+    #[test]
+    fn log_is_not_rotated_when_appended_to() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("out.log");
+        fs::write(&path, "one\n").unwrap();
+        let file = File::open(&path).unwrap();
+        fs::OpenOptions::new().append(true)
+                              .open(&path)
+                              .and_then(|mut f| io::Write::write_all(&mut f, b"two\n"))
+                              .unwrap();
+        assert!(!is_rotated(&file, &path, 4));
+    }
+}
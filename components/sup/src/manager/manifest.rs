@@ -0,0 +1,208 @@
+//! Declarative lists of the services a Supervisor should run.
+//!
+//! A manifest is a TOML file with a `[[service]]` table for each service, using the same keys as
+//! a service spec file:
+//!
+//! ```toml
+//! [[service]]
+//! ident = "core/redis"
+//! update_strategy = "rolling"
+//!
+//! [[service]]
+//! ident = "core/nginx"
+//! binds = ["backend:redis.default"]
+//! ```
+//!
+//! Applying a manifest only changes the Supervisor's spec files. The Supervisor then starts,
+//! stops, restarts, or updates services to match them, just as it does when a spec file is
+//! changed by `hab svc load` or `hab svc update`.
+
+use super::service::{spec::ServiceSpec,
+                     DesiredState};
+use crate::error::Error;
+use habitat_core::package::PackageIdent;
+use std::{collections::{HashMap,
+                        HashSet},
+          fmt,
+          result,
+          str::FromStr};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default, rename = "service")]
+    services: Vec<ServiceSpec>,
+}
+
+impl Manifest {
+    /// The operations which bring the services with the specs `loaded` in line with this
+    /// manifest. Services are matched by name, just as spec files are.
+    pub fn plan<I>(&self, loaded: I) -> Vec<Operation>
+        where I: IntoIterator<Item = ServiceSpec>
+    {
+        let mut loaded = loaded.into_iter()
+                               .map(|spec| (spec.ident.name.clone(), spec))
+                               .collect::<HashMap<_, _>>();
+        let mut plan = Vec::new();
+        for spec in &self.services {
+            match loaded.remove(&spec.ident.name) {
+                Some(ref from) if from == spec => {}
+                Some(from) => {
+                    plan.push(Operation::Update { from,
+                                                  to: spec.clone() })
+                }
+                None => plan.push(Operation::Load(spec.clone())),
+            }
+        }
+        let mut unloaded = loaded.into_iter()
+                                 .map(|(_, spec)| Operation::Unload(spec))
+                                 .collect::<Vec<_>>();
+        unloaded.sort_by(|a, b| a.ident().name.cmp(&b.ident().name));
+        plan.extend(unloaded);
+        plan
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(toml: &str) -> result::Result<Self, Self::Err> {
+        let manifest: Manifest = toml::from_str(toml).map_err(Error::ManifestParse)?;
+        let mut names = HashSet::new();
+        for spec in &manifest.services {
+            if spec.ident == PackageIdent::default() {
+                return Err(Error::MissingRequiredIdent);
+            }
+            if !names.insert(&spec.ident.name) {
+                return Err(Error::DuplicateManifestService(spec.ident.name.clone()));
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// A change to the Supervisor's services needed to apply a manifest.
+#[derive(Debug, PartialEq)]
+pub enum Operation {
+    /// Load a service which is not loaded yet.
+    Load(ServiceSpec),
+    /// Change the spec of a loaded service.
+    Update {
+        from: ServiceSpec,
+        to:   ServiceSpec,
+    },
+    /// Unload a service which is not in the manifest.
+    Unload(ServiceSpec),
+}
+
+impl Operation {
+    pub fn ident(&self) -> &PackageIdent {
+        match self {
+            Operation::Load(spec) | Operation::Unload(spec) => &spec.ident,
+            Operation::Update { to, .. } => &to.ident,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Load(spec) => write!(f, "Load {}", spec.ident),
+            Operation::Unload(spec) => write!(f, "Unload {}", spec.ident),
+            Operation::Update { from, to } => {
+                let effect = match (from.desired_state, to.desired_state) {
+                    (DesiredState::Down, DesiredState::Up) => " and start it",
+                    (DesiredState::Up, DesiredState::Down) => " and stop it",
+                    (DesiredState::Up, DesiredState::Up) if !from.can_apply_live(to) => {
+                        " and restart it"
+                    }
+                    _ => "",
+                };
+                write!(f, "Update {}{}", to.ident, effect)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use habitat_core::ChannelIdent;
+
+    fn spec(ident: &str) -> ServiceSpec { ServiceSpec::new(ident.parse().unwrap()) }
+
+    #[test]
+    fn parses_service_specs() {
+        let toml = r#"
+            [[service]]
+            ident = "core/redis"
+            channel = "unstable"
+
+            [[service]]
+            ident = "core/nginx"
+            desired_state = "down"
+        "#;
+        let manifest: Manifest = toml.parse().unwrap();
+        let mut redis = spec("core/redis");
+        redis.channel = ChannelIdent::unstable();
+        let mut nginx = spec("core/nginx");
+        nginx.desired_state = DesiredState::Down;
+        assert_eq!(manifest.services, vec![redis, nginx]);
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        match "[[service]]\ngroup = \"prod\"".parse::<Manifest>() {
+            Err(Error::MissingRequiredIdent) => (),
+            other => panic!("Expected Error::MissingRequiredIdent, got {:?}", other),
+        }
+        let duplicate =
+            "[[service]]\nident = \"core/redis\"\n[[service]]\nident = \"core/redis/5.0.7\"";
+        match duplicate.parse::<Manifest>() {
+            Err(Error::DuplicateManifestService(name)) => assert_eq!(name, "redis"),
+            other => panic!("Expected Error::DuplicateManifestService, got {:?}", other),
+        }
+        assert!("[[services]]\nident = \"core/redis\"".parse::<Manifest>()
+                                                      .is_err());
+    }
+
+    #[test]
+    fn plans_loads_updates_and_unloads() {
+        let mut live = spec("core/live");
+        live.channel = ChannelIdent::unstable();
+        let mut moved = spec("core/moved");
+        moved.group = "prod".to_string();
+        let mut stopped = spec("core/stopped");
+        stopped.desired_state = DesiredState::Down;
+        let manifest = Manifest { services: vec![spec("core/unchanged"),
+                                                 spec("core/new"),
+                                                 live.clone(),
+                                                 moved.clone(),
+                                                 stopped.clone()], };
+        let loaded = vec![spec("core/unchanged"),
+                          spec("core/zombie"),
+                          spec("core/live"),
+                          spec("core/moved"),
+                          spec("core/stopped"),
+                          spec("core/old")];
+
+        let plan = manifest.plan(loaded);
+        assert_eq!(plan,
+                   vec![Operation::Load(spec("core/new")),
+                        Operation::Update { from: spec("core/live"),
+                                            to:   live, },
+                        Operation::Update { from: spec("core/moved"),
+                                            to:   moved, },
+                        Operation::Update { from: spec("core/stopped"),
+                                            to:   stopped, },
+                        Operation::Unload(spec("core/old")),
+                        Operation::Unload(spec("core/zombie")),]);
+        assert_eq!(plan.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                   vec!["Load core/new",
+                        "Update core/live",
+                        "Update core/moved and restart it",
+                        "Update core/stopped and stop it",
+                        "Unload core/old",
+                        "Unload core/zombie"]);
+    }
+}
@@ -12,6 +12,7 @@ mod context;
 mod health;
 mod hook_runner;
 mod hooks;
+mod metrics;
 #[cfg(windows)]
 mod pipe_hook_client;
 pub mod spec;
@@ -55,7 +56,8 @@ pub use habitat_common::templating::{config::{Cfg,
                                                PkgProxy}};
 use habitat_common::{outputln,
                      templating::{config::CfgRenderer,
-                                  hooks::Hook},
+                                  hooks::{ExitCode,
+                                          Hook}},
                      FeatureFlag};
 #[cfg(windows)]
 use habitat_core::os::users;
@@ -64,11 +66,15 @@ use habitat_core::{crypto::hash,
                         svc_hooks_path,
                         SvcDir,
                         FS_ROOT_PATH},
-                   os::process::ShutdownTimeout,
+                   os::process::{ShutdownTimeout,
+                                 Signal},
                    package::{metadata::Bind,
                              PackageIdent,
                              PackageInstall},
-                   service::{HealthCheckInterval,
+                   service::{HealthCheck,
+                             HealthCheckInterval,
+                             MaxUnavailable,
+                             RestartPolicy,
                              ServiceBind,
                              ServiceGroup},
                    ChannelIdent};
@@ -92,7 +98,9 @@ use std::{self,
           path::{Path,
                  PathBuf},
           result,
-          sync::{Arc,
+          sync::{atomic::{AtomicBool,
+                          Ordering},
+                 Arc,
                  Mutex},
           time::SystemTime};
 
@@ -184,6 +192,8 @@ pub struct Service {
     pub topology:            Topology,
     pub update_strategy:     UpdateStrategy,
     pub update_condition:    UpdateCondition,
+    pub max_unavailable:     MaxUnavailable,
+    pub canaries:            u32,
     pub cfg:                 Cfg,
     pub pkg:                 Pkg,
     pub sys:                 Arc<Sys>,
@@ -196,6 +206,9 @@ pub struct Service {
     // migrated away from the event loop architecture to an architecture that had a top level
     // `Service` future. See https://github.com/habitat-sh/habitat/issues/7112
     initialization_state:    Arc<RwLock<InitializationState>>,
+    /// Whether a failure of the init hook was reported since the hook last succeeded. A failed
+    /// init hook is run again on every tick, but its failure is only published once.
+    init_failure_reported:   Arc<AtomicBool>,
 
     config_renderer:        CfgRenderer,
     // Note: This field is really only needed for serializing a
@@ -209,6 +222,9 @@ pub struct Service {
     // hook, we need to wrap some Arc<Mutex<_>> protection around it
     // :(
     health_check_result:    Arc<Mutex<HealthCheckResult>>,
+    /// The health check result that was last shared with the ring. When the cached result
+    /// differs from this, a new service rumor needs to be gossiped.
+    gossiped_health:        HealthCheckResult,
     last_election_status:   ElectionStatus,
     /// The mapping of bind name to a service group, specified by the
     /// user when the service definition was loaded into the Supervisor.
@@ -240,6 +256,8 @@ pub struct Service {
     supervisor:             Arc<Mutex<Supervisor>>,
     svc_encrypted_password: Option<String>,
     health_check_interval:  HealthCheckInterval,
+    health_check:           Option<HealthCheck>,
+    restart_policy:         RestartPolicy,
 
     gateway_state: Arc<GatewayState>,
 
@@ -276,6 +294,7 @@ impl Service {
                      channel: spec.channel,
                      desired_state: spec.desired_state,
                      health_check_result: Arc::new(Mutex::new(HealthCheckResult::Unknown)),
+                     gossiped_health: HealthCheckResult::Unknown,
                      hooks: HookTable::load(&pkg.name,
                                             &hooks_root,
                                             svc_hooks_path(&service_group.service()),
@@ -285,6 +304,7 @@ impl Service {
                      needs_restart: false,
                      initialization_state:
                          Arc::new(RwLock::new(InitializationState::Uninitialized)),
+                     init_failure_reported: Arc::new(AtomicBool::new(false)),
                      manager_fs_cfg,
                      supervisor: Arc::new(Mutex::new(Supervisor::new(&service_group,
                                                                      pid_source))),
@@ -299,9 +319,13 @@ impl Service {
                      topology: spec.topology,
                      update_strategy: spec.update_strategy,
                      update_condition: spec.update_condition,
+                     max_unavailable: spec.max_unavailable,
+                     canaries: spec.canaries,
                      config_from: spec.config_from,
                      svc_encrypted_password: spec.svc_encrypted_password,
                      health_check_interval: spec.health_check_interval,
+                     health_check: spec.health_check,
+                     restart_policy: spec.restart_policy,
                      gateway_state,
                      health_check_handle: None,
                      post_run_handle: None,
@@ -413,6 +437,7 @@ impl Service {
         debug!("Starting health checks for {}", self.pkg.ident);
         let mut rx = health::check_repeatedly(Arc::clone(&self.supervisor),
                                               self.hooks.health_check.clone(),
+                                              self.health_check.clone(),
                                               self.health_check_interval,
                                               self.service_group.clone(),
                                               self.pkg.clone(),
@@ -516,8 +541,21 @@ impl Service {
         gs.lock_gsw().remove(&service_group);
 
         if let Some(hook) = self.post_stop() {
-            if let Err(e) = hook.into_future().await {
-                outputln!(preamble service_group, "Service stop failed: {}", e);
+            match hook.into_future().await {
+                Ok((true, _)) => (),
+                Ok((false, _)) => {
+                    event::hook_failed(self.to_service_metadata(),
+                                       hooks::PostStopHook::file_name(),
+                                       None,
+                                       None);
+                }
+                Err(e) => {
+                    outputln!(preamble service_group, "Service stop failed: {}", e);
+                    event::hook_failed(self.to_service_metadata(),
+                                       hooks::PostStopHook::file_name(),
+                                       None,
+                                       Some(e.to_string()));
+                }
             }
         }
     }
@@ -533,7 +571,8 @@ impl Service {
 
     /// Performs updates and executes hooks.
     ///
-    /// Returns `true` if the service was marked to be restarted or reconfigured.
+    /// Returns `true` if the service was marked to be restarted or reconfigured, or if its health
+    /// changed; in either case a new service rumor should be gossiped.
     pub fn tick(&mut self, census_ring: &CensusRing, launcher: &LauncherCli) -> bool {
         // We may need to block the service from starting until all
         // its binds are satisfied
@@ -605,6 +644,11 @@ impl Service {
                             outputln!(preamble self.service_group,
                                       "Executing hooks; {} is the leader",
                                       leader_id.to_string());
+                            if *leader_id == self.sys.member_id {
+                                event::election_won(self);
+                            } else {
+                                event::election_lost(self, leader_id);
+                            }
                             self.last_election_status = census_group.election_status;
                         }
                         self.execute_hooks(launcher, &template_update);
@@ -612,7 +656,24 @@ impl Service {
                 }
             }
         }
-        template_data_changed
+        let health_changed = self.health_changed_since_last_gossip();
+        template_data_changed || health_changed
+    }
+
+    /// Returns `true` if the cached health check result differs from the one that was last
+    /// gossiped, recording the current result as gossiped.
+    fn health_changed_since_last_gossip(&mut self) -> bool {
+        let current = *self.health_check_result
+                           .lock()
+                           .expect("Could not unlock health_check_result");
+        if current == self.gossiped_health {
+            false
+        } else {
+            debug!("Health of {} changed from '{}' to '{}'",
+                   self.service_group, self.gossiped_health, current);
+            self.gossiped_health = current;
+            true
+        }
     }
 
     pub fn to_spec(&self) -> ServiceSpec {
@@ -623,6 +684,8 @@ impl Service {
         spec.topology = self.topology;
         spec.update_strategy = self.update_strategy;
         spec.update_condition = self.update_condition;
+        spec.max_unavailable = self.max_unavailable;
+        spec.canaries = self.canaries;
         spec.binds = self.binds.clone();
         spec.binding_mode = self.binding_mode;
         spec.config_from = self.config_from.clone();
@@ -630,6 +693,8 @@ impl Service {
             spec.svc_encrypted_password = Some(password.clone())
         }
         spec.health_check_interval = self.health_check_interval;
+        spec.health_check = self.health_check.clone();
+        spec.restart_policy = self.restart_policy;
         spec.shutdown_timeout = self.shutdown_timeout;
         spec
     }
@@ -771,12 +836,137 @@ impl Service {
             .map(|b| b.exports.iter().collect())
     }
 
-    /// Updates the process state of the service's supervisor
+    /// Updates the process state of the service's supervisor, sending an event if the Supervisor
+    /// gives up on restarting the process.
     fn check_process(&mut self, launcher: &LauncherCli) -> bool {
+        let (up, crash_loop_exits) = {
+            let mut supervisor = self.supervisor.lock().expect("Couldn't lock supervisor");
+            let was_crash_loop = supervisor.status() == ProcessState::CrashLoop;
+            let up = supervisor.check_process(launcher, &self.restart_policy);
+            let crash_loop_exits =
+                if !was_crash_loop && supervisor.status() == ProcessState::CrashLoop {
+                    Some(supervisor.exits())
+                } else {
+                    None
+                };
+            (up, crash_loop_exits)
+        };
+        if let Some(exits) = crash_loop_exits {
+            event::service_crash_loop(self, exits, self.restart_policy.window());
+        }
+        up
+    }
+
+    /// Returns true if the service's process is down and its restart policy allows it to be
+    /// restarted now.
+    fn may_restart(&self) -> bool {
         self.supervisor
             .lock()
             .expect("Couldn't lock supervisor")
-            .check_process(launcher)
+            .may_restart(&self.restart_policy)
+    }
+
+    /// The state of the service's process.
+    pub fn process_state(&self) -> ProcessState {
+        self.supervisor
+            .lock()
+            .expect("Couldn't lock supervisor")
+            .status()
+    }
+
+    /// Update the metrics reported for this service on the HTTP gateway's `/metrics` endpoint.
+    pub fn record_metrics(&self) { metrics::record(self) }
+
+    /// Stop reporting this service's metrics, because it was unloaded.
+    pub fn remove_metrics(&self) { metrics::remove(self) }
+
+    /// Forget the previous exits of the service's process so that its restart policy starts over.
+    /// Called when the service is stopped on purpose.
+    pub fn forget_exits(&self) {
+        self.supervisor
+            .lock()
+            .expect("Couldn't lock supervisor")
+            .forget_exits();
+    }
+
+    /// Apply the settings of `spec` which can be changed without restarting the service (see
+    /// `ServiceSpec::can_apply_live`). Returns true if the settings for package updates changed,
+    /// in which case the service must be registered with the `ServiceUpdater` again.
+    pub fn apply_spec(&mut self, spec: &ServiceSpec) -> bool {
+        let updates_changed = self.bldr_url != spec.bldr_url
+                              || self.channel != spec.channel
+                              || self.update_strategy != spec.update_strategy
+                              || self.update_condition != spec.update_condition
+                              || self.max_unavailable != spec.max_unavailable
+                              || self.canaries != spec.canaries;
+        self.bldr_url = spec.bldr_url.clone();
+        self.channel = spec.channel.clone();
+        self.update_strategy = spec.update_strategy;
+        self.update_condition = spec.update_condition;
+        self.max_unavailable = spec.max_unavailable;
+        self.canaries = spec.canaries;
+        self.shutdown_timeout = spec.shutdown_timeout;
+        self.restart_policy = spec.restart_policy;
+
+        if self.health_check_interval != spec.health_check_interval
+           || self.health_check != spec.health_check
+        {
+            self.health_check_interval = spec.health_check_interval;
+            self.health_check = spec.health_check.clone();
+            // Health checks only run once the service is initialized; until then they pick up
+            // the new settings when they start.
+            if self.health_check_handle.is_some() {
+                self.restart_health_checks();
+            }
+        }
+        updates_changed
+    }
+
+    /// Returns true if the service's package has a reload hook.
+    pub fn has_reload_hook(&self) -> bool { self.hooks.reload.is_some() }
+
+    /// Reload the service without restarting it. If `signal` is given it is sent to the service's
+    /// process, otherwise the reload hook is run.
+    pub fn reload(&self, signal: Option<Signal>) {
+        match signal {
+            Some(signal) => self.signal(signal),
+            None => {
+                if let Some(ref hook) = self.hooks.reload {
+                    let _timer = hook_timer("reload");
+                    outputln!(preamble self.service_group, "Reloading");
+                    let result = hook.run(&self.service_group,
+                                          &self.pkg,
+                                          self.svc_encrypted_password.as_ref());
+                    self.report_hook_failure(hooks::ReloadHook::file_name(), result);
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: Signal) {
+        let pid = self.supervisor
+                      .lock()
+                      .expect("Couldn't lock supervisor")
+                      .pid();
+        match pid {
+            Some(pid) => {
+                outputln!(preamble self.service_group, "Sending {} to process {}", signal, pid);
+                if let Err(e) = habitat_core::os::process::signal(pid, signal) {
+                    outputln!(preamble self.service_group, "Failed to send {}: {}", signal, e);
+                }
+            }
+            None => {
+                outputln!(preamble self.service_group,
+                          "Not sending {}; the service is not running", signal);
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    fn signal(&self, signal: Signal) {
+        outputln!(preamble self.service_group,
+                  "Not sending {}; signals are not supported on Windows", signal);
     }
 
     /// Updates the service configuration with data from a census group if the census group has
@@ -791,6 +981,7 @@ impl Service {
                 }
                 self.cfg
                     .set_gossip(config.incarnation, config.value.clone());
+                event::service_config_applied(self, config.incarnation);
                 true
             }
             None => false,
@@ -841,6 +1032,10 @@ impl Service {
                                           self.sys.as_sys_info(),
                                           exported);
         rumor.incarnation = incarnation;
+        let health = *self.health_check_result
+                          .lock()
+                          .expect("Could not unlock health_check_result");
+        rumor.health = health.into();
         rumor
     }
 
@@ -855,21 +1050,25 @@ impl Service {
                                               self.svc_encrypted_password.clone());
             // These clones are unfortunate. async/await will make this much better.
             let service_group = self.service_group.clone();
+            let service_metadata = self.to_service_metadata();
             let initialization_state = Arc::clone(&self.initialization_state);
-            let initialization_state_for_err = Arc::clone(&self.initialization_state);
+            let init_failure_reported = Arc::clone(&self.init_failure_reported);
             let f = async move {
-                match hook_runner.into_future().await {
-                    Ok((exit_value, _)) => {
-                        *initialization_state.write() = if exit_value {
-                            InitializationState::InitializerFinished
-                        } else {
-                            InitializationState::Uninitialized
-                        };
+                let error = match hook_runner.into_future().await {
+                    Ok((true, _)) => {
+                        *initialization_state.write() = InitializationState::InitializerFinished;
+                        init_failure_reported.store(false, Ordering::Relaxed);
+                        return;
                     }
+                    Ok((false, _)) => None,
                     Err(e) => {
                         outputln!(preamble service_group, "Service initialization failed: {}", e);
-                        *initialization_state_for_err.write() = InitializationState::Uninitialized;
+                        Some(e.to_string())
                     }
+                };
+                *initialization_state.write() = InitializationState::Uninitialized;
+                if !init_failure_reported.swap(true, Ordering::Relaxed) {
+                    event::hook_failed(service_metadata, hooks::InitHook::file_name(), None, error);
                 }
             };
             let (f, handle) = future::abortable(f);
@@ -891,17 +1090,17 @@ impl Service {
         let _timer = hook_timer("reconfigure");
 
         if let Some(ref hook) = self.hooks.reload {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.svc_encrypted_password.as_ref())
-                .ok();
+            let result = hook.run(&self.service_group,
+                                  &self.pkg,
+                                  self.svc_encrypted_password.as_ref());
+            self.report_hook_failure(hooks::ReloadHook::file_name(), result);
         }
 
         if let Some(ref hook) = self.hooks.reconfigure {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.svc_encrypted_password.as_ref())
-                .ok();
+            let result = hook.run(&self.service_group,
+                                  &self.pkg,
+                                  self.svc_encrypted_password.as_ref());
+            self.report_hook_failure(hooks::ReconfigureHook::file_name(), result);
             // The intention here is to do a health check soon after a service's configuration
             // changes, as a way to (among other things) detect potential impacts when bound
             // services change exported configuration.
@@ -915,13 +1114,33 @@ impl Service {
                                               self.service_group.clone(),
                                               self.pkg.clone(),
                                               self.svc_encrypted_password.clone());
-            let f = HookRunner::retryable_future(hook_runner);
+            let service_metadata = self.to_service_metadata();
+            let f = HookRunner::retryable_future(hook_runner, move |result| {
+                let (exit_status, error) = match result {
+                    Ok(ExitCode(code)) => (Some(*code), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                event::hook_failed(service_metadata,
+                                   hooks::PostRunHook::file_name(),
+                                   exit_status,
+                                   error);
+            });
             let (f, handle) = future::abortable(f);
             self.post_run_handle = Some(handle);
             tokio::spawn(f);
         }
     }
 
+    /// Send an event if a hook which exits with an `ExitCode` failed.
+    fn report_hook_failure(&self, hook: &str, result: habitat_common::error::Result<ExitCode>) {
+        let (exit_status, error) = match result {
+            Ok(ExitCode(0)) => return,
+            Ok(ExitCode(code)) => (Some(code), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        event::hook_failed(self.to_service_metadata(), hook, exit_status, error);
+    }
+
     /// Stop the `post-run` retry future. This will stop this retry loop regardless of `post-run`'s
     /// exit code.
     fn stop_post_run(&mut self) {
@@ -1058,15 +1277,15 @@ impl Service {
             }
             InitializationState::Initialized => {
                 // If the service is initialized and the process is not running, the process
-                // unexpectedly died and needs to be restarted.
-                if !up || template_update.needs_restart() {
+                // unexpectedly died and needs to be restarted once its restart policy allows it.
+                if template_update.needs_restart() || (!up && self.may_restart()) {
                     // TODO (DM): This flag is a hack. We have the `TaskExecutor` here. We could
                     // just schedule the `stop` future, but the `Manager` wraps
                     // the `stop` future with additional functionality. Can we
                     // refactor to make this flag unnecessary?
                     self.needs_restart = true;
                     return true;
-                } else if template_update.needs_reconfigure() {
+                } else if up && template_update.needs_reconfigure() {
                     // Only reconfigure if we did NOT restart the service
                     self.reconfigure();
                     return true;
@@ -1107,6 +1326,7 @@ impl Service {
             if self.cache_service_file(&service_file) {
                 outputln!(preamble self.service_group, "Service file updated, {}",
                     service_file.filename);
+                event::service_file_updated(self, &service_file.filename, service_file.incarnation);
                 updated = true;
             }
         }
@@ -1248,9 +1468,9 @@ impl<'a> Serialize for ServiceProxy<'a> {
         where S: Serializer
     {
         let num_fields: usize = if self.config_rendering == ConfigRendering::Full {
-            27
+            29
         } else {
-            26
+            28
         };
 
         let s = &self.service;
@@ -1291,6 +1511,8 @@ impl<'a> Serialize for ServiceProxy<'a> {
         strukt.serialize_field("topology", &s.topology)?;
         strukt.serialize_field("update_strategy", &s.update_strategy)?;
         strukt.serialize_field("update_condition", &s.update_condition)?;
+        strukt.serialize_field("max_unavailable", &s.max_unavailable)?;
+        strukt.serialize_field("canaries", &s.canaries)?;
         strukt.serialize_field("user_config_updated", &s.user_config_updated)?;
         strukt.end()
     }
@@ -1351,6 +1573,25 @@ mod tests {
                                                             didn't")
     }
 
+    #[tokio::test]
+    async fn restarts_are_counted_across_restarts_of_the_service() {
+        let restart_policy = RestartPolicy::default();
+        let service = initialize_test_service().await;
+        let restarts = metrics::restarts(&service);
+
+        // Each time the process crashes, the service is stopped, and started again with a new
+        // Supervisor.
+        for _ in 0..2 {
+            let service = initialize_test_service().await;
+            service.supervisor
+                   .lock()
+                   .expect("Couldn't lock supervisor")
+                   .record_exit(&restart_policy);
+            service.record_metrics();
+        }
+        assert_eq!(metrics::restarts(&service), restarts + 2);
+    }
+
     #[tokio::test]
     async fn service_proxy_conforms_to_the_schema() {
         let service = initialize_test_service().await;
@@ -3,18 +3,27 @@ use crate::{error::Error,
                                hooks::HealthCheckHook,
                                supervisor::Supervisor,
                                ProcessOutput,
-                               ProcessState}};
+                               ProcessState,
+                               StandardStreams}};
+use habitat_butterfly::rumor::service::ServiceHealth;
 use habitat_common::{outputln,
-                     templating::package::Pkg};
-use habitat_core::service::{HealthCheckInterval,
+                     templating::{hooks,
+                                  package::Pkg}};
+use habitat_core::service::{HealthCheck,
+                            HealthCheckInterval,
                             ServiceGroup};
 use std::{cmp,
           convert::TryFrom,
           fmt,
+          fs,
+          process::{self,
+                    Stdio},
           sync::{Arc,
                  Mutex},
-          time::Duration};
-use tokio::{sync::mpsc::{self,
+          time::{Duration,
+                 Instant}};
+use tokio::{net::TcpStream,
+            sync::mpsc::{self,
                          UnboundedReceiver},
             time};
 
@@ -29,6 +38,32 @@ pub enum HealthCheckResult {
     Unknown,
 }
 
+impl Default for HealthCheckResult {
+    fn default() -> Self { HealthCheckResult::Unknown }
+}
+
+impl From<HealthCheckResult> for ServiceHealth {
+    fn from(result: HealthCheckResult) -> Self {
+        match result {
+            HealthCheckResult::Ok => ServiceHealth::Ok,
+            HealthCheckResult::Warning => ServiceHealth::Warning,
+            HealthCheckResult::Critical => ServiceHealth::Critical,
+            HealthCheckResult::Unknown => ServiceHealth::Unknown,
+        }
+    }
+}
+
+impl From<ServiceHealth> for HealthCheckResult {
+    fn from(health: ServiceHealth) -> Self {
+        match health {
+            ServiceHealth::Ok => HealthCheckResult::Ok,
+            ServiceHealth::Warning => HealthCheckResult::Warning,
+            ServiceHealth::Critical => HealthCheckResult::Critical,
+            ServiceHealth::Unknown => HealthCheckResult::Unknown,
+        }
+    }
+}
+
 /// Convert health check hook exit codes into `HealthCheckResult`.
 impl TryFrom<i32> for HealthCheckResult {
     type Error = Error;
@@ -56,28 +91,57 @@ impl fmt::Display for HealthCheckResult {
     }
 }
 
+/// The output of a built-in health check run by the Supervisor instead of a hook.
+#[derive(Debug)]
+pub struct NativeCheckOutput {
+    pub result:           HealthCheckResult,
+    pub standard_streams: StandardStreams,
+    /// The exit code of an `exec` check's command, if it ran to completion.
+    pub exit_code:        Option<i32>,
+}
+
+impl NativeCheckOutput {
+    fn passed(stdout: String) -> Self {
+        Self { result:           HealthCheckResult::Ok,
+               standard_streams: StandardStreams { stdout: Some(stdout),
+                                                   stderr: None, },
+               exit_code:        None, }
+    }
+
+    fn failed(result: HealthCheckResult, stderr: String) -> Self {
+        Self { result,
+               standard_streams: StandardStreams { stdout: None,
+                                                   stderr: Some(stderr), },
+               exit_code: None }
+    }
+}
+
 /// The possible statuses from running a health check hook.
 pub enum HealthCheckHookStatus {
     Ran(ProcessOutput, Duration),
     FailedToRun(Duration),
     FailedToStart,
     NoHook,
+    /// A built-in health check ran instead of a hook.
+    Native(NativeCheckOutput, Duration),
 }
 
 impl HealthCheckHookStatus {
     pub fn maybe_duration(&self) -> Option<Duration> {
-        if let Self::Ran(_, duration) | Self::FailedToRun(duration) = self {
-            Some(*duration)
-        } else {
-            None
+        match self {
+            Self::Ran(_, duration) | Self::FailedToRun(duration) | Self::Native(_, duration) => {
+                Some(*duration)
+            }
+            Self::FailedToStart | Self::NoHook => None,
         }
     }
 
-    pub fn maybe_process_output(self) -> Option<ProcessOutput> {
-        if let Self::Ran(output, _) = self {
-            Some(output)
-        } else {
-            None
+    /// The exit code and standard streams of the check, if it produced any.
+    pub fn into_output(self) -> (Option<i32>, StandardStreams) {
+        match self {
+            Self::Ran(output, _) => (output.exit_status().code(), output.standard_streams()),
+            Self::Native(output, _) => (output.exit_code, output.standard_streams),
+            _ => (None, StandardStreams::default()),
         }
     }
 }
@@ -94,14 +158,18 @@ pub struct HealthCheckBundle {
     pub interval: HealthCheckInterval,
 }
 
-/// Run the health check hook and get the hook status and result.
+/// Run the health check hook and get the hook status and result. A built-in health check takes
+/// precedence over the hook.
 async fn check(supervisor: Arc<Mutex<Supervisor>>,
                hook: Option<Arc<HealthCheckHook>>,
+               health_check: Option<HealthCheck>,
                service_group: ServiceGroup,
                package: Pkg,
                password: Option<String>)
                -> (HealthCheckHookStatus, HealthCheckResult) {
-    let status = if let Some(hook) = hook {
+    let status = if let Some(health_check) = health_check {
+        check_natively(&health_check, &service_group, &package).await
+    } else if let Some(hook) = hook {
         let result = hook_runner::HookRunner::new(hook,
                                                   service_group.clone(),
                                                   package.clone(),
@@ -146,6 +214,7 @@ async fn check(supervisor: Arc<Mutex<Supervisor>>,
             // unknown.
             HealthCheckResult::Unknown
         }
+        HealthCheckHookStatus::Native(output, _) => output.result,
         HealthCheckHookStatus::NoHook => {
             //  There was no hook to run. Use the supervisor status as a healthcheck.
             match supervisor.lock()
@@ -153,7 +222,7 @@ async fn check(supervisor: Arc<Mutex<Supervisor>>,
                             .status()
             {
                 ProcessState::Up => HealthCheckResult::Ok,
-                ProcessState::Down => HealthCheckResult::Critical,
+                ProcessState::Down | ProcessState::CrashLoop => HealthCheckResult::Critical,
             }
         }
     };
@@ -161,12 +230,159 @@ async fn check(supervisor: Arc<Mutex<Supervisor>>,
     (status, result)
 }
 
+/// Run a built-in health check. Its output is written to the same log files as the output of a
+/// `health-check` hook so that it is reported by the HTTP gateway.
+async fn check_natively(health_check: &HealthCheck,
+                        service_group: &ServiceGroup,
+                        package: &Pkg)
+                        -> HealthCheckHookStatus {
+    let start = Instant::now();
+    let timeout = health_check.timeout();
+    let output = match health_check {
+        HealthCheck::Tcp { address, .. } => {
+            match time::timeout(timeout, TcpStream::connect(address.as_str())).await {
+                Ok(Ok(_)) => NativeCheckOutput::passed(format!("Connected to {}", address)),
+                Ok(Err(e)) => {
+                    NativeCheckOutput::failed(HealthCheckResult::Critical,
+                                              format!("Failed to connect to {}: {}", address, e))
+                }
+                Err(_) => {
+                    NativeCheckOutput::failed(HealthCheckResult::Critical,
+                                              format!("Timed out connecting to {}", address))
+                }
+            }
+        }
+        HealthCheck::Http { url, status, .. } => check_http(url, *status, timeout).await,
+        HealthCheck::Exec { command, .. } => check_exec(command, package, timeout).await,
+    };
+    let duration = start.elapsed();
+
+    if output.result != HealthCheckResult::Ok {
+        if let Some(stderr) = &output.standard_streams.stderr {
+            outputln!(preamble service_group,
+                      "Health check {} failed: {}", health_check, stderr.trim_end());
+        }
+    }
+    write_output_logs(service_group, &output.standard_streams);
+    HealthCheckHookStatus::Native(output, duration)
+}
+
+async fn check_http(url: &str, status: Option<u16>, timeout: Duration) -> NativeCheckOutput {
+    // Health checks are aimed at the service itself, so never send them through a proxy.
+    let client = match reqwest::Client::builder().no_proxy()
+                                                 .timeout(timeout)
+                                                 .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return NativeCheckOutput::failed(HealthCheckResult::Unknown,
+                                             format!("Failed to create HTTP client: {}", e))
+        }
+    };
+    match client.get(url).send().await {
+        Ok(response) => {
+            let actual = response.status();
+            let healthy = match status {
+                Some(expected) => actual.as_u16() == expected,
+                None => actual.is_success(),
+            };
+            let msg = format!("GET {} responded with {}", url, actual);
+            if healthy {
+                NativeCheckOutput::passed(msg)
+            } else {
+                NativeCheckOutput::failed(HealthCheckResult::Critical, msg)
+            }
+        }
+        Err(e) => {
+            NativeCheckOutput::failed(HealthCheckResult::Critical,
+                                      format!("GET {} failed: {}", url, e))
+        }
+    }
+}
+
+/// Run `command` in the package's environment, as the service user when the Supervisor is able
+/// to.
+async fn check_exec(command: &[String], package: &Pkg, timeout: Duration) -> NativeCheckOutput {
+    let (program, args) = match command.split_first() {
+        Some(split) => split,
+        None => {
+            return NativeCheckOutput::failed(HealthCheckResult::Unknown,
+                                             "No health check command was given".to_string())
+        }
+    };
+    let mut cmd = process::Command::new(program);
+    cmd.args(args)
+       .envs(package.env.iter())
+       .stdin(Stdio::null())
+       .stdout(Stdio::piped())
+       .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use habitat_core::os::users;
+        use std::os::unix::process::CommandExt;
+
+        if users::can_run_services_as_svc_user() {
+            match (users::get_uid_by_name(&package.svc_user),
+                   users::get_gid_by_name(&package.svc_group))
+            {
+                (Some(uid), Some(gid)) => {
+                    cmd.uid(uid).gid(gid);
+                }
+                _ => {
+                    let msg = format!("No uid or gid could be found for {}:{}",
+                                      package.svc_user, package.svc_group);
+                    return NativeCheckOutput::failed(HealthCheckResult::Unknown, msg);
+                }
+            }
+        }
+    }
+
+    let mut cmd = tokio::process::Command::from(cmd);
+    cmd.kill_on_drop(true);
+    match time::timeout(timeout, cmd.output()).await {
+        Ok(Ok(output)) => {
+            let exit_code = output.status.code();
+            let result = exit_code.and_then(|code| HealthCheckResult::try_from(code).ok())
+                                  .unwrap_or(HealthCheckResult::Unknown);
+            let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            NativeCheckOutput { result,
+                                standard_streams: StandardStreams { stdout: Some(stdout),
+                                                                    stderr: Some(stderr), },
+                                exit_code }
+        }
+        Ok(Err(e)) => {
+            NativeCheckOutput::failed(HealthCheckResult::Unknown,
+                                      format!("Failed to run {}: {}", program, e))
+        }
+        Err(_) => {
+            NativeCheckOutput::failed(HealthCheckResult::Critical,
+                                      format!("Timed out running {}", program))
+        }
+    }
+}
+
+fn write_output_logs(service_group: &ServiceGroup, standard_streams: &StandardStreams) {
+    let logs =
+        [(hooks::stdout_log_path::<HealthCheckHook>(service_group), &standard_streams.stdout),
+         (hooks::stderr_log_path::<HealthCheckHook>(service_group), &standard_streams.stderr)];
+    for (path, content) in logs.iter() {
+        let content = content.as_deref().unwrap_or_default();
+        if let Err(e) = fs::write(path, content) {
+            debug!("Failed to write health check output to {}: {}",
+                   path.display(),
+                   e);
+        }
+    }
+}
+
 /// Start a task to repeatedly check the service health, followed by an appropriate delay, forever.
 /// The function returns the receiving end of a channel that acts as a stream of
 /// `HealthCheckBundle`s. When this receiving end is dropped or closed health checking will be
 /// stopped.
 pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
                         hook: Option<Arc<HealthCheckHook>>,
+                        health_check: Option<HealthCheck>,
                         nominal_interval: HealthCheckInterval,
                         service_group: ServiceGroup,
                         package: Pkg,
@@ -184,6 +400,7 @@ pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
         loop {
             let (status, result) = check(Arc::clone(&supervisor),
                                          hook.as_ref().map(Arc::clone),
+                                         health_check.clone(),
                                          service_group.clone(),
                                          package.clone(),
                                          password.clone()).await;
@@ -218,3 +435,143 @@ pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
 
     rx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use habitat_core::{os::users,
+                       package::{PackageIdent,
+                                 PackageInstall}};
+    use std::{io::{Read,
+                   Write},
+              net::TcpListener,
+              path::PathBuf,
+              thread};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    async fn pkg() -> Pkg {
+        let ident = PackageIdent::new("testing", "health", Some("1.0.0"), Some("20200101000000"));
+        let pkg_install = PackageInstall::new_from_parts(ident,
+                                                         PathBuf::from("/tmp"),
+                                                         PathBuf::from("/tmp"),
+                                                         PathBuf::from("/tmp"));
+        let mut pkg = Pkg::from_install(&pkg_install).await.unwrap();
+        // Run exec checks as whoever runs the tests.
+        pkg.svc_user = users::get_current_username().unwrap();
+        pkg.svc_group = users::get_current_groupname().unwrap();
+        pkg
+    }
+
+    fn service_group() -> ServiceGroup { ServiceGroup::new("health", "default", None).unwrap() }
+
+    fn native_result(status: HealthCheckHookStatus) -> HealthCheckResult {
+        match status {
+            HealthCheckHookStatus::Native(output, _) => output.result,
+            _ => panic!("A built-in health check should run instead of a hook"),
+        }
+    }
+
+    /// Answer one request with each of `statuses` in turn.
+    fn serve(statuses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/health", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = Vec::new();
+                let mut buf = [0; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buf[..n]);
+                }
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: \
+                                        close\r\n\r\n",
+                                       status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn tcp_check_passes_when_the_port_accepts_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let health_check =
+            HealthCheck::Tcp { address:      listener.local_addr().unwrap().to_string(),
+                               timeout_secs: 5, };
+
+        let status = check_natively(&health_check, &service_group(), &pkg().await).await;
+
+        assert_eq!(native_result(status), HealthCheckResult::Ok);
+    }
+
+    #[tokio::test]
+    async fn tcp_check_fails_when_the_port_is_closed() {
+        // Nothing listens on the port once the listener is dropped.
+        let address = TcpListener::bind("127.0.0.1:0").unwrap()
+                                                      .local_addr()
+                                                      .unwrap()
+                                                      .to_string();
+        let health_check = HealthCheck::Tcp { address,
+                                              timeout_secs: 5 };
+
+        let status = check_natively(&health_check, &service_group(), &pkg().await).await;
+
+        assert_eq!(native_result(status), HealthCheckResult::Critical);
+    }
+
+    #[tokio::test]
+    async fn http_check_passes_on_success_and_fails_on_server_errors() {
+        let url = serve(vec!["204 No Content", "503 Service Unavailable"]);
+
+        assert_eq!(check_http(&url, None, TIMEOUT).await.result,
+                   HealthCheckResult::Ok);
+        assert_eq!(check_http(&url, None, TIMEOUT).await.result,
+                   HealthCheckResult::Critical);
+    }
+
+    #[tokio::test]
+    async fn http_check_requires_the_expected_status() {
+        let url = serve(vec!["200 OK", "401 Unauthorized"]);
+
+        assert_eq!(check_http(&url, Some(401), TIMEOUT).await.result,
+                   HealthCheckResult::Critical);
+        assert_eq!(check_http(&url, Some(401), TIMEOUT).await.result,
+                   HealthCheckResult::Ok);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn exec_check_maps_exit_codes_to_results() {
+        let pkg = pkg().await;
+        for &(code, expected) in &[(0, HealthCheckResult::Ok),
+                                   (1, HealthCheckResult::Warning),
+                                   (2, HealthCheckResult::Critical),
+                                   (3, HealthCheckResult::Unknown)]
+        {
+            let command = vec!["sh".to_string(), "-c".to_string(), format!("exit {}", code)];
+
+            let output = check_exec(&command, &pkg, TIMEOUT).await;
+
+            assert_eq!(output.result, expected, "exit code {}", code);
+            assert_eq!(output.exit_code, Some(code));
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn exec_check_is_critical_when_it_times_out() {
+        let command = vec!["sleep".to_string(), "10".to_string()];
+        let start = Instant::now();
+
+        let output = check_exec(&command, &pkg().await, Duration::from_millis(100)).await;
+
+        assert_eq!(output.result, HealthCheckResult::Critical);
+        assert_eq!(output.exit_code, None);
+        assert!(start.elapsed() < TIMEOUT);
+    }
+}
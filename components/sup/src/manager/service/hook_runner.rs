@@ -15,6 +15,7 @@ use crate::error::{Error,
 use habitat_common::templating::hooks::Hook;
 use habitat_core::service::ServiceGroup;
 use std::{clone::Clone,
+          result,
           sync::Arc,
           time::{Duration,
                  Instant}};
@@ -51,17 +52,30 @@ impl<H> HookRunner<H> where H: Hook + Sync + 'static
                      passwd }
     }
 
-    pub async fn retryable_future(self) {
+    /// Run the hook until it succeeds, calling `on_failure` with the exit value or error of
+    /// the first failed attempt. The attempts after it are only logged.
+    pub async fn retryable_future<F>(self, on_failure: F)
+        where F: FnOnce(result::Result<&H::ExitValue, &Error>)
+    {
+        let mut on_failure = Some(on_failure);
         loop {
             match self.clone().into_future().await {
                 Ok((exit_value, _duration)) => {
                     if H::should_retry(&exit_value) {
+                        if let Some(on_failure) = on_failure.take() {
+                            on_failure(Ok(&exit_value));
+                        }
                         debug!("Retrying the '{}' hook", H::file_name());
                     } else {
                         break;
                     }
                 }
-                Err(e) => error!("Error running the '{}' hook: {:?}", H::file_name(), e),
+                Err(e) => {
+                    if let Some(on_failure) = on_failure.take() {
+                        on_failure(Err(&e));
+                    }
+                    error!("Error running the '{}' hook: {:?}", H::file_name(), e)
+                }
             }
         }
     }
@@ -0,0 +1,257 @@
+//! Per-service metrics served by the HTTP gateway's `/metrics` endpoint.
+//!
+//! Every metric is labelled with the service's group and the ident of the package it runs. The
+//! resource usage of a service's process is read from `/proc`, so it is only reported on Linux.
+//! Memory and file descriptors are only reported while the process is up, while CPU time adds up
+//! across every process the service has run. Processes spawned by the service's process are not
+//! counted.
+
+use super::{HealthCheckResult,
+            ProcessState,
+            Service};
+use habitat_core::{os::process::Pid,
+                   service::ServiceGroup,
+                   util::ToI64};
+use prometheus::{CounterVec,
+                 IntCounterVec,
+                 IntGaugeVec};
+use std::{collections::HashMap,
+          sync::Mutex,
+          time::SystemTime};
+
+const LABELS: &[&str] = &["service_group", "ident"];
+
+lazy_static! {
+    static ref RESIDENT_MEMORY: IntGaugeVec =
+        register_int_gauge_vec!("hab_sup_service_resident_memory_bytes",
+                                "Resident memory of the service's process. Linux only",
+                                LABELS).unwrap();
+    static ref CPU_TIME: CounterVec =
+        register_counter_vec!("hab_sup_service_cpu_seconds_total",
+                              "User and system CPU time of the service's processes. Linux only",
+                              LABELS).unwrap();
+    /// The process whose CPU time was last added to `CPU_TIME` for each service group, and how
+    /// much of its CPU time was added.
+    static ref CPU_TIME_COUNTED: Mutex<HashMap<String, (Pid, f64)>> = Mutex::new(HashMap::new());
+    static ref OPEN_FILE_DESCRIPTORS: IntGaugeVec =
+        register_int_gauge_vec!("hab_sup_service_open_file_descriptors",
+                                "Open file descriptors of the service's process. Linux only",
+                                LABELS).unwrap();
+    static ref UPTIME: IntGaugeVec = register_int_gauge_vec!("hab_sup_service_uptime_seconds",
+                                                             "Seconds since the service's \
+                                                              process started, or 0 if it is not \
+                                                              running",
+                                                             LABELS).unwrap();
+    static ref RESTARTS: IntCounterVec =
+        register_int_counter_vec!("hab_sup_service_restarts_total",
+                                  "Times the service's process exited unexpectedly",
+                                  LABELS).unwrap();
+    /// The exits of each service group's process which were not added to `RESTARTS` yet. They
+    /// are kept here rather than by the service's `Supervisor`, which is replaced every time the
+    /// service is restarted.
+    static ref UNCOUNTED_EXITS: Mutex<HashMap<String, i64>> = Mutex::new(HashMap::new());
+    static ref HEALTH_CHECK: IntGaugeVec =
+        register_int_gauge_vec!("hab_sup_service_health_check",
+                                "Result of the service's last health check: 0 for ok, 1 for \
+                                 warning, 2 for critical, and 3 for unknown",
+                                LABELS).unwrap();
+    static ref CONFIG_INCARNATION: IntGaugeVec =
+        register_int_gauge_vec!("hab_sup_service_config_incarnation",
+                                "Incarnation of the configuration applied to the service group",
+                                LABELS).unwrap();
+}
+
+/// Resource usage of a process.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, PartialEq)]
+struct ProcessStats {
+    resident_memory_bytes: i64,
+    cpu_seconds:           f64,
+    open_file_descriptors: i64,
+}
+
+/// Note that the process of `service_group` exited unexpectedly.
+pub fn record_exit(service_group: &ServiceGroup) {
+    *UNCOUNTED_EXITS.lock()
+                    .expect("UNCOUNTED_EXITS lock poisoned")
+                    .entry(service_group.to_string())
+                    .or_insert(0) += 1;
+}
+
+/// Update the metrics of `service`.
+pub fn record(service: &Service) {
+    let ident = service.pkg.ident.to_string();
+    let service_group = service.service_group.to_string();
+    let labels = &[service_group.as_str(), ident.as_str()];
+
+    let (state, state_entered, pid) = {
+        let supervisor = service.supervisor.lock().expect("Couldn't lock supervisor");
+        (supervisor.status(), supervisor.state_entered(), supervisor.pid())
+    };
+    let uptime = match state {
+        ProcessState::Up => {
+            SystemTime::now().duration_since(state_entered)
+                             .map(|uptime| uptime.as_secs())
+                             .unwrap_or(0)
+        }
+        ProcessState::Down | ProcessState::CrashLoop => 0,
+    };
+    let health = *service.health_check_result
+                         .lock()
+                         .expect("Could not unlock health_check_result");
+
+    UPTIME.with_label_values(labels).set(uptime.to_i64());
+    let exits = UNCOUNTED_EXITS.lock()
+                               .expect("UNCOUNTED_EXITS lock poisoned")
+                               .remove(&service_group)
+                               .unwrap_or(0);
+    RESTARTS.with_label_values(labels).inc_by(exits);
+    HEALTH_CHECK.with_label_values(labels)
+                .set(health_check_value(health));
+    CONFIG_INCARNATION.with_label_values(labels)
+                      .set(service.cfg.gossip_incarnation.to_i64());
+
+    match pid.filter(|_| state == ProcessState::Up)
+             .and_then(|pid| process_stats(pid).map(|stats| (pid, stats)))
+    {
+        Some((pid, stats)) => {
+            RESIDENT_MEMORY.with_label_values(labels)
+                           .set(stats.resident_memory_bytes);
+            let mut counted = CPU_TIME_COUNTED.lock()
+                                              .expect("CPU_TIME_COUNTED lock poisoned");
+            let counted = counted.entry(service_group.clone()).or_insert((pid, 0.0));
+            CPU_TIME.with_label_values(labels)
+                    .inc_by(uncounted_cpu_seconds(*counted, pid, stats.cpu_seconds));
+            *counted = (pid, stats.cpu_seconds);
+            OPEN_FILE_DESCRIPTORS.with_label_values(labels)
+                                 .set(stats.open_file_descriptors);
+        }
+        None => remove_process_stats(labels),
+    }
+}
+
+/// The CPU time of process `pid` that has not been added to a service's total yet, given the
+/// process whose CPU time was `counted` last.
+fn uncounted_cpu_seconds(counted: (Pid, f64), pid: Pid, cpu_seconds: f64) -> f64 {
+    match counted {
+        (counted_pid, counted_seconds) if counted_pid == pid => {
+            (cpu_seconds - counted_seconds).max(0.0)
+        }
+        // A new process, none of whose CPU time was counted yet.
+        _ => cpu_seconds,
+    }
+}
+
+/// Stop reporting the metrics of `service`. This is only done when the service is unloaded, so
+/// that counters add up across restarts of the service.
+pub fn remove(service: &Service) {
+    let ident = service.pkg.ident.to_string();
+    let service_group = service.service_group.to_string();
+    let labels = &[service_group.as_str(), ident.as_str()];
+    for gauge in &[&*UPTIME, &*HEALTH_CHECK, &*CONFIG_INCARNATION] {
+        gauge.remove_label_values(labels).ok();
+    }
+    RESTARTS.remove_label_values(labels).ok();
+    CPU_TIME.remove_label_values(labels).ok();
+    CPU_TIME_COUNTED.lock()
+                    .expect("CPU_TIME_COUNTED lock poisoned")
+                    .remove(&service_group);
+    UNCOUNTED_EXITS.lock()
+                   .expect("UNCOUNTED_EXITS lock poisoned")
+                   .remove(&service_group);
+    remove_process_stats(labels);
+}
+
+/// The restarts reported for `service`.
+#[cfg(test)]
+pub fn restarts(service: &Service) -> i64 {
+    let ident = service.pkg.ident.to_string();
+    let service_group = service.service_group.to_string();
+    RESTARTS.with_label_values(&[service_group.as_str(), ident.as_str()])
+            .get()
+}
+
+/// Stop reporting the stats which only describe a running process.
+fn remove_process_stats(labels: &[&str]) {
+    RESIDENT_MEMORY.remove_label_values(labels).ok();
+    OPEN_FILE_DESCRIPTORS.remove_label_values(labels).ok();
+}
+
+/// The value of a health check result, which matches the exit code of a health-check hook
+/// returning it.
+fn health_check_value(health: HealthCheckResult) -> i64 {
+    match health {
+        HealthCheckResult::Ok => 0,
+        HealthCheckResult::Warning => 1,
+        HealthCheckResult::Critical => 2,
+        HealthCheckResult::Unknown => 3,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_stats(pid: Pid) -> Option<ProcessStats> {
+    let proc_dir = std::path::Path::new("/proc").join(pid.to_string());
+    let stat = std::fs::read_to_string(proc_dir.join("stat")).ok()?;
+    let statm = std::fs::read_to_string(proc_dir.join("statm")).ok()?;
+    let open_file_descriptors = std::fs::read_dir(proc_dir.join("fd")).ok()?.count();
+    // These cannot fail on Linux.
+    let clock_ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as i64;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as i64;
+    parse_process_stats(&stat, &statm, clock_ticks, page_size).map(|(memory, cpu)| {
+        ProcessStats { resident_memory_bytes: memory,
+                       cpu_seconds:           cpu,
+                       open_file_descriptors: open_file_descriptors.to_i64(), }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_stats(_pid: Pid) -> Option<ProcessStats> { None }
+
+/// Parse the resident memory in bytes and the CPU time in seconds of a process from the contents
+/// of its `/proc/<pid>/stat` and `/proc/<pid>/statm` files. See proc(5).
+#[cfg(target_os = "linux")]
+fn parse_process_stats(stat: &str,
+                       statm: &str,
+                       clock_ticks: i64,
+                       page_size: i64)
+                       -> Option<(i64, f64)> {
+    // The second field is the process's name in parentheses, which may itself contain spaces
+    // and parentheses, so the fields are counted from the last closing parenthesis. The first
+    // field after it is the third field of the file.
+    let fields = stat[stat.rfind(')')? + 1..].split_whitespace()
+                                             .collect::<Vec<_>>();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    let resident_pages = statm.split_whitespace().nth(1)?.parse::<i64>().ok()?;
+    Some((resident_pages * page_size, (utime + stime) as f64 / clock_ticks as f64))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_stat_files() {
+        let stat = "4242 (redis (server)) S 1 4242 4242 0 -1 4194560 1093 0 0 0 250 150 0 0 20 0 \
+                    4 0 12345 56389632 2237 18446744073709551615";
+        let statm = "13767 2237 1820 382 0 1683 0";
+        assert_eq!(parse_process_stats(stat, statm, 100, 4096),
+                   Some((2237 * 4096, 4.0)));
+        assert_eq!(parse_process_stats("4242 (redis) S 1", statm, 100, 4096),
+                   None);
+    }
+
+    #[test]
+    fn counts_the_cpu_time_of_each_process_once() {
+        assert_eq!(uncounted_cpu_seconds((42, 0.0), 42, 1.5), 1.5);
+        assert_eq!(uncounted_cpu_seconds((42, 1.5), 42, 4.0), 2.5);
+        assert_eq!(uncounted_cpu_seconds((42, 4.0), 43, 0.5), 0.5);
+    }
+
+    #[test]
+    fn reads_the_stats_of_a_running_process() {
+        let stats = process_stats(std::process::id() as Pid).unwrap();
+        assert!(stats.resident_memory_bytes > 0);
+        assert!(stats.open_file_descriptors > 0);
+    }
+}
@@ -8,7 +8,10 @@ use habitat_core::{fs::atomic_write,
                    os::process::ShutdownTimeout,
                    package::{PackageIdent,
                              PackageInstall},
-                   service::{HealthCheckInterval,
+                   service::{HealthCheck,
+                             HealthCheckInterval,
+                             MaxUnavailable,
+                             RestartPolicy,
                              ServiceBind},
                    url::DEFAULT_BLDR_URL,
                    util::serde_string,
@@ -86,6 +89,9 @@ pub struct ServiceSpec {
     pub topology:               Topology,
     pub update_strategy:        UpdateStrategy,
     pub update_condition:       UpdateCondition,
+    pub max_unavailable:        MaxUnavailable,
+    /// The number of followers which update during the canary stage of a canary update.
+    pub canaries:               u32,
     pub binds:                  Vec<ServiceBind>,
     pub binding_mode:           BindingMode,
     pub config_from:            Option<PathBuf>,
@@ -93,15 +99,16 @@ pub struct ServiceSpec {
     pub desired_state:          DesiredState,
     pub shutdown_timeout:       Option<ShutdownTimeout>,
     pub svc_encrypted_password: Option<String>,
-    // it is important that the health check interval
-    // is the last field to be serialized because it
-    // is serialized as a table. Individual values
-    // serialized after the health check interval will
-    // break the parser.
+    // it is important that the health check interval,
+    // health check, and restart policy are the last fields to be
+    // serialized because they are serialized as tables. Individual values
+    // serialized after them will break the parser.
     // Note that there is an issue to ultimately fix this:
     // https://github.com/habitat-sh/habitat/issues/6469
-    // and eliminate the need to keep this field last.
+    // and eliminate the need to keep these fields last.
     pub health_check_interval:  HealthCheckInterval,
+    pub health_check:           Option<HealthCheck>,
+    pub restart_policy:         RestartPolicy,
 }
 
 impl ServiceSpec {
@@ -113,11 +120,15 @@ impl ServiceSpec {
                topology: Topology::default(),
                update_strategy: UpdateStrategy::default(),
                update_condition: UpdateCondition::default(),
+               max_unavailable: MaxUnavailable::default(),
+               canaries: 1,
                binds: Vec::default(),
                binding_mode: BindingMode::Strict,
                config_from: None,
                desired_state: DesiredState::default(),
                health_check_interval: HealthCheckInterval::default(),
+               health_check: None,
+               restart_policy: RestartPolicy::default(),
                svc_encrypted_password: None,
                shutdown_timeout: None }
     }
@@ -214,10 +225,10 @@ impl ServiceSpec {
     }
 
     pub fn merge_svc_load(mut self, svc_load: habitat_sup_protocol::ctl::SvcLoad) -> Result<Self> {
-        self.ident =
+        let ident =
             svc_load.ident
-                    .ok_or_else(|| net::err(net::ErrCode::BadPayload, "No ident specified"))?
-                    .into();
+                    .ok_or_else(|| net::err(net::ErrCode::BadPayload, "No ident specified"))?;
+        self.ident = PackageIdent::try_from(ident)?;
         if let Some(group) = svc_load.group {
             self.group = group;
         }
@@ -242,6 +253,18 @@ impl ServiceSpec {
                 self.update_condition = update_condition;
             }
         }
+        if let Some(max_unavailable) = svc_load.max_unavailable {
+            self.max_unavailable =
+                max_unavailable.parse()
+                               .map_err(|e| net::err(net::ErrCode::BadPayload, e))?;
+        }
+        if let Some(canaries) = svc_load.canaries {
+            if canaries == 0 {
+                return Err(net::err(net::ErrCode::BadPayload,
+                                    "A canary update needs at least one canary").into());
+            }
+            self.canaries = canaries;
+        }
         if let Some(list) = svc_load.binds {
             self.binds = list.binds
                              .into_iter()
@@ -265,8 +288,69 @@ impl ServiceSpec {
         if let Some(shutdown_timeout) = svc_load.shutdown_timeout {
             self.shutdown_timeout = Some(ShutdownTimeout::from(shutdown_timeout));
         }
+        if let Some(max_restarts) = svc_load.max_restarts {
+            self.restart_policy.max_restarts = max_restarts;
+        }
+        if let Some(restart_window) = svc_load.restart_window {
+            self.restart_policy.window_secs = restart_window.into();
+        }
+        if let Some(restart_backoff) = svc_load.restart_backoff {
+            self.restart_policy.backoff_secs = restart_backoff.into();
+        }
+        if let Some(max_restart_backoff) = svc_load.max_restart_backoff {
+            self.restart_policy.max_backoff_secs = max_restart_backoff.into();
+        }
+        if let Some(health_check) = svc_load.health_check {
+            self.health_check =
+                Some(health_check.parse()
+                                 .map_err(|e| net::err(net::ErrCode::BadPayload, e))?);
+        }
         Ok(self)
     }
+
+    /// Change the settings given in `svc_update`, leaving the rest of the spec alone.
+    pub fn merge_svc_update(self,
+                            svc_update: habitat_sup_protocol::ctl::SvcUpdate)
+                            -> Result<Self> {
+        let svc_load =
+            habitat_sup_protocol::ctl::SvcLoad { ident: Some(self.ident.clone().into()),
+                                                 binds: svc_update.binds,
+                                                 binding_mode: svc_update.binding_mode,
+                                                 bldr_url: svc_update.bldr_url,
+                                                 bldr_channel: svc_update.bldr_channel,
+                                                 update_strategy: svc_update.update_strategy,
+                                                 update_condition: svc_update.update_condition,
+                                                 max_unavailable: svc_update.max_unavailable,
+                                                 canaries: svc_update.canaries,
+                                                 health_check_interval:
+                                                     svc_update.health_check_interval,
+                                                 health_check: svc_update.health_check,
+                                                 shutdown_timeout: svc_update.shutdown_timeout,
+                                                 max_restarts: svc_update.max_restarts,
+                                                 restart_window: svc_update.restart_window,
+                                                 restart_backoff: svc_update.restart_backoff,
+                                                 max_restart_backoff:
+                                                     svc_update.max_restart_backoff,
+                                                 ..Default::default() };
+        self.merge_svc_load(svc_load)
+    }
+
+    /// Returns true if `other` differs from this spec only in settings which can be applied to a
+    /// running service without restarting it.
+    pub fn can_apply_live(&self, other: &ServiceSpec) -> bool {
+        let mut live = other.clone();
+        live.bldr_url = self.bldr_url.clone();
+        live.channel = self.channel.clone();
+        live.update_strategy = self.update_strategy;
+        live.update_condition = self.update_condition;
+        live.max_unavailable = self.max_unavailable;
+        live.canaries = self.canaries;
+        live.shutdown_timeout = self.shutdown_timeout;
+        live.health_check_interval = self.health_check_interval;
+        live.health_check = self.health_check.clone();
+        live.restart_policy = self.restart_policy;
+        *self == live
+    }
 }
 
 impl FromStr for ServiceSpec {
@@ -339,12 +423,23 @@ mod test {
             topology = "leader"
             update_strategy = "rolling"
             update_condition = "latest"
+            max_unavailable = "3"
+            canaries = 2
             binds = ["cache:redis.cache@acmecorp", "db:postgres.app@acmecorp"]
             config_from = "/only/for/development"
 
             [health_check_interval]
             secs = 5
             nanos = 0
+
+            [health_check]
+            type = "tcp"
+            address = "localhost:5432"
+            timeout_secs = 2
+
+            [restart_policy]
+            max_restarts = 3
+            window_secs = 60
             "#;
         let spec = ServiceSpec::from_str(toml).unwrap();
 
@@ -355,6 +450,8 @@ mod test {
         assert_eq!(spec.topology, Topology::Leader);
         assert_eq!(spec.update_strategy, UpdateStrategy::Rolling);
         assert_eq!(spec.update_condition, UpdateCondition::Latest);
+        assert_eq!(spec.max_unavailable, MaxUnavailable::Count(3));
+        assert_eq!(spec.canaries, 2);
         assert_eq!(spec.binds,
                    vec![ServiceBind::from_str("cache:redis.cache@acmecorp").unwrap(),
                         ServiceBind::from_str("db:postgres.app@acmecorp").unwrap(),]);
@@ -362,6 +459,13 @@ mod test {
                    Some(PathBuf::from("/only/for/development")));
         assert_eq!(spec.health_check_interval,
                    HealthCheckInterval::from_str("5").unwrap());
+        assert_eq!(spec.health_check,
+                   Some(HealthCheck::Tcp { address:      "localhost:5432".to_string(),
+                                           timeout_secs: 2, }));
+        assert_eq!(spec.restart_policy,
+                   RestartPolicy { max_restarts: 3,
+                                   window_secs: 60,
+                                   ..RestartPolicy::default() });
     }
 
     #[test]
@@ -427,12 +531,18 @@ mod test {
                           topology:               Topology::Leader,
                           update_strategy:        UpdateStrategy::AtOnce,
                           update_condition:       UpdateCondition::Latest,
+                          max_unavailable:        MaxUnavailable::Percent(25),
+                          canaries:               3,
                           binds:                  vec![ServiceBind::from_str("cache:redis.cache@\
                                                                               acmecorp").unwrap(),
                                                        ServiceBind::from_str("db:postgres.app@\
                                                                               acmecorp").unwrap(),],
                           binding_mode:           BindingMode::Relaxed,
                           health_check_interval:  HealthCheckInterval::from_str("123").unwrap(),
+                          health_check:
+                              Some(HealthCheck::from_str("exec:pg_isready -q").unwrap()),
+                          restart_policy:         RestartPolicy { max_restarts: 5,
+                                                                  ..RestartPolicy::default() },
                           config_from:            Some(PathBuf::from("/only/for/development")),
                           desired_state:          DesiredState::Down,
                           svc_encrypted_password: None,
@@ -445,6 +555,8 @@ mod test {
         assert!(toml.contains(r#"channel = "unstable""#));
         assert!(toml.contains(r#"topology = "leader""#));
         assert!(toml.contains(r#"update_strategy = "at-once""#));
+        assert!(toml.contains(r#"max_unavailable = "25%""#));
+        assert!(toml.contains("canaries = 3"));
         assert!(toml.contains(r#""cache:redis.cache@acmecorp""#));
         assert!(toml.contains(r#""db:postgres.app@acmecorp""#));
         assert!(toml.contains(r#"desired_state = "down""#));
@@ -454,6 +566,12 @@ mod test {
         assert!(toml.contains(r#"secs = 123"#));
         assert!(toml.contains(r#"nanos = 0"#));
         assert!(toml.contains(r#"shutdown_timeout = 10"#));
+        assert!(toml.contains(r#"[health_check]"#));
+        assert!(toml.contains(r#"type = "exec""#));
+        assert!(toml.contains(r#"command = ["pg_isready", "-q"]"#));
+        assert!(toml.contains(r#"[restart_policy]"#));
+        assert!(toml.contains(r#"max_restarts = 5"#));
+        assert_eq!(ServiceSpec::from_str(&toml).unwrap(), spec);
     }
 
     #[test]
@@ -531,6 +649,8 @@ mod test {
         let spec = ServiceSpec::from_file(path).unwrap();
 
         assert_eq!(spec.health_check_interval, HealthCheckInterval::default());
+        assert_eq!(spec.max_unavailable, MaxUnavailable::default());
+        assert_eq!(spec.canaries, 1);
     }
 
     #[test]
@@ -596,12 +716,16 @@ mod test {
                           topology:               Topology::Leader,
                           update_strategy:        UpdateStrategy::AtOnce,
                           update_condition:       UpdateCondition::Latest,
+                          max_unavailable:        MaxUnavailable::Percent(25),
+                          canaries:               3,
                           binds:                  vec![ServiceBind::from_str("cache:redis.cache@\
                                                                               acmecorp").unwrap(),
                                                        ServiceBind::from_str("db:postgres.app@\
                                                                               acmecorp").unwrap(),],
                           binding_mode:           BindingMode::Relaxed,
                           health_check_interval:  HealthCheckInterval::from_str("23").unwrap(),
+                          health_check:           None,
+                          restart_policy:         RestartPolicy::default(),
                           config_from:            Some(PathBuf::from("/only/for/development")),
                           desired_state:          DesiredState::Down,
                           svc_encrypted_password: None,
@@ -615,6 +739,8 @@ mod test {
         assert!(toml.contains(r#"channel = "unstable""#));
         assert!(toml.contains(r#"topology = "leader""#));
         assert!(toml.contains(r#"update_strategy = "at-once""#));
+        assert!(toml.contains(r#"max_unavailable = "25%""#));
+        assert!(toml.contains("canaries = 3"));
         assert!(toml.contains(r#""cache:redis.cache@acmecorp""#));
         assert!(toml.contains(r#""db:postgres.app@acmecorp""#));
         assert!(toml.contains(r#"desired_state = "down""#));
@@ -736,6 +862,52 @@ mod test {
         }
     }
 
+    #[test]
+    fn service_spec_merge_svc_update_changes_only_given_settings() {
+        let mut spec = ServiceSpec::new(PackageIdent::from_str("core/redis/1.2.3").unwrap());
+        spec.binds = vec![ServiceBind::from_str("db:postgres.app").unwrap()];
+        spec.update_strategy = UpdateStrategy::Rolling;
+
+        let mut svc_update = habitat_sup_protocol::ctl::SvcUpdate::default();
+        svc_update.ident = Some(PackageIdent::from_str("core/redis").unwrap().into());
+        svc_update.bldr_channel = Some("unstable".to_string());
+        svc_update.shutdown_timeout = Some(30);
+        svc_update.canaries = Some(2);
+        let updated = spec.clone().merge_svc_update(svc_update).unwrap();
+
+        assert_eq!(updated.ident, spec.ident);
+        assert_eq!(updated.channel, ChannelIdent::unstable());
+        assert_eq!(updated.shutdown_timeout, Some(ShutdownTimeout::from(30)));
+        assert_eq!(updated.binds, spec.binds);
+        assert_eq!(updated.update_strategy, UpdateStrategy::Rolling);
+        assert_eq!(updated.canaries, 2);
+
+        let mut svc_update = habitat_sup_protocol::ctl::SvcUpdate::default();
+        svc_update.canaries = Some(0);
+        assert!(spec.merge_svc_update(svc_update).is_err());
+    }
+
+    #[test]
+    fn service_spec_can_apply_live() {
+        let spec = ServiceSpec::new(PackageIdent::from_str("core/redis").unwrap());
+
+        let mut live = spec.clone();
+        live.channel = ChannelIdent::unstable();
+        live.update_strategy = UpdateStrategy::AtOnce;
+        live.health_check_interval = HealthCheckInterval::from_str("5").unwrap();
+        live.shutdown_timeout = Some(ShutdownTimeout::from(30));
+        live.canaries = 2;
+        assert!(spec.can_apply_live(&live));
+
+        let mut restart = live.clone();
+        restart.binds = vec![ServiceBind::from_str("db:postgres.app").unwrap()];
+        assert!(!spec.can_apply_live(&restart));
+
+        let mut restart = live;
+        restart.topology = Topology::Leader;
+        assert!(!spec.can_apply_live(&restart));
+    }
+
     /// This is to support backward compatibility with the old
     /// application/environment functionality that is being removed.
     #[test]
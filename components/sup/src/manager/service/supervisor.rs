@@ -2,8 +2,10 @@
 ///
 /// The Supervisor is responsible for running any services we are asked to start. It handles
 /// spawning the new process, watching for failure, and ensuring the service is either up or
-/// down. If the process dies, the Supervisor will restart it.
-use super::{terminator,
+/// down. If the process dies, the Supervisor will restart it, backing off according to the
+/// service's `RestartPolicy` and giving up if the process keeps dying.
+use super::{metrics,
+            terminator,
             ProcessState};
 #[cfg(unix)]
 use crate::error::Error;
@@ -20,12 +22,14 @@ use habitat_core::{fs,
                         Permissions},
                    os::process::{self,
                                  Pid},
-                   service::ServiceGroup};
+                   service::{RestartPolicy,
+                             ServiceGroup}};
 use habitat_launcher_client::LauncherCli;
 use serde::{ser::SerializeStruct,
             Serialize,
             Serializer};
-use std::{fs::File,
+use std::{fs::{self as stdfs,
+               File},
           io::{BufRead,
                BufReader,
                Write},
@@ -71,6 +75,8 @@ pub struct Supervisor {
     /// Regardless of the value of `pid_source`, the current PID will
     /// always be written to this path, for use by service hooks.
     pid_file:      PathBuf,
+    /// When the process exited unexpectedly within the current restart window.
+    exits:         Exits,
 }
 
 impl Supervisor {
@@ -82,16 +88,24 @@ impl Supervisor {
     /// be removed.
     pub fn new(service_group: &ServiceGroup, pid_source: ServicePidSource) -> Supervisor {
         let pid_file = fs::svc_pid_file(service_group.service());
+        let exits = Exits::load(fs::svc_path(service_group.service()).join(EXITS_FILE));
         Supervisor { service_group: service_group.clone(),
                      state: ProcessState::Down,
                      state_entered: SystemTime::now(),
                      pid_source,
                      pid: None,
-                     pid_file }
+                     pid_file,
+                     exits }
     }
 
-    /// Check if the child process is running
-    pub fn check_process(&mut self, launcher: &LauncherCli) -> bool {
+    /// Check if the child process is running. If the process was up and is no longer running, the
+    /// exit is recorded and the process is marked as crash looping if it has exited too many
+    /// times according to `restart_policy`.
+    pub fn check_process(&mut self,
+                         launcher: &LauncherCli,
+                         restart_policy: &RestartPolicy)
+                         -> bool {
+        let was_up = self.state == ProcessState::Up;
         self.pid = self.pid
                        .or_else(|| {
                            if self.pid_source == ServicePidSource::Files {
@@ -118,13 +132,56 @@ impl Supervisor {
         if self.pid.is_some() {
             self.change_state(ProcessState::Up);
         } else {
-            self.change_state(ProcessState::Down);
+            if was_up {
+                self.record_exit(restart_policy);
+            }
+            if self.state != ProcessState::CrashLoop {
+                self.change_state(ProcessState::Down);
+            }
             Self::cleanup_pidfile(&self.pid_file);
         }
 
         self.pid.is_some()
     }
 
+    pub(super) fn record_exit(&mut self, restart_policy: &RestartPolicy) {
+        self.exits
+            .record(SystemTime::now(), restart_policy.window());
+        metrics::record_exit(&self.service_group);
+        let exits = self.exits.count();
+        if restart_policy.is_crash_loop(exits) {
+            outputln!(preamble self.service_group,
+                      "Process exited {} times within {}s; it will no longer be restarted",
+                      exits,
+                      restart_policy.window_secs);
+            self.change_state(ProcessState::CrashLoop);
+        } else {
+            outputln!(preamble self.service_group,
+                      "Process exited unexpectedly; restarting in {}s",
+                      restart_policy.backoff(exits).as_secs());
+        }
+    }
+
+    /// The number of times the process exited unexpectedly within the current restart window.
+    pub fn exits(&self) -> usize { self.exits.count() }
+
+    /// Returns true if the process is down and may be restarted: it is not crash looping and the
+    /// backoff following its most recent exit has elapsed.
+    pub fn may_restart(&self, restart_policy: &RestartPolicy) -> bool {
+        match self.state {
+            ProcessState::Up | ProcessState::CrashLoop => false,
+            ProcessState::Down => {
+                self.exits.last().map_or(true, |exited| {
+                                     let backoff = restart_policy.backoff(self.exits.count());
+                                     exited.elapsed().map_or(true, |elapsed| elapsed >= backoff)
+                                 })
+            }
+        }
+    }
+
+    /// Forget the process's previous exits, for example because it was stopped on purpose.
+    pub fn forget_exits(&mut self) { self.exits.clear(); }
+
     // NOTE: the &self argument is only used to get access to
     // self.service_group, and even then only for Linux :/
     #[cfg(unix)]
@@ -213,7 +270,8 @@ impl Supervisor {
                                  &pkg.svc_run,
                                  user_info,
                                  svc_password, // Windows optional
-                                 (*pkg.env).clone())?;
+                                 (*pkg.env).clone(),
+                                 &fs::svc_log_file(self.service_group.service()))?;
         if pid == 0 {
             warn!(target: "pidfile_tracing", "Spawned service for {} has a PID of 0!", group);
         }
@@ -226,6 +284,9 @@ impl Supervisor {
     /// Is the process up or down?
     pub fn status(&self) -> ProcessState { self.state }
 
+    /// The PID of the service's process, if it is running.
+    pub fn pid(&self) -> Option<Pid> { self.pid }
+
     /// Returns a future that stops a service asynchronously.
     pub fn stop(&self, shutdown_config: ShutdownConfig) {
         let service_group = self.service_group.clone();
@@ -318,6 +379,63 @@ impl Serialize for Supervisor {
     }
 }
 
+const EXITS_FILE: &str = "EXITS";
+
+/// The times at which a process exited unexpectedly. These are written to the service's directory
+/// so that they outlive the service being stopped and started again to restart the process.
+#[derive(Debug)]
+struct Exits {
+    path:  PathBuf,
+    times: Vec<SystemTime>,
+}
+
+impl Exits {
+    fn load(path: PathBuf) -> Self {
+        let times = match stdfs::read_to_string(&path) {
+            Ok(contents) => {
+                contents.lines()
+                        .filter_map(|line| line.trim().parse().ok())
+                        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+                        .collect()
+            }
+            Err(_) => Vec::new(),
+        };
+        Self { path, times }
+    }
+
+    /// Record an exit at `time`, forgetting any exits which are older than `window`.
+    fn record(&mut self, time: SystemTime, window: Duration) {
+        self.times
+            .retain(|t| time.duration_since(*t).map_or(true, |age| age < window));
+        self.times.push(time);
+        self.save();
+    }
+
+    fn count(&self) -> usize { self.times.len() }
+
+    fn last(&self) -> Option<SystemTime> { self.times.last().copied() }
+
+    fn clear(&mut self) {
+        self.times.clear();
+        if let Err(e) = stdfs::remove_file(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Unable to remove {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+    fn save(&self) {
+        let contents: String = self.times
+                                   .iter()
+                                   .filter_map(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                                   .map(|since_epoch| format!("{}\n", since_epoch.as_secs()))
+                                   .collect();
+        if let Err(e) = stdfs::write(&self.path, contents) {
+            warn!("Unable to write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
 fn read_pid<T>(pid_file: T) -> Option<Pid>
     where T: AsRef<Path>
 {
@@ -362,3 +480,30 @@ fn read_pid<T>(pid_file: T) -> Option<Pid>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn exits_outside_the_window_are_forgotten() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join(EXITS_FILE);
+        let window = Duration::from_secs(600);
+        let now = SystemTime::now();
+
+        let mut exits = Exits::load(path.clone());
+        exits.record(now - Duration::from_secs(900), window);
+        exits.record(now - Duration::from_secs(300), window);
+        exits.record(now, window);
+        assert_eq!(exits.count(), 2);
+
+        let reloaded = Exits::load(path.clone());
+        assert_eq!(reloaded.count(), 2);
+
+        exits.clear();
+        assert_eq!(exits.count(), 0);
+        assert!(!path.exists());
+    }
+}
@@ -2,13 +2,16 @@ mod package_update_worker;
 mod rolling_update_worker;
 
 use self::{package_update_worker::PackageUpdateWorker,
-           rolling_update_worker::RollingUpdateWorker};
+           rolling_update_worker::{RollingUpdateWorker,
+                                   Rollout}};
 use crate::{census::CensusRing,
             manager::service::{Service,
                                UpdateStrategy}};
 use futures::future::{self,
                       AbortHandle};
-use habitat_butterfly;
+use habitat_butterfly::{self,
+                        rumor::service::{Canary,
+                                         Rollout as RolloutRumor}};
 use habitat_common::outputln;
 use habitat_core::{package::PackageIdent,
                    service::ServiceGroup};
@@ -36,6 +39,14 @@ pub struct ServiceUpdater {
     butterfly:   habitat_butterfly::Server,
     census_ring: Arc<RwLock<CensusRing>>,
     updates:     Arc<Mutex<HashMap<ServiceGroup, PackageIdent>>>,
+    /// The state of rolling updates led by this Supervisor. This must outlive the individual
+    /// workers because the update leader restarts with its new package before the rest of the
+    /// group has finished updating.
+    rollouts:    Arc<Mutex<HashMap<ServiceGroup, Rollout>>>,
+    /// Operator decisions on the canary stage of rolling updates made on this Supervisor. Like
+    /// `rollouts`, these must survive the restart of a service so they keep being gossiped until
+    /// the rollout they were made for is no longer in progress.
+    canaries:    Arc<Mutex<HashMap<ServiceGroup, Canary>>>,
     workers:     HashMap<ServiceGroup, Worker>,
 }
 
@@ -44,6 +55,8 @@ impl ServiceUpdater {
         ServiceUpdater { butterfly,
                          census_ring,
                          updates: Arc::default(),
+                         rollouts: Arc::default(),
+                         canaries: Arc::default(),
                          workers: HashMap::new() }
     }
 
@@ -59,7 +72,7 @@ impl ServiceUpdater {
                 let worker = self.at_once_worker(service);
                 self.spawn_worker(service_group, worker);
             }
-            UpdateStrategy::Rolling => {
+            UpdateStrategy::Rolling | UpdateStrategy::Canary => {
                 let worker = self.rolling_worker(service, Arc::clone(&self.census_ring));
                 self.spawn_worker(service_group, worker);
             }
@@ -72,6 +85,32 @@ impl ServiceUpdater {
         self.updates.lock().remove(service_group);
     }
 
+    /// Forget any rolling update state for a service. Unlike `remove`, this should only be called
+    /// when the service is no longer loaded and not merely restarting for an update.
+    pub fn forget_rollout(&mut self, service_group: &ServiceGroup) {
+        self.rollouts.lock().remove(service_group);
+        self.canaries.lock().remove(service_group);
+    }
+
+    /// Record an operator decision on the canary stage of a rolling update of a service.
+    pub fn decide_canary(&mut self, service_group: ServiceGroup, canary: Canary) {
+        self.canaries.lock().insert(service_group, canary);
+    }
+
+    /// The state of the rolling update of a service led by this Supervisor, as it is gossiped.
+    pub fn rollout(&self, service_group: &ServiceGroup) -> Option<RolloutRumor> {
+        self.rollouts
+            .lock()
+            .get(service_group)
+            .map(Rollout::to_rumor)
+    }
+
+    /// The last operator decision on the canary stage of a rolling update of a service made on
+    /// this Supervisor.
+    pub fn canary(&self, service_group: &ServiceGroup) -> Option<Canary> {
+        self.canaries.lock().get(service_group).cloned()
+    }
+
     /// Check if this service has an update. If it does return the package ident of the update.
     ///
     /// Once an update is detected, this function will always return an update until the service is
@@ -109,9 +148,13 @@ impl ServiceUpdater {
         let service_group = service.service_group.clone();
         let full_ident = service.pkg.ident.clone();
         let updates = Arc::clone(&self.updates);
-        let worker = RollingUpdateWorker::new(service, census_ring, self.butterfly.clone());
+        let butterfly = self.butterfly.clone();
+        let worker = RollingUpdateWorker::new(service,
+                                              census_ring,
+                                              Arc::clone(&self.rollouts),
+                                              Arc::clone(&self.canaries));
         async move {
-            let new_ident = worker.run().await;
+            let new_ident = worker.run(butterfly).await;
             debug!("'{}' rolling updater found update from '{}' to '{}'",
                    service_group, full_ident, new_ident);
             Self::update_message(&new_ident, full_ident.as_ref());
@@ -69,6 +69,14 @@ impl PackageUpdateWorker {
     /// package is found.
     // TODO (DM): The returned package ident should use FullyQualifiedPackageIdent.
     pub async fn update_to(&self, ident: PackageIdent) -> PackageIdent {
+        self.update_to_ignoring(ident, None).await
+    }
+
+    /// Same as `update_to`, but a change to the `ignored` package is not considered an update.
+    async fn update_to_ignoring(&self,
+                                ident: PackageIdent,
+                                ignored: Option<&PackageIdent>)
+                                -> PackageIdent {
         let delay = PackageUpdateWorkerPeriod::get();
         loop {
             let package_result = match self.update_condition {
@@ -82,7 +90,11 @@ impl PackageUpdateWorker {
             };
             match package_result {
                 Ok(package) => {
-                    if &package.ident != self.full_ident.as_ref() {
+                    if Some(&package.ident) == ignored {
+                        trace!("'{}' package update worker is ignoring '{}'",
+                               self.service_group,
+                               package.ident);
+                    } else if &package.ident != self.full_ident.as_ref() {
                         debug!("'{}' package update worker found change from '{}' to '{}' for \
                                 '{}' in channel '{}' using '{}' update condition",
                                self.service_group,
@@ -92,14 +104,15 @@ impl PackageUpdateWorker {
                                self.channel,
                                self.update_condition);
                         break package.ident;
+                    } else {
+                        trace!("'{}' package update worker did not find change from '{}' for '{}' \
+                                in channel '{}' using '{}' update condition",
+                               self.service_group,
+                               self.full_ident,
+                               ident,
+                               self.channel,
+                               self.update_condition)
                     }
-                    trace!("'{}' package update worker did not find change from '{}' for '{}' in \
-                            channel '{}' using '{}' update condition",
-                           self.service_group,
-                           self.full_ident,
-                           ident,
-                           self.channel,
-                           self.update_condition)
                 }
                 Err(err) => {
                     warn!("'{}' package update worker failed to install '{}' from channel '{}', \
@@ -113,6 +126,49 @@ impl PackageUpdateWorker {
 
     /// Use the service spec's package ident to search for packages.
     pub async fn update(&self) -> PackageIdent { self.update_to(self.ident.clone()).await }
+
+    /// Install exactly the specified package, regardless of the update condition.
+    ///
+    /// This is used to roll back a failed rolling update to the package that was previously
+    /// running, which may no longer be the head of the channel being tracked.
+    pub async fn rollback_to(&self, ident: PackageIdent) -> PackageIdent {
+        let delay = PackageUpdateWorkerPeriod::get();
+        let install_source = ident.clone().into();
+        loop {
+            match util::pkg::install_no_ui(&self.builder_url, &install_source, &self.channel).await
+            {
+                Ok(package) => break package.ident,
+                Err(err) => {
+                    warn!("'{}' package update worker failed to install '{}' for rollback from \
+                           channel '{}', err: {}",
+                          self.service_group, ident, self.channel, err)
+                }
+            }
+            time::delay_for(delay).await;
+        }
+    }
+
+    /// Use the service spec's package ident to search for packages, never updating to `ignored`.
+    ///
+    /// This keeps a package whose rolling update was rolled back from immediately being rolled
+    /// out again.
+    pub async fn update_ignoring(&self, ignored: &PackageIdent) -> PackageIdent {
+        self.update_to_ignoring(self.ident.clone(), Some(ignored))
+            .await
+    }
+}
+
+#[cfg(test)]
+impl PackageUpdateWorker {
+    /// A worker for a service running `full_ident` that tests do not let look for updates.
+    pub fn for_test(service_group: ServiceGroup, full_ident: FullyQualifiedPackageIdent) -> Self {
+        Self { service_group,
+               ident: full_ident.as_ref().clone(),
+               full_ident,
+               update_condition: UpdateCondition::Latest,
+               channel: ChannelIdent::stable(),
+               builder_url: String::new() }
+    }
 }
 
 #[cfg(test)]
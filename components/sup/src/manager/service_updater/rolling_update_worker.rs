@@ -1,23 +1,105 @@
 use super::package_update_worker::PackageUpdateWorker;
 use crate::{census::{CensusGroup,
-                     CensusRing},
-            manager::service::{Service,
-                               Topology}};
-use habitat_butterfly;
-use habitat_common::owning_refs::RwLockReadGuardRef;
+                     CensusMember,
+                     CensusRing,
+                     MemberId},
+            manager::service::{HealthCheckResult,
+                               Service,
+                               Topology,
+                               UpdateStrategy}};
+use habitat_butterfly::{self,
+                        rumor::service::{Canary,
+                                         CanaryDecision,
+                                         Rollout as RolloutRumor,
+                                         RolloutStatus}};
+use habitat_common::{outputln,
+                     owning_refs::RwLockReadGuardRef};
 use habitat_core::{package::PackageIdent,
-                   service::ServiceGroup};
-use parking_lot::RwLock;
+                   service::{MaxUnavailable,
+                             ServiceGroup}};
+use parking_lot::{Mutex,
+                  RwLock};
 use std::{self,
+          collections::HashMap,
           sync::Arc,
-          time::Duration};
+          time::{Duration,
+                 Instant}};
 use tokio::{self,
             time};
 
+static LOGKEY: &str = "RU";
+
 // The census ring does not have an async API. We make it look async by making API calls in a
 // loop with this delay after each call.
 const DELAY: Duration = Duration::from_secs(1);
 
+habitat_core::env_config_duration!(
+    /// How long an updated member has to report an `Ok` health check before the rolling update
+    /// is considered failed and is rolled back.
+    RollingUpdateHealthWindow,
+    HAB_ROLLING_UPDATE_HEALTH_WINDOW_SECS => from_secs,
+    RollingUpdateHealthWindow::DEFAULT);
+
+impl RollingUpdateHealthWindow {
+    const DEFAULT: Duration = Duration::from_secs(300);
+}
+
+/// The state of a rolling update led by this Supervisor as the update leader. It is gossiped with
+/// the service rumor so that followers learn about a rollback and a newly elected update leader
+/// can take over the rollout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rollout {
+    /// The leader updated from `from` to `to`. Followers are taking their turns and every member
+    /// has to report an `Ok` health check before the rollout is finished.
+    InProgress {
+        from: PackageIdent,
+        to:   PackageIdent,
+    },
+    /// The rollout of `rejected` failed and the group was rolled back to `from`. Until a different
+    /// package is found, `rejected` is not considered an update.
+    RolledBack {
+        from:     PackageIdent,
+        rejected: PackageIdent,
+    },
+}
+
+impl Rollout {
+    /// The rollout as it is gossiped in the service rumor.
+    pub fn to_rumor(&self) -> RolloutRumor {
+        match self {
+            Rollout::InProgress { from, to } => {
+                RolloutRumor { from:   from.to_string(),
+                               to:     to.to_string(),
+                               status: RolloutStatus::InProgress, }
+            }
+            Rollout::RolledBack { from, rejected } => {
+                RolloutRumor { from:   from.to_string(),
+                               to:     rejected.to_string(),
+                               status: RolloutStatus::RolledBack, }
+            }
+        }
+    }
+
+    /// Parse a gossiped rollout. Returns `None` if it does not name valid packages.
+    fn from_rumor(rumor: &RolloutRumor) -> Option<Self> {
+        let from = rumor.from.parse().ok()?;
+        let to = rumor.to.parse().ok()?;
+        match rumor.status {
+            RolloutStatus::InProgress => Some(Rollout::InProgress { from, to }),
+            RolloutStatus::RolledBack => Some(Rollout::RolledBack { from, rejected: to }),
+        }
+    }
+}
+
+/// If `leader` gossips that it rolled back the rollout of `rejected`, returns the package the
+/// group was rolled back to.
+fn rolled_back_to(leader: &CensusMember, rejected: &PackageIdent) -> Option<PackageIdent> {
+    match leader.rollout.as_ref().and_then(Rollout::from_rumor) {
+        Some(Rollout::RolledBack { from, rejected: r }) if r == *rejected => Some(from),
+        _ => None,
+    }
+}
+
 /// The role of the supervisor in the rolling update.
 enum Role {
     Leader,
@@ -25,15 +107,21 @@ enum Role {
 }
 
 /// Possible events when a follower is waiting for a rolling update to start.
+#[derive(Debug, PartialEq)]
 enum FollowerUpdateStartEvent {
     /// The leader died and this follower was chosen as the leader.
     PromotedToLeader,
     /// An update started and we have a specific package to update to.
     // TODO (DM): This should use FullyQualifiedPackageIdent.
     UpdateTo(PackageIdent),
+    /// The leader rolled back a failed update that this follower already took. The follower
+    /// rolls back immediately instead of waiting for its turn.
+    // TODO (DM): This should use FullyQualifiedPackageIdent.
+    RollbackTo(PackageIdent),
 }
 
 /// Possible events when a follower is waiting for its turn to update.
+#[derive(Debug, PartialEq)]
 enum FollowerUpdateTurnEvent {
     /// The leader died and this follower was chosen as the leader.
     PromotedToLeader,
@@ -45,64 +133,279 @@ enum FollowerUpdateTurnEvent {
     /// An update started and we have a specific package to update to.
     // TODO (DM): This should use FullyQualifiedPackageIdent.
     UpdateTo(PackageIdent),
+    /// The leader rolled back a failed update that this follower already took.
+    // TODO (DM): This should use FullyQualifiedPackageIdent.
+    RollbackTo(PackageIdent),
+}
+
+/// Possible outcomes when the leader is waiting for the members of a rollout to become healthy.
+#[derive(Debug, PartialEq)]
+enum LeaderRolloutHealthEvent {
+    /// Every member updated and reported an `Ok` health check.
+    Healthy,
+    /// A member reported a `Critical` health check or did not become healthy in time.
+    Unhealthy,
+    /// An operator aborted the canary stage of the rollout.
+    Aborted,
+}
+
+/// What the update leader does once it has checked on the rollout it is responsible for.
+#[derive(Debug, PartialEq)]
+enum LeaderRolloutAction {
+    /// Wait for the next update. A change to the `ignored` package is not considered an update.
+    WaitForUpdate { ignored: Option<PackageIdent> },
+    /// Roll the group back from the `rejected` package to `from`.
+    RollBack {
+        from:     PackageIdent,
+        rejected: PackageIdent,
+    },
 }
 
 /// The worker for handling rolling updates.
 ///
 /// The basic behavior of the update is to elect an update leader. The leader waits for an update.
-/// When an update is detected, the leader is updated and each follower takes a turn to update.
+/// When an update is detected, the leader is updated and the followers take turns to update with
+/// up to `max_unavailable` of them updating concurrently. A follower takes its turn once the member
+/// `max_unavailable` places before it reports an `Ok` health check. If an updated member goes
+/// `Critical` or does not become healthy within `RollingUpdateHealthWindow`, the leader rolls back
+/// and gossips that it did so. Every member that already updated then follows it back to the
+/// previous package. If a different member is elected update leader while a rollout is in
+/// progress, it takes over watching the rollout.
+///
+/// With the canary update strategy, only the leader and the first `canaries` followers update.
+/// The rest of the followers wait until an operator promotes the update. If an operator aborts
+/// the update instead, the leader rolls it back. Operator decisions are forgotten once the
+/// rollout they were made for is no longer in progress.
 pub struct RollingUpdateWorker {
     service_group:         ServiceGroup,
     topology:              Topology,
+    update_strategy:       UpdateStrategy,
+    max_unavailable:       MaxUnavailable,
+    canaries:              u32,
+    current_ident:         PackageIdent,
     package_update_worker: PackageUpdateWorker,
     census_ring:           Arc<RwLock<CensusRing>>,
-    butterfly:             habitat_butterfly::Server,
+    rollouts:              Arc<Mutex<HashMap<ServiceGroup, Rollout>>>,
+    canary_decisions:      Arc<Mutex<HashMap<ServiceGroup, Canary>>>,
 }
 
 impl RollingUpdateWorker {
     pub fn new(service: &Service,
                census_ring: Arc<RwLock<CensusRing>>,
-               butterfly: habitat_butterfly::Server)
+               rollouts: Arc<Mutex<HashMap<ServiceGroup, Rollout>>>,
+               canary_decisions: Arc<Mutex<HashMap<ServiceGroup, Canary>>>)
                -> Self {
         Self { service_group: service.service_group.clone(),
                topology: service.topology,
+               update_strategy: service.update_strategy,
+               max_unavailable: service.max_unavailable,
+               canaries: service.canaries,
+               current_ident: service.pkg.ident.as_ref().clone(),
                package_update_worker: PackageUpdateWorker::from(service),
                census_ring,
-               butterfly }
+               rollouts,
+               canary_decisions }
     }
 
-    pub async fn run(self) -> PackageIdent {
+    pub async fn run(self, butterfly: habitat_butterfly::Server) -> PackageIdent {
         // Determine this services suitablity and start the update leader election
         let suitability = self.update_election_suitability(self.topology).await;
-        self.butterfly
-            .start_update_election_rsw_mlr_rhw(&self.service_group, suitability, 0);
+        butterfly.start_update_election_rsw_mlr_rhw(&self.service_group, suitability, 0);
         // Determine this services role in the rolling update
         match self.update_role().await {
-            Role::Leader => {
-                // Wait for an update which will trigger follower updates through the census
-                // protocol
-                self.package_update_worker.update().await
-            }
+            Role::Leader => self.lead().await,
             Role::Follower => {
                 // Wait till it is our turn to update. It is possible that while we are waiting the
                 // leader dies and we are promoted to update leader.
                 match self.follower_wait_for_update_turn().await {
-                    FollowerUpdateTurnEvent::PromotedToLeader => {
-                        // Wait for an update which will trigger follower updates through the
-                        // census protocol
-                        self.package_update_worker.update().await
-                    }
+                    FollowerUpdateTurnEvent::PromotedToLeader => self.lead().await,
                     FollowerUpdateTurnEvent::PromotedToLeaderMidUpdate(new_ident) => {
                         // Update to the same package as the old leader allowing all followers to
-                        // finish updating
-                        self.package_update_worker.update_to(new_ident).await
+                        // finish updating. As the new leader we are now responsible for watching
+                        // the health of the rollout.
+                        let new_ident = self.package_update_worker.update_to(new_ident).await;
+                        self.start_rollout(&new_ident);
+                        new_ident
                     }
                     FollowerUpdateTurnEvent::UpdateTo(new_ident) => {
                         // Update to the package we were instructed to
                         self.package_update_worker.update_to(new_ident).await
                     }
+                    FollowerUpdateTurnEvent::RollbackTo(ident) => {
+                        self.package_update_worker.rollback_to(ident).await
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run the update leader. Finish the rollout this Supervisor is responsible for, rolling it
+    /// back if it does not become healthy. Otherwise, wait for an update which will trigger
+    /// follower updates through the census protocol.
+    async fn lead(&self) -> PackageIdent {
+        match self.leader_check_rollout().await {
+            LeaderRolloutAction::RollBack { from, rejected } => {
+                self.roll_back(from, rejected).await
+            }
+            LeaderRolloutAction::WaitForUpdate { ignored } => {
+                let new_ident = match ignored {
+                    Some(rejected) => self.package_update_worker.update_ignoring(&rejected).await,
+                    None => self.package_update_worker.update().await,
+                };
+                self.start_rollout(&new_ident);
+                new_ident
+            }
+        }
+    }
+
+    /// Check on the rollout this Supervisor is responsible for as the update leader. A rollout in
+    /// progress is watched until it becomes healthy or has to be rolled back.
+    async fn leader_check_rollout(&self) -> LeaderRolloutAction {
+        match self.leader_rollout().await {
+            Some(Rollout::InProgress { from, to }) => {
+                if to != self.current_ident {
+                    debug!("'{}' rolling update leader is not running '{}'; abandoning the \
+                            rollout",
+                           self.service_group, to);
+                    self.rollouts.lock().remove(&self.service_group);
+                    self.canary_decisions.lock().remove(&self.service_group);
+                    return LeaderRolloutAction::WaitForUpdate { ignored: None };
+                }
+                let event = self.leader_wait_for_healthy_rollout(&to).await;
+                // The canary stage is over whatever the outcome of the rollout is
+                self.canary_decisions.lock().remove(&self.service_group);
+                match event {
+                    LeaderRolloutHealthEvent::Healthy => {
+                        outputln!("Rolling update of {} to {} finished",
+                                  self.service_group,
+                                  to);
+                        self.rollouts.lock().remove(&self.service_group);
+                        LeaderRolloutAction::WaitForUpdate { ignored: None }
+                    }
+                    LeaderRolloutHealthEvent::Unhealthy => {
+                        outputln!("Rolling update of {} to {} failed; rolling back to {}",
+                                  self.service_group,
+                                  to,
+                                  from);
+                        LeaderRolloutAction::RollBack { from, rejected: to }
+                    }
+                    LeaderRolloutHealthEvent::Aborted => {
+                        outputln!("Canary of {} to {} was aborted; rolling back to {}",
+                                  self.service_group,
+                                  to,
+                                  from);
+                        LeaderRolloutAction::RollBack { from, rejected: to }
+                    }
+                }
+            }
+            Some(Rollout::RolledBack { rejected, .. }) => {
+                LeaderRolloutAction::WaitForUpdate { ignored: Some(rejected), }
+            }
+            None => LeaderRolloutAction::WaitForUpdate { ignored: None },
+        }
+    }
+
+    /// The rollout this Supervisor is responsible for as the update leader. That is the rollout it
+    /// started itself or, if it was elected after another member led one, the rollout gossiped by
+    /// that member. A rollout taken over from another member is gossiped by this Supervisor from
+    /// now on.
+    async fn leader_rollout(&self) -> Option<Rollout> {
+        if let Some(rollout) = self.rollouts.lock().get(&self.service_group).cloned() {
+            return Some(rollout);
+        }
+        let rollout = self.gossiped_rollout().await?;
+        debug!("'{}' rolling update leader is taking over {:?}",
+               self.service_group, rollout);
+        self.rollouts
+            .lock()
+            .insert(self.service_group.clone(), rollout.clone());
+        Some(rollout)
+    }
+
+    /// Find a rollout that another member gossips from a time it was the update leader and that
+    /// this Supervisor has to take over. That is a rollout to the package this Supervisor runs
+    /// which is still in progress, or a rollback to it.
+    async fn gossiped_rollout(&self) -> Option<Rollout> {
+        let census_group = self.census_group().await;
+        let me = census_group.me().map(|me| me.member_id.clone());
+        census_group.members()
+                    .filter(|member| Some(&member.member_id) != me.as_ref())
+                    .filter_map(|member| member.rollout.as_ref())
+                    .filter_map(Rollout::from_rumor)
+                    .find(|rollout| {
+                        match rollout {
+                            Rollout::InProgress { to, .. } => *to == self.current_ident,
+                            Rollout::RolledBack { from, .. } => *from == self.current_ident,
+                        }
+                    })
+    }
+
+    /// Reject the rollout of `rejected` and roll the group back to `from`.
+    async fn roll_back(&self, from: PackageIdent, rejected: PackageIdent) -> PackageIdent {
+        self.rollouts.lock().insert(self.service_group.clone(),
+                                    Rollout::RolledBack { from: from.clone(),
+                                                          rejected });
+        self.package_update_worker.rollback_to(from).await
+    }
+
+    /// Record that this Supervisor, as the update leader, is rolling out `to`.
+    fn start_rollout(&self, to: &PackageIdent) {
+        self.rollouts.lock().insert(self.service_group.clone(),
+                                    Rollout::InProgress { from: self.current_ident.clone(),
+                                                          to:   to.clone(), });
+    }
+
+    /// Wait until every alive member is running `to` and reports an `Ok` health check. The rollout
+    /// is unhealthy as soon as an updated member reports `Critical`, or when an updated member
+    /// does not report `Ok` within `RollingUpdateHealthWindow`, or when an operator aborts the
+    /// canary stage of the rollout.
+    async fn leader_wait_for_healthy_rollout(&self, to: &PackageIdent) -> LeaderRolloutHealthEvent {
+        let window: Duration = RollingUpdateHealthWindow::configured_value().into();
+        let mut waiting_since: HashMap<MemberId, Instant> = HashMap::new();
+        loop {
+            {
+                let census_group = self.census_group().await;
+                if census_group.canary_decision(to) == Some(CanaryDecision::Abort) {
+                    return LeaderRolloutHealthEvent::Aborted;
+                }
+                let mut finished = true;
+                for member in census_group.members().filter(|m| m.alive()) {
+                    if member.pkg != *to {
+                        // This member has not taken its turn yet
+                        finished = false;
+                        continue;
+                    }
+                    match member.health_check {
+                        HealthCheckResult::Ok => {
+                            waiting_since.remove(&member.member_id);
+                        }
+                        HealthCheckResult::Critical => {
+                            warn!("'{}' member '{}' is critical after updating to '{}'",
+                                  self.service_group, member.member_id, to);
+                            return LeaderRolloutHealthEvent::Unhealthy;
+                        }
+                        HealthCheckResult::Warning | HealthCheckResult::Unknown => {
+                            finished = false;
+                            let since = waiting_since.entry(member.member_id.clone())
+                                                     .or_insert_with(Instant::now);
+                            if since.elapsed() > window {
+                                warn!("'{}' member '{}' did not become healthy within {:?} after \
+                                       updating to '{}'",
+                                      self.service_group, member.member_id, window, to);
+                                return LeaderRolloutHealthEvent::Unhealthy;
+                            }
+                            trace!("'{}' waiting for member '{}' to become healthy: {}",
+                                   self.service_group,
+                                   member.member_id,
+                                   member.health_check);
+                        }
+                    }
+                }
+                if finished {
+                    break LeaderRolloutHealthEvent::Healthy;
                 }
             }
+            time::delay_for(DELAY).await;
         }
     }
 
@@ -194,6 +497,7 @@ impl RollingUpdateWorker {
         loop {
             {
                 let census_group = self.census_group().await;
+                self.forget_finished_canary_decision(&census_group);
                 match (census_group.update_leader(), census_group.me()) {
                     (Some(leader), Some(me)) => {
                         // If the current leader is no longer alive, it is possible that this
@@ -203,7 +507,20 @@ impl RollingUpdateWorker {
                                    self.service_group);
                             break FollowerUpdateStartEvent::PromotedToLeader;
                         }
-                        if leader.pkg != me.pkg {
+                        if let Some(rollback_to) = rolled_back_to(leader, &me.pkg) {
+                            // The leader rejected the package this follower runs. Every member
+                            // that already updated follows it back immediately.
+                            debug!("'{}' rolled back a rolling update: leader='{}' follower='{}' \
+                                    rollback_to='{}'",
+                                   self.service_group, leader.pkg, me.pkg, rollback_to);
+                            break FollowerUpdateStartEvent::RollbackTo(rollback_to);
+                        } else if rolled_back_to(leader, &leader.pkg).is_some() {
+                            // The leader rejected the package it still runs and is about to roll
+                            // back itself. That package is not an update.
+                            trace!("'{}' rolling update leader is rolling back '{}'",
+                                   self.service_group,
+                                   leader.pkg);
+                        } else if leader.pkg != me.pkg {
                             // The leader has a new package starting a rolling update
                             debug!("'{}' started a rolling update: leader='{}' follower='{}'",
                                    self.service_group, leader.pkg, me.pkg);
@@ -227,17 +544,34 @@ impl RollingUpdateWorker {
     }
 
     async fn follower_wait_for_update_turn(&self) -> FollowerUpdateTurnEvent {
-        let update_to = match self.follower_wait_for_update_start().await {
-            FollowerUpdateStartEvent::PromotedToLeader => {
-                return FollowerUpdateTurnEvent::PromotedToLeader
+        loop {
+            let update_to = match self.follower_wait_for_update_start().await {
+                FollowerUpdateStartEvent::PromotedToLeader => {
+                    return FollowerUpdateTurnEvent::PromotedToLeader
+                }
+                FollowerUpdateStartEvent::RollbackTo(ident) => {
+                    return FollowerUpdateTurnEvent::RollbackTo(ident)
+                }
+                FollowerUpdateStartEvent::UpdateTo(ident) => ident,
+            };
+            if let Some(event) = self.follower_wait_for_turn_in_rollout(update_to).await {
+                break event;
             }
-            FollowerUpdateStartEvent::UpdateTo(ident) => ident,
-        };
+        }
+    }
+
+    /// Wait for this follower's turn during a rollout of `update_to`. Returns `None` if the
+    /// leader rolled the update back before this follower's turn came.
+    async fn follower_wait_for_turn_in_rollout(&self,
+                                               update_to: PackageIdent)
+                                               -> Option<FollowerUpdateTurnEvent> {
         loop {
             {
                 let census_group = self.census_group().await;
+                let alive_members = census_group.members().filter(|m| m.alive()).count();
+                let max_unavailable = self.max_unavailable.members(alive_members);
                 match (census_group.update_leader(),
-                       census_group.previous_peer(),
+                       census_group.update_turn_peer(max_unavailable),
                        census_group.me())
                 {
                     (Some(leader), Some(peer), Some(me)) => {
@@ -247,28 +581,57 @@ impl RollingUpdateWorker {
                             debug!("'{}' rolling update follower was promoted to the leader mid \
                                     update. Immediately updating to '{}'.",
                                    self.service_group, update_to);
-                            break FollowerUpdateTurnEvent::PromotedToLeaderMidUpdate(update_to);
+                            let event =
+                                FollowerUpdateTurnEvent::PromotedToLeaderMidUpdate(update_to);
+                            break Some(event);
                         }
-                        if leader.pkg != update_to {
+                        if rolled_back_to(leader, &update_to).is_some() {
+                            // The leader rolled back before this follower took its turn. There is
+                            // nothing to do until the next rollout starts.
+                            debug!("'{}' rolling update to '{}' was rolled back before this \
+                                    followers turn",
+                                   self.service_group, update_to);
+                            break None;
+                        } else if leader.pkg != update_to {
                             // The leader died in the middle of the rolling update. Wait for the new
                             // leader to finish updating.
                             debug!("'{}' is in a rolling update but its leader died. Waiting for \
                                     new leader to finish updating: leader='{}' peer='{}' \
                                     follower='{}' update_to='{}'",
                                    self.service_group, leader.pkg, peer.pkg, me.pkg, update_to);
-                        } else if peer.pkg == update_to {
-                            // It is now this followers turn. The previous peer is done updating.
-                            // The first time this condition is true the previous peer is the
-                            // rolling update leader making this condition trivially true. This
-                            // will trigger all the followers to start their updates one after
-                            // another.
+                        } else if self.update_strategy == UpdateStrategy::Canary
+                                  && census_group.update_follower_position()
+                                                 .map_or(false, |p| p > self.canaries as usize)
+                                  && census_group.canary_decision(&update_to)
+                                     != Some(CanaryDecision::Promote)
+                        {
+                            // The rolling update is in its canary stage. Only the first `canaries`
+                            // followers after the leader are canaries. The rest wait for an
+                            // operator to promote the update.
+                            debug!("'{}' is in the canary stage of a rolling update and waiting \
+                                    for promotion: leader='{}' follower='{}' update_to='{}'",
+                                   self.service_group, leader.pkg, me.pkg, update_to);
+                        } else if peer.pkg == update_to
+                                  && peer.health_check == HealthCheckResult::Ok
+                        {
+                            // It is now this followers turn. The peer `max_unavailable` places
+                            // before this follower is done updating and reports that it is
+                            // healthy. The first `max_unavailable` followers after the leader
+                            // have the rolling update leader as their peer, making them update
+                            // together. This will trigger the remaining followers to start their
+                            // updates in batches of at most `max_unavailable`.
                             debug!("'{}' is in a rolling update and it is this followers turn to \
                                     update: leader='{}' peer='{}' follower='{}' update_to='{}'",
                                    self.service_group, leader.pkg, peer.pkg, me.pkg, update_to);
-                            break FollowerUpdateTurnEvent::UpdateTo(update_to);
+                            break Some(FollowerUpdateTurnEvent::UpdateTo(update_to));
+                        } else if peer.pkg == update_to {
+                            // The peer updated but has not reported a healthy health check yet.
+                            debug!("'{}' is in a rolling update but the peer is not yet healthy: \
+                                    peer='{}' health='{}' update_to='{}'",
+                                   self.service_group, peer.pkg, peer.health_check, update_to);
                         } else {
-                            // It is not this followers turn to update. The previous peer has not
-                            // updated yet.
+                            // It is not this followers turn to update. The peer has not updated
+                            // yet.
                             debug!("'{}' is in a rolling update but it is not this followers \
                                     turn to update: leader='{}' peer='{}' follower='{}' \
                                     update_to='{}'",
@@ -277,7 +640,7 @@ impl RollingUpdateWorker {
                     }
                     _ => {
                         error!("The census group for '{}' is in a bad state. It could not \
-                                determine the update leader, previous peer, or its own identity.",
+                                determine the update leader, turn peer, or its own identity.",
                                self.service_group);
                         debug_assert!(false);
                     }
@@ -287,6 +650,32 @@ impl RollingUpdateWorker {
         }
     }
 
+    /// Forget the operator decision on the canary stage of a rollout made on this Supervisor once
+    /// no alive member gossips that the rollout is still in progress.
+    fn forget_finished_canary_decision(&self, census_group: &CensusGroup) {
+        let mut canary_decisions = self.canary_decisions.lock();
+        let finished = match canary_decisions.get(&self.service_group) {
+            Some(canary) => {
+                !census_group.members()
+                             .filter(|m| m.alive())
+                             .filter_map(|m| m.rollout.as_ref())
+                             .filter_map(Rollout::from_rumor)
+                             .any(|rollout| {
+                                 match rollout {
+                                     Rollout::InProgress { to, .. } => to.to_string() == canary.pkg,
+                                     Rollout::RolledBack { .. } => false,
+                                 }
+                             })
+            }
+            None => false,
+        };
+        if finished {
+            debug!("'{}' forgetting the canary decision of a finished rollout",
+                   self.service_group);
+            canary_decisions.remove(&self.service_group);
+        }
+    }
+
     /// Returns a reference to the services census group. The reference is behind a read write lock
     /// so the lifetime of the reference should be minimized to avoid uneccesarily holding the lock.
     async fn census_group(&self) -> RwLockReadGuardRef<'_, CensusRing, CensusGroup> {
@@ -308,3 +697,342 @@ impl RollingUpdateWorker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use habitat_butterfly::{member::{Health,
+                                     Member,
+                                     MemberList},
+                            rumor::{election::{ElectionUpdate as ElectionUpdateRumor,
+                                               Term},
+                                    service::{Canary,
+                                              Service as ServiceRumor,
+                                              ServiceHealth,
+                                              SysInfo},
+                                    RumorStore}};
+    use habitat_core::{fs::CACHE_KEY_PATH,
+                       locked_env_var};
+
+    const OLD: &str = "core/redis/4.0.14/20190319155852";
+    const NEW: &str = "core/redis/5.0.7/20200106160823";
+
+    locked_env_var!(HAB_ROLLING_UPDATE_HEALTH_WINDOW_SECS,
+                    lock_health_window_var);
+
+    fn ident(ident: &str) -> PackageIdent { ident.parse().expect("valid package ident") }
+
+    fn service_group() -> ServiceGroup { "redis.default".parse().expect("valid service group") }
+
+    fn rumor(member_id: &str, pkg: &str, health: ServiceHealth) -> ServiceRumor {
+        let mut rumor = ServiceRumor::new(member_id,
+                                          &ident(pkg),
+                                          service_group(),
+                                          SysInfo::default(),
+                                          None);
+        rumor.health = health;
+        rumor
+    }
+
+    /// A census ring in which `me` sees every member of `rumors` alive and `update_leader` as the
+    /// elected update leader.
+    fn census_ring(me: &str,
+                   update_leader: &str,
+                   rumors: Vec<ServiceRumor>)
+                   -> Arc<RwLock<CensusRing>> {
+        let service_store = RumorStore::default();
+        let member_list = MemberList::new();
+        for rumor in rumors {
+            let mut member = Member::default();
+            member.id = rumor.member_id.clone();
+            member_list.insert_mlw(member, Health::Alive);
+            service_store.insert_rsw(rumor);
+        }
+        let election_update_store = RumorStore::default();
+        let mut election =
+            ElectionUpdateRumor::new(update_leader, &service_group(), Term::default(), 0, true);
+        election.finish();
+        election_update_store.insert_rsw(election);
+
+        let mut ring = CensusRing::new(me);
+        ring.update_from_rumors_rsr_mlr(&*CACHE_KEY_PATH,
+                                        &service_store,
+                                        &RumorStore::default(),
+                                        &election_update_store,
+                                        &member_list,
+                                        &RumorStore::default(),
+                                        &RumorStore::default());
+        Arc::new(RwLock::new(ring))
+    }
+
+    fn worker(current: &str, census_ring: Arc<RwLock<CensusRing>>) -> RollingUpdateWorker {
+        let full_ident = current.parse()
+                                .expect("valid fully qualified package ident");
+        RollingUpdateWorker { service_group: service_group(),
+                              topology: Topology::Standalone,
+                              update_strategy: UpdateStrategy::Rolling,
+                              max_unavailable: MaxUnavailable::default(),
+                              canaries: 1,
+                              current_ident: ident(current),
+                              package_update_worker: PackageUpdateWorker::for_test(service_group(),
+                                                                                   full_ident),
+                              census_ring,
+                              rollouts: Arc::default(),
+                              canary_decisions: Arc::default() }
+    }
+
+    fn in_progress() -> Rollout {
+        Rollout::InProgress { from: ident(OLD),
+                              to:   ident(NEW), }
+    }
+
+    fn rolled_back() -> Rollout {
+        Rollout::RolledBack { from:     ident(OLD),
+                              rejected: ident(NEW), }
+    }
+
+    #[test]
+    fn rollout_survives_a_round_trip_through_its_rumor() {
+        for rollout in &[in_progress(), rolled_back()] {
+            assert_eq!(Rollout::from_rumor(&rollout.to_rumor()).as_ref(),
+                       Some(rollout));
+        }
+    }
+
+    #[tokio::test]
+    async fn leader_wait_for_healthy_rollout_is_healthy_when_every_member_updated() {
+        let ring = census_ring("a",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Ok),
+                                    rumor("c", NEW, ServiceHealth::Ok),]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.leader_wait_for_healthy_rollout(&ident(NEW)).await,
+                   LeaderRolloutHealthEvent::Healthy);
+    }
+
+    #[tokio::test]
+    async fn leader_wait_for_healthy_rollout_is_unhealthy_when_an_updated_member_is_critical() {
+        let ring = census_ring("a",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Critical),
+                                    rumor("c", OLD, ServiceHealth::Ok),]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.leader_wait_for_healthy_rollout(&ident(NEW)).await,
+                   LeaderRolloutHealthEvent::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn leader_wait_for_healthy_rollout_is_unhealthy_when_a_member_does_not_become_healthy() {
+        let window = lock_health_window_var();
+        window.set("0");
+        let ring = census_ring("a",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Unknown),]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.leader_wait_for_healthy_rollout(&ident(NEW)).await,
+                   LeaderRolloutHealthEvent::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn leader_wait_for_healthy_rollout_is_aborted_by_an_operator() {
+        let mut aborter = rumor("b", OLD, ServiceHealth::Ok);
+        aborter.canary = Some(Canary { pkg:      NEW.to_string(),
+                                       decision: CanaryDecision::Abort, });
+        let ring = census_ring("a", "a", vec![rumor("a", NEW, ServiceHealth::Ok), aborter]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.leader_wait_for_healthy_rollout(&ident(NEW)).await,
+                   LeaderRolloutHealthEvent::Aborted);
+    }
+
+    #[tokio::test]
+    async fn lead_finishes_a_healthy_rollout() {
+        let ring = census_ring("a",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Ok),]);
+        let worker = worker(NEW, ring);
+        worker.rollouts
+              .lock()
+              .insert(service_group(), in_progress());
+        assert_eq!(worker.leader_check_rollout().await,
+                   LeaderRolloutAction::WaitForUpdate { ignored: None });
+        assert!(worker.rollouts.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn lead_rolls_back_an_unhealthy_rollout() {
+        let ring = census_ring("a",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Critical),]);
+        let worker = worker(NEW, ring);
+        worker.rollouts
+              .lock()
+              .insert(service_group(), in_progress());
+        assert_eq!(worker.leader_check_rollout().await,
+                   LeaderRolloutAction::RollBack { from:     ident(OLD),
+                                                   rejected: ident(NEW), });
+    }
+
+    #[tokio::test]
+    async fn lead_takes_over_a_rollout_gossiped_by_the_previous_leader() {
+        let mut previous_leader = rumor("a", NEW, ServiceHealth::Ok);
+        previous_leader.rollout = Some(in_progress().to_rumor());
+        let ring = census_ring("b",
+                               "b",
+                               vec![previous_leader,
+                                    rumor("b", NEW, ServiceHealth::Ok),
+                                    rumor("c", NEW, ServiceHealth::Critical),]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.leader_check_rollout().await,
+                   LeaderRolloutAction::RollBack { from:     ident(OLD),
+                                                   rejected: ident(NEW), });
+        assert_eq!(worker.rollouts.lock().get(&service_group()),
+                   Some(&in_progress()));
+    }
+
+    #[tokio::test]
+    async fn lead_keeps_ignoring_a_package_the_previous_leader_rejected() {
+        let mut previous_leader = rumor("a", OLD, ServiceHealth::Ok);
+        previous_leader.rollout = Some(rolled_back().to_rumor());
+        let ring = census_ring("b",
+                               "b",
+                               vec![previous_leader, rumor("b", OLD, ServiceHealth::Ok)]);
+        let worker = worker(OLD, ring);
+        assert_eq!(worker.leader_check_rollout().await,
+                   LeaderRolloutAction::WaitForUpdate { ignored: Some(ident(NEW)), });
+        assert_eq!(worker.rollouts.lock().get(&service_group()),
+                   Some(&rolled_back()));
+    }
+
+    #[tokio::test]
+    async fn lead_abandons_a_rollout_of_a_package_it_does_not_run() {
+        let ring = census_ring("a", "a", vec![rumor("a", OLD, ServiceHealth::Ok)]);
+        let worker = worker(OLD, ring);
+        worker.rollouts
+              .lock()
+              .insert(service_group(), in_progress());
+        assert_eq!(worker.leader_check_rollout().await,
+                   LeaderRolloutAction::WaitForUpdate { ignored: None });
+        assert!(worker.rollouts.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn follower_rolls_back_when_the_leader_gossips_a_rollback() {
+        let mut leader = rumor("a", OLD, ServiceHealth::Ok);
+        leader.rollout = Some(rolled_back().to_rumor());
+        let ring = census_ring("b", "a", vec![leader, rumor("b", NEW, ServiceHealth::Ok)]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.follower_wait_for_update_start().await,
+                   FollowerUpdateStartEvent::RollbackTo(ident(OLD)));
+    }
+
+    #[tokio::test]
+    async fn follower_updates_to_an_older_leader_package_without_a_rollback() {
+        let ring = census_ring("b",
+                               "a",
+                               vec![rumor("a", OLD, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Ok),]);
+        let worker = worker(NEW, ring);
+        assert_eq!(worker.follower_wait_for_update_start().await,
+                   FollowerUpdateStartEvent::UpdateTo(ident(OLD)));
+    }
+
+    #[tokio::test]
+    async fn follower_wait_for_turn_in_rollout_updates_after_a_healthy_peer() {
+        let ring = census_ring("b",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", OLD, ServiceHealth::Ok),]);
+        let worker = worker(OLD, ring);
+        assert_eq!(worker.follower_wait_for_turn_in_rollout(ident(NEW)).await,
+                   Some(FollowerUpdateTurnEvent::UpdateTo(ident(NEW))));
+    }
+
+    #[tokio::test]
+    async fn follower_wait_for_turn_in_rollout_waits_for_promotion_after_the_canaries() {
+        let ring = census_ring("c",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Ok),
+                                    rumor("c", OLD, ServiceHealth::Ok),]);
+        let mut worker = worker(OLD, ring);
+        worker.update_strategy = UpdateStrategy::Canary;
+        assert!(time::timeout(DELAY * 2,
+                              worker.follower_wait_for_turn_in_rollout(ident(NEW)))
+                    .await
+                    .is_err());
+
+        worker.canaries = 2;
+        assert_eq!(worker.follower_wait_for_turn_in_rollout(ident(NEW)).await,
+                   Some(FollowerUpdateTurnEvent::UpdateTo(ident(NEW))));
+    }
+
+    #[tokio::test]
+    async fn lead_forgets_the_canary_decision_of_a_finished_rollout() {
+        let ring = census_ring("a",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Ok),]);
+        let worker = worker(NEW, ring);
+        worker.rollouts
+              .lock()
+              .insert(service_group(), in_progress());
+        worker.canary_decisions.lock().insert(service_group(),
+                                              Canary { pkg:      NEW.to_string(),
+                                                       decision: CanaryDecision::Promote, });
+        worker.leader_check_rollout().await;
+        assert!(worker.canary_decisions.lock().is_empty());
+    }
+
+    #[test]
+    fn follower_forgets_the_canary_decision_once_the_rollout_is_no_longer_in_progress() {
+        let mut leader = rumor("a", NEW, ServiceHealth::Ok);
+        leader.rollout = Some(in_progress().to_rumor());
+        let ring = census_ring("b", "a", vec![leader, rumor("b", NEW, ServiceHealth::Ok)]);
+        let worker = worker(NEW, Arc::clone(&ring));
+        worker.canary_decisions.lock().insert(service_group(),
+                                              Canary { pkg:      NEW.to_string(),
+                                                       decision: CanaryDecision::Promote, });
+        let guard = ring.read();
+        let census_group = guard.census_group_for(&service_group())
+                                .expect("census group");
+        worker.forget_finished_canary_decision(census_group);
+        assert!(!worker.canary_decisions.lock().is_empty());
+        drop(guard);
+
+        let ring = census_ring("b",
+                               "a",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", NEW, ServiceHealth::Ok),]);
+        let guard = ring.read();
+        let census_group = guard.census_group_for(&service_group())
+                                .expect("census group");
+        worker.forget_finished_canary_decision(census_group);
+        assert!(worker.canary_decisions.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn follower_wait_for_turn_in_rollout_stops_when_the_leader_rolls_back() {
+        let mut leader = rumor("a", NEW, ServiceHealth::Critical);
+        leader.rollout = Some(rolled_back().to_rumor());
+        let ring = census_ring("b", "a", vec![leader, rumor("b", OLD, ServiceHealth::Ok)]);
+        let worker = worker(OLD, ring);
+        assert_eq!(worker.follower_wait_for_turn_in_rollout(ident(NEW)).await,
+                   None);
+    }
+
+    #[tokio::test]
+    async fn follower_wait_for_turn_in_rollout_takes_over_when_promoted_to_leader() {
+        let ring = census_ring("b",
+                               "b",
+                               vec![rumor("a", NEW, ServiceHealth::Ok),
+                                    rumor("b", OLD, ServiceHealth::Ok),]);
+        let worker = worker(OLD, ring);
+        assert_eq!(worker.follower_wait_for_turn_in_rollout(ident(NEW)).await,
+                   Some(FollowerUpdateTurnEvent::PromotedToLeaderMidUpdate(ident(NEW))));
+    }
+}